@@ -2,14 +2,26 @@
 // License, v. 2.0. If a copy of the MPL was not distributed with this
 // file, You can obtain one at https://mozilla.org/MPL/2.0/.
 
-use std::path::{Path, PathBuf};
+use std::{
+    collections::{HashMap, HashSet},
+    path::{Path, PathBuf},
+};
 
 use log::{debug, trace};
 
 use ra_ap_cfg::{CfgAtom, CfgDiff, CfgExpr};
-use ra_ap_hir::{self as hir, AsAssocItem, Crate, HasAttrs, HirFileIdExt as _, ModuleSource};
-use ra_ap_ide::{AnalysisHost, Edition, RootDatabase};
-use ra_ap_ide_db::FxHashMap;
+use ra_ap_hir::{
+    self as hir, AsAssocItem, Crate, HasAttrs, HirFileIdExt as _, ModuleSource, Semantics,
+};
+use ra_ap_hir_def::{self as hir_def, nameres::diagnostics::DefDiagnosticKind};
+use ra_ap_ide::{AnalysisHost, Edition, FileId, RootDatabase, TryToNav as _};
+use ra_ap_ide_db::{
+    base_db::{salsa::Durability, CrateId, SourceDatabase as _, SourceDatabaseExt as _},
+    defs::Definition,
+    documentation::docs_from_attrs,
+    search::SearchScope,
+    FxHashMap, LineIndexDatabase as _,
+};
 use ra_ap_load_cargo::{LoadCargoConfig, ProcMacroServerChoice};
 use ra_ap_paths::{AbsPathBuf, Utf8PathBuf};
 use ra_ap_project_model::{
@@ -17,12 +29,17 @@ use ra_ap_project_model::{
     ProjectManifest, ProjectWorkspace, ProjectWorkspaceKind, RustLibSource, TargetData,
 };
 use ra_ap_project_model::{CargoWorkspace, Package, Target, TargetKind};
-use ra_ap_syntax::{ast, AstNode, SourceFile};
+use ra_ap_syntax::{
+    ast::{self, HasModuleItem as _, HasName as _},
+    AstNode, SourceFile, TextRange, TextSize,
+};
 use ra_ap_vfs::Vfs;
 
 use crate::{
-    item::{ItemCfgAttr, ItemTestAttr},
+    glob,
+    item::{ItemCfgAttr, ItemDeprecatedAttr, ItemTestAttr},
     options::{GeneralOptions, ProjectOptions},
+    progress,
 };
 
 pub struct LoadOptions {
@@ -37,7 +54,7 @@ pub fn load_workspace(
     general_options: &GeneralOptions,
     project_options: &ProjectOptions,
     load_options: &LoadOptions,
-) -> anyhow::Result<(Crate, AnalysisHost, Vfs)> {
+) -> anyhow::Result<(Vec<Crate>, AnalysisHost, Vfs)> {
     let project_path = {
         let project_path = project_options.manifest_path.as_path();
 
@@ -52,49 +69,229 @@ pub fn load_workspace(
         }
     };
 
-    let cargo_config = cargo_config(project_options, load_options);
-    let load_config = load_config();
+    let cargo_config = cargo_config(project_options, load_options)?;
+    let load_config = load_config(project_options);
 
-    let progress = |string| {
+    let progress = |string: String| {
         trace!("Progress: {}", string);
+        progress::loading(&string);
     };
 
-    let mut project_workspace = load_project_workspace(&project_path, &cargo_config, &progress)?;
+    let mut project_workspace = load_project_workspace(&project_path, &cargo_config, &progress)
+        .map_err(|err| {
+            if project_options.offline {
+                anyhow::anyhow!(
+                    indoc::indoc! {
+                        "{}
+
+                        Running in offline mode, so dependencies that are not available locally
+                        cannot be downloaded. Try running `cargo fetch` while online first."
+                    },
+                    err
+                )
+            } else {
+                err
+            }
+        })?;
 
-    let (package, target) = select_package_and_target(&project_workspace, project_options)?;
+    // Non-Cargo projects (i.e. `rust-project.json`) have no notion of packages and targets,
+    // so their crates get selected from the loaded database instead:
 
-    if general_options.verbose {
+    let is_json_project = matches!(project_workspace.kind, ProjectWorkspaceKind::Json { .. });
+
+    let packages_and_targets = if is_json_project {
+        vec![]
+    } else {
+        select_packages_and_targets(&project_workspace, project_options)?
+    };
+
+    if general_options.verbose && !is_json_project {
         eprintln!();
-        eprintln!("crate");
-        eprintln!("└── package: {}", package.name);
-        eprintln!("    └── target: {}", target.name);
+        if packages_and_targets.len() == 1 {
+            eprintln!("crate");
+        } else {
+            eprintln!("crates");
+        }
+        let count = packages_and_targets.len();
+        for (pos, (package, target)) in packages_and_targets.iter().enumerate() {
+            let is_last = pos + 1 == count;
+            let (branch, indentation) = if is_last {
+                ("└──", "    ")
+            } else {
+                ("├──", "│   ")
+            };
+            eprintln!("{branch} package: {}", package.name);
+            eprintln!("{indentation}└── target: {}", target.name);
+        }
         eprintln!();
     }
 
+    let bin_targets = bin_targets(&project_workspace);
+
     if load_config.load_out_dirs_from_check {
         let build_scripts = project_workspace.run_build_scripts(&cargo_config, &progress)?;
         project_workspace.set_build_scripts(build_scripts)
     }
 
-    let (db, vfs, _proc_macro_client) =
+    progress::stage(progress::Stage::Analyzing);
+
+    let (mut db, vfs, _proc_macro_client) =
         ra_ap_load_cargo::load_workspace(project_workspace, &cargo_config.extra_env, &load_config)?;
 
+    mark_bin_crates(&mut db, &vfs, &bin_targets);
+
+    if project_options.no_deps {
+        strip_dependencies(&mut db);
+    }
+
     let host = AnalysisHost::with_database(db);
 
-    let krate = find_crate(host.raw_database(), &vfs, &target)?;
+    let krates = if is_json_project {
+        let krates = select_json_crates(host.raw_database(), project_options)?;
+
+        if general_options.verbose {
+            eprintln!();
+            eprintln!("project");
+            let count = krates.len();
+            for (pos, krate) in krates.iter().enumerate() {
+                let branch = if pos + 1 == count {
+                    "└──"
+                } else {
+                    "├──"
+                };
+                eprintln!(
+                    "{branch} crate: {}",
+                    crate_name(*krate, host.raw_database())
+                );
+            }
+            eprintln!();
+        }
+
+        krates
+    } else {
+        packages_and_targets
+            .iter()
+            .map(|(_package, target)| find_crate(host.raw_database(), &vfs, target))
+            .collect::<anyhow::Result<Vec<_>>>()?
+    };
+
+    let krates = if project_options.expand_path_deps {
+        with_path_dependencies(krates, host.raw_database())
+    } else {
+        krates
+    };
+
+    Ok((krates, host, vfs))
+}
+
+/// Returns the root files and names of the workspace's binary targets
+/// (i.e. its binaries and examples).
+fn bin_targets(project_workspace: &ProjectWorkspace) -> Vec<(AbsPathBuf, String)> {
+    let ProjectWorkspaceKind::Cargo { ref cargo, .. } = project_workspace.kind else {
+        return vec![];
+    };
 
-    Ok((krate, host, vfs))
+    cargo
+        .packages()
+        .flat_map(|package_idx| cargo[package_idx].targets.iter())
+        .map(|target_idx| &cargo[*target_idx])
+        .filter(|target| matches!(target.kind, TargetKind::Bin | TargetKind::Example))
+        .map(|target| (target.root.clone(), target.name.clone()))
+        .collect()
 }
 
-pub fn cargo_config(project_options: &ProjectOptions, load_options: &LoadOptions) -> CargoConfig {
-    let all_targets = false;
+/// Sets `CARGO_BIN_NAME` for the crates of binary targets, as cargo does when building them
+/// (but rust-analyzer doesn't), which tells them apart from libraries later on.
+fn mark_bin_crates(db: &mut RootDatabase, vfs: &Vfs, bin_targets: &[(AbsPathBuf, String)]) {
+    if bin_targets.is_empty() {
+        return;
+    }
+
+    let mut crate_graph = (*db.crate_graph()).clone();
+
+    for (_crate_id, crate_data) in crate_graph.iter_mut() {
+        let root_path = vfs.file_path(crate_data.root_file_id);
+
+        let Some(root_path) = root_path.as_path() else {
+            continue;
+        };
+
+        if let Some((_, name)) = bin_targets.iter().find(|(path, _)| path == root_path) {
+            crate_data.env.set("CARGO_BIN_NAME", name.clone());
+        }
+    }
+
+    db.set_crate_graph_with_durability(crate_graph.into(), Durability::HIGH);
+}
+
+/// Detaches local crates from their non-local dependencies,
+/// which prevents the latter from ever getting analyzed.
+fn strip_dependencies(db: &mut RootDatabase) {
+    let mut crate_graph = (*db.crate_graph()).clone();
+
+    let local_crate_ids: HashSet<CrateId> = crate_graph
+        .iter()
+        .filter(|crate_id| crate_graph[*crate_id].origin.is_local())
+        .collect();
+
+    for (crate_id, crate_data) in crate_graph.iter_mut() {
+        if local_crate_ids.contains(&crate_id) {
+            crate_data
+                .dependencies
+                .retain(|dependency| local_crate_ids.contains(&dependency.crate_id));
+        }
+    }
+
+    db.set_crate_graph_with_durability(crate_graph.into(), Durability::HIGH);
+}
+
+/// Appends the (transitive) local path dependencies of `krates`.
+fn with_path_dependencies(mut krates: Vec<Crate>, db: &RootDatabase) -> Vec<Crate> {
+    let mut pending = krates.clone();
+
+    while let Some(krate) = pending.pop() {
+        for dependency in krate.dependencies(db) {
+            let dependency_krate = dependency.krate;
+
+            if !dependency_krate.origin(db).is_local() || krates.contains(&dependency_krate) {
+                continue;
+            }
+
+            debug!("Expanding path dependency: {:#?}", dependency.name);
+
+            krates.push(dependency_krate);
+            pending.insert(0, dependency_krate);
+        }
+    }
+
+    krates
+}
+
+/// Splits feature lists (e.g. `--features "a,b c"`) the same way cargo does.
+fn selected_features(project_options: &ProjectOptions) -> Vec<String> {
+    project_options
+        .features
+        .iter()
+        .flat_map(|features| features.split([',', ' ']))
+        .filter(|feature| !feature.is_empty())
+        .map(|feature| feature.to_owned())
+        .collect()
+}
+
+pub fn cargo_config(
+    project_options: &ProjectOptions,
+    load_options: &LoadOptions,
+) -> anyhow::Result<CargoConfig> {
+    // Checking all targets (rather than just the library and binaries) builds the
+    // build scripts and proc-macros of dev-dependencies, which `#[cfg(test)]` code uses:
+    let all_targets = load_options.cfg_test;
 
     // List of features to activate (or deactivate).
     let features = if project_options.all_features {
         CargoFeatures::All
     } else {
         CargoFeatures::Selected {
-            features: project_options.features.clone(),
+            features: selected_features(project_options),
             no_default_features: project_options.no_default_features,
         }
     };
@@ -105,12 +302,17 @@ pub fn cargo_config(project_options: &ProjectOptions, load_options: &LoadOptions
     // Whether to invoke cargo metadata on the sysroot crate
     let sysroot_query_metadata = false;
 
-    // Whether to load sysroot crates (`std`, `core` & friends).
-    let sysroot = if load_options.sysroot {
-        Some(RustLibSource::Discover)
-    } else {
-        None
-    };
+    // Whether to load sysroot crates (`std`, `core` & friends),
+    // which `include!`s of build scripts' outputs need in order to get expanded:
+    let sysroot =
+        if (load_options.sysroot || project_options.with_out_dirs) && !project_options.no_deps {
+            match &project_options.sysroot {
+                Some(sysroot_path) => Some(RustLibSource::Path(sysroot_abs_path(sysroot_path)?)),
+                None => Some(RustLibSource::Discover),
+            }
+        } else {
+            None
+        };
 
     // Rustc private crate source
     let rustc_source = None;
@@ -127,15 +329,25 @@ pub fn cargo_config(project_options: &ProjectOptions, load_options: &LoadOptions
         },
     };
 
-    // Setup RUSTC_WRAPPER to point to `rust-analyzer` binary itself.
+    // Setup RUSTC_WRAPPER to point to the `cargo-modules` binary itself.
     // (We use that to compile only proc macros and build scripts
-    // during the initial `cargo check`.)
+    // during the initial `cargo check`, see `rustc_wrapper`.)
     let wrap_rustc_in_build_scripts = true;
 
     let run_build_script_command = None;
 
     // FIXME: support extra environment variables via CLI:
-    let extra_env = FxHashMap::default();
+    let mut extra_env = FxHashMap::default();
+
+    // Prevent any cargo invocations from accessing the network:
+    if project_options.offline {
+        extra_env.insert("CARGO_NET_OFFLINE".to_owned(), "true".to_owned());
+    }
+
+    // Resolve the sysroot (and run cargo) via the given toolchain:
+    if let Some(toolchain) = &project_options.toolchain {
+        extra_env.insert("RUSTUP_TOOLCHAIN".to_owned(), toolchain.clone());
+    }
 
     let invocation_strategy = InvocationStrategy::PerWorkspace;
     let invocation_location = InvocationLocation::Workspace;
@@ -146,7 +358,7 @@ pub fn cargo_config(project_options: &ProjectOptions, load_options: &LoadOptions
 
     let target_dir = None;
 
-    CargoConfig {
+    Ok(CargoConfig {
         all_targets,
         features,
         target,
@@ -162,13 +374,36 @@ pub fn cargo_config(project_options: &ProjectOptions, load_options: &LoadOptions
         sysroot_src,
         extra_args,
         target_dir,
+    })
+}
+
+/// Returns the sysroot's absolute path, failing if there is no such directory
+/// (as rust-analyzer would otherwise silently analyze without any sysroot crates).
+fn sysroot_abs_path(sysroot_path: &Path) -> anyhow::Result<AbsPathBuf> {
+    if !sysroot_path.is_dir() {
+        anyhow::bail!("No sysroot found at path `{}`", sysroot_path.display());
     }
+
+    let path_buf = std::env::current_dir()?.join(sysroot_path);
+    let Ok(utf8_path_buf) = Utf8PathBuf::from_path_buf(path_buf) else {
+        anyhow::bail!(
+            "Sysroot path `{}` is not valid UTF-8",
+            sysroot_path.display()
+        );
+    };
+
+    Ok(AbsPathBuf::assert(utf8_path_buf))
 }
 
-pub fn load_config() -> LoadCargoConfig {
-    let load_out_dirs_from_check = true;
+pub fn load_config(project_options: &ProjectOptions) -> LoadCargoConfig {
+    // Run build scripts, so that code included from `OUT_DIR` gets resolved:
+    let load_out_dirs_from_check = !project_options.no_build_scripts;
     let prefill_caches = false;
-    let with_proc_macro_server = ProcMacroServerChoice::Sysroot;
+    let with_proc_macro_server = if project_options.no_proc_macros {
+        ProcMacroServerChoice::None
+    } else {
+        ProcMacroServerChoice::Sysroot
+    };
 
     LoadCargoConfig {
         load_out_dirs_from_check,
@@ -190,10 +425,10 @@ pub fn load_project_workspace(
     ProjectWorkspace::load(root, cargo_config, &progress)
 }
 
-pub fn select_package_and_target(
+pub fn select_packages_and_targets(
     project_workspace: &ProjectWorkspace,
     options: &ProjectOptions,
-) -> anyhow::Result<(PackageData, TargetData)> {
+) -> anyhow::Result<Vec<(PackageData, TargetData)>> {
     let cargo_workspace = match project_workspace.kind {
         ProjectWorkspaceKind::Cargo { ref cargo, .. } => Ok(cargo),
         ProjectWorkspaceKind::Json { .. } => Err(anyhow::anyhow!("Unexpected JSON workspace")),
@@ -202,21 +437,135 @@ pub fn select_package_and_target(
         }
     }?;
 
-    let package_idx = select_package(cargo_workspace, options)?;
-    let package = cargo_workspace[package_idx].clone();
-    debug!("Selected package: {:#?}", package.name);
+    let mut packages_and_targets = vec![];
+
+    for package_idx in select_packages(cargo_workspace, options)? {
+        let package = cargo_workspace[package_idx].clone();
+        debug!("Selected package: {:#?}", package.name);
+
+        for target_idx in select_targets(cargo_workspace, package_idx, options)? {
+            let target = cargo_workspace[target_idx].clone();
+            debug!("Selected target: {:#?}", target.name);
+
+            packages_and_targets.push((package.clone(), target));
+        }
+    }
+
+    Ok(packages_and_targets)
+}
+
+pub fn select_json_crates(
+    db: &RootDatabase,
+    options: &ProjectOptions,
+) -> anyhow::Result<Vec<Crate>> {
+    let is_target_selected = options.lib
+        || options.bin.is_some()
+        || options.example.is_some()
+        || options.test.is_some()
+        || options.bench.is_some()
+        || options.build_script
+        || options.merge_targets
+        || options.with_build_script;
+
+    if is_target_selected {
+        anyhow::bail!(indoc::indoc! {
+            "Target selection is not supported for `rust-project.json` projects,
+            please select crates via --package flag instead."
+        });
+    }
+
+    // Crates declared in `rust-project.json` as workspace members are the only local ones:
+
+    let krates: Vec<_> = Crate::all(db)
+        .into_iter()
+        .filter(|krate| krate.origin(db).is_local() && krate.display_name(db).is_some())
+        .collect();
+
+    // If project contains no crates, bail out:
+
+    if krates.is_empty() {
+        anyhow::bail!("No crates found");
+    }
+
+    let crate_list_items: Vec<_> = krates
+        .iter()
+        .map(|krate| format!("- {}", crate_name(*krate, db)))
+        .collect();
+
+    let crate_list = crate_list_items.join("\n");
+
+    // If project contains a single crate, just pick it:
+
+    if options.package.is_empty() {
+        if krates.len() == 1 {
+            return Ok(krates);
+        }
+
+        anyhow::bail!(
+            indoc::indoc! {
+                "Multiple crates present in project,
+                please explicitly select one via --package flag.
+
+                Crates present in project:
+                {}
+                "
+            },
+            crate_list
+        );
+    }
+
+    // If project contains multiple crates, select the ones provided via options:
+
+    let mut selected: Vec<Crate> = vec![];
+
+    for pattern in &options.package {
+        let pattern = pattern.replace('-', "_");
+
+        let matching: Vec<_> = krates
+            .iter()
+            .cloned()
+            .filter(|krate| {
+                let name = crate_name(*krate, db);
+                if glob::is_glob(&pattern) {
+                    glob::glob_matches(&pattern, &name)
+                } else {
+                    name == pattern
+                }
+            })
+            .collect();
+
+        if matching.is_empty() {
+            anyhow::bail!(
+                indoc::indoc! {
+                    "No crate found with name {:?}.
+
+                    Crates present in project:
+                    {}
+                    "
+                },
+                pattern,
+                crate_list,
+            );
+        }
+
+        for krate in matching {
+            if !selected.contains(&krate) {
+                selected.push(krate);
+            }
+        }
+    }
+
+    // Keep crates in the order in which they are declared in the project:
 
-    let target_idx = select_target(cargo_workspace, package_idx, options)?;
-    let target = cargo_workspace[target_idx].clone();
-    debug!("Selected target: {:#?}", target.name);
+    selected.sort_by_key(|krate| krates.iter().position(|other| other == krate));
 
-    Ok((package, target))
+    Ok(selected)
 }
 
-pub fn select_package(
+pub fn select_packages(
     workspace: &CargoWorkspace,
     options: &ProjectOptions,
-) -> anyhow::Result<Package> {
+) -> anyhow::Result<Vec<Package>> {
     let packages: Vec<_> = workspace
         .packages()
         .filter(|idx| workspace[*idx].is_member)
@@ -242,33 +591,61 @@ pub fn select_package(
 
     let package_list = package_list_items.join("\n");
 
-    // If project contains multiple packages, select the one provided via options:
+    // If project contains multiple packages, select the ones provided via options:
 
-    if let Some(package_name) = &options.package {
-        let package_idx = packages.into_iter().find(|package_idx| {
-            let package = &workspace[*package_idx];
-            package.name == *package_name
-        });
+    if !options.package.is_empty() {
+        let mut selected_packages: Vec<Package> = vec![];
 
-        return package_idx.ok_or_else(|| {
-            anyhow::anyhow!(
-                indoc::indoc! {
-                    "No package found with name {:?}.
+        for package_name in &options.package {
+            let matching_packages: Vec<_> = packages
+                .iter()
+                .cloned()
+                .filter(|package_idx| {
+                    let package = &workspace[*package_idx];
+                    if glob::is_glob(package_name) {
+                        glob::glob_matches(package_name, &package.name)
+                    } else {
+                        package.name == *package_name
+                    }
+                })
+                .collect();
+
+            if matching_packages.is_empty() {
+                anyhow::bail!(
+                    indoc::indoc! {
+                        "No package found with name {:?}.
 
                         Packages present in workspace:
                         {}
                         "
-                },
-                package_name,
-                package_list,
-            )
+                    },
+                    package_name,
+                    package_list,
+                );
+            }
+
+            for package_idx in matching_packages {
+                if !selected_packages.contains(&package_idx) {
+                    selected_packages.push(package_idx);
+                }
+            }
+        }
+
+        // Keep the packages in the order they appear in the workspace:
+        selected_packages.sort_by_key(|package_idx| {
+            packages
+                .iter()
+                .position(|idx| idx == package_idx)
+                .unwrap_or(usize::MAX)
         });
+
+        return Ok(selected_packages);
     }
 
     // If project contains a single packages, just pick it:
 
     if package_count == 1 {
-        return Ok(packages[0]);
+        return Ok(vec![packages[0]]);
     }
 
     Err(anyhow::anyhow!(
@@ -284,6 +661,68 @@ pub fn select_package(
     ))
 }
 
+pub fn select_targets(
+    workspace: &CargoWorkspace,
+    package_idx: Package,
+    options: &ProjectOptions,
+) -> anyhow::Result<Vec<Target>> {
+    let mut targets = if options.merge_targets {
+        select_merged_targets(workspace, package_idx)?
+    } else {
+        vec![select_target(workspace, package_idx, options)?]
+    };
+
+    // The build script (if any) gets processed alongside the selected targets:
+
+    if options.with_build_script {
+        let build_script = workspace[package_idx]
+            .targets
+            .iter()
+            .cloned()
+            .find(|target_idx| workspace[*target_idx].kind == TargetKind::BuildScript);
+
+        if let Some(build_script) = build_script {
+            if !targets.contains(&build_script) {
+                targets.push(build_script);
+            }
+        }
+    }
+
+    Ok(targets)
+}
+
+fn select_merged_targets(
+    workspace: &CargoWorkspace,
+    package_idx: Package,
+) -> anyhow::Result<Vec<Target>> {
+    let package = &workspace[package_idx];
+
+    // Retrieve list of indices for bin/lib targets, with the library going first:
+
+    let mut targets: Vec<_> = package
+        .targets
+        .iter()
+        .cloned()
+        .filter(|target_idx| {
+            let target = &workspace[*target_idx];
+            matches!(target.kind, TargetKind::Bin | TargetKind::Lib { .. })
+        })
+        .collect();
+
+    targets.sort_by_key(|target_idx| {
+        let target = &workspace[*target_idx];
+        !matches!(target.kind, TargetKind::Lib { .. })
+    });
+
+    // If package contains no bin/lib targets, bail out:
+
+    if targets.is_empty() {
+        anyhow::bail!("No library or binary targets found");
+    }
+
+    Ok(targets)
+}
+
 pub fn select_target(
     workspace: &CargoWorkspace,
     package_idx: Package,
@@ -291,7 +730,7 @@ pub fn select_target(
 ) -> anyhow::Result<Target> {
     let package = &workspace[package_idx];
 
-    // Retrieve list of indices for bin/lib targets:
+    // Retrieve list of indices for selectable targets:
 
     let targets: Vec<_> = package
         .targets
@@ -302,15 +741,26 @@ pub fn select_target(
             match target.kind {
                 TargetKind::Bin => true,
                 TargetKind::Lib { .. } => true,
-                TargetKind::Example => false,
-                TargetKind::Test => false,
-                TargetKind::Bench => false,
+                TargetKind::Example => true,
+                TargetKind::Test => true,
+                TargetKind::Bench => true,
                 TargetKind::Other => false,
-                TargetKind::BuildScript => false,
+                TargetKind::BuildScript => true,
             }
         })
         .collect();
 
+    // Only bin/lib targets get picked implicitly, all others have to be selected explicitly:
+
+    let default_targets: Vec<_> = targets
+        .iter()
+        .cloned()
+        .filter(|target_idx| {
+            let target = &workspace[*target_idx];
+            matches!(target.kind, TargetKind::Bin | TargetKind::Lib { .. })
+        })
+        .collect();
+
     let target_count = targets.len();
 
     // If package contains no targets, bail out:
@@ -328,11 +778,11 @@ pub fn select_target(
             match target.kind {
                 TargetKind::Bin => format!("- {} (--bin {})", target.name, target.name),
                 TargetKind::Lib { .. } => format!("- {} (--lib)", target.name),
-                TargetKind::Example => unreachable!(),
-                TargetKind::Test => unreachable!(),
-                TargetKind::Bench => unreachable!(),
+                TargetKind::Example => format!("- {} (--example {})", target.name, target.name),
+                TargetKind::Test => format!("- {} (--test {})", target.name, target.name),
+                TargetKind::Bench => format!("- {} (--bench {})", target.name, target.name),
                 TargetKind::Other => unreachable!(),
-                TargetKind::BuildScript => unreachable!(),
+                TargetKind::BuildScript => format!("- {} (--build-script)", target.name),
             }
         })
         .collect();
@@ -382,10 +832,109 @@ pub fn select_target(
         });
     }
 
-    // If project contains a single target, just pick it:
+    if let Some(example_name) = &options.example {
+        let target = targets.into_iter().find(|target_idx| {
+            let target = &workspace[*target_idx];
+            (target.kind == TargetKind::Example) && (target.name == example_name[..])
+        });
+
+        return target.ok_or_else(|| {
+            anyhow::anyhow!(
+                indoc::indoc! {
+                    "No example target found with name {:?}.
 
-    if target_count == 1 {
-        return Ok(targets[0]);
+                        Targets present in package:
+                        {}
+                        "
+                },
+                example_name,
+                target_list,
+            )
+        });
+    }
+
+    if let Some(test_name) = &options.test {
+        let target = targets.into_iter().find(|target_idx| {
+            let target = &workspace[*target_idx];
+            (target.kind == TargetKind::Test) && (target.name == test_name[..])
+        });
+
+        return target.ok_or_else(|| {
+            anyhow::anyhow!(
+                indoc::indoc! {
+                    "No test target found with name {:?}.
+
+                        Targets present in package:
+                        {}
+                        "
+                },
+                test_name,
+                target_list,
+            )
+        });
+    }
+
+    if let Some(bench_name) = &options.bench {
+        let target = targets.into_iter().find(|target_idx| {
+            let target = &workspace[*target_idx];
+            (target.kind == TargetKind::Bench) && (target.name == bench_name[..])
+        });
+
+        return target.ok_or_else(|| {
+            anyhow::anyhow!(
+                indoc::indoc! {
+                    "No bench target found with name {:?}.
+
+                        Targets present in package:
+                        {}
+                        "
+                },
+                bench_name,
+                target_list,
+            )
+        });
+    }
+
+    if options.build_script {
+        let target = targets.into_iter().find(|target_idx| {
+            let target = &workspace[*target_idx];
+            target.kind == TargetKind::BuildScript
+        });
+
+        return target.ok_or_else(|| {
+            anyhow::anyhow!(
+                indoc::indoc! {
+                    "No build script target found.
+
+                        Targets present in package:
+                        {}
+                        "
+                },
+                target_list,
+            )
+        });
+    }
+
+    // If package contains no bin/lib targets, bail out:
+
+    if default_targets.is_empty() {
+        anyhow::bail!(
+            indoc::indoc! {
+                "No library or binary targets found,
+                please explicitly select one via --example, --test, --bench or --build-script flag.
+
+                Targets present in package:
+                {}
+                "
+            },
+            target_list
+        );
+    }
+
+    // If project contains a single bin/lib target, just pick it:
+
+    if default_targets.len() == 1 {
+        return Ok(default_targets[0]);
     }
 
     Err(anyhow::anyhow!(
@@ -408,22 +957,41 @@ pub fn find_crate(db: &RootDatabase, vfs: &Vfs, target: &TargetData) -> anyhow::
 
     let krate = crates.into_iter().find(|krate| {
         let vfs_path = vfs.file_path(krate.root_file(db));
-        let crate_root_path = vfs_path.as_path().unwrap();
 
-        crate_root_path == target_root_path
+        vfs_path
+            .as_path()
+            .is_some_and(|crate_root_path| crate_root_path == target_root_path)
     });
 
     krate.ok_or_else(|| anyhow::anyhow!("Crate not found"))
 }
 
 pub(crate) fn crate_name(krate: hir::Crate, db: &RootDatabase) -> String {
-    // Obtain the crate's declaration name:
-    let display_name = &krate.display_name(db).unwrap();
+    // Obtain the crate's declaration name (with nameless crates, such as those of
+    // incomplete project models, getting a placeholder rather than aborting the analysis):
+    let Some(display_name) = krate.display_name(db) else {
+        return "_".to_owned();
+    };
 
     // Since a crate's name may contain `-` we canonicalize it by replacing with `_`:
     display_name.replace('-', "_")
 }
 
+/// Returns whether the crate is a package's build script (i.e. its `build.rs`).
+pub(crate) fn crate_is_build_script(krate: hir::Crate, db: &RootDatabase) -> bool {
+    // Cargo names the targets of all build scripts `build-script-build`:
+    crate_name(krate, db) == "build_script_build"
+}
+
+/// Returns whether the crate is a binary (i.e. the crate of a `bin` or `example` target).
+pub(crate) fn crate_is_bin(krate: hir::Crate, db: &RootDatabase) -> bool {
+    // Binaries got marked the same way cargo marks them (see `mark_bin_crates`):
+    db.crate_graph()[krate.into()]
+        .env
+        .get("CARGO_BIN_NAME")
+        .is_some()
+}
+
 pub(crate) fn krate(module_def_hir: hir::ModuleDef, db: &RootDatabase) -> Option<hir::Crate> {
     module(module_def_hir, db).map(|module| module.krate())
 }
@@ -444,7 +1012,7 @@ pub(crate) fn display_name(module_def_hir: hir::ModuleDef, db: &RootDatabase) ->
                 module_hir
                     .name(db)
                     .map(|name| name.display(db).to_string())
-                    .expect("name")
+                    .unwrap_or_else(|| "_".to_owned())
             }
         }
         hir::ModuleDef::Const(const_hir) => {
@@ -457,7 +1025,7 @@ pub(crate) fn display_name(module_def_hir: hir::ModuleDef, db: &RootDatabase) ->
         module_def_hir => module_def_hir
             .name(db)
             .map(|name| name.display(db).to_string())
-            .expect("name"),
+            .unwrap_or_else(|| "_".to_owned()),
     }
 }
 
@@ -569,6 +1137,59 @@ pub(crate) fn parse_ast<N: AstNode>(text: &str) -> N {
     node
 }
 
+/// Returns the paths under which the crates' items are (publicly) re-exported by other modules
+/// (e.g. `my_crate::Foo` for a `pub use internal::foo::Foo;` within `my_crate`'s root module),
+/// with chains of re-exports resolved to the items they ultimately refer to.
+pub(crate) fn reexport_paths(
+    krates: &[hir::Crate],
+    db: &RootDatabase,
+) -> HashMap<hir::ModuleDef, Vec<String>> {
+    let mut paths: HashMap<hir::ModuleDef, Vec<String>> = HashMap::new();
+
+    for reexporting_krate in krates.iter().cloned() {
+        let root_module = reexporting_krate.root_module();
+        let mut pending = vec![root_module];
+
+        while let Some(module) = pending.pop() {
+            let module_path = display_path(hir::ModuleDef::Module(module), db);
+
+            // Only imports visible outside of their module are of interest (i.e. `pub(…) use …`):
+            for (name, scope_def) in module.scope(db, Some(root_module)) {
+                let hir::ScopeDef::ModuleDef(module_def) = scope_def else {
+                    continue;
+                };
+
+                let is_declared_here = match module_def {
+                    hir::ModuleDef::Module(module_hir) => module_hir.parent(db) == Some(module),
+                    module_def => module_def.module(db) == Some(module),
+                };
+
+                if is_declared_here {
+                    if let hir::ModuleDef::Module(module_hir) = module_def {
+                        pending.push(module_hir);
+                    }
+                    continue;
+                }
+
+                let is_internal =
+                    krate(module_def, db).is_some_and(|krate| krates.contains(&krate));
+
+                if !is_internal {
+                    continue;
+                }
+
+                let name = name.display(db).to_string();
+                paths
+                    .entry(module_def)
+                    .or_default()
+                    .push(format!("{module_path}::{name}"));
+            }
+        }
+    }
+
+    paths
+}
+
 pub(crate) fn use_tree_matches_item_path(use_tree: &ast::UseTree, item_path: &str) -> bool {
     if item_path.is_empty() {
         return false;
@@ -586,14 +1207,12 @@ pub(crate) fn use_tree_matches_path(use_tree: &ast::UseTree, path: &ast::Path) -
     if let Some(use_tree_path) = use_tree.path() {
         for use_tree_segment in use_tree_path.segments() {
             match path_segments_iter.next() {
-                Some(path_segment) => {
-                    if use_tree_segment.syntax().text() == path_segment.syntax().text() {
-                        continue;
-                    } else {
-                        return false;
-                    }
+                Some(path_segment)
+                    if use_tree_segment.syntax().text() == path_segment.syntax().text() =>
+                {
+                    continue;
                 }
-                None => {
+                _ => {
                     return false;
                 }
             }
@@ -689,6 +1308,293 @@ pub fn test_attr(module_def_hir: hir::ModuleDef, db: &RootDatabase) -> Option<It
     }
 }
 
+/// Returns `true` if the item only exists for the sake of tests, i.e. if it's a `#[test]` function,
+/// or if it (or any of its enclosing modules) is gated behind `#[cfg(test)]`.
+pub(crate) fn is_test_only(module_def_hir: hir::ModuleDef, db: &RootDatabase) -> bool {
+    let requires_cfg_test = |module_def_hir: hir::ModuleDef| {
+        cfgs(module_def_hir, db)
+            .iter()
+            .any(|cfg| matches!(cfg, CfgExpr::Atom(CfgAtom::Flag(flag)) if flag.as_str() == "test"))
+    };
+
+    if test_attr(module_def_hir, db).is_some() || requires_cfg_test(module_def_hir) {
+        return true;
+    }
+
+    let mut module_hir = module_def_hir.module(db);
+
+    while let Some(parent_hir) = module_hir {
+        if requires_cfg_test(hir::ModuleDef::Module(parent_hir)) {
+            return true;
+        }
+
+        module_hir = parent_hir.parent(db);
+    }
+
+    false
+}
+
+/// Returns the Cargo feature gating the item (e.g. via `#[cfg(feature = "json")]`),
+/// as required by the item itself or by the closest of its enclosing modules.
+///
+/// Of conditions involving several features (e.g. `all(feature = "net", feature = "tls")`)
+/// the first one counts, while negated ones (e.g. `not(feature = "tls")`) don't gate anything.
+pub(crate) fn gating_feature(module_def_hir: hir::ModuleDef, db: &RootDatabase) -> Option<String> {
+    fn required_feature(cfg: &CfgExpr) -> Option<String> {
+        match cfg {
+            CfgExpr::Atom(CfgAtom::KeyValue { key, value }) if key.as_str() == "feature" => {
+                Some(value.to_string())
+            }
+            CfgExpr::All(cfgs) | CfgExpr::Any(cfgs) => cfgs.iter().find_map(required_feature),
+            _ => None,
+        }
+    }
+
+    if let Some(feature) = cfg(module_def_hir, db).as_ref().and_then(required_feature) {
+        return Some(feature);
+    }
+
+    let mut module_hir = module_def_hir.module(db);
+
+    while let Some(parent_hir) = module_hir {
+        let parent_cfg = cfg(hir::ModuleDef::Module(parent_hir), db);
+
+        if let Some(feature) = parent_cfg.as_ref().and_then(required_feature) {
+            return Some(feature);
+        }
+
+        module_hir = parent_hir.parent(db);
+    }
+
+    None
+}
+
+fn attrs(module_def_hir: hir::ModuleDef, db: &RootDatabase) -> Option<hir::AttrsWithOwner> {
+    match module_def_hir {
+        hir::ModuleDef::Module(r#mod) => Some(r#mod.attrs(db)),
+        hir::ModuleDef::Function(r#fn) => Some(r#fn.attrs(db)),
+        hir::ModuleDef::Adt(adt) => Some(adt.attrs(db)),
+        hir::ModuleDef::Variant(r#variant) => Some(r#variant.attrs(db)),
+        hir::ModuleDef::Const(r#const) => Some(r#const.attrs(db)),
+        hir::ModuleDef::Static(r#static) => Some(r#static.attrs(db)),
+        hir::ModuleDef::Trait(r#trait) => Some(r#trait.attrs(db)),
+        hir::ModuleDef::TraitAlias(trait_type) => Some(trait_type.attrs(db)),
+        hir::ModuleDef::TypeAlias(type_alias) => Some(type_alias.attrs(db)),
+        hir::ModuleDef::BuiltinType(_builtin_type) => None,
+        hir::ModuleDef::Macro(r#macro) => Some(r#macro.attrs(db)),
+    }
+}
+
+pub fn deprecated_attr(
+    module_def_hir: hir::ModuleDef,
+    db: &RootDatabase,
+) -> Option<ItemDeprecatedAttr> {
+    let attrs = attrs(module_def_hir, db)?;
+
+    if attrs.by_key("deprecated").exists() {
+        Some(ItemDeprecatedAttr)
+    } else {
+        None
+    }
+}
+
+/// Returns the first non-empty line of the item's doc comment, if any.
+pub fn doc_summary(module_def_hir: hir::ModuleDef, db: &RootDatabase) -> Option<String> {
+    let attrs = attrs(module_def_hir, db)?;
+    let docs = docs_from_attrs(&attrs)?;
+
+    docs.lines()
+        .map(str::trim)
+        .find(|line| !line.is_empty())
+        .map(str::to_owned)
+}
+
+/// Returns the problems rust-analyzer ran into while resolving `module`
+/// (e.g. a `mod foo;` declaration without a file, a macro failing to expand,
+/// or a malformed `#[cfg(…)]` attribute), which leave its contents only partially known.
+pub(crate) fn resolution_errors(module: hir::Module, db: &RootDatabase) -> Vec<String> {
+    let mut errors = vec![];
+
+    if cfg(hir::ModuleDef::Module(module), db).is_some_and(|cfg| is_malformed_cfg(&cfg)) {
+        errors.push("malformed `#[cfg(…)]` attribute".to_owned());
+    }
+
+    // Modules declared without a file get reported by the module declaring them:
+    if let Some(parent) = module.parent(db) {
+        let parent_id = hir_def::ModuleId::from(parent);
+        let name = module.name(db).map(|name| name.display(db).to_string());
+
+        for diagnostic in parent_id.def_map(db).diagnostics() {
+            if diagnostic.in_module != parent_id.local_id {
+                continue;
+            }
+
+            let DefDiagnosticKind::UnresolvedModule { ast, candidates } = &diagnostic.kind else {
+                continue;
+            };
+
+            let declared_name = ast.to_node(db).name().map(|name| name.text().to_string());
+
+            if declared_name.is_some() && declared_name == name {
+                let candidates: Vec<String> = candidates
+                    .iter()
+                    .map(|candidate| format!("`{candidate}`"))
+                    .collect();
+
+                errors.push(format!("no file found at {}", candidates.join(" or ")));
+            }
+        }
+    }
+
+    let module_id = hir_def::ModuleId::from(module);
+
+    for diagnostic in module_id.def_map(db).diagnostics() {
+        if diagnostic.in_module != module_id.local_id {
+            continue;
+        }
+
+        // Unresolved imports and macro calls are left out, as they're to be expected
+        // for analyses without sysroot crates (or dependencies):
+        let error = match &diagnostic.kind {
+            DefDiagnosticKind::MacroError { .. }
+            | DefDiagnosticKind::MacroExpansionParseError { .. } => "a macro failed to expand",
+            DefDiagnosticKind::MacroDefError { .. } => "a macro definition is malformed",
+            _ => continue,
+        };
+
+        if !errors.iter().any(|existing| existing == error) {
+            errors.push(error.to_owned());
+        }
+    }
+
+    errors
+}
+
+fn is_malformed_cfg(cfg: &CfgExpr) -> bool {
+    match cfg {
+        CfgExpr::Invalid => true,
+        CfgExpr::Atom(_) => false,
+        CfgExpr::All(cfgs) | CfgExpr::Any(cfgs) => cfgs.iter().any(is_malformed_cfg),
+        CfgExpr::Not(cfg) => is_malformed_cfg(cfg),
+    }
+}
+
+/// Warns about the modules of `krates` that rust-analyzer failed to fully resolve,
+/// which get marked as "unresolved" within trees and graphs (rather than failing the analysis).
+pub(crate) fn warn_unresolved(krates: &[hir::Crate], db: &RootDatabase) {
+    for krate in krates {
+        for module in krate.modules(db) {
+            let errors = resolution_errors(module, db);
+
+            if errors.is_empty() {
+                continue;
+            }
+
+            let path = display_path(hir::ModuleDef::Module(module), db);
+
+            for error in errors {
+                debug!("Could not fully resolve module {path:?}: {error}");
+                progress::unresolved(&path, &error);
+            }
+        }
+    }
+}
+
+/// Returns the items imported into `module` via re-exports (i.e. `pub use …` & co.),
+/// which are visible outside of the module itself.
+pub(crate) fn reexports(module: hir::Module, db: &RootDatabase) -> Vec<hir::ModuleDef> {
+    let module_id = hir_def::ModuleId::from(module);
+    let def_map = module_id.def_map(db);
+    let scope = &def_map[module_id.local_id].scope;
+
+    let is_reexport = |visibility: hir_def::visibility::Visibility| match visibility {
+        hir_def::visibility::Visibility::Module(visible_module_id, _) => {
+            visible_module_id != module_id
+        }
+        hir_def::visibility::Visibility::Public => true,
+    };
+
+    let mut reexports = vec![];
+
+    for (_name, per_ns) in scope.entries() {
+        if let Some((def_id, visibility, Some(_import))) = per_ns.types {
+            if is_reexport(visibility) {
+                reexports.push(hir::ModuleDef::from(def_id));
+            }
+        }
+
+        if let Some((def_id, visibility, Some(_import))) = per_ns.values {
+            if is_reexport(visibility) {
+                reexports.push(hir::ModuleDef::from(def_id));
+            }
+        }
+
+        if let Some((macro_id, visibility, Some(_import))) = per_ns.macros {
+            if is_reexport(visibility) {
+                reexports.push(hir::ModuleDef::Macro(macro_id.into()));
+            }
+        }
+    }
+
+    reexports
+}
+
+/// Returns the named items of `module`'s scope that are visible from anywhere (i.e. `pub`),
+/// along with whether they got imported into it (i.e. re-exported), rather than declared by it.
+pub(crate) fn public_scope(
+    module: hir::Module,
+    db: &RootDatabase,
+) -> Vec<(String, hir::ModuleDef, bool)> {
+    let module_id = hir_def::ModuleId::from(module);
+    let def_map = module_id.def_map(db);
+    let scope = &def_map[module_id.local_id].scope;
+
+    let is_public = |visibility: hir_def::visibility::Visibility| {
+        visibility == hir_def::visibility::Visibility::Public
+    };
+
+    // Items imported via globs (e.g. `pub use errors::*`) don't record their import,
+    // so they get told apart from the module's own items by where they're declared:
+    let is_imported = |module_def_hir: hir::ModuleDef, import: bool| {
+        import || module_def_hir.module(db) != Some(module)
+    };
+
+    let mut entries = vec![];
+
+    for (name, per_ns) in scope.entries() {
+        let name = name.display(db).to_string();
+
+        if let Some((def_id, visibility, import)) = per_ns.types {
+            if is_public(visibility) {
+                let module_def_hir = hir::ModuleDef::from(def_id);
+                let is_import = is_imported(module_def_hir, import.is_some());
+                entries.push((name.clone(), module_def_hir, is_import));
+            }
+        }
+
+        if let Some((def_id, visibility, import)) = per_ns.values {
+            if is_public(visibility) {
+                let module_def_hir = hir::ModuleDef::from(def_id);
+                let is_import = is_imported(module_def_hir, import.is_some());
+                entries.push((name.clone(), module_def_hir, is_import));
+            }
+        }
+
+        if let Some((macro_id, visibility, import)) = per_ns.macros {
+            if is_public(visibility) {
+                let module_def_hir = hir::ModuleDef::Macro(macro_id.into());
+                let is_import = is_imported(module_def_hir, import.is_some());
+                entries.push((name.clone(), module_def_hir, is_import));
+            }
+        }
+    }
+
+    // Items living in multiple namespaces (e.g. unit structs) get listed once:
+    entries.dedup_by(|lhs, rhs| lhs.0 == rhs.0 && lhs.1 == rhs.1);
+
+    entries
+}
+
 pub fn module_file(module: hir::Module, db: &RootDatabase, vfs: &Vfs) -> Option<PathBuf> {
     let module_source = module.definition_source(db);
     let is_file_module: bool = match &module_source.value {
@@ -716,6 +1622,221 @@ pub fn module_file(module: hir::Module, db: &RootDatabase, vfs: &Vfs) -> Option<
     Some(path.to_owned())
 }
 
+/// Returns the file path, as well as the (1-based) line and column of an item's name.
+pub(crate) fn location(
+    module_def_hir: hir::ModuleDef,
+    db: &RootDatabase,
+) -> Option<(PathBuf, u32, u32)> {
+    let nav = module_def_hir.try_to_nav(db)?.call_site;
+
+    let path = file_path(nav.file_id, db)?;
+
+    let line_index = db.line_index(nav.file_id);
+    let line_col = line_index.line_col(nav.focus_or_full_range().start());
+
+    Some((path, line_col.line + 1, line_col.col + 1))
+}
+
+/// Returns the file path, as well as the (1-based) line and column, of each reference to any of `targets`
+/// within the source of `module_def_hir` (or, for modules, within their own `use` statements), in order of appearance.
+pub(crate) fn reference_locations(
+    module_def_hir: hir::ModuleDef,
+    targets: &[hir::ModuleDef],
+    db: &RootDatabase,
+) -> Vec<(PathBuf, u32, u32)> {
+    let Some(nav) = module_def_hir.try_to_nav(db).map(|nav| nav.call_site) else {
+        return vec![];
+    };
+
+    let Some(path) = file_path(nav.file_id, db) else {
+        return vec![];
+    };
+
+    // A module's "uses" edges stem from its imports, while its items have edges of their own:
+    let ranges: Vec<TextRange> = match module_def_hir {
+        hir::ModuleDef::Module(module_hir) => {
+            let module_source = module_hir.definition_source(db);
+            let items: Vec<ast::Item> = match module_source.value {
+                ModuleSource::SourceFile(source_file) => source_file.items().collect(),
+                ModuleSource::Module(module) => module
+                    .item_list()
+                    .map(|item_list| item_list.items().collect())
+                    .unwrap_or_default(),
+                ModuleSource::BlockExpr(_) => vec![],
+            };
+
+            items
+                .into_iter()
+                .filter(|item| matches!(item, ast::Item::Use(_)))
+                .map(|item| item.syntax().text_range())
+                .collect()
+        }
+        _ => vec![nav.full_range],
+    };
+
+    let sema = Semantics::new(db);
+    let scope = SearchScope::single_file(nav.file_id);
+
+    let mut offsets: Vec<TextSize> = targets
+        .iter()
+        .flat_map(|target_hir| {
+            Definition::from(*target_hir)
+                .usages(&sema)
+                .in_scope(&scope)
+                .all()
+                .into_iter()
+                .flat_map(|(_file_id, references)| references)
+                .map(|reference| reference.range.start())
+                .collect::<Vec<_>>()
+        })
+        .filter(|offset| ranges.iter().any(|range| range.contains(*offset)))
+        .collect();
+
+    offsets.sort();
+    offsets.dedup();
+
+    let line_index = db.line_index(nav.file_id);
+
+    offsets
+        .into_iter()
+        .map(|offset| {
+            let line_col = line_index.line_col(offset);
+            (path.clone(), line_col.line + 1, line_col.col + 1)
+        })
+        .collect()
+}
+
+/// Returns the number of (non-blank) lines of code of an item's source,
+/// including those of any of its inline sub-items (i.e. `mod foo { … }`).
+pub(crate) fn lines_of_code(module_def_hir: hir::ModuleDef, db: &RootDatabase) -> Option<usize> {
+    let nav = module_def_hir.try_to_nav(db)?.call_site;
+
+    // Both `FileLoader` and `SourceDatabaseExt` provide a `file_text`:
+    let text = ra_ap_ide_db::base_db::SourceDatabaseExt::file_text(db, nav.file_id);
+    let source = text.get(std::ops::Range::<usize>::from(nav.full_range))?;
+
+    Some(
+        source
+            .lines()
+            .filter(|line| !line.trim().is_empty())
+            .count(),
+    )
+}
+
+/// Returns the path of a module's own file (i.e. `foo.rs` for `mod foo;`),
+/// or `None` for modules declared inline (i.e. `mod foo { … }`).
+pub(crate) fn module_file_path(module: hir::Module, db: &RootDatabase) -> Option<PathBuf> {
+    let module_source = module.definition_source(db);
+
+    if !matches!(module_source.value, ModuleSource::SourceFile(_)) {
+        return None;
+    }
+
+    file_path(module_source.file_id.original_file(db), db)
+}
+
+/// Returns the file path, the (1-based) line and column, as well as the text (e.g. `foo::*`),
+/// of each glob import declared by `module` itself (i.e. not by any of its sub-modules).
+pub(crate) fn glob_imports(
+    module: hir::Module,
+    db: &RootDatabase,
+) -> Vec<(PathBuf, u32, u32, String)> {
+    let module_source = module.definition_source(db);
+
+    // Modules generated by macros have no source of their own to point to:
+    if module_source.file_id.is_macro() {
+        return vec![];
+    }
+
+    let items: Vec<ast::Item> = match &module_source.value {
+        ModuleSource::SourceFile(source_file) => source_file.items().collect(),
+        ModuleSource::Module(module) => module
+            .item_list()
+            .map(|item_list| item_list.items().collect())
+            .unwrap_or_default(),
+        ModuleSource::BlockExpr(_) => vec![],
+    };
+
+    let file_id = module_source.file_id.original_file(db);
+
+    let Some(file_path) = file_path(file_id, db) else {
+        return vec![];
+    };
+
+    let line_index = db.line_index(file_id);
+
+    items
+        .into_iter()
+        .filter_map(|item| match item {
+            ast::Item::Use(use_item) => use_item.use_tree(),
+            _ => None,
+        })
+        .flat_map(|use_tree| {
+            use_tree
+                .syntax()
+                .descendants()
+                .filter_map(ast::UseTree::cast)
+                .collect::<Vec<_>>()
+        })
+        .filter(|use_tree| use_tree.star_token().is_some())
+        .map(|use_tree| {
+            let line_col = line_index.line_col(use_tree.syntax().text_range().start());
+            let text = use_tree.syntax().text().to_string();
+
+            (file_path.clone(), line_col.line + 1, line_col.col + 1, text)
+        })
+        .collect()
+}
+
+/// Returns whether a `--focus-on` argument denotes a source file (e.g. `src/parser/expr.rs`)
+/// rather than a use tree (e.g. `my_crate::parser::expr`).
+pub(crate) fn is_focus_file(focus_on: &str) -> bool {
+    focus_on.ends_with(".rs")
+}
+
+/// Returns the paths of the crates' modules owned by the source file at `file_path`,
+/// given either relative to the current directory, or as a suffix of the file's full path.
+///
+/// A file may be owned by several modules (e.g. one of a package's library and one of its binary).
+pub(crate) fn modules_owning_file(
+    file_path: &Path,
+    krates: &[hir::Crate],
+    db: &RootDatabase,
+) -> Vec<String> {
+    let canonical_path = std::env::current_dir()
+        .ok()
+        .and_then(|dir_path| dir_path.join(file_path).canonicalize().ok());
+
+    let mut module_paths: Vec<String> = krates
+        .iter()
+        .flat_map(|krate| krate.modules(db))
+        .filter(|module| {
+            let Some(module_file_path) = module_file_path(*module, db) else {
+                return false;
+            };
+
+            canonical_path.as_deref() == Some(module_file_path.as_path())
+                || module_file_path.ends_with(file_path)
+        })
+        .map(|module| display_path(hir::ModuleDef::Module(module), db))
+        .collect();
+
+    module_paths.sort();
+    module_paths.dedup();
+
+    module_paths
+}
+
+fn file_path(file_id: FileId, db: &RootDatabase) -> Option<PathBuf> {
+    let source_root = db.source_root(db.file_source_root(file_id));
+    let vfs_path = source_root.path_for_file(&file_id)?;
+    let abs_path = vfs_path.as_path()?;
+
+    let path: &Path = abs_path.as_ref();
+
+    Some(path.to_owned())
+}
+
 pub fn moduledef_is_crate(module_def_hir: hir::ModuleDef, _db: &RootDatabase) -> bool {
     let hir::ModuleDef::Module(module) = module_def_hir else {
         return false;
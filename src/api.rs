@@ -0,0 +1,46 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at https://mozilla.org/MPL/2.0/.
+
+//! A stable API for embedding cargo-modules' analysis in other tools.
+//!
+//! Unlike the types used by the command-line interface this API doesn't expose
+//! any of rust-analyzer's (frequently changing) types, but only plain data:
+//!
+//! ```no_run
+//! use cargo_modules::api::{Builder, DotPrinter, Filter, Printer as _};
+//!
+//! fn main() -> anyhow::Result<()> {
+//!     let graph = Builder::new("path/to/Cargo.toml").lib().build()?;
+//!
+//!     for node_id in graph.node_ids() {
+//!         let node = graph.node(node_id).unwrap();
+//!         println!("{} {}", node.kind, node.path);
+//!     }
+//!
+//!     let graph = Filter::new().no_fns(true).focus_on("crate::net").apply(&graph)?;
+//!
+//!     let mut dot = String::new();
+//!     DotPrinter::new().print(&graph, &mut dot)?;
+//!
+//!     Ok(())
+//! }
+//! ```
+
+pub mod analyzers;
+
+mod builder;
+mod filter;
+mod graph;
+mod printer;
+mod visitor;
+
+pub use self::{
+    builder::Builder,
+    filter::Filter,
+    graph::{EdgeData, EdgeKind, Location, ModuleGraph, NodeData, NodeId},
+    printer::{DotPrinter, JsonPrinter, Printer},
+    visitor::Visitor,
+};
+
+pub(crate) use self::graph::{node_data, visit};
@@ -0,0 +1,282 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at https://mozilla.org/MPL/2.0/.
+
+//! Built-in analyses, run as [`Visitor`]s while building a graph.
+
+use std::{
+    collections::{BTreeMap, HashMap},
+    fmt,
+};
+
+use super::{
+    graph::{EdgeData, EdgeKind, NodeData, NodeId},
+    visitor::Visitor,
+};
+
+/// The names of the built-in analyzers, as accepted by [`by_name`].
+pub const NAMES: [&str; 3] = ["stats", "fan-out", "no-std"];
+
+/// A visitor reporting its findings once the graph has been built.
+pub trait Analyzer: Visitor {
+    fn report(&self) -> String;
+}
+
+/// Returns the built-in analyzer with the given name, if any.
+pub fn by_name(name: &str) -> Option<Box<dyn Analyzer>> {
+    match name {
+        "stats" => Some(Box::<Stats>::default()),
+        "fan-out" => Some(Box::<FanOut>::default()),
+        "no-std" => Some(Box::<NoStd>::default()),
+        _ => None,
+    }
+}
+
+/// Counts the graph's nodes and edges by kind.
+#[derive(Clone, Default, Debug)]
+pub struct Stats {
+    nodes: BTreeMap<String, usize>,
+    edges: BTreeMap<&'static str, usize>,
+}
+
+impl Visitor for Stats {
+    fn visit_module(&mut self, node_id: NodeId, node: &NodeData) {
+        self.visit_item(node_id, node);
+    }
+
+    fn visit_item(&mut self, _node_id: NodeId, node: &NodeData) {
+        *self.nodes.entry(node.kind.clone()).or_default() += 1;
+    }
+
+    fn visit_edge(&mut self, edge: &EdgeData) {
+        let kind = match edge.kind {
+            EdgeKind::Uses => "uses",
+            EdgeKind::ReExports => "reexports",
+            EdgeKind::Owns => "owns",
+        };
+
+        *self.edges.entry(kind).or_default() += 1;
+    }
+}
+
+impl Analyzer for Stats {
+    fn report(&self) -> String {
+        let mut report = String::new();
+
+        let node_count: usize = self.nodes.values().sum();
+        report += &format!("Nodes: {node_count}\n");
+
+        for (kind, count) in &self.nodes {
+            report += &format!("  {kind}: {count}\n");
+        }
+
+        let edge_count: usize = self.edges.values().sum();
+        report += &format!("Edges: {edge_count}\n");
+
+        for (kind, count) in &self.edges {
+            report += &format!("  {kind}: {count}\n");
+        }
+
+        report
+    }
+}
+
+/// Lists the nodes using the most other nodes.
+#[derive(Clone, Default, Debug)]
+pub struct FanOut {
+    paths: HashMap<NodeId, String>,
+    uses: HashMap<NodeId, usize>,
+}
+
+impl FanOut {
+    const LIMIT: usize = 10;
+}
+
+impl Visitor for FanOut {
+    fn visit_module(&mut self, node_id: NodeId, node: &NodeData) {
+        self.visit_item(node_id, node);
+    }
+
+    fn visit_item(&mut self, node_id: NodeId, node: &NodeData) {
+        self.paths.insert(node_id, node.path.clone());
+    }
+
+    fn visit_edge(&mut self, edge: &EdgeData) {
+        if edge.kind == EdgeKind::Uses {
+            *self.uses.entry(edge.source).or_default() += 1;
+        }
+    }
+}
+
+impl Analyzer for FanOut {
+    fn report(&self) -> String {
+        let mut counts: Vec<(&str, usize)> = self
+            .uses
+            .iter()
+            .map(|(node_id, count)| (self.paths[node_id].as_str(), *count))
+            .collect();
+
+        // Sort by descending count, then by path for stable output:
+        counts.sort_by(|lhs, rhs| rhs.1.cmp(&lhs.1).then(lhs.0.cmp(rhs.0)));
+
+        let mut report = String::from("Highest fan-out (outgoing \"uses\" edges):\n");
+
+        for (path, count) in counts.into_iter().take(Self::LIMIT) {
+            report += &format!("  {count:>4} {path}\n");
+        }
+
+        report
+    }
+}
+
+/// The standard library crate a node (transitively) depends on.
+#[derive(Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Debug)]
+pub enum Requirement {
+    /// The node only depends on `core` (or on nothing at all).
+    Core,
+    /// The node depends on `alloc`, but not on `std`.
+    Alloc,
+    /// The node depends on `std`.
+    Std,
+}
+
+impl Requirement {
+    /// Returns the requirement of an external node by its crate (i.e. the first segment of its path).
+    fn of_external(path: &str) -> Self {
+        match path.split("::").next() {
+            Some("std") => Self::Std,
+            Some("alloc") => Self::Alloc,
+            _ => Self::Core,
+        }
+    }
+}
+
+impl fmt::Display for Requirement {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.pad(match self {
+            Self::Core => "core",
+            Self::Alloc => "alloc",
+            Self::Std => "std",
+        })
+    }
+}
+
+/// Marks which modules and items (transitively) depend on `std`,
+/// as opposed to only on `core` or `alloc`.
+///
+/// A node requires whatever the nodes it uses (or re-exports) require,
+/// and a module additionally requires whatever its items require.
+#[derive(Clone, Default, Debug)]
+pub struct NoStd {
+    paths: HashMap<NodeId, String>,
+    external: HashMap<NodeId, bool>,
+    modules: Vec<NodeId>,
+    owns: Vec<(NodeId, NodeId)>,
+    uses: Vec<(NodeId, NodeId)>,
+}
+
+impl NoStd {
+    /// Returns the requirement of each of the graph's nodes.
+    pub fn requirements(&self) -> HashMap<NodeId, Requirement> {
+        let mut requirements: HashMap<NodeId, Requirement> = self
+            .paths
+            .iter()
+            .map(|(node_id, path)| {
+                let requirement = if self.external[node_id] {
+                    Requirement::of_external(path)
+                } else {
+                    Requirement::Core
+                };
+                (*node_id, requirement)
+            })
+            .collect();
+
+        // Propagate the requirements from the used nodes to their users
+        // and from the owned nodes to their owners, until they settle:
+        let dependencies: Vec<(NodeId, NodeId)> = self
+            .uses
+            .iter()
+            .chain(&self.owns)
+            .copied()
+            .filter(|(source, _)| !self.external[source])
+            .collect();
+
+        loop {
+            let mut changed = false;
+
+            for (source, target) in &dependencies {
+                let required = requirements[target];
+
+                if required > requirements[source] {
+                    requirements.insert(*source, required);
+                    changed = true;
+                }
+            }
+
+            if !changed {
+                return requirements;
+            }
+        }
+    }
+}
+
+impl Visitor for NoStd {
+    fn visit_module(&mut self, node_id: NodeId, node: &NodeData) {
+        self.visit_item(node_id, node);
+
+        if !node.is_external {
+            self.modules.push(node_id);
+        }
+    }
+
+    fn visit_item(&mut self, node_id: NodeId, node: &NodeData) {
+        self.paths.insert(node_id, node.path.clone());
+        self.external.insert(node_id, node.is_external);
+    }
+
+    fn visit_edge(&mut self, edge: &EdgeData) {
+        match edge.kind {
+            EdgeKind::Owns => self.owns.push((edge.source, edge.target)),
+            EdgeKind::Uses | EdgeKind::ReExports => self.uses.push((edge.source, edge.target)),
+        }
+    }
+}
+
+impl Analyzer for NoStd {
+    fn report(&self) -> String {
+        let requirements = self.requirements();
+
+        let mut modules: Vec<(&str, Requirement)> = self
+            .modules
+            .iter()
+            .map(|node_id| (self.paths[node_id].as_str(), requirements[node_id]))
+            .collect();
+
+        modules.sort();
+
+        let mut report = String::from("no_std compatibility (by module):\n");
+
+        for (path, requirement) in modules {
+            report += &format!("  {requirement:<5} {path}\n");
+        }
+
+        let mut std_uses: Vec<(&str, &str)> = self
+            .uses
+            .iter()
+            .filter(|(source, target)| !self.external[source] && self.external[target])
+            .filter(|(_, target)| Requirement::of_external(&self.paths[target]) == Requirement::Std)
+            .map(|(source, target)| (self.paths[source].as_str(), self.paths[target].as_str()))
+            .collect();
+
+        std_uses.sort();
+        std_uses.dedup();
+
+        report += &format!("Uses of std ({}):\n", std_uses.len());
+
+        for (source, target) in std_uses {
+            report += &format!("  {source} -> {target}\n");
+        }
+
+        report
+    }
+}
@@ -0,0 +1,152 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at https://mozilla.org/MPL/2.0/.
+
+use std::path::PathBuf;
+
+use clap::Parser as _;
+use log::trace;
+
+use crate::{
+    analyzer::{load_workspace, LoadOptions},
+    graph::GraphBuilder,
+    options::{ColorChoice, GeneralOptions, ProjectOptions},
+};
+
+use super::{graph::ModuleGraph, visitor::Visitor};
+
+/// Loads a project and builds its [`ModuleGraph`].
+///
+/// The builder's methods mirror the command-line interface's project options.
+#[derive(Clone, Debug)]
+pub struct Builder {
+    project: ProjectOptions,
+    cfg_test: bool,
+    sysroot: bool,
+}
+
+impl Builder {
+    /// Creates a builder for the project at `manifest_path`
+    /// (i.e. a `Cargo.toml`, a `rust-project.json`, or a directory containing either).
+    pub fn new(manifest_path: impl Into<PathBuf>) -> Self {
+        let mut project = ProjectOptions::parse_from(["cargo-modules"]);
+        project.manifest_path = manifest_path.into();
+
+        Self {
+            project,
+            cfg_test: false,
+            sysroot: false,
+        }
+    }
+
+    /// Processes only the package's library.
+    pub fn lib(mut self) -> Self {
+        self.project.lib = true;
+        self
+    }
+
+    /// Processes only the specified binary.
+    pub fn bin(mut self, name: impl Into<String>) -> Self {
+        self.project.bin = Some(name.into());
+        self
+    }
+
+    /// Processes the package's library and binaries as a single merged graph.
+    pub fn merge_targets(mut self) -> Self {
+        self.project.merge_targets = true;
+        self
+    }
+
+    /// Adds a package to process (see `cargo help pkgid`), accepting glob patterns.
+    pub fn package(mut self, package: impl Into<String>) -> Self {
+        self.project.package.push(package.into());
+        self
+    }
+
+    /// Adds a feature to activate.
+    pub fn feature(mut self, feature: impl Into<String>) -> Self {
+        self.project.features.push(feature.into());
+        self
+    }
+
+    /// Activates all available features.
+    pub fn all_features(mut self, all_features: bool) -> Self {
+        self.project.all_features = all_features;
+        self
+    }
+
+    /// Doesn't activate the `default` feature.
+    pub fn no_default_features(mut self, no_default_features: bool) -> Self {
+        self.project.no_default_features = no_default_features;
+        self
+    }
+
+    /// Analyzes for the given target triple (e.g. `x86_64-pc-windows-msvc`).
+    pub fn target(mut self, target: impl Into<String>) -> Self {
+        self.project.target = Some(target.into());
+        self
+    }
+
+    /// Resolves the sysroot via the given rustup toolchain (e.g. `nightly-2024-06-01`).
+    pub fn toolchain(mut self, toolchain: impl Into<String>) -> Self {
+        self.project.toolchain = Some(toolchain.into());
+        self
+    }
+
+    /// Analyzes against the sysroot at `sysroot_path` (as printed by `rustc --print sysroot`).
+    pub fn sysroot_path(mut self, sysroot_path: impl Into<PathBuf>) -> Self {
+        self.project.sysroot = Some(sysroot_path.into());
+        self
+    }
+
+    /// Analyzes with `#[cfg(test)]` enabled (i.e as if built via `cargo test`).
+    pub fn cfg_test(mut self, cfg_test: bool) -> Self {
+        self.cfg_test = cfg_test;
+        self
+    }
+
+    /// Includes sysroot crates (`std`, `core` & friends) in the analysis.
+    pub fn sysroot(mut self, sysroot: bool) -> Self {
+        self.sysroot = sysroot;
+        self
+    }
+
+    /// Loads the project and builds its graph.
+    pub fn build(self) -> anyhow::Result<ModuleGraph> {
+        self.load(None)
+    }
+
+    /// Loads the project and builds its graph, notifying `visitor` of each node and edge.
+    pub fn build_with_visitor(self, visitor: &mut dyn Visitor) -> anyhow::Result<ModuleGraph> {
+        self.load(Some(visitor))
+    }
+
+    fn load(self, visitor: Option<&mut dyn Visitor>) -> anyhow::Result<ModuleGraph> {
+        let general_options = GeneralOptions {
+            verbose: false,
+            timings: false,
+            summary: None,
+            theme: "dark".to_owned(),
+            color: ColorChoice::Never,
+        };
+
+        let load_options = LoadOptions {
+            cfg_test: self.cfg_test,
+            sysroot: self.sysroot,
+        };
+
+        let (krates, host, _vfs) = load_workspace(&general_options, &self.project, &load_options)?;
+
+        trace!("Building graph ...");
+
+        let mut builder = GraphBuilder::new(host.raw_database(), &krates);
+
+        if let Some(visitor) = visitor {
+            builder = builder.with_visitor(visitor);
+        }
+
+        let (graph, root_idxs) = builder.build()?;
+
+        Ok(ModuleGraph::new(krates, host, graph, root_idxs))
+    }
+}
@@ -0,0 +1,167 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at https://mozilla.org/MPL/2.0/.
+
+use clap::Parser as _;
+use log::trace;
+
+use crate::{
+    command::dependencies::{filter, options::Options},
+    selector::Selector,
+};
+
+use super::graph::ModuleGraph;
+
+/// Filters a [`ModuleGraph`], just like the `dependencies` command does.
+///
+/// The filter's methods mirror the command-line interface's selection options.
+#[derive(Clone, Default, Debug)]
+pub struct Filter {
+    focus_on: Option<String>,
+    max_depth: Option<usize>,
+    select: Option<String>,
+    no_externs: bool,
+    no_fns: bool,
+    no_modules: bool,
+    no_sysroot: bool,
+    collapse_sysroot: bool,
+    collapse_externs: bool,
+    extern_filter: Vec<String>,
+    no_traits: bool,
+    no_types: bool,
+    no_uses: bool,
+    no_test_uses: bool,
+}
+
+impl Filter {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Focuses the graph on a particular path or use-tree's environment,
+    /// e.g. `"foo::bar::{self, baz, blee::*}"`.
+    pub fn focus_on(mut self, focus_on: impl Into<String>) -> Self {
+        self.focus_on = Some(focus_on.into());
+        self
+    }
+
+    /// Limits the graph's depth relative to the crate's root node, or the nodes focused on.
+    pub fn max_depth(mut self, max_depth: usize) -> Self {
+        self.max_depth = Some(max_depth);
+        self
+    }
+
+    /// Selects nodes or edges via an expression,
+    /// e.g. `kind == mod && path =~ "net::*"` or `edges(uses) from api to db`.
+    pub fn select(mut self, select: impl Into<String>) -> Self {
+        self.select = Some(select.into());
+        self
+    }
+
+    /// Filters out extern items from extern crates.
+    pub fn no_externs(mut self, no_externs: bool) -> Self {
+        self.no_externs = no_externs;
+        self
+    }
+
+    /// Filters out functions (e.g. fns, async fns, const fns).
+    pub fn no_fns(mut self, no_fns: bool) -> Self {
+        self.no_fns = no_fns;
+        self
+    }
+
+    /// Filters out modules (e.g. `mod foo`, `mod foo {}`).
+    pub fn no_modules(mut self, no_modules: bool) -> Self {
+        self.no_modules = no_modules;
+        self
+    }
+
+    /// Filters out sysroot crates (`std`, `core` & friends).
+    pub fn no_sysroot(mut self, no_sysroot: bool) -> Self {
+        self.no_sysroot = no_sysroot;
+        self
+    }
+
+    /// Collapses items of sysroot crates (`std`, `core` & friends) into a single node per crate.
+    pub fn collapse_sysroot(mut self, collapse_sysroot: bool) -> Self {
+        self.collapse_sysroot = collapse_sysroot;
+        self
+    }
+
+    /// Collapses items of extern crates into a single node per crate.
+    pub fn collapse_externs(mut self, collapse_externs: bool) -> Self {
+        self.collapse_externs = collapse_externs;
+        self
+    }
+
+    /// Only retains the items of extern crates whose names match the glob pattern (e.g. `"tokio*"`).
+    /// May be called multiple times, retaining the crates matching any of the patterns.
+    pub fn extern_filter(mut self, pattern: impl Into<String>) -> Self {
+        self.extern_filter.push(pattern.into());
+        self
+    }
+
+    /// Filters out traits (e.g. trait, unsafe trait).
+    pub fn no_traits(mut self, no_traits: bool) -> Self {
+        self.no_traits = no_traits;
+        self
+    }
+
+    /// Filters out types (e.g. structs, unions, enums).
+    pub fn no_types(mut self, no_types: bool) -> Self {
+        self.no_types = no_types;
+        self
+    }
+
+    /// Filters out "use" edges.
+    pub fn no_uses(mut self, no_uses: bool) -> Self {
+        self.no_uses = no_uses;
+        self
+    }
+
+    /// Filters out "use" edges stemming from test-only code (i.e. `#[cfg(test)]` modules and items).
+    pub fn no_test_uses(mut self, no_test_uses: bool) -> Self {
+        self.no_test_uses = no_test_uses;
+        self
+    }
+
+    /// Returns the filtered graph.
+    pub fn apply(&self, graph: &ModuleGraph) -> anyhow::Result<ModuleGraph> {
+        let options = self.options()?;
+
+        trace!("Filtering graph ...");
+
+        let filter = filter::Filter::new(&options, graph.db(), graph.krates());
+        let filtered = filter.filter(graph.graph(), graph.root_idxs())?;
+
+        Ok(graph.with_graph(filtered))
+    }
+
+    fn options(&self) -> anyhow::Result<Options> {
+        let mut options = Options::parse_from(["dependencies"]);
+
+        options.focus_on.clone_from(&self.focus_on);
+        options.max_depth = self.max_depth;
+
+        options.select = match &self.select {
+            Some(select) => Some(select.parse::<Selector>().map_err(anyhow::Error::msg)?),
+            None => None,
+        };
+
+        let selection = &mut options.selection;
+
+        selection.no_externs = self.no_externs;
+        selection.no_fns = self.no_fns;
+        selection.no_modules = self.no_modules;
+        selection.no_sysroot = self.no_sysroot;
+        selection.collapse_sysroot = self.collapse_sysroot;
+        selection.collapse_externs = self.collapse_externs;
+        selection.extern_filter.clone_from(&self.extern_filter);
+        selection.no_traits = self.no_traits;
+        selection.no_types = self.no_types;
+        selection.no_uses = self.no_uses;
+        selection.no_test_uses = self.no_test_uses;
+
+        Ok(options)
+    }
+}
@@ -0,0 +1,274 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at https://mozilla.org/MPL/2.0/.
+
+use std::{fmt, path::PathBuf, rc::Rc};
+
+use petgraph::{
+    graph::NodeIndex,
+    visit::{EdgeRef as _, IntoEdgeReferences as _},
+};
+use ra_ap_hir as hir;
+use ra_ap_ide::{AnalysisHost, RootDatabase};
+
+use crate::{
+    analyzer,
+    graph::{Edge, Graph, Node, Relationship},
+};
+
+use super::visitor::Visitor;
+
+/// Identifies a node within a [`ModuleGraph`] (and graphs filtered from it).
+#[derive(Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Debug)]
+pub struct NodeId(usize);
+
+impl NodeId {
+    pub(crate) fn from_index(node_idx: NodeIndex) -> Self {
+        Self(node_idx.index())
+    }
+
+    pub(crate) fn index(self) -> NodeIndex {
+        NodeIndex::new(self.0)
+    }
+}
+
+/// The data of a node (i.e. a crate, module or item).
+#[derive(Clone, PartialEq, Eq, Debug)]
+#[non_exhaustive]
+pub struct NodeData {
+    /// The node's name (e.g. `Bar`).
+    pub name: String,
+    /// The node's path (e.g. `foo::Bar`).
+    pub path: String,
+    /// The node's kind (e.g. `crate`, `mod`, `struct`).
+    pub kind: String,
+    /// The node's visibility (e.g. `pub`, `pub(crate)`).
+    pub visibility: String,
+    /// Whether the node is a crate's root.
+    pub is_crate: bool,
+    /// Whether the node belongs to a crate outside of the analyzed ones.
+    pub is_external: bool,
+    /// The node's identifier derived from its crate name, path and kind, which (unlike its [`NodeId`])
+    /// stays the same across runs and crate versions.
+    pub stable_id: String,
+}
+
+pub(crate) fn node_data(node: &Node, krates: &[hir::Crate], db: &RootDatabase) -> NodeData {
+    let is_external = analyzer::krate(node.hir, db).map_or(true, |krate| !krates.contains(&krate));
+
+    NodeData {
+        name: node.display_name(db),
+        path: node.display_path(db),
+        kind: node.kind_display_name(db).to_string(),
+        visibility: node.visibility(db).to_string(),
+        is_crate: analyzer::moduledef_is_crate(node.hir, db),
+        is_external,
+        stable_id: node.stable_id(db),
+    }
+}
+
+/// Notifies `visitor` of each of the (already built) graph's nodes, followed by its edges.
+pub(crate) fn visit(
+    graph: &Graph<Node, Edge>,
+    krates: &[hir::Crate],
+    db: &RootDatabase,
+    visitor: &mut dyn Visitor,
+) {
+    for node_idx in graph.node_indices() {
+        let node = &graph[node_idx];
+        let node_id = NodeId::from_index(node_idx);
+        let node_data = node_data(node, krates, db);
+
+        match node.hir {
+            hir::ModuleDef::Module(_) => visitor.visit_module(node_id, &node_data),
+            _ => visitor.visit_item(node_id, &node_data),
+        }
+    }
+
+    for edge_ref in graph.edge_references() {
+        visitor.visit_edge(&EdgeData {
+            source: NodeId::from_index(edge_ref.source()),
+            target: NodeId::from_index(edge_ref.target()),
+            kind: edge_ref.weight().kind.into(),
+            weight: edge_ref.weight().weight,
+        });
+    }
+}
+
+/// The source location of a node's name.
+#[derive(Clone, PartialEq, Eq, Debug)]
+#[non_exhaustive]
+pub struct Location {
+    pub file_path: PathBuf,
+    /// The 1-based line.
+    pub line: u32,
+    /// The 1-based column.
+    pub column: u32,
+}
+
+#[derive(Copy, Clone, PartialEq, Eq, Hash, Debug)]
+pub enum EdgeKind {
+    /// The source node uses the target node.
+    Uses,
+    /// The source node re-exports the target node (e.g. via `pub use`).
+    ReExports,
+    /// The source node owns (i.e. contains) the target node.
+    Owns,
+}
+
+impl From<Relationship> for EdgeKind {
+    fn from(relationship: Relationship) -> Self {
+        match relationship {
+            Relationship::Uses => Self::Uses,
+            Relationship::ReExports => Self::ReExports,
+            Relationship::Owns => Self::Owns,
+        }
+    }
+}
+
+#[derive(Copy, Clone, PartialEq, Eq, Hash, Debug)]
+#[non_exhaustive]
+pub struct EdgeData {
+    pub source: NodeId,
+    pub target: NodeId,
+    pub kind: EdgeKind,
+    /// The number of item-level relationships the edge stands for
+    /// (e.g. the number of items of a module that use another module's items).
+    pub weight: usize,
+}
+
+/// The analysis a graph's nodes refer to, which is shared with any graphs filtered from it.
+struct Workspace {
+    krates: Vec<hir::Crate>,
+    host: AnalysisHost,
+}
+
+/// The graph of a project's crates, modules and items, as well as their relationships.
+#[derive(Clone)]
+pub struct ModuleGraph {
+    workspace: Rc<Workspace>,
+    graph: Graph<Node, Edge>,
+    root_idxs: Vec<NodeIndex>,
+}
+
+impl ModuleGraph {
+    pub(super) fn new(
+        krates: Vec<hir::Crate>,
+        host: AnalysisHost,
+        graph: Graph<Node, Edge>,
+        root_idxs: Vec<NodeIndex>,
+    ) -> Self {
+        let workspace = Rc::new(Workspace { krates, host });
+
+        Self {
+            workspace,
+            graph,
+            root_idxs,
+        }
+    }
+
+    /// Returns a graph with different nodes and edges, sharing this graph's analysis.
+    pub(super) fn with_graph(&self, graph: Graph<Node, Edge>) -> Self {
+        Self {
+            workspace: self.workspace.clone(),
+            graph,
+            root_idxs: self.root_idxs.clone(),
+        }
+    }
+
+    /// Returns the crates' root nodes.
+    pub fn roots(&self) -> Vec<NodeId> {
+        self.root_idxs
+            .iter()
+            .copied()
+            .map(NodeId::from_index)
+            .collect()
+    }
+
+    pub fn node_count(&self) -> usize {
+        self.graph.node_count()
+    }
+
+    pub fn edge_count(&self) -> usize {
+        self.graph.edge_count()
+    }
+
+    pub fn node_ids(&self) -> impl Iterator<Item = NodeId> + '_ {
+        self.graph.node_indices().map(NodeId::from_index)
+    }
+
+    pub fn node(&self, node_id: NodeId) -> Option<NodeData> {
+        let node = self.graph.node_weight(node_id.index())?;
+
+        Some(node_data(node, self.krates(), self.db()))
+    }
+
+    /// Returns the source location of the node's name, if available.
+    pub fn location(&self, node_id: NodeId) -> Option<Location> {
+        let node = self.graph.node_weight(node_id.index())?;
+
+        let (file_path, line, column) = analyzer::location(node.hir, self.db())?;
+
+        Some(Location {
+            file_path,
+            line,
+            column,
+        })
+    }
+
+    /// Returns the node with the given path (e.g. `foo::Bar`), if any.
+    pub fn find(&self, path: &str) -> Option<NodeId> {
+        let db = self.db();
+
+        self.graph
+            .node_indices()
+            .find(|node_idx| self.graph[*node_idx].display_path(db) == path)
+            .map(NodeId::from_index)
+    }
+
+    pub fn edges(&self) -> impl Iterator<Item = EdgeData> + '_ {
+        self.graph.edge_references().map(|edge_ref| EdgeData {
+            source: NodeId::from_index(edge_ref.source()),
+            target: NodeId::from_index(edge_ref.target()),
+            kind: edge_ref.weight().kind.into(),
+            weight: edge_ref.weight().weight,
+        })
+    }
+
+    /// Returns the edges leaving the given node.
+    pub fn outgoing(&self, node_id: NodeId) -> impl Iterator<Item = EdgeData> + '_ {
+        self.graph
+            .edges(node_id.index())
+            .map(move |edge_ref| EdgeData {
+                source: node_id,
+                target: NodeId::from_index(edge_ref.target()),
+                kind: edge_ref.weight().kind.into(),
+                weight: edge_ref.weight().weight,
+            })
+    }
+
+    pub(crate) fn krates(&self) -> &[hir::Crate] {
+        &self.workspace.krates
+    }
+
+    pub(crate) fn db(&self) -> &RootDatabase {
+        self.workspace.host.raw_database()
+    }
+
+    pub(crate) fn graph(&self) -> &Graph<Node, Edge> {
+        &self.graph
+    }
+
+    pub(crate) fn root_idxs(&self) -> &[NodeIndex] {
+        &self.root_idxs
+    }
+}
+
+impl fmt::Debug for ModuleGraph {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ModuleGraph")
+            .field("node_count", &self.node_count())
+            .field("edge_count", &self.edge_count())
+            .finish()
+    }
+}
@@ -0,0 +1,89 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at https://mozilla.org/MPL/2.0/.
+
+use std::fmt;
+
+use clap::Parser as _;
+
+use crate::command::{
+    dependencies::{
+        options::{LayoutAlgorithm, LayoutDirection, Options},
+        printer,
+    },
+    serve::export::graph_to_json,
+};
+
+use super::graph::ModuleGraph;
+
+/// Renders a [`ModuleGraph`] into some textual format.
+pub trait Printer {
+    fn print(&self, graph: &ModuleGraph, f: &mut dyn fmt::Write) -> anyhow::Result<()>;
+}
+
+/// Renders a graph in graphviz' dot format, as printed by the `dependencies` command.
+#[derive(Clone, Debug)]
+pub struct DotPrinter {
+    layout: LayoutAlgorithm,
+    direction: LayoutDirection,
+}
+
+impl DotPrinter {
+    pub fn new() -> Self {
+        Self {
+            layout: LayoutAlgorithm::Neato,
+            direction: LayoutDirection::LeftToRight,
+        }
+    }
+
+    /// Sets the graph layout algorithm to use
+    /// (e.g. dot, neato, twopi, circo, fdp, sfdp).
+    pub fn layout(mut self, layout: &str) -> anyhow::Result<Self> {
+        self.layout = layout.parse().map_err(anyhow::Error::msg)?;
+        Ok(self)
+    }
+
+    /// Sets the direction to lay out the graph's ranks in
+    /// (e.g. TB, LR, BT, RL).
+    pub fn direction(mut self, direction: &str) -> anyhow::Result<Self> {
+        self.direction = direction.parse().map_err(anyhow::Error::msg)?;
+        Ok(self)
+    }
+}
+
+impl Default for DotPrinter {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Printer for DotPrinter {
+    fn print(&self, graph: &ModuleGraph, f: &mut dyn fmt::Write) -> anyhow::Result<()> {
+        let mut options = Options::parse_from(["dependencies"]);
+        options.layout = self.layout;
+        options.layout_direction = self.direction;
+
+        let printer = printer::Printer::new(&options, graph.krates(), graph.db());
+        printer.fmt(f, graph.graph(), graph.root_idxs())
+    }
+}
+
+/// Renders a graph as JSON, as served by the `serve` command.
+#[derive(Clone, Default, Debug)]
+pub struct JsonPrinter;
+
+impl JsonPrinter {
+    pub fn new() -> Self {
+        Self
+    }
+}
+
+impl Printer for JsonPrinter {
+    fn print(&self, graph: &ModuleGraph, f: &mut dyn fmt::Write) -> anyhow::Result<()> {
+        let json = graph_to_json(graph.graph(), graph.root_idxs(), graph.krates(), graph.db())?;
+
+        f.write_str(&json.dump())?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,43 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at https://mozilla.org/MPL/2.0/.
+
+use super::graph::{EdgeData, NodeData, NodeId};
+
+/// Gets notified of each node and edge as they get discovered while building a graph
+/// (see [`Builder::build_with_visitor`](super::Builder::build_with_visitor)).
+///
+/// Visitors see the full graph, prior to any filtering.
+pub trait Visitor {
+    /// Called for each discovered module (including crates' root modules).
+    fn visit_module(&mut self, _node_id: NodeId, _node: &NodeData) {}
+
+    /// Called for each discovered item other than modules (e.g. functions, types, traits).
+    fn visit_item(&mut self, _node_id: NodeId, _node: &NodeData) {}
+
+    /// Called for each discovered edge, after both of its nodes have been visited.
+    fn visit_edge(&mut self, _edge: &EdgeData) {}
+}
+
+impl<V> Visitor for Vec<Box<V>>
+where
+    V: Visitor + ?Sized,
+{
+    fn visit_module(&mut self, node_id: NodeId, node: &NodeData) {
+        for visitor in self.iter_mut() {
+            visitor.visit_module(node_id, node);
+        }
+    }
+
+    fn visit_item(&mut self, node_id: NodeId, node: &NodeData) {
+        for visitor in self.iter_mut() {
+            visitor.visit_item(node_id, node);
+        }
+    }
+
+    fn visit_edge(&mut self, edge: &EdgeData) {
+        for visitor in self.iter_mut() {
+            visitor.visit_edge(edge);
+        }
+    }
+}
@@ -0,0 +1,353 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at https://mozilla.org/MPL/2.0/.
+
+//! On-disk cache of command outputs, keyed by the command's arguments, color and toolchain settings,
+//! as well as the contents of the project's (and its path dependencies') source files,
+//! manifests and configs.
+
+use std::{
+    ffi::OsStr,
+    fs,
+    hash::Hasher,
+    path::{Path, PathBuf},
+    process,
+};
+
+use fnv::FnvHasher;
+use log::{debug, trace};
+
+use crate::{config, glob::glob_matches, options::ProjectOptions};
+
+const CACHE_DIR_NAME: &str = "cargo-modules";
+const IGNORED_DIR_NAMES: [&str; 2] = ["target", ".git"];
+const DEPENDENCY_TABLE_NAMES: [&str; 3] =
+    ["dependencies", "dev-dependencies", "build-dependencies"];
+const TOOLCHAIN_FILE_PATHS: [&str; 4] = [
+    ".cargo/config",
+    ".cargo/config.toml",
+    "rust-toolchain",
+    "rust-toolchain.toml",
+];
+const ENV_VAR_NAMES: [&str; 13] = [
+    "NO_COLOR",
+    "CLICOLOR",
+    "CLICOLOR_FORCE",
+    "RUSTUP_TOOLCHAIN",
+    "RUSTC",
+    "RUSTC_WRAPPER",
+    "RUSTFLAGS",
+    "CARGO",
+    "CARGO_HOME",
+    "CARGO_BUILD_TARGET",
+    "CARGO_BUILD_RUSTFLAGS",
+    "CARGO_ENCODED_RUSTFLAGS",
+    "RUST_SRC_PATH",
+];
+
+#[derive(Debug)]
+pub(crate) struct Cache {
+    dir_path: PathBuf,
+    command_key: u64,
+    content_key: u64,
+}
+
+impl Cache {
+    pub(crate) fn new(project_options: &ProjectOptions, command: &str) -> anyhow::Result<Self> {
+        let project_path = project_options.manifest_path.canonicalize()?;
+        let project_dir = if project_path.is_file() {
+            project_path
+                .parent()
+                .map(Path::to_path_buf)
+                .unwrap_or(project_path)
+        } else {
+            project_path
+        };
+
+        let workspace_dir = workspace_dir(&project_dir);
+
+        let target_dir = match std::env::var_os("CARGO_TARGET_DIR") {
+            Some(target_dir) => PathBuf::from(target_dir),
+            None => workspace_dir.join("target"),
+        };
+
+        let dir_path = target_dir.join(CACHE_DIR_NAME);
+
+        // Besides the command's (config-merged) arguments, the output also depends on
+        // whether it gets colored and on the toolchain used for analyzing the project:
+
+        let command_key = {
+            let mut hasher = FnvHasher::default();
+            hash_field(&mut hasher, env!("CARGO_PKG_VERSION").as_bytes());
+            hash_field(&mut hasher, project_dir.as_os_str().as_encoded_bytes());
+            hash_field(&mut hasher, command.as_bytes());
+            hash_field(&mut hasher, &[u8::from(yansi::is_enabled())]);
+            hash_field(&mut hasher, &rustc_version(&project_dir));
+            for var_name in ENV_VAR_NAMES {
+                let value = std::env::var_os(var_name).unwrap_or_default();
+                hash_field(&mut hasher, var_name.as_bytes());
+                hash_field(&mut hasher, value.as_encoded_bytes());
+            }
+            hasher.finish()
+        };
+
+        let content_key = {
+            let mut file_paths = vec![];
+            collect_source_files(&workspace_dir, &mut file_paths)?;
+            collect_path_dependency_files(&workspace_dir, &mut file_paths)?;
+            file_paths.sort();
+
+            // Cargo and rustup pick up their configs from any of the project's ancestor directories:
+            for dir_path in project_dir.ancestors() {
+                for relative_path in TOOLCHAIN_FILE_PATHS {
+                    let file_path = dir_path.join(relative_path);
+                    if file_path.is_file() && !file_paths.contains(&file_path) {
+                        file_paths.push(file_path);
+                    }
+                }
+            }
+
+            let mut hasher = FnvHasher::default();
+            for file_path in file_paths {
+                hash_field(&mut hasher, file_path.as_os_str().as_encoded_bytes());
+                hash_field(&mut hasher, &fs::read(&file_path)?);
+            }
+            hasher.finish()
+        };
+
+        Ok(Self {
+            dir_path,
+            command_key,
+            content_key,
+        })
+    }
+
+    pub(crate) fn load(&self) -> Option<String> {
+        let file_path = self.file_path();
+
+        match fs::read_to_string(&file_path) {
+            Ok(output) => {
+                debug!("Using cached output from {file_path:?}");
+                Some(output)
+            }
+            Err(_) => {
+                trace!("No cached output found at {file_path:?}");
+                None
+            }
+        }
+    }
+
+    pub(crate) fn store(&self, output: &str) -> anyhow::Result<()> {
+        fs::create_dir_all(&self.dir_path)?;
+
+        // Outputs of previous runs of the same command are stale by now:
+        let command_prefix = format!("{:016x}-", self.command_key);
+        for entry in fs::read_dir(&self.dir_path)? {
+            let entry = entry?;
+            if entry
+                .file_name()
+                .to_str()
+                .is_some_and(|file_name| file_name.starts_with(&command_prefix))
+            {
+                fs::remove_file(entry.path())?;
+            }
+        }
+
+        let file_path = self.file_path();
+        debug!("Caching output at {file_path:?}");
+        fs::write(file_path, output)?;
+
+        Ok(())
+    }
+
+    fn file_path(&self) -> PathBuf {
+        self.dir_path.join(format!(
+            "{:016x}-{:016x}.out",
+            self.command_key, self.content_key
+        ))
+    }
+}
+
+/// Hashes `bytes`, followed by a separator, so that adjacent fields can't run into each other.
+fn hash_field(hasher: &mut FnvHasher, bytes: &[u8]) {
+    hasher.write(bytes);
+    hasher.write_u8(0xff);
+}
+
+/// Returns rustc's verbose version info, whose commit hash changes with every toolchain update
+/// (unlike the toolchain's name, e.g. `stable`).
+fn rustc_version(project_dir: &Path) -> Vec<u8> {
+    // Rustup picks the toolchain based on the directory it gets invoked in:
+    let rustc = std::env::var_os("RUSTC").unwrap_or_else(|| "rustc".into());
+
+    process::Command::new(rustc)
+        .arg("-vV")
+        .current_dir(project_dir)
+        .output()
+        .map(|output| output.stdout)
+        .unwrap_or_default()
+}
+
+/// Returns the directory of the workspace `project_dir` is a member of, or `project_dir` itself.
+fn workspace_dir(project_dir: &Path) -> PathBuf {
+    project_dir
+        .ancestors()
+        .find(|dir_path| {
+            let manifest = read_manifest(&dir_path.join("Cargo.toml"));
+
+            manifest
+                .as_ref()
+                .and_then(|manifest| manifest.get("workspace"))
+                .and_then(toml::Value::as_table)
+                .is_some_and(|workspace| is_workspace_member(workspace, dir_path, project_dir))
+        })
+        .unwrap_or(project_dir)
+        .to_path_buf()
+}
+
+/// Returns whether `project_dir` is the root of, or matches the `members` (but not the `exclude`)
+/// of the `workspace` table found in `workspace_dir`.
+fn is_workspace_member(workspace: &toml::Table, workspace_dir: &Path, project_dir: &Path) -> bool {
+    let Ok(relative_path) = project_dir.strip_prefix(workspace_dir) else {
+        return false;
+    };
+
+    if relative_path.as_os_str().is_empty() {
+        return true;
+    }
+
+    // Workspaces always list their members with `/` as separator:
+    let relative_path = relative_path
+        .components()
+        .map(|component| component.as_os_str().to_string_lossy())
+        .collect::<Vec<_>>()
+        .join("/");
+
+    let is_matched_by = |key: &str| {
+        workspace
+            .get(key)
+            .and_then(toml::Value::as_array)
+            .is_some_and(|patterns| {
+                patterns
+                    .iter()
+                    .filter_map(toml::Value::as_str)
+                    .any(|pattern| {
+                        glob_matches(
+                            pattern.trim_start_matches("./").trim_end_matches('/'),
+                            &relative_path,
+                        )
+                    })
+            })
+    };
+
+    is_matched_by("members") && !is_matched_by("exclude")
+}
+
+fn read_manifest(path: &Path) -> Option<toml::Table> {
+    let contents = fs::read_to_string(path).ok()?;
+
+    toml::from_str(&contents).ok()
+}
+
+/// Collects the source files of path dependencies living outside of `workspace_dir`,
+/// as declared by any of the manifests among `file_paths` (including those collected along the way).
+fn collect_path_dependency_files(
+    workspace_dir: &Path,
+    file_paths: &mut Vec<PathBuf>,
+) -> anyhow::Result<()> {
+    let mut dependency_dirs: Vec<PathBuf> = vec![];
+    let mut file_idx = 0;
+
+    while file_idx < file_paths.len() {
+        let file_path = file_paths[file_idx].clone();
+        file_idx += 1;
+
+        if file_path.file_name() != Some(OsStr::new("Cargo.toml")) {
+            continue;
+        }
+
+        let (Some(manifest), Some(manifest_dir)) = (read_manifest(&file_path), file_path.parent())
+        else {
+            continue;
+        };
+
+        for dependency_path in path_dependency_paths(&manifest) {
+            let Ok(dependency_dir) = manifest_dir.join(dependency_path).canonicalize() else {
+                continue;
+            };
+
+            if dependency_dir.starts_with(workspace_dir)
+                || dependency_dirs.contains(&dependency_dir)
+            {
+                continue;
+            }
+
+            trace!("Collecting files of path dependency at {dependency_dir:?}");
+
+            collect_source_files(&dependency_dir, file_paths)?;
+            dependency_dirs.push(dependency_dir);
+        }
+    }
+
+    Ok(())
+}
+
+/// Returns the `path`s of a manifest's dependencies, including target-specific, workspace-wide
+/// and patched ones.
+fn path_dependency_paths(manifest: &toml::Table) -> Vec<&str> {
+    let tables_of = |key: &str| {
+        manifest
+            .get(key)
+            .and_then(toml::Value::as_table)
+            .into_iter()
+            .flat_map(|table| table.values())
+    };
+
+    let dependency_tables = std::iter::once(manifest)
+        .chain(tables_of("target").filter_map(toml::Value::as_table))
+        .flat_map(|table| {
+            DEPENDENCY_TABLE_NAMES
+                .iter()
+                .filter_map(|table_name| table.get(*table_name))
+        })
+        .chain(
+            manifest
+                .get("workspace")
+                .and_then(|workspace| workspace.get("dependencies")),
+        )
+        .chain(tables_of("patch"));
+
+    dependency_tables
+        .filter_map(toml::Value::as_table)
+        .flat_map(|dependencies| dependencies.values())
+        .filter_map(|dependency| dependency.get("path")?.as_str())
+        .collect()
+}
+
+fn collect_source_files(dir_path: &Path, file_paths: &mut Vec<PathBuf>) -> anyhow::Result<()> {
+    for entry in fs::read_dir(dir_path)? {
+        let entry = entry?;
+        let path = entry.path();
+        let file_name = entry.file_name();
+        let file_name = file_name.to_string_lossy();
+
+        if entry.file_type()?.is_dir() {
+            if !IGNORED_DIR_NAMES.contains(&&file_name[..]) {
+                collect_source_files(&path, file_paths)?;
+            }
+            continue;
+        }
+
+        let is_source_file = path.extension().is_some_and(|extension| extension == "rs")
+            || file_name == "Cargo.toml"
+            || file_name == "Cargo.lock"
+            || file_name == "rust-project.json"
+            || file_name == config::FILE_NAME;
+
+        if is_source_file {
+            file_paths.push(path);
+        }
+    }
+
+    Ok(())
+}
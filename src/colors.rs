@@ -3,3 +3,5 @@
 // file, You can obtain one at https://mozilla.org/MPL/2.0/.
 
 pub(crate) mod cli;
+pub(crate) mod styles;
+pub(crate) mod theme;
@@ -2,81 +2,101 @@
 // License, v. 2.0. If a copy of the MPL was not distributed with this
 // file, You can obtain one at https://mozilla.org/MPL/2.0/.
 
+use std::{
+    env,
+    io::{self, IsTerminal as _},
+};
+
+use log::debug;
 pub use yansi::Color;
 
-#[derive(Clone, Debug)]
-pub(crate) struct ColorPalette {
-    #[allow(dead_code)]
-    pub purple: Color,
-    pub red: Color,
-    pub orange: Color,
-    pub yellow: Color,
-    pub green: Color,
-    pub cyan: Color,
-    pub blue: Color,
-    #[allow(dead_code)]
-    pub black: Color,
-    #[allow(dead_code)]
-    pub gray: Color,
-    #[allow(dead_code)]
-    pub white: Color,
-}
+use crate::options::ColorChoice;
+
+use super::theme::{self, Palette, Rgb};
+
+pub(crate) type ColorPalette = Palette<Color>;
 
 enum ColorDepth {
+    /// The 16 basic ANSI colors.
+    Basic,
+    /// The 256-color palette.
     Fixed,
+    /// 24-bit "true" colors.
     Rgb,
 }
 
+/// Enables or disables colored output, as per `choice`.
+pub(crate) fn init(choice: ColorChoice) {
+    let enabled = match choice {
+        ColorChoice::Always => true,
+        ColorChoice::Never => false,
+        ColorChoice::Auto => {
+            if env::var_os("NO_COLOR").is_some() {
+                false
+            } else if env::var_os("CLICOLOR_FORCE").is_some_and(|value| value != "0") {
+                true
+            } else {
+                io::stdout().is_terminal()
+            }
+        }
+    };
+
+    debug!(
+        "{} color output",
+        if enabled { "Enabling" } else { "Disabling" }
+    );
+
+    if enabled {
+        yansi::enable();
+    } else {
+        yansi::disable();
+    }
+}
+
 fn color_depth() -> Option<ColorDepth> {
-    if std::env::var("NO_COLOR").is_ok() {
+    if !yansi::is_enabled() {
         return None;
     }
 
-    match std::env::var("COLORTERM").as_deref() {
+    match env::var("COLORTERM").as_deref() {
         Ok("truecolor") | Ok("24bit") => return Some(ColorDepth::Rgb),
         _ => {}
     };
 
-    Some(ColorDepth::Fixed)
+    // Terminals that don't advertise 256-color support (e.g. `xterm`, `linux` or `vt100`)
+    // are assumed to only support the basic colors, while an absent `TERM` (as is usual
+    // on Windows) is assumed to mean a modern terminal:
+
+    match env::var("TERM").as_deref() {
+        Ok(term) if !term.contains("256") => Some(ColorDepth::Basic),
+        _ => Some(ColorDepth::Fixed),
+    }
+}
+
+/// The basic colors closest to each of the palette's colors, regardless of theme.
+fn basic_palette() -> ColorPalette {
+    Palette {
+        purple: Color::Magenta,
+        red: Color::Red,
+        orange: Color::Yellow,
+        yellow: Color::BrightYellow,
+        green: Color::Green,
+        cyan: Color::Cyan,
+        blue: Color::Blue,
+        black: Color::Black,
+        gray: Color::BrightBlack,
+        white: Color::White,
+    }
 }
 
 pub(crate) fn color_palette() -> ColorPalette {
+    let theme = theme::current();
+
     match color_depth() {
-        Some(ColorDepth::Fixed) => ColorPalette {
-            purple: Color::Fixed(133),
-            red: Color::Fixed(167),
-            orange: Color::Fixed(209),
-            yellow: Color::Fixed(215),
-            green: Color::Fixed(107),
-            cyan: Color::Fixed(74),
-            blue: Color::Fixed(68),
-            black: Color::Fixed(0),
-            gray: Color::Fixed(8),
-            white: Color::Fixed(15),
-        },
-        Some(ColorDepth::Rgb) => ColorPalette {
-            purple: Color::Rgb(196, 104, 223),
-            red: Color::Rgb(219, 77, 89),
-            orange: Color::Rgb(255, 178, 102),
-            yellow: Color::Rgb(247, 210, 99),
-            green: Color::Rgb(129, 193, 105),
-            cyan: Color::Rgb(105, 190, 210),
-            blue: Color::Rgb(84, 142, 200),
-            black: Color::Rgb(0, 0, 0),
-            gray: Color::Rgb(127, 127, 127),
-            white: Color::Rgb(255, 255, 255),
-        },
-        None => ColorPalette {
-            purple: Color::Primary,
-            red: Color::Primary,
-            orange: Color::Primary,
-            yellow: Color::Primary,
-            green: Color::Primary,
-            cyan: Color::Primary,
-            blue: Color::Primary,
-            black: Color::Primary,
-            gray: Color::Primary,
-            white: Color::Primary,
-        },
+        Some(_) if theme.monochrome => theme.terminal_fixed.map(|_| Color::Primary),
+        Some(ColorDepth::Basic) => basic_palette(),
+        Some(ColorDepth::Fixed) => theme.terminal_fixed.map(Color::Fixed),
+        Some(ColorDepth::Rgb) => theme.terminal.map(|Rgb(r, g, b)| Color::Rgb(r, g, b)),
+        None => theme.terminal_fixed.map(|_| Color::Primary),
     }
 }
@@ -0,0 +1,103 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at https://mozilla.org/MPL/2.0/.
+
+//! Per-kind node styles of graph outputs, as defined via a config's `[styles.<kind>]` tables:
+//!
+//! ```toml
+//! [styles.mod]
+//! shape = "folder"
+//! fillcolor = "#81c169"
+//!
+//! [styles.trait]
+//! shape = "hexagon"
+//! ```
+
+use std::{collections::HashMap, path::Path, sync::OnceLock};
+
+use anyhow::{bail, Context as _};
+use log::debug;
+
+use crate::config;
+
+pub(crate) const KINDS: [&str; 15] = [
+    "crate", "mod", "struct", "union", "enum", "variant", "trait", "fn", "type", "const", "static",
+    "macro", "builtin", "extern", "orphan",
+];
+
+static STYLES: OnceLock<KindStyles> = OnceLock::new();
+
+/// The graphviz attributes (e.g. `shape = "folder"`) to apply to nodes, by kind.
+#[derive(Clone, Default, PartialEq, Eq, Debug)]
+pub(crate) struct KindStyles(HashMap<String, Vec<(String, String)>>);
+
+impl KindStyles {
+    fn from_table(table: &toml::Table) -> anyhow::Result<Self> {
+        let mut styles = HashMap::new();
+
+        for (kind, attrs) in table {
+            if !KINDS.contains(&kind.as_str()) {
+                bail!(
+                    "Unknown node kind `{kind}` for styles, expected one of {}",
+                    KINDS.join(", ")
+                );
+            }
+
+            let Some(attrs) = attrs.as_table() else {
+                bail!("Expected a table of attributes for styles of `{kind}`");
+            };
+
+            let attrs = attrs
+                .iter()
+                .map(|(key, value)| {
+                    let value = match value {
+                        toml::Value::String(string) => string.clone(),
+                        toml::Value::Integer(integer) => integer.to_string(),
+                        toml::Value::Float(float) => float.to_string(),
+                        toml::Value::Boolean(boolean) => boolean.to_string(),
+                        _ => bail!("Unsupported value for attribute `{key}` of styles of `{kind}`"),
+                    };
+                    Ok((key.clone(), value))
+                })
+                .collect::<anyhow::Result<Vec<_>>>()?;
+
+            styles.insert(kind.clone(), attrs);
+        }
+
+        Ok(Self(styles))
+    }
+
+    /// Returns the attributes to apply to nodes of `kind` (e.g. `mod`, `fn`, `extern`).
+    pub fn get(&self, kind: &str) -> &[(String, String)] {
+        self.0.get(kind).map(Vec::as_slice).unwrap_or_default()
+    }
+}
+
+/// Selects the styles defined by the config of the project at `manifest_path`, if any,
+/// to be used for all subsequent graph output.
+pub(crate) fn select(manifest_path: &Path) -> anyhow::Result<()> {
+    let Some((config_path, config)) = config::load(manifest_path)? else {
+        return Ok(());
+    };
+
+    let Some(table) = config.get("styles") else {
+        return Ok(());
+    };
+
+    let styles = table
+        .as_table()
+        .context("Expected a table of node kinds for `styles`")
+        .and_then(KindStyles::from_table)
+        .with_context(|| format!("Invalid config in {}", config_path.display()))?;
+
+    debug!("Using node styles from {}", config_path.display());
+
+    let _ = STYLES.set(styles);
+
+    Ok(())
+}
+
+/// Returns the selected styles (defaulting to none).
+pub(crate) fn current() -> &'static KindStyles {
+    STYLES.get_or_init(KindStyles::default)
+}
@@ -0,0 +1,295 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at https://mozilla.org/MPL/2.0/.
+
+//! Color themes, from which the styles of all (tree, graph and diagnostic) outputs get derived.
+
+use std::{ops::Range, path::Path, sync::OnceLock};
+
+use anyhow::{bail, Context as _};
+use log::debug;
+
+use crate::config;
+
+pub(crate) const NAMES: [&str; 4] = ["dark", "light", "monochrome", "colorblind-safe"];
+
+static THEME: OnceLock<Theme> = OnceLock::new();
+
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub(crate) struct Rgb(pub u8, pub u8, pub u8);
+
+impl Rgb {
+    fn parse(hex: &str) -> Option<Self> {
+        let hex = hex.strip_prefix('#')?;
+
+        if hex.len() != 6 {
+            return None;
+        }
+
+        let component = |range: Range<usize>| u8::from_str_radix(hex.get(range)?, 16).ok();
+
+        Some(Self(component(0..2)?, component(2..4)?, component(4..6)?))
+    }
+
+    /// Returns the closest color of the 256-color palette's 6×6×6 color cube.
+    fn to_fixed(self) -> u8 {
+        const LEVELS: [u8; 6] = [0, 95, 135, 175, 215, 255];
+
+        let level = |component: u8| {
+            (0..LEVELS.len())
+                .min_by_key(|&index| LEVELS[index].abs_diff(component))
+                .expect("non-empty levels") as u8
+        };
+
+        let Self(r, g, b) = self;
+
+        16 + 36 * level(r) + 6 * level(g) + level(b)
+    }
+}
+
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub(crate) struct Palette<C> {
+    pub purple: C,
+    pub red: C,
+    pub orange: C,
+    pub yellow: C,
+    pub green: C,
+    pub cyan: C,
+    pub blue: C,
+    pub black: C,
+    pub gray: C,
+    pub white: C,
+}
+
+impl<C: Copy> Palette<C> {
+    pub fn map<D>(&self, f: impl Fn(C) -> D) -> Palette<D> {
+        Palette {
+            purple: f(self.purple),
+            red: f(self.red),
+            orange: f(self.orange),
+            yellow: f(self.yellow),
+            green: f(self.green),
+            cyan: f(self.cyan),
+            blue: f(self.blue),
+            black: f(self.black),
+            gray: f(self.gray),
+            white: f(self.white),
+        }
+    }
+
+    fn get_mut(&mut self, name: &str) -> Option<&mut C> {
+        match name {
+            "purple" => Some(&mut self.purple),
+            "red" => Some(&mut self.red),
+            "orange" => Some(&mut self.orange),
+            "yellow" => Some(&mut self.yellow),
+            "green" => Some(&mut self.green),
+            "cyan" => Some(&mut self.cyan),
+            "blue" => Some(&mut self.blue),
+            "black" => Some(&mut self.black),
+            "gray" => Some(&mut self.gray),
+            "white" => Some(&mut self.white),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub(crate) struct Theme {
+    /// The colors of terminal output on true-color terminals.
+    pub terminal: Palette<Rgb>,
+    /// The colors of terminal output on 256-color terminals.
+    pub terminal_fixed: Palette<u8>,
+    /// The colors of graph output (e.g. the fill colors of nodes).
+    pub graph: Palette<Rgb>,
+    /// Whether terminal output should go without colors (keeping only bold, dim, etc.).
+    pub monochrome: bool,
+}
+
+impl Theme {
+    pub fn dark() -> Self {
+        Self {
+            terminal: Palette {
+                purple: Rgb(196, 104, 223),
+                red: Rgb(219, 77, 89),
+                orange: Rgb(255, 178, 102),
+                yellow: Rgb(247, 210, 99),
+                green: Rgb(129, 193, 105),
+                cyan: Rgb(105, 190, 210),
+                blue: Rgb(84, 142, 200),
+                black: Rgb(0, 0, 0),
+                gray: Rgb(127, 127, 127),
+                white: Rgb(255, 255, 255),
+            },
+            terminal_fixed: Palette {
+                purple: 133,
+                red: 167,
+                orange: 209,
+                yellow: 215,
+                green: 107,
+                cyan: 74,
+                blue: 68,
+                black: 0,
+                gray: 8,
+                white: 15,
+            },
+            graph: Palette {
+                purple: Rgb(186, 111, 167),
+                red: Rgb(219, 83, 103),
+                orange: Rgb(254, 148, 84),
+                yellow: Rgb(248, 192, 76),
+                green: Rgb(129, 193, 105),
+                cyan: Rgb(105, 190, 210),
+                blue: Rgb(83, 151, 200),
+                black: Rgb(0, 0, 0),
+                gray: Rgb(127, 127, 127),
+                white: Rgb(255, 255, 255),
+            },
+            monochrome: false,
+        }
+    }
+
+    pub fn light() -> Self {
+        Self::from_palette(Palette {
+            purple: Rgb(136, 57, 168),
+            red: Rgb(180, 38, 52),
+            orange: Rgb(191, 95, 0),
+            yellow: Rgb(150, 110, 0),
+            green: Rgb(46, 125, 50),
+            cyan: Rgb(0, 121, 145),
+            blue: Rgb(21, 87, 176),
+            black: Rgb(0, 0, 0),
+            gray: Rgb(100, 100, 100),
+            white: Rgb(255, 255, 255),
+        })
+    }
+
+    pub fn monochrome() -> Self {
+        Self {
+            monochrome: true,
+            ..Self::from_palette(Palette {
+                purple: Rgb(96, 96, 96),
+                red: Rgb(144, 144, 144),
+                orange: Rgb(176, 176, 176),
+                yellow: Rgb(208, 208, 208),
+                green: Rgb(240, 240, 240),
+                cyan: Rgb(224, 224, 224),
+                blue: Rgb(112, 112, 112),
+                black: Rgb(0, 0, 0),
+                gray: Rgb(127, 127, 127),
+                white: Rgb(255, 255, 255),
+            })
+        }
+    }
+
+    /// The Okabe-Ito palette, which remains distinguishable for the common forms of color blindness.
+    pub fn colorblind_safe() -> Self {
+        Self::from_palette(Palette {
+            purple: Rgb(204, 121, 167),
+            red: Rgb(213, 94, 0),
+            orange: Rgb(230, 159, 0),
+            yellow: Rgb(240, 228, 66),
+            green: Rgb(0, 158, 115),
+            cyan: Rgb(86, 180, 233),
+            blue: Rgb(0, 114, 178),
+            black: Rgb(0, 0, 0),
+            gray: Rgb(127, 127, 127),
+            white: Rgb(255, 255, 255),
+        })
+    }
+
+    fn from_palette(palette: Palette<Rgb>) -> Self {
+        Self {
+            terminal: palette,
+            terminal_fixed: palette.map(Rgb::to_fixed),
+            graph: palette,
+            monochrome: false,
+        }
+    }
+
+    fn builtin(name: &str) -> Option<Self> {
+        match name {
+            "dark" => Some(Self::dark()),
+            "light" => Some(Self::light()),
+            "monochrome" => Some(Self::monochrome()),
+            "colorblind-safe" => Some(Self::colorblind_safe()),
+            _ => None,
+        }
+    }
+
+    /// Returns a theme defined by a config's `[themes.<name>]` table, which overrides
+    /// individual colors (e.g. `red = "#d55e00"`) of its `base` theme (defaulting to `dark`).
+    fn custom(name: &str, table: &toml::Table) -> anyhow::Result<Self> {
+        let base = match table.get("base") {
+            Some(toml::Value::String(base)) => base.as_str(),
+            Some(_) => bail!("Expected the name of a built-in theme for `base` of theme `{name}`"),
+            None => "dark",
+        };
+
+        let Some(mut theme) = Self::builtin(base) else {
+            bail!("Unknown base theme `{base}` for theme `{name}`");
+        };
+
+        for (key, value) in table {
+            if key == "base" {
+                continue;
+            }
+
+            let Some(rgb) = value.as_str().and_then(Rgb::parse) else {
+                bail!("Expected a `#rrggbb` color for `{key}` of theme `{name}`");
+            };
+
+            let (Some(terminal), Some(terminal_fixed), Some(graph)) = (
+                theme.terminal.get_mut(key),
+                theme.terminal_fixed.get_mut(key),
+                theme.graph.get_mut(key),
+            ) else {
+                bail!("Unknown color `{key}` for theme `{name}`");
+            };
+
+            *terminal = rgb;
+            *terminal_fixed = rgb.to_fixed();
+            *graph = rgb;
+        }
+
+        Ok(theme)
+    }
+}
+
+/// Selects the theme named `name`, either built-in or defined by the config of the project
+/// at `manifest_path`, to be used for all subsequent output.
+pub(crate) fn select(name: &str, manifest_path: &Path) -> anyhow::Result<()> {
+    let theme = match Theme::builtin(name) {
+        Some(theme) => theme,
+        None => {
+            let config = config::load(manifest_path)?;
+
+            let Some((config_path, table)) = config.as_ref().and_then(|(config_path, config)| {
+                let table = config.get("themes")?.get(name)?.as_table()?;
+                Some((config_path, table))
+            }) else {
+                bail!(
+                    "Unknown theme `{name}`, expected one of {} or a theme defined in `{}`",
+                    NAMES.join(", "),
+                    config::FILE_NAME
+                );
+            };
+
+            Theme::custom(name, table)
+                .with_context(|| format!("Invalid config in {}", config_path.display()))?
+        }
+    };
+
+    debug!("Using {name:?} theme");
+
+    // Commands that get re-run (e.g. by `watch`) keep their initially selected theme:
+
+    let _ = THEME.set(theme);
+
+    Ok(())
+}
+
+/// Returns the selected theme (defaulting to `dark`).
+pub(crate) fn current() -> &'static Theme {
+    THEME.get_or_init(Theme::dark)
+}
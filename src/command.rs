@@ -2,21 +2,63 @@
 // License, v. 2.0. If a copy of the MPL was not distributed with this
 // file, You can obtain one at https://mozilla.org/MPL/2.0/.
 
-use clap::Parser;
+use std::path::Path;
+
+use clap::{Parser, Subcommand};
+use log::warn;
+use ra_ap_hir as hir;
+use ra_ap_ide::RootDatabase;
 
 use crate::{
-    analyzer::{load_workspace, LoadOptions},
-    options::{GeneralOptions, ProjectOptions},
+    analyzer::{self, load_workspace, LoadOptions},
+    cache::Cache,
+    colors,
+    options::{ColorChoice, GeneralOptions, MessageFormat, ProjectOptions},
+    output,
+    progress::{self, Stage},
+    summary, timings, viewer,
+    worktree::Worktree,
 };
 
 use self::{
-    dependencies::command::Command as DependenciesCommand,
-    orphans::command::Command as OrphansCommand, structure::command::Command as StructureCommand,
+    between::command::Command as BetweenCommand, check::command::Command as CheckCommand,
+    compare::command::Command as CompareCommand,
+    completions::command::Command as CompletionsCommand, crates::command::Command as CratesCommand,
+    daemon::command::Command as DaemonCommand,
+    dependencies::command::Command as DependenciesCommand, dependencies::options::GraphFormat,
+    docgen::command::Command as DocgenCommand, docs::command::Command as DocsCommand,
+    export::command::Command as ExportCommand, facade::command::Command as FacadeCommand,
+    features::command::Command as FeaturesCommand, fetch::command::Command as FetchCommand,
+    impact::command::Command as ImpactCommand, imports::command::Command as ImportsCommand,
+    orphans::command::Command as OrphansCommand, scaffold::command::Command as ScaffoldCommand,
+    schema::command::Command as SchemaCommand, serve::command::Command as ServeCommand,
+    stats::command::Command as StatsCommand, structure::command::Command as StructureCommand,
+    tui::command::Command as TuiCommand, watch::command::Command as WatchCommand,
 };
 
+pub mod between;
+pub mod check;
+pub mod compare;
+pub mod completions;
+pub mod crates;
+pub mod daemon;
 pub mod dependencies;
+pub mod docgen;
+pub mod docs;
+pub mod export;
+pub mod facade;
+pub mod features;
+pub mod fetch;
+pub mod impact;
+pub mod imports;
 pub mod orphans;
+pub mod scaffold;
+pub mod schema;
+pub mod serve;
+pub mod stats;
 pub mod structure;
+pub mod tui;
+pub mod watch;
 
 #[derive(Parser, Clone, PartialEq, Eq, Debug)]
 #[command(
@@ -24,6 +66,60 @@ pub mod structure;
     about = "Visualize/analyze a crate's internal structure."
 )]
 pub enum Command {
+    #[command(flatten)]
+    Nested(Box<NestedCommand>),
+
+    #[command(
+        name = "fetch",
+        about = "Downloads a published crate and runs a command on it.",
+        after_help = r#"
+        Example: `cargo modules fetch serde@1.0.200 structure --lib`
+        "#
+    )]
+    Fetch(FetchCommand),
+
+    #[command(
+        name = "watch",
+        about = "Re-runs a command whenever the crate's files change.",
+        after_help = r#"
+        Example: `cargo modules watch dependencies --lib`
+        "#
+    )]
+    Watch(WatchCommand),
+
+    #[command(
+        name = "schema",
+        about = "Prints the JSON schema of a machine-readable output.",
+        after_help = r#"
+        Example: `cargo modules schema graph > graph.schema.json`
+        "#
+    )]
+    Schema(SchemaCommand),
+
+    #[command(
+        name = "scaffold",
+        about = "Creates the files and `mod` declarations of a module hierarchy.",
+        after_help = r#"
+        Example: `cargo modules scaffold modules.txt --dry-run`,
+        with `modules.txt` in the tree format printed by `cargo modules structure`
+        "#
+    )]
+    Scaffold(ScaffoldCommand),
+
+    #[command(
+        name = "completions",
+        about = "Prints a shell completion script (bash, elvish, fish, powershell or zsh).",
+        after_help = r#"
+        Example: `cargo modules completions bash > ~/.local/share/bash-completion/completions/cargo-modules`
+        "#
+    )]
+    Completions(CompletionsCommand),
+}
+
+/// The commands that analyze a project, which (unlike `fetch` and `watch` themselves,
+/// as clap would otherwise recurse endlessly) can also be run by `fetch`.
+#[derive(Subcommand, Clone, PartialEq, Eq, Debug)]
+pub enum NestedCommand {
     #[command(
         name = "structure",
         about = "Prints a crate's hierarchical structure as a tree."
@@ -35,42 +131,461 @@ pub enum Command {
         about = "Prints a crate's internal dependencies as a graph.",
         after_help = r#"
         If you have xdot installed on your system, you can run this using:
-        `cargo modules dependencies | xdot -`
+        `cargo modules dependencies --open` (or `cargo modules dependencies | xdot -`)
         "#
     )]
     Dependencies(DependenciesCommand),
 
+    #[command(
+        name = "between",
+        about = "Prints the subgraph of all dependency paths leading from one module to another.",
+        after_help = r#"
+        Example: `cargo modules between my_crate::api my_crate::db --max-length 3`
+        "#
+    )]
+    Between(BetweenCommand),
+
+    #[command(
+        name = "impact",
+        about = "Prints the subgraph of all modules (transitively) depending on a module.",
+        after_help = r#"
+        Example: `cargo modules impact my_crate::db --list`
+        "#
+    )]
+    Impact(ImpactCommand),
+
     #[command(
         name = "orphans",
         about = "Detects unlinked source files within a crate's directory."
     )]
     Orphans(OrphansCommand),
+
+    #[command(
+        name = "stats",
+        about = "Prints statistics about a crate's structure (e.g. module depths, edges, cycles)."
+    )]
+    Stats(StatsCommand),
+
+    #[command(
+        name = "imports",
+        about = "Summarizes a crate's `use` imports by target (e.g. the most imported crates)."
+    )]
+    Imports(ImportsCommand),
+
+    #[command(
+        name = "compare",
+        about = "Compares the structure of two crates (e.g. shared modules, sizes, coupling).",
+        after_help = r#"
+        Example: `cargo modules compare -p crate_a -p crate_b`
+        "#
+    )]
+    Compare(CompareCommand),
+
+    #[command(
+        name = "check",
+        about = "Checks a crate's modules against the dependency budgets of its config, as well as for lints."
+    )]
+    Check(CheckCommand),
+
+    #[command(
+        name = "docs",
+        about = "Reports a crate's documentation coverage (i.e. modules and public items lacking docs)."
+    )]
+    Docs(DocsCommand),
+
+    #[command(
+        name = "docgen",
+        about = "Generates a Markdown architecture document (i.e. module tree, summaries, diagrams).",
+        after_help = r#"
+        Example: `cargo modules docgen -o ARCHITECTURE.md`
+        "#
+    )]
+    Docgen(DocgenCommand),
+
+    #[command(
+        name = "export",
+        about = "Exports the module/item graph as GraphML (e.g. for yEd, Gephi or Cytoscape).",
+        after_help = r#"
+        Example: `cargo modules export -o modules.graphml`
+        "#
+    )]
+    Export(ExportCommand),
+
+    #[command(
+        name = "crates",
+        about = "Prints a crate's dependencies on other crates as a graph, flagging unused ones.",
+        after_help = r#"
+        Example: `cargo modules crates --lib | dot -Tsvg > crates.svg`
+        "#
+    )]
+    Crates(CratesCommand),
+
+    #[command(
+        name = "facade",
+        about = "Audits a crate's root `pub use` facade (e.g. surfaced modules, unreachable public items).",
+        after_help = r#"
+        Example: `cargo modules facade --lib`
+        "#
+    )]
+    Facade(FacadeCommand),
+
+    #[command(
+        name = "features",
+        about = "Analyzes a crate under multiple feature combinations, reporting feature-gated items.",
+        after_help = r#"
+        Example: `cargo modules features --lib --depth 2`
+        "#
+    )]
+    Features(FeaturesCommand),
+
+    #[command(
+        name = "tui",
+        about = "Explores a crate's structure and dependencies interactively."
+    )]
+    Tui(TuiCommand),
+
+    #[command(
+        name = "serve",
+        about = "Serves an interactive graph viewer, re-analyzing the crate on changes.",
+        after_help = r#"
+        Example: `cargo modules serve --lib`, then open http://127.0.0.1:7878/
+        "#
+    )]
+    Serve(ServeCommand),
+
+    #[command(
+        name = "daemon",
+        about = "Answers queries about a crate over stdio JSON-RPC, keeping its analysis loaded.",
+        after_help = r#"
+        Example: `echo '{"jsonrpc": "2.0", "id": 1, "method": "tree"}' | cargo modules daemon --lib`
+        "#
+    )]
+    Daemon(DaemonCommand),
 }
 
-impl Command {
+impl From<NestedCommand> for Command {
+    fn from(command: NestedCommand) -> Self {
+        Self::Nested(Box::new(command))
+    }
+}
+
+/// A command's rendered output, along with the outcome of its checks
+/// (e.g. `--acyclic`), which only get reported once the output has been emitted.
+pub(crate) struct Rendered {
+    pub(crate) output: String,
+    pub(crate) checked: anyhow::Result<()>,
+}
+
+impl Rendered {
+    fn unchecked(output: String) -> Self {
+        Self {
+            output,
+            checked: Ok(()),
+        }
+    }
+}
+
+impl NestedCommand {
     pub(crate) fn sanitize(&mut self) {
         match self {
             Self::Structure(command) => command.sanitize(),
             Self::Dependencies(command) => command.sanitize(),
+            Self::Between(command) => command.sanitize(),
+            Self::Impact(command) => command.sanitize(),
             Self::Orphans(command) => command.sanitize(),
+            Self::Stats(command) => command.sanitize(),
+            Self::Imports(command) => command.sanitize(),
+            Self::Compare(command) => command.sanitize(),
+            Self::Docgen(command) => command.sanitize(),
+            Self::Export(command) => command.sanitize(),
+            Self::Crates(command) => command.sanitize(),
+            Self::Facade(command) => command.sanitize(),
+            Self::Features(command) => command.sanitize(),
+            Self::Check(command) => command.sanitize(),
+            Self::Docs(command) => command.sanitize(),
+            Self::Tui(command) => command.sanitize(),
+            Self::Serve(command) => command.sanitize(),
+            Self::Daemon(command) => command.sanitize(),
         }
     }
 
-    pub fn run(self) -> Result<(), anyhow::Error> {
+    fn run(self) -> Result<(), anyhow::Error> {
+        // Serving keeps the analysis alive and re-exports the graph on changes:
+
+        if let Self::Serve(command) = self {
+            return command.run();
+        }
+
+        // The daemon keeps the analysis alive and answers queries against it:
+
+        if let Self::Daemon(command) = self {
+            return command.run();
+        }
+
+        // Feature combinations each get loaded (and analyzed) on their own:
+
+        if let Self::Features(command) = self {
+            return command.run();
+        }
+
         let general_options = self.general_options();
         let project_options = self.project_options();
         let load_options = self.load_options();
 
-        let (krate, host, vfs) = load_workspace(general_options, project_options, &load_options)?;
+        if self.message_format() == Some(MessageFormat::Json) {
+            progress::enable();
+        }
+
+        // Timings and summaries get reported once the command is done (whether it succeeded or not):
+
+        let _timings = general_options.timings.then(timings::start);
+        let _summary = general_options.summary.map(summary::start);
+
+        let open_format = self.open_format();
+        let output_path = self.output_path();
+
+        // Viewers get a plain copy of the output, without any terminal colors:
+
+        if open_format.is_some() {
+            yansi::disable();
+        }
+
+        let cache = if self.is_cache_enabled() {
+            Some(Cache::new(project_options, &format!("{self:?}"))?)
+        } else {
+            None
+        };
+
+        if let Some(output) = cache.as_ref().and_then(Cache::load) {
+            Self::emit(&output, open_format, output_path)?;
+
+            // Cached outputs don't tell whether the project still passes the command's checks
+            // (e.g. `--acyclic` or `--fail-on`), so those still need the project to be analyzed:
+
+            if !self.has_checks() {
+                return Ok(());
+            }
+
+            progress::stage(Stage::Loading);
+
+            let (krates, host, _vfs) =
+                load_workspace(general_options, project_options, &load_options)?;
+
+            return self.check(&krates, host.raw_database());
+        }
+
+        progress::stage(Stage::Loading);
+
+        let (krates, host, vfs) = load_workspace(general_options, project_options, &load_options)?;
         let db = host.raw_database();
 
+        if summary::is_enabled() {
+            summary::record_modules(krates.iter().map(|krate| krate.modules(db).len()).sum());
+        }
+
+        // Modules rust-analyzer failed to fully resolve still get analyzed (as far as possible),
+        // but are worth a warning, as their contents are likely incomplete:
+
+        analyzer::warn_unresolved(&krates, db);
+
+        // Outputs that are to be cached, opened or written to a file need to be rendered in full,
+        // while all others get streamed directly to stdout
+        // (orphans, stats, imports, comparisons, documents and checks get written to their file
+        // by the command itself):
+
+        let is_written_to_file = output_path.is_some()
+            && !matches!(
+                self,
+                Self::Orphans(_)
+                    | Self::Stats(_)
+                    | Self::Imports(_)
+                    | Self::Compare(_)
+                    | Self::Docgen(_)
+                    | Self::Export(_)
+                    | Self::Crates(_)
+                    | Self::Facade(_)
+                    | Self::Check(_)
+            );
+
+        if cache.is_some() || open_format.is_some() || is_written_to_file {
+            let Rendered { output, checked } = self.render(&krates, db)?;
+
+            // Outputs of failed checks never get cached, as their errors would go missing on re-use:
+
+            if let Some(cache) = cache.as_ref().filter(|_| checked.is_ok()) {
+                if let Err(err) = cache.store(&output) {
+                    warn!("Could not cache output: {err}");
+                    progress::warning(&format!("Could not cache output: {err}"));
+                }
+            }
+
+            Self::emit(&output, open_format, output_path)?;
+
+            return checked;
+        }
+
         match self {
             #[allow(unused_variables)]
-            Self::Structure(command) => command.run(krate, db),
+            Self::Structure(command) => command.run(&krates, db),
             #[allow(unused_variables)]
-            Self::Dependencies(command) => command.run(krate, db),
+            Self::Dependencies(command) => command.run(&krates, db),
+            Self::Between(command) => command.run(&krates, db),
+            Self::Impact(command) => command.run(&krates, db),
             #[allow(unused_variables)]
-            Self::Orphans(command) => command.run(krate, db, &vfs),
+            Self::Orphans(command) => command.run(&krates, db),
+            Self::Stats(command) => command.run(&krates, db),
+            Self::Imports(command) => command.run(&krates, db),
+            Self::Compare(command) => command.run(&krates, db),
+            Self::Docgen(command) => command.run(&krates, db),
+            Self::Export(command) => command.run(&krates, db),
+            Self::Crates(command) => command.run(&krates, db),
+            Self::Facade(command) => command.run(&krates, db),
+            Self::Features(_) => unreachable!(),
+            Self::Check(command) => command.run(&krates, db),
+            Self::Docs(command) => command.run(&krates, db),
+            Self::Tui(command) => command.run(&krates, db, &vfs),
+            Self::Serve(_) => unreachable!(),
+            Self::Daemon(_) => unreachable!(),
+        }
+    }
+
+    fn render(&self, krates: &[hir::Crate], db: &RootDatabase) -> anyhow::Result<Rendered> {
+        match self {
+            Self::Structure(command) => command.render(krates, db),
+            Self::Dependencies(command) => command.render(krates, db),
+            Self::Between(command) => command.render(krates, db).map(Rendered::unchecked),
+            Self::Impact(command) => command.render(krates, db).map(Rendered::unchecked),
+            _ => unreachable!(),
+        }
+    }
+
+    fn emit(
+        output: &str,
+        open_format: Option<viewer::Format>,
+        output_path: Option<&Path>,
+    ) -> anyhow::Result<()> {
+        match (open_format, output_path) {
+            (Some(format), _) => viewer::open(output, format),
+            (None, Some(output_path)) => output::write_atomically(output_path, output),
+            (None, None) => {
+                print!("{output}");
+                Ok(())
+            }
+        }
+    }
+
+    pub(crate) fn output_path(&self) -> Option<&Path> {
+        match self {
+            Self::Structure(command) => command.options.output.as_deref(),
+            Self::Dependencies(command) => command.options.output.as_deref(),
+            Self::Between(command) => command.options.dependencies.output.as_deref(),
+            Self::Impact(command) => command.options.dependencies.output.as_deref(),
+            Self::Orphans(command) => command.options.output.as_deref(),
+            Self::Stats(command) => command.options.output.as_deref(),
+            Self::Imports(command) => command.options.output.as_deref(),
+            Self::Compare(command) => command.options.output.as_deref(),
+            Self::Docgen(command) => command.options.output.as_deref(),
+            Self::Export(command) => command.options.output.as_deref(),
+            Self::Crates(command) => command.options.output.as_deref(),
+            Self::Facade(command) => command.options.output.as_deref(),
+            Self::Features(command) => command.options.output.as_deref(),
+            Self::Check(command) => command.options.output.as_deref(),
+            Self::Docs(_) | Self::Tui(_) | Self::Serve(_) | Self::Daemon(_) => None,
+        }
+    }
+
+    fn has_checks(&self) -> bool {
+        match self {
+            Self::Structure(command) => command.options.acyclic,
+            Self::Dependencies(command) => {
+                command.options.acyclic || !command.options.fail_on.is_empty()
+            }
+            _ => false,
+        }
+    }
+
+    /// Runs the command's checks on their own, without printing its output.
+    fn check(&self, krates: &[hir::Crate], db: &RootDatabase) -> anyhow::Result<()> {
+        match self {
+            Self::Structure(command) => command.check(krates, db),
+            Self::Dependencies(command) => command.check(krates, db),
+            _ => Ok(()),
+        }
+    }
+
+    fn message_format(&self) -> Option<MessageFormat> {
+        match self {
+            Self::Structure(command) => Some(command.options.message_format),
+            Self::Dependencies(command) => Some(command.options.message_format),
+            Self::Orphans(command) => Some(command.options.message_format),
+            _ => None,
+        }
+    }
+
+    fn open_format(&self) -> Option<viewer::Format> {
+        match self {
+            Self::Structure(command) => command.options.open.then_some(viewer::Format::Text),
+            Self::Dependencies(command) => {
+                let format = if command.options.template.is_some()
+                    || command.options.graph_format != GraphFormat::Dot
+                {
+                    viewer::Format::Text
+                } else {
+                    viewer::Format::Dot
+                };
+
+                command.options.open.then_some(format)
+            }
+            Self::Between(command) => {
+                let options = &command.options.dependencies;
+
+                let format =
+                    if options.template.is_some() || options.graph_format != GraphFormat::Dot {
+                        viewer::Format::Text
+                    } else {
+                        viewer::Format::Dot
+                    };
+
+                options.open.then_some(format)
+            }
+            Self::Impact(command) => {
+                let options = &command.options.dependencies;
+
+                let format = if options.template.is_some()
+                    || options.graph_format != GraphFormat::Dot
+                    || command.options.list
+                {
+                    viewer::Format::Text
+                } else {
+                    viewer::Format::Dot
+                };
+
+                options.open.then_some(format)
+            }
+            _ => None,
+        }
+    }
+
+    fn is_cache_enabled(&self) -> bool {
+        match self {
+            Self::Structure(command) => command.options.cache,
+            Self::Dependencies(command) => command.options.cache,
+            Self::Between(_)
+            | Self::Impact(_)
+            | Self::Orphans(_)
+            | Self::Stats(_)
+            | Self::Imports(_)
+            | Self::Compare(_)
+            | Self::Docgen(_)
+            | Self::Export(_)
+            | Self::Crates(_)
+            | Self::Facade(_)
+            | Self::Features(_)
+            | Self::Check(_)
+            | Self::Docs(_)
+            | Self::Tui(_)
+            | Self::Serve(_)
+            | Self::Daemon(_) => false,
         }
     }
 
@@ -78,25 +593,213 @@ impl Command {
         match self {
             Self::Structure(command) => &command.options.general,
             Self::Dependencies(command) => &command.options.general,
+            Self::Between(command) => &command.options.dependencies.general,
+            Self::Impact(command) => &command.options.dependencies.general,
             Self::Orphans(command) => &command.options.general,
+            Self::Stats(command) => &command.options.general,
+            Self::Imports(command) => &command.options.general,
+            Self::Compare(command) => &command.options.general,
+            Self::Docgen(command) => &command.options.general,
+            Self::Export(command) => &command.options.general,
+            Self::Crates(command) => &command.options.general,
+            Self::Facade(command) => &command.options.general,
+            Self::Features(command) => &command.options.general,
+            Self::Check(command) => &command.options.general,
+            Self::Docs(command) => &command.options.general,
+            Self::Tui(command) => &command.options.general,
+            Self::Serve(command) => &command.options.general,
+            Self::Daemon(command) => &command.options.general,
         }
     }
 
-    #[allow(dead_code)]
     fn project_options(&self) -> &ProjectOptions {
         match self {
             Self::Structure(command) => &command.options.project,
             Self::Dependencies(command) => &command.options.project,
+            Self::Between(command) => &command.options.dependencies.project,
+            Self::Impact(command) => &command.options.dependencies.project,
             Self::Orphans(command) => &command.options.project,
+            Self::Stats(command) => &command.options.project,
+            Self::Imports(command) => &command.options.project,
+            Self::Compare(command) => &command.options.project,
+            Self::Docgen(command) => &command.options.project,
+            Self::Export(command) => &command.options.project,
+            Self::Crates(command) => &command.options.project,
+            Self::Facade(command) => &command.options.project,
+            Self::Features(command) => &command.options.project,
+            Self::Check(command) => &command.options.project,
+            Self::Docs(command) => &command.options.project,
+            Self::Tui(command) => &command.options.project,
+            Self::Serve(command) => &command.options.project,
+            Self::Daemon(command) => &command.options.project,
+        }
+    }
+
+    fn project_options_mut(&mut self) -> &mut ProjectOptions {
+        match self {
+            Self::Structure(command) => &mut command.options.project,
+            Self::Dependencies(command) => &mut command.options.project,
+            Self::Between(command) => &mut command.options.dependencies.project,
+            Self::Impact(command) => &mut command.options.dependencies.project,
+            Self::Orphans(command) => &mut command.options.project,
+            Self::Stats(command) => &mut command.options.project,
+            Self::Imports(command) => &mut command.options.project,
+            Self::Compare(command) => &mut command.options.project,
+            Self::Docgen(command) => &mut command.options.project,
+            Self::Export(command) => &mut command.options.project,
+            Self::Crates(command) => &mut command.options.project,
+            Self::Facade(command) => &mut command.options.project,
+            Self::Features(command) => &mut command.options.project,
+            Self::Check(command) => &mut command.options.project,
+            Self::Docs(command) => &mut command.options.project,
+            Self::Tui(command) => &mut command.options.project,
+            Self::Serve(command) => &mut command.options.project,
+            Self::Daemon(command) => &mut command.options.project,
         }
     }
 
-    #[allow(dead_code)]
     fn load_options(&self) -> LoadOptions {
         match self {
             Self::Structure(command) => command.load_options(),
             Self::Dependencies(command) => command.load_options(),
+            Self::Between(command) => command.load_options(),
+            Self::Impact(command) => command.load_options(),
             Self::Orphans(command) => command.load_options(),
+            Self::Stats(command) => command.load_options(),
+            Self::Imports(command) => command.load_options(),
+            Self::Compare(command) => command.load_options(),
+            Self::Docgen(command) => command.load_options(),
+            Self::Export(command) => command.load_options(),
+            Self::Crates(command) => command.load_options(),
+            Self::Facade(command) => command.load_options(),
+            Self::Features(command) => command.load_options(),
+            Self::Check(command) => command.load_options(),
+            Self::Docs(command) => command.load_options(),
+            Self::Tui(command) => command.load_options(),
+            Self::Serve(command) => command.load_options(),
+            Self::Daemon(command) => command.load_options(),
+        }
+    }
+}
+
+impl Command {
+    pub(crate) fn sanitize(&mut self) {
+        match self {
+            Self::Nested(command) => command.sanitize(),
+            Self::Fetch(command) => command.sanitize(),
+            Self::Watch(command) => command.sanitize(),
+            Self::Schema(command) => command.sanitize(),
+            Self::Scaffold(command) => command.sanitize(),
+            Self::Completions(command) => command.sanitize(),
+        }
+    }
+
+    pub fn run(self) -> Result<(), anyhow::Error> {
+        // Schemas don't depend on any project:
+
+        if let Self::Schema(command) = self {
+            return command.run();
+        }
+
+        // Scaffolding creates a project's modules, rather than analyzing them:
+
+        if let Self::Scaffold(command) = self {
+            return command.run();
+        }
+
+        // Completion scripts get generated from the command-line interface alone:
+
+        if let Self::Completions(command) = self {
+            return command.run();
+        }
+
+        colors::cli::init(self.general_options().color);
+
+        // Files get a plain copy of the output, without any terminal colors
+        // (unless explicitly asked for):
+
+        if self.output_path().is_some() && self.general_options().color != ColorChoice::Always {
+            yansi::disable();
+        }
+
+        colors::theme::select(
+            &self.general_options().theme,
+            &self.project_options().manifest_path,
+        )?;
+
+        colors::styles::select(&self.project_options().manifest_path)?;
+
+        // Fetching happens before (and determines) the project to load:
+
+        if let Self::Fetch(command) = self {
+            return command.run();
+        }
+
+        // Revisions get checked out into a temporary worktree, which then gets analyzed instead
+        // (and removed once done):
+
+        if let Some(rev) = self.project_options().at.clone() {
+            let worktree = Worktree::add(&self.project_options().manifest_path, &rev)?;
+
+            let mut command = self;
+            command.project_options_mut().at = None;
+            command.project_options_mut().manifest_path = worktree.manifest_path().to_owned();
+
+            return command.run();
+        }
+
+        // Watching keeps the analysis alive and re-runs the command on changes:
+
+        if let Self::Watch(command) = self {
+            return command.run();
+        }
+
+        let Self::Nested(command) = self else {
+            unreachable!();
+        };
+
+        (*command).run()
+    }
+
+    pub(crate) fn output_path(&self) -> Option<&Path> {
+        match self {
+            Self::Nested(command) => command.output_path(),
+            Self::Fetch(command) => command.command.output_path(),
+            Self::Watch(command) => command.output_path(),
+            Self::Schema(_) | Self::Scaffold(_) | Self::Completions(_) => None,
+        }
+    }
+
+    fn general_options(&self) -> &GeneralOptions {
+        match self {
+            Self::Nested(command) => command.general_options(),
+            Self::Fetch(command) => command.command.general_options(),
+            Self::Watch(command) => command.general_options(),
+            Self::Schema(_) => unreachable!("`schema` doesn't process a project"),
+            Self::Scaffold(_) => unreachable!("`scaffold` doesn't process a project"),
+            Self::Completions(_) => unreachable!("`completions` doesn't process a project"),
+        }
+    }
+
+    fn project_options(&self) -> &ProjectOptions {
+        match self {
+            Self::Nested(command) => command.project_options(),
+            Self::Fetch(command) => command.command.project_options(),
+            Self::Watch(command) => command.project_options(),
+            Self::Schema(_) => unreachable!("`schema` doesn't process a project"),
+            Self::Scaffold(_) => unreachable!("`scaffold` doesn't process a project"),
+            Self::Completions(_) => unreachable!("`completions` doesn't process a project"),
+        }
+    }
+
+    fn project_options_mut(&mut self) -> &mut ProjectOptions {
+        match self {
+            Self::Nested(command) => command.project_options_mut(),
+            Self::Fetch(command) => command.command.project_options_mut(),
+            Self::Watch(command) => command.project_options_mut(),
+            Self::Schema(_) => unreachable!("`schema` doesn't process a project"),
+            Self::Scaffold(_) => unreachable!("`scaffold` doesn't process a project"),
+            Self::Completions(_) => unreachable!("`completions` doesn't process a project"),
         }
     }
 }
@@ -0,0 +1,26 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at https://mozilla.org/MPL/2.0/.
+
+use clap::Parser;
+
+use crate::command::dependencies::options::Options as DependenciesOptions;
+
+#[derive(Parser, Clone, PartialEq, Eq, Debug)]
+#[group(id = "BetweenOptions")]
+pub struct Options {
+    /// The path of the module (or item) the paths start from (e.g. `my_crate::api`).
+    #[arg(value_name = "FROM")]
+    pub from: String,
+
+    /// The path of the module (or item) the paths lead to (e.g. `my_crate::db`).
+    #[arg(value_name = "TO")]
+    pub to: String,
+
+    /// Only include paths of at most this many edges.
+    #[arg(long = "max-length")]
+    pub max_length: Option<usize>,
+
+    #[command(flatten)]
+    pub dependencies: DependenciesOptions,
+}
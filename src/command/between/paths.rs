@@ -0,0 +1,128 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at https://mozilla.org/MPL/2.0/.
+
+//! Extraction of the paths between two items, as printed by `between`.
+
+use std::collections::{hash_map::Entry, HashMap, HashSet, VecDeque};
+
+use petgraph::{
+    graph::NodeIndex,
+    stable_graph::EdgeIndex,
+    visit::{EdgeRef as _, IntoEdgeReferences as _},
+    Direction,
+};
+
+use crate::{
+    command::dependencies::split::{owned_nodes, owner_node},
+    graph::{Edge, Graph, Node, Relationship},
+};
+
+/// Returns the subgraph consisting of all paths of "uses" (and "re-exports") edges
+/// leading from `from_idx` (or any of its sub-items) to `to_idx` (or any of its sub-items)
+/// with at most `max_length` edges, along with the (transitive) owners of their nodes,
+/// up to their crates' root nodes.
+pub(crate) fn paths_subgraph(
+    graph: &Graph<Node, Edge>,
+    from_idx: NodeIndex,
+    to_idx: NodeIndex,
+    max_length: Option<usize>,
+) -> Graph<Node, Edge> {
+    let max_length = max_length.unwrap_or(usize::MAX);
+
+    let distances_from = distances(graph, &subtree(graph, from_idx), Direction::Outgoing);
+    let distances_to = distances(graph, &subtree(graph, to_idx), Direction::Incoming);
+
+    // An edge lies on a path of at most `max_length` edges,
+    // if the shortest path through it is no longer than that:
+    let path_edge_idxs: HashSet<EdgeIndex> = graph
+        .edge_references()
+        .filter(|edge_ref| edge_ref.weight().kind != Relationship::Owns)
+        .filter(|edge_ref| {
+            match (
+                distances_from.get(&edge_ref.source()),
+                distances_to.get(&edge_ref.target()),
+            ) {
+                (Some(from), Some(to)) => from.saturating_add(1).saturating_add(*to) <= max_length,
+                _ => false,
+            }
+        })
+        .map(|edge_ref| edge_ref.id())
+        .collect();
+
+    let path_node_idxs = path_edge_idxs.iter().flat_map(|edge_idx| {
+        let (source_idx, target_idx) = graph.edge_endpoints(*edge_idx).expect("edge endpoints");
+        [source_idx, target_idx]
+    });
+
+    let mut nodes_to_keep: HashSet<NodeIndex> = HashSet::default();
+    let mut stack: Vec<NodeIndex> = [from_idx, to_idx]
+        .into_iter()
+        .chain(path_node_idxs)
+        .collect();
+
+    while let Some(node_idx) = stack.pop() {
+        if nodes_to_keep.insert(node_idx) {
+            stack.extend(owner_node(graph, node_idx));
+        }
+    }
+
+    let mut subgraph = graph.clone();
+
+    subgraph.retain_nodes(|_, node_idx| nodes_to_keep.contains(&node_idx));
+
+    subgraph.retain_edges(|subgraph, edge_idx| {
+        subgraph[edge_idx].kind == Relationship::Owns || path_edge_idxs.contains(&edge_idx)
+    });
+
+    subgraph
+}
+
+/// Returns the node along with all of its (transitive) sub-items.
+fn subtree(graph: &Graph<Node, Edge>, node_idx: NodeIndex) -> Vec<NodeIndex> {
+    let mut subtree_idxs: HashSet<NodeIndex> = HashSet::default();
+    let mut stack = vec![node_idx];
+
+    while let Some(node_idx) = stack.pop() {
+        if subtree_idxs.insert(node_idx) {
+            stack.extend(owned_nodes(graph, node_idx));
+        }
+    }
+
+    subtree_idxs.into_iter().collect()
+}
+
+/// Returns the lengths of the shortest paths of non-"owns" edges
+/// from (or, for incoming edges, to) any of the given nodes.
+fn distances(
+    graph: &Graph<Node, Edge>,
+    node_idxs: &[NodeIndex],
+    direction: Direction,
+) -> HashMap<NodeIndex, usize> {
+    let mut distances: HashMap<NodeIndex, usize> =
+        node_idxs.iter().map(|node_idx| (*node_idx, 0)).collect();
+
+    let mut queue: VecDeque<NodeIndex> = node_idxs.iter().copied().collect();
+
+    while let Some(node_idx) = queue.pop_front() {
+        let distance = distances[&node_idx];
+
+        let neighbor_idxs: Vec<NodeIndex> = graph
+            .edges_directed(node_idx, direction)
+            .filter(|edge_ref| edge_ref.weight().kind != Relationship::Owns)
+            .map(|edge_ref| match direction {
+                Direction::Outgoing => edge_ref.target(),
+                Direction::Incoming => edge_ref.source(),
+            })
+            .collect();
+
+        for neighbor_idx in neighbor_idxs {
+            if let Entry::Vacant(entry) = distances.entry(neighbor_idx) {
+                entry.insert(distance + 1);
+                queue.push_back(neighbor_idx);
+            }
+        }
+    }
+
+    distances
+}
@@ -0,0 +1,300 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at https://mozilla.org/MPL/2.0/.
+
+//! Per-module dependency budgets, as defined via a config's `[budgets."<path>"]` tables:
+//!
+//! ```toml
+//! [budgets."crate::core"]
+//! max-uses = 5
+//! allow = ["crate::util", "serde::*"]
+//! ```
+//!
+//! A module's uses comprise all items used by the module (or any of its items)
+//! that lie outside of the module itself.
+
+use std::{
+    collections::{BTreeSet, HashMap},
+    fmt,
+    path::Path,
+};
+
+use anyhow::{bail, Context as _};
+use petgraph::{graph::NodeIndex, visit::EdgeRef as _, Direction};
+use ra_ap_hir as hir;
+use ra_ap_ide::RootDatabase;
+
+use crate::{
+    analyzer, config,
+    glob::{glob_matches, is_glob},
+    graph::{Edge, Graph, Node, Relationship},
+};
+
+/// The keys supported by a budget's table.
+const KEYS: [&str; 2] = ["max-uses", "allow"];
+
+/// The dependency budget of a single module.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub(crate) struct Budget {
+    /// The module's path, as configured (e.g. `crate::core`).
+    pub(crate) path: String,
+    /// The maximum number of items the module may use.
+    pub(crate) max_uses: Option<usize>,
+    /// The paths (or glob patterns) of the only items the module may use, if restricted.
+    pub(crate) allow: Option<Vec<String>>,
+}
+
+impl Budget {
+    fn from_table(path: &str, table: &toml::Table) -> anyhow::Result<Self> {
+        for key in table.keys() {
+            if !KEYS.contains(&key.as_str()) {
+                bail!(
+                    "Unknown key `{key}` for budget of `{path}`, expected one of {}",
+                    KEYS.join(", ")
+                );
+            }
+        }
+
+        let max_uses = match table.get("max-uses") {
+            Some(toml::Value::Integer(max_uses)) if *max_uses >= 0 => Some(*max_uses as usize),
+            Some(_) => {
+                bail!("Expected a non-negative integer for `max-uses` of budget of `{path}`")
+            }
+            None => None,
+        };
+
+        let allow = match table.get("allow") {
+            Some(toml::Value::Array(values)) => Some(
+                values
+                    .iter()
+                    .map(|value| {
+                        value.as_str().map(str::to_owned).with_context(|| {
+                            format!("Expected an array of paths for `allow` of budget of `{path}`")
+                        })
+                    })
+                    .collect::<anyhow::Result<Vec<_>>>()?,
+            ),
+            Some(_) => bail!("Expected an array of paths for `allow` of budget of `{path}`"),
+            None => None,
+        };
+
+        Ok(Self {
+            path: path.to_owned(),
+            max_uses,
+            allow,
+        })
+    }
+}
+
+/// Returns the budgets defined by the config of the project at `manifest_path`, if any.
+pub(crate) fn load(manifest_path: &Path) -> anyhow::Result<Vec<Budget>> {
+    let Some((config_path, config)) = config::load(manifest_path)? else {
+        return Ok(vec![]);
+    };
+
+    let Some(table) = config.get("budgets") else {
+        return Ok(vec![]);
+    };
+
+    let context = || format!("Invalid config in {}", config_path.display());
+
+    let table = table
+        .as_table()
+        .context("Expected a table of module paths for `budgets`")
+        .with_context(context)?;
+
+    table
+        .iter()
+        .map(|(path, budget)| {
+            let Some(budget) = budget.as_table() else {
+                bail!("Expected a table for budget of `{path}`");
+            };
+            Budget::from_table(path, budget)
+        })
+        .collect::<anyhow::Result<Vec<_>>>()
+        .with_context(context)
+}
+
+/// The outcome of checking a module against its budget.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub(crate) struct Report {
+    pub(crate) budget: Budget,
+    /// The (full) paths of the items used by the module.
+    pub(crate) uses: BTreeSet<String>,
+    pub(crate) violations: Vec<String>,
+}
+
+impl fmt::Display for Report {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let status = if self.violations.is_empty() {
+            "ok"
+        } else {
+            "FAILED"
+        };
+
+        let count = self.uses.len();
+        let noun = if count == 1 { "use" } else { "uses" };
+
+        write!(f, "{status:<6} {}: {count} {noun}", self.budget.path)?;
+
+        if let Some(max_uses) = self.budget.max_uses {
+            write!(f, " (at most {max_uses})")?;
+        }
+
+        Ok(())
+    }
+}
+
+/// The error of runs exceeding any of their modules' budgets.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct Failure {
+    violations: Vec<(String, String)>,
+}
+
+impl fmt::Display for Failure {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let count = self.violations.len();
+        let noun = if count == 1 {
+            "violation"
+        } else {
+            "violations"
+        };
+
+        write!(f, "Found {count} budget {noun}:")?;
+
+        for (path, violation) in &self.violations {
+            write!(f, "\n  {path}: {violation}")?;
+        }
+
+        Ok(())
+    }
+}
+
+impl std::error::Error for Failure {}
+
+impl Failure {
+    /// Returns a failure listing the reports' violations, if any.
+    pub(crate) fn from_reports(reports: &[Report]) -> Option<Self> {
+        let violations: Vec<(String, String)> = reports
+            .iter()
+            .flat_map(|report| {
+                report
+                    .violations
+                    .iter()
+                    .map(|violation| (report.budget.path.clone(), violation.clone()))
+            })
+            .collect();
+
+        (!violations.is_empty()).then_some(Self { violations })
+    }
+}
+
+/// Checks the modules of the (unfiltered) graph against their budgets.
+pub(crate) fn check(
+    budgets: &[Budget],
+    graph: &Graph<Node, Edge>,
+    krates: &[hir::Crate],
+    db: &RootDatabase,
+) -> anyhow::Result<Vec<Report>> {
+    let crate_names: Vec<String> = krates
+        .iter()
+        .map(|krate| analyzer::crate_name(*krate, db))
+        .collect();
+
+    let paths: HashMap<NodeIndex, String> = graph
+        .node_indices()
+        .map(|node_idx| (node_idx, graph[node_idx].display_path(db)))
+        .collect();
+
+    budgets
+        .iter()
+        .map(|budget| {
+            let module_path = resolve_paths(&budget.path, &crate_names)
+                .into_iter()
+                .find(|path| {
+                    paths.iter().any(|(node_idx, node_path)| {
+                        node_path == path
+                            && matches!(graph[*node_idx].hir, hir::ModuleDef::Module(_))
+                    })
+                })
+                .with_context(|| format!("Unknown module `{}` in budgets", budget.path))?;
+
+            let uses = uses_of(&module_path, graph, &paths);
+
+            let mut violations = vec![];
+
+            if let Some(max_uses) = budget.max_uses {
+                if uses.len() > max_uses {
+                    violations.push(format!(
+                        "Uses {} items, exceeding its budget of {max_uses}",
+                        uses.len()
+                    ));
+                }
+            }
+
+            if let Some(allow) = &budget.allow {
+                let allow: Vec<String> = allow
+                    .iter()
+                    .flat_map(|pattern| resolve_paths(pattern, &crate_names))
+                    .collect();
+
+                violations.extend(
+                    uses.iter()
+                        .filter(|path| !allow.iter().any(|pattern| is_allowed(pattern, path)))
+                        .map(|path| format!("Uses `{path}`, which isn't allowed")),
+                );
+            }
+
+            Ok(Report {
+                budget: budget.clone(),
+                uses,
+                violations,
+            })
+        })
+        .collect()
+}
+
+/// Returns the paths of all items outside of the module that are used from within it.
+fn uses_of(
+    module_path: &str,
+    graph: &Graph<Node, Edge>,
+    paths: &HashMap<NodeIndex, String>,
+) -> BTreeSet<String> {
+    let is_within = |path: &str| is_same_or_descendant(module_path, path);
+
+    paths
+        .iter()
+        .filter(|(_, path)| is_within(path))
+        .flat_map(|(node_idx, _)| graph.edges_directed(*node_idx, Direction::Outgoing))
+        .filter(|edge_ref| edge_ref.weight().kind == Relationship::Uses)
+        .map(|edge_ref| &paths[&edge_ref.target()])
+        .filter(|path| !is_within(path))
+        .cloned()
+        .collect()
+}
+
+/// Returns the candidate paths of a configured path, substituting `crate::`
+/// with the name of each of the analyzed crates.
+pub(crate) fn resolve_paths(path: &str, crate_names: &[String]) -> Vec<String> {
+    match path.strip_prefix("crate::") {
+        Some(rest) => crate_names
+            .iter()
+            .map(|crate_name| format!("{crate_name}::{rest}"))
+            .collect(),
+        None if path == "crate" => crate_names.to_vec(),
+        None => vec![path.to_owned()],
+    }
+}
+
+fn is_allowed(pattern: &str, path: &str) -> bool {
+    if is_glob(pattern) {
+        glob_matches(pattern, path)
+    } else {
+        is_same_or_descendant(pattern, path)
+    }
+}
+
+pub(crate) fn is_same_or_descendant(ancestor: &str, path: &str) -> bool {
+    path.strip_prefix(ancestor)
+        .is_some_and(|rest| rest.is_empty() || rest.starts_with("::"))
+}
@@ -0,0 +1,116 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at https://mozilla.org/MPL/2.0/.
+
+use std::fmt::Write as _;
+
+use anyhow::bail;
+use clap::Parser;
+use log::trace;
+use ra_ap_hir as hir;
+use ra_ap_ide::RootDatabase;
+
+use crate::{
+    analyzer::LoadOptions,
+    config,
+    graph::GraphBuilder,
+    output,
+    progress::{self, Stage},
+    quickfix,
+};
+
+use super::{
+    budget::{self, Failure},
+    lints::{self, Level, Levels},
+    options::Options,
+};
+
+#[derive(Parser, Clone, PartialEq, Eq, Debug)]
+pub struct Command {
+    #[command(flatten)]
+    pub options: Options,
+}
+
+impl Command {
+    pub fn new(options: Options) -> Self {
+        Self { options }
+    }
+
+    pub(crate) fn sanitize(&mut self) {}
+
+    #[doc(hidden)]
+    pub fn run(self, krates: &[hir::Crate], db: &RootDatabase) -> anyhow::Result<()> {
+        let budgets = budget::load(&self.options.project.manifest_path)?;
+
+        let levels = Levels::new(&self.options.allow, &self.options.warn, &self.options.deny);
+
+        if budgets.is_empty() && levels.is_empty() {
+            bail!(
+                "No budgets found, expected `[budgets.\"<path>\"]` tables in `{}` (e.g. `[budgets.\"crate::core\"]`)",
+                config::FILE_NAME
+            );
+        }
+
+        trace!("Building graph ...");
+        progress::stage(Stage::Building);
+
+        let (graph, crate_node_idxs) = GraphBuilder::new(db, krates).build()?;
+
+        trace!("Checking budgets ...");
+
+        let reports = budget::check(&budgets, &graph, krates, db)?;
+
+        trace!("Checking lints ...");
+
+        let findings = lints::check(
+            &levels,
+            self.options.max_nesting,
+            &graph,
+            &crate_node_idxs,
+            krates,
+            db,
+        )?;
+
+        progress::stage(Stage::Printing);
+
+        let mut output = String::new();
+
+        for report in &reports {
+            writeln!(output, "{report}")?;
+        }
+
+        let project_dir = quickfix::project_dir(&self.options.project);
+
+        for finding in &findings {
+            let level = levels.level(finding.lint);
+
+            if level == Level::Warn && !finding.is_suppressed {
+                progress::warning(&finding.message);
+            }
+
+            writeln!(output, "{}", finding.formatted(level, &project_dir))?;
+        }
+
+        match &self.options.output {
+            Some(output_path) => output::write_atomically(output_path, output)?,
+            None => print!("{output}"),
+        }
+
+        // Exceeded budgets take precedence over denied lints, as they're explicitly configured:
+        if let Some(failure) = Failure::from_reports(&reports) {
+            return Err(failure.into());
+        }
+
+        match lints::Failure::from_findings(&findings, &levels) {
+            Some(failure) => Err(failure.into()),
+            None => Ok(()),
+        }
+    }
+
+    pub fn load_options(&self) -> LoadOptions {
+        LoadOptions {
+            cfg_test: self.options.cfg_test,
+            sysroot: false,
+        }
+    }
+}
@@ -0,0 +1,438 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at https://mozilla.org/MPL/2.0/.
+
+//! Lints, as checked at the levels given via `--allow`, `--warn` and `--deny`
+//! (mirroring rustc's `-A`, `-W` and `-D`).
+//!
+//! All lints are allowed by default, so that teams can adopt them one at a time,
+//! warning about a lint's findings at first, then denying them once they got fixed.
+//! Known findings can be suppressed individually via comments (see [`super::suppressions`]).
+
+use std::{
+    collections::{HashMap, HashSet},
+    fmt,
+    path::{Path, PathBuf},
+    str::FromStr,
+};
+
+use petgraph::graph::NodeIndex;
+use ra_ap_hir as hir;
+use ra_ap_ide::RootDatabase;
+
+use crate::{
+    analyzer,
+    command::{
+        dependencies::cycles::tri_color::{CycleDetector, TriColorDepthFirstSearch},
+        orphans::scanner,
+    },
+    graph::{Edge, Graph, Node},
+    item::ItemVisibility,
+    quickfix, summary,
+};
+
+use super::suppressions::Suppressions;
+
+/// A lint, as named on the command-line (e.g. `--deny cycles`).
+#[derive(Copy, Clone, PartialEq, Eq, Hash, Debug)]
+pub enum Lint {
+    /// Circular dependencies between items.
+    Cycles,
+    /// Source files not linked via `mod` (i.e. orphans).
+    Orphans,
+    /// Glob imports (i.e. `use foo::*;`),
+    /// other than of preludes, or of `super::*` within test modules.
+    GlobImports,
+    /// Modules nested deeper than `--max-nesting` levels (with the crate's root module at level 0).
+    DeepNesting,
+    /// `pub` items that aren't reachable from outside their crate (i.e. could be `pub(crate)`).
+    UnusedPub,
+}
+
+impl Lint {
+    /// All lints, in the order their findings get reported in.
+    pub const ALL: [Self; 5] = [
+        Self::Cycles,
+        Self::Orphans,
+        Self::GlobImports,
+        Self::DeepNesting,
+        Self::UnusedPub,
+    ];
+}
+
+impl FromStr for Lint {
+    type Err = &'static str;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "cycles" => Ok(Self::Cycles),
+            "orphans" => Ok(Self::Orphans),
+            "glob-imports" => Ok(Self::GlobImports),
+            "deep-nesting" => Ok(Self::DeepNesting),
+            "unused-pub" => Ok(Self::UnusedPub),
+            _ => Err(
+                "Unrecognized lint (e.g. cycles, orphans, glob-imports, deep-nesting, unused-pub)",
+            ),
+        }
+    }
+}
+
+impl fmt::Display for Lint {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::Cycles => "cycles",
+            Self::Orphans => "orphans",
+            Self::GlobImports => "glob-imports",
+            Self::DeepNesting => "deep-nesting",
+            Self::UnusedPub => "unused-pub",
+        })
+    }
+}
+
+/// The level of a lint, ordered by strictness.
+#[derive(Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Debug)]
+pub(crate) enum Level {
+    Allow,
+    Warn,
+    Deny,
+}
+
+impl fmt::Display for Level {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::Allow => "allow",
+            Self::Warn => "warning",
+            Self::Deny => "error",
+        })
+    }
+}
+
+/// The levels of all lints, with any lint not given a level being allowed.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub(crate) struct Levels(HashMap<Lint, Level>);
+
+impl Levels {
+    /// Returns the levels of the given lints, with a lint given several of them
+    /// getting the strictest one (e.g. `--warn cycles --deny cycles` denying cycles).
+    pub(crate) fn new(allow: &[Lint], warn: &[Lint], deny: &[Lint]) -> Self {
+        let mut levels: HashMap<Lint, Level> = HashMap::new();
+
+        for (lints, level) in [
+            (allow, Level::Allow),
+            (warn, Level::Warn),
+            (deny, Level::Deny),
+        ] {
+            for lint in lints {
+                let entry = levels.entry(*lint).or_insert(level);
+                *entry = (*entry).max(level);
+            }
+        }
+
+        Self(levels)
+    }
+
+    pub(crate) fn level(&self, lint: Lint) -> Level {
+        self.0.get(&lint).copied().unwrap_or(Level::Allow)
+    }
+
+    /// Returns whether all lints are allowed.
+    pub(crate) fn is_empty(&self) -> bool {
+        Lint::ALL
+            .iter()
+            .all(|lint| self.level(*lint) == Level::Allow)
+    }
+}
+
+/// A finding of a lint.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub(crate) struct Finding {
+    pub(crate) lint: Lint,
+    pub(crate) message: String,
+    pub(crate) file_path: Option<PathBuf>,
+    /// The (1-based) line and column within `file_path`, if known.
+    pub(crate) line_col: Option<(u32, u32)>,
+    /// Whether the finding got suppressed via a comment (e.g. `// cargo-modules: allow(cycles)`).
+    pub(crate) is_suppressed: bool,
+}
+
+impl Finding {
+    /// Returns the finding formatted as per its lint's `level` (or as "allowed", if suppressed),
+    /// with its file path relative to `project_dir`.
+    pub(crate) fn formatted(&self, level: Level, project_dir: &Path) -> String {
+        let level = if self.is_suppressed {
+            "allowed".to_owned()
+        } else {
+            level.to_string()
+        };
+
+        let mut formatted = format!("{level}[{}]: {}", self.lint, self.message);
+
+        if let Some(file_path) = &self.file_path {
+            let file_path = quickfix::relative_path(file_path, project_dir);
+
+            formatted += &format!("\n  --> {}", file_path.display());
+
+            if let Some((line, column)) = self.line_col {
+                formatted += &format!(":{line}:{column}");
+            }
+        }
+
+        formatted
+    }
+}
+
+/// The error of runs with findings of any denied lints.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct Failure {
+    count: usize,
+}
+
+impl fmt::Display for Failure {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let count = self.count;
+        let noun = if count == 1 { "finding" } else { "findings" };
+
+        write!(f, "Found {count} {noun} of denied lints")
+    }
+}
+
+impl std::error::Error for Failure {}
+
+impl Failure {
+    /// Returns a failure counting the findings of denied lints, if any.
+    pub(crate) fn from_findings(findings: &[Finding], levels: &Levels) -> Option<Self> {
+        let count = findings
+            .iter()
+            .filter(|finding| !finding.is_suppressed && levels.level(finding.lint) == Level::Deny)
+            .count();
+
+        (count > 0).then_some(Self { count })
+    }
+}
+
+/// Checks the crates (and their unfiltered graph) for the findings of all non-allowed lints.
+pub(crate) fn check(
+    levels: &Levels,
+    max_nesting: usize,
+    graph: &Graph<Node, Edge>,
+    crate_node_idxs: &[NodeIndex],
+    krates: &[hir::Crate],
+    db: &RootDatabase,
+) -> anyhow::Result<Vec<Finding>> {
+    let mut findings = vec![];
+
+    for lint in Lint::ALL {
+        if levels.level(lint) == Level::Allow {
+            continue;
+        }
+
+        let mut lint_findings = match lint {
+            Lint::Cycles => check_cycles(graph, crate_node_idxs, db),
+            Lint::Orphans => check_orphans(krates, db)?,
+            Lint::GlobImports => check_glob_imports(krates, db),
+            Lint::DeepNesting => check_deep_nesting(max_nesting, krates, db),
+            Lint::UnusedPub => check_unused_pub(krates, db),
+        };
+
+        // Sort by location, then by message for stable output:
+        lint_findings.sort_by(|lhs, rhs| {
+            (&lhs.file_path, lhs.line_col, &lhs.message).cmp(&(
+                &rhs.file_path,
+                rhs.line_col,
+                &rhs.message,
+            ))
+        });
+
+        findings.extend(lint_findings);
+    }
+
+    let mut suppressions = Suppressions::default();
+
+    for finding in &mut findings {
+        finding.is_suppressed = suppressions.suppresses(finding);
+    }
+
+    Ok(findings)
+}
+
+fn finding_at(
+    lint: Lint,
+    message: String,
+    module_def_hir: hir::ModuleDef,
+    db: &RootDatabase,
+) -> Finding {
+    let (file_path, line_col) = match analyzer::location(module_def_hir, db) {
+        Some((file_path, line, column)) => (Some(file_path), Some((line, column))),
+        None => (None, None),
+    };
+
+    Finding {
+        lint,
+        message,
+        file_path,
+        line_col,
+        is_suppressed: false,
+    }
+}
+
+fn check_cycles(
+    graph: &Graph<Node, Edge>,
+    crate_node_idxs: &[NodeIndex],
+    db: &RootDatabase,
+) -> Vec<Finding> {
+    crate_node_idxs
+        .iter()
+        .filter_map(|crate_node_idx| {
+            let cycle = TriColorDepthFirstSearch::new(graph)
+                .run_from(*crate_node_idx, &mut CycleDetector)?;
+
+            let first = &graph[cycle[0]];
+            let last = &graph[*cycle.last()?];
+
+            let message = format!(
+                "circular dependency between `{}` and `{}`",
+                first.display_path(db),
+                last.display_path(db)
+            );
+
+            Some(finding_at(Lint::Cycles, message, first.hir, db))
+        })
+        .collect()
+}
+
+fn check_orphans(krates: &[hir::Crate], db: &RootDatabase) -> anyhow::Result<Vec<Finding>> {
+    let orphans = scanner::scan_all(db, krates)?;
+
+    summary::record_orphans(orphans.len());
+
+    Ok(orphans
+        .into_iter()
+        .map(|orphan| Finding {
+            lint: Lint::Orphans,
+            message: format!(
+                "orphaned source file `{}`, not linked via `mod` by `{}`",
+                orphan.name, orphan.parent_module_path
+            ),
+            file_path: Some(orphan.file_path),
+            line_col: None,
+            is_suppressed: false,
+        })
+        .collect())
+}
+
+fn check_glob_imports(krates: &[hir::Crate], db: &RootDatabase) -> Vec<Finding> {
+    let mut findings = vec![];
+
+    for module in krates.iter().flat_map(|krate| krate.modules(db)) {
+        let is_test_module = analyzer::is_test_only(hir::ModuleDef::Module(module), db);
+
+        for (file_path, line, column, glob) in analyzer::glob_imports(module, db) {
+            let path = glob.trim_end_matches("::*");
+
+            // Preludes are meant to be glob-imported, as are the items under test by tests:
+            if path == "prelude"
+                || path.ends_with("::prelude")
+                || (is_test_module && path == "super")
+            {
+                continue;
+            }
+
+            findings.push(Finding {
+                lint: Lint::GlobImports,
+                message: format!(
+                    "glob import of `{glob}` within `{}`",
+                    analyzer::display_path(hir::ModuleDef::Module(module), db)
+                ),
+                file_path: Some(file_path),
+                line_col: Some((line, column)),
+                is_suppressed: false,
+            });
+        }
+    }
+
+    findings
+}
+
+fn check_deep_nesting(
+    max_nesting: usize,
+    krates: &[hir::Crate],
+    db: &RootDatabase,
+) -> Vec<Finding> {
+    krates
+        .iter()
+        .flat_map(|krate| krate.modules(db))
+        .filter_map(|module| {
+            // The path to the root includes the module itself, with the root module at level 0:
+            let depth = module.path_to_root(db).len() - 1;
+
+            if depth <= max_nesting {
+                return None;
+            }
+
+            let module_def_hir = hir::ModuleDef::Module(module);
+            let noun = if depth == 1 { "level" } else { "levels" };
+
+            let message = format!(
+                "module `{}` is nested {depth} {noun} deep (at most {max_nesting})",
+                analyzer::display_path(module_def_hir, db)
+            );
+
+            Some(finding_at(Lint::DeepNesting, message, module_def_hir, db))
+        })
+        .collect()
+}
+
+fn check_unused_pub(krates: &[hir::Crate], db: &RootDatabase) -> Vec<Finding> {
+    let mut findings = vec![];
+
+    for krate in krates.iter().cloned() {
+        // Nothing of a binary is reachable from outside of it:
+        let reachable = if analyzer::crate_is_bin(krate, db) {
+            HashSet::new()
+        } else {
+            reachable_items(krate, db)
+        };
+
+        for module in krate.modules(db) {
+            for module_def_hir in module.declarations(db) {
+                if ItemVisibility::new(module_def_hir, db) != ItemVisibility::Public
+                    || reachable.contains(&module_def_hir)
+                {
+                    continue;
+                }
+
+                let message = format!(
+                    "`{}` is declared `pub`, but isn't reachable from outside of its crate",
+                    analyzer::display_path(module_def_hir, db)
+                );
+
+                findings.push(finding_at(Lint::UnusedPub, message, module_def_hir, db));
+            }
+        }
+    }
+
+    findings
+}
+
+/// Returns the items reachable from outside of the crate,
+/// i.e. those within the public scopes of its root module and of any modules reachable from it.
+fn reachable_items(krate: hir::Crate, db: &RootDatabase) -> HashSet<hir::ModuleDef> {
+    let mut reachable = HashSet::new();
+    let mut modules = vec![krate.root_module()];
+
+    while let Some(module) = modules.pop() {
+        for (_name, module_def_hir, _is_reexport) in analyzer::public_scope(module, db) {
+            if !reachable.insert(module_def_hir) {
+                continue;
+            }
+
+            if let hir::ModuleDef::Module(sub_module) = module_def_hir {
+                if sub_module.krate() == krate {
+                    modules.push(sub_module);
+                }
+            }
+        }
+    }
+
+    reachable
+}
@@ -0,0 +1,49 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at https://mozilla.org/MPL/2.0/.
+
+use std::path::PathBuf;
+
+use clap::Parser;
+
+use crate::options::{GeneralOptions, ProjectOptions};
+
+use super::lints::Lint;
+
+#[derive(Parser, Clone, PartialEq, Eq, Debug)]
+#[group(id = "CheckOptions")]
+pub struct Options {
+    #[command(flatten)]
+    pub general: GeneralOptions,
+
+    #[command(flatten)]
+    pub project: ProjectOptions,
+
+    /// Allow a lint, ignoring its findings
+    /// (e.g. cycles, orphans, glob-imports, deep-nesting, unused-pub). May be passed multiple times.
+    #[arg(short = 'A', long = "allow", value_name = "LINT")]
+    pub allow: Vec<Lint>,
+
+    /// Warn about a lint's findings
+    /// (e.g. cycles, orphans, glob-imports, deep-nesting, unused-pub). May be passed multiple times.
+    #[arg(short = 'W', long = "warn", value_name = "LINT")]
+    pub warn: Vec<Lint>,
+
+    /// Deny a lint, failing (with exit code 2) on any of its findings
+    /// (e.g. cycles, orphans, glob-imports, deep-nesting, unused-pub). May be passed multiple times.
+    #[arg(short = 'D', long = "deny", value_name = "LINT")]
+    pub deny: Vec<Lint>,
+
+    /// The maximum depth of module nesting allowed by the `deep-nesting` lint
+    /// (with the crate's root module at level 0).
+    #[arg(long = "max-nesting", value_name = "N", default_value = "4")]
+    pub max_nesting: usize,
+
+    /// Analyze with `#[cfg(test)]` enabled (i.e as if built via `cargo test`).
+    #[arg(long = "cfg-test")]
+    pub cfg_test: bool,
+
+    /// Write the output to this file (atomically replacing it) instead of printing it.
+    #[arg(short = 'o', long = "output")]
+    pub output: Option<PathBuf>,
+}
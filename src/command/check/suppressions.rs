@@ -0,0 +1,96 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at https://mozilla.org/MPL/2.0/.
+
+//! Suppressions of lint findings via comments (e.g. `// cargo-modules: allow(cycles)`),
+//! letting known violations pass while new ones still fail.
+//!
+//! A suppression applies to the finding on its own line, or on the line below it
+//! (skipping any further comments and attributes in between).
+//! Findings of whole files (e.g. orphans) get suppressed among the file's leading comments.
+
+use std::{
+    collections::HashMap,
+    fs,
+    path::{Path, PathBuf},
+};
+
+use super::lints::{Finding, Lint};
+
+/// The marker introducing suppressions within comments.
+const MARKER: &str = "cargo-modules:";
+
+/// The suppressions of source files, as read from each file once.
+#[derive(Default, Debug)]
+pub(crate) struct Suppressions {
+    lines: HashMap<PathBuf, Vec<String>>,
+}
+
+impl Suppressions {
+    /// Returns whether the finding got suppressed via a comment.
+    pub(crate) fn suppresses(&mut self, finding: &Finding) -> bool {
+        let Some(file_path) = &finding.file_path else {
+            return false;
+        };
+
+        let lines = self
+            .lines
+            .entry(file_path.clone())
+            .or_insert_with(|| read_lines(file_path));
+
+        let allows = |line: &str| allowed_lints(line).contains(&finding.lint);
+
+        let Some((line, _)) = finding.line_col else {
+            return lines
+                .iter()
+                .map(|line| line.trim())
+                .take_while(|line| line.is_empty() || line.starts_with("//"))
+                .any(allows);
+        };
+
+        let line_idx = (line as usize).saturating_sub(1).min(lines.len());
+
+        if lines.get(line_idx).is_some_and(|line| allows(line)) {
+            return true;
+        }
+
+        lines[..line_idx]
+            .iter()
+            .rev()
+            .map(|line| line.trim())
+            .take_while(|line| line.starts_with("//") || line.starts_with("#["))
+            .any(allows)
+    }
+}
+
+fn read_lines(file_path: &Path) -> Vec<String> {
+    fs::read_to_string(file_path)
+        .map(|source| source.lines().map(str::to_owned).collect())
+        .unwrap_or_default()
+}
+
+/// Returns the lints allowed by the line's comment, if any
+/// (e.g. `cycles` and `orphans` for `// cargo-modules: allow(cycles, orphans)`).
+fn allowed_lints(line: &str) -> Vec<Lint> {
+    let Some((_, comment)) = line.split_once("//") else {
+        return vec![];
+    };
+
+    // Doc comments (e.g. `//! cargo-modules: allow(orphans)`) count just as well:
+    let comment = comment.trim_start_matches(['/', '!']).trim_start();
+
+    let lints = comment
+        .strip_prefix(MARKER)
+        .and_then(|rest| rest.trim_start().strip_prefix("allow("))
+        .and_then(|rest| rest.split_once(')'))
+        .map(|(lints, _)| lints);
+
+    let Some(lints) = lints else {
+        return vec![];
+    };
+
+    lints
+        .split(',')
+        .filter_map(|lint| lint.trim().parse().ok())
+        .collect()
+}
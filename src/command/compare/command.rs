@@ -0,0 +1,74 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at https://mozilla.org/MPL/2.0/.
+
+use clap::Parser;
+use log::trace;
+use ra_ap_hir as hir;
+use ra_ap_ide::RootDatabase;
+
+use crate::{
+    analyzer::LoadOptions,
+    graph::GraphBuilder,
+    output,
+    progress::{self, Stage},
+};
+
+use super::{options::Options, report::Report};
+
+#[derive(Parser, Clone, PartialEq, Eq, Debug)]
+pub struct Command {
+    #[command(flatten)]
+    pub options: Options,
+}
+
+impl Command {
+    pub fn new(options: Options) -> Self {
+        Self { options }
+    }
+
+    pub(crate) fn sanitize(&mut self) {}
+
+    #[doc(hidden)]
+    pub fn run(self, krates: &[hir::Crate], db: &RootDatabase) -> anyhow::Result<()> {
+        let &[lhs_krate, rhs_krate] = krates else {
+            anyhow::bail!(
+                "Expected exactly two crates to compare (e.g. via `-p crate_a -p crate_b`), found {}",
+                krates.len()
+            );
+        };
+
+        trace!("Building graph ...");
+        progress::stage(Stage::Building);
+
+        let (graph, crate_node_idxs) = GraphBuilder::new(db, krates).build()?;
+
+        let report = Report::new(
+            (lhs_krate, crate_node_idxs[0]),
+            (rhs_krate, crate_node_idxs[1]),
+            &graph,
+            self.options.move_similarity,
+            db,
+        );
+
+        trace!("Printing comparison ...");
+        progress::stage(Stage::Printing);
+
+        let output = report.to_string();
+
+        match &self.options.output {
+            Some(output_path) => output::write_atomically(output_path, output),
+            None => {
+                print!("{output}");
+                Ok(())
+            }
+        }
+    }
+
+    pub fn load_options(&self) -> LoadOptions {
+        LoadOptions {
+            cfg_test: self.options.cfg_test,
+            sysroot: false,
+        }
+    }
+}
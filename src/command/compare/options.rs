@@ -0,0 +1,36 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at https://mozilla.org/MPL/2.0/.
+
+use std::path::PathBuf;
+
+use clap::Parser;
+
+use crate::options::{GeneralOptions, ProjectOptions};
+
+#[derive(Parser, Clone, PartialEq, Eq, Debug)]
+#[group(id = "CompareOptions")]
+pub struct Options {
+    #[command(flatten)]
+    pub general: GeneralOptions,
+
+    #[command(flatten)]
+    pub project: ProjectOptions,
+
+    /// The minimum similarity (as the percentage of their items found in both) of two modules
+    /// only found in either crate for reporting them as moved (or renamed), rather than unrelated.
+    #[arg(
+        long = "move-similarity",
+        default_value = "80",
+        value_parser = clap::value_parser!(u8).range(1..=100)
+    )]
+    pub move_similarity: u8,
+
+    /// Analyze with `#[cfg(test)]` enabled (i.e as if built via `cargo test`).
+    #[arg(long = "cfg-test")]
+    pub cfg_test: bool,
+
+    /// Write the output to this file (atomically replacing it) instead of printing it.
+    #[arg(short = 'o', long = "output")]
+    pub output: Option<PathBuf>,
+}
@@ -0,0 +1,368 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at https://mozilla.org/MPL/2.0/.
+
+//! Structural comparison of two crates, as printed by `compare`.
+//!
+//! Modules only found in either crate get matched up by the similarity of their items,
+//! with those of sufficient similarity getting reported as moved (or renamed),
+//! rather than as unrelated additions and removals.
+
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    fmt,
+};
+
+use petgraph::{graph::NodeIndex, visit::EdgeRef as _, Direction};
+use ra_ap_hir as hir;
+use ra_ap_ide::RootDatabase;
+
+use crate::{
+    analyzer,
+    graph::{Edge, Graph, Node, Relationship},
+};
+
+/// The structural metrics of a single crate.
+#[derive(Clone, PartialEq, Eq, Debug)]
+struct Profile {
+    /// The crate's name.
+    name: String,
+    /// The number of items (other than modules) per module,
+    /// by the module's path relative to the crate's root module (e.g. `net::http`).
+    items_by_module: BTreeMap<String, usize>,
+    /// The (kind-prefixed) names of the items per module (e.g. `struct Request`),
+    /// for matching up moved modules.
+    item_names_by_module: BTreeMap<String, BTreeSet<String>>,
+    /// The depth of the deepest module (with the crate's root module at depth 0).
+    max_depth: usize,
+    /// The number of "uses" edges between the crate's own items.
+    internal_uses: usize,
+    /// The number of "uses" edges from the crate's items to those of other crates.
+    external_uses: usize,
+}
+
+impl Profile {
+    fn new(
+        krate: hir::Crate,
+        crate_node_idx: NodeIndex,
+        graph: &Graph<Node, Edge>,
+        db: &RootDatabase,
+    ) -> Self {
+        let name = analyzer::crate_name(krate, db);
+
+        let mut items_by_module: BTreeMap<String, usize> = BTreeMap::new();
+        let mut item_names_by_module: BTreeMap<String, BTreeSet<String>> = BTreeMap::new();
+        let mut max_depth = 0;
+        let mut internal_uses = 0;
+        let mut external_uses = 0;
+
+        // Walk the crate's module tree depth-first, following its "owns" edges,
+        // attributing each item to the innermost module containing it:
+        let mut stack = vec![(crate_node_idx, String::new(), 0)];
+
+        while let Some((node_idx, module_path, depth)) = stack.pop() {
+            let is_module = matches!(graph[node_idx].hir, hir::ModuleDef::Module(_));
+
+            if is_module {
+                items_by_module.entry(module_path.clone()).or_default();
+                max_depth = max_depth.max(depth);
+            } else {
+                let node = &graph[node_idx];
+                *items_by_module.entry(module_path.clone()).or_default() += 1;
+                item_names_by_module
+                    .entry(module_path.clone())
+                    .or_default()
+                    .insert(format!(
+                        "{} {}",
+                        node.kind_display_name(db),
+                        node.display_name(db)
+                    ));
+            }
+
+            for edge_ref in graph.edges_directed(node_idx, Direction::Outgoing) {
+                let target_idx = edge_ref.target();
+
+                match edge_ref.weight().kind {
+                    Relationship::Owns => {
+                        let target = &graph[target_idx];
+
+                        let (module_path, depth) = match target.hir {
+                            hir::ModuleDef::Module(_) => {
+                                let name = analyzer::name(target.hir, db).unwrap_or_default();
+                                let path = if module_path.is_empty() {
+                                    name
+                                } else {
+                                    format!("{module_path}::{name}")
+                                };
+                                (path, depth + 1)
+                            }
+                            _ => (module_path.clone(), depth),
+                        };
+
+                        stack.push((target_idx, module_path, depth));
+                    }
+                    Relationship::Uses => {
+                        if analyzer::krate(graph[target_idx].hir, db) == Some(krate) {
+                            internal_uses += 1;
+                        } else {
+                            external_uses += 1;
+                        }
+                    }
+                    Relationship::ReExports => {}
+                }
+            }
+        }
+
+        Self {
+            name,
+            items_by_module,
+            item_names_by_module,
+            max_depth,
+            internal_uses,
+            external_uses,
+        }
+    }
+
+    fn module_count(&self) -> usize {
+        self.items_by_module.len()
+    }
+
+    fn item_count(&self) -> usize {
+        self.items_by_module.values().sum()
+    }
+
+    fn uses(&self) -> usize {
+        self.internal_uses + self.external_uses
+    }
+
+    /// Returns the average number of "uses" edges per item.
+    fn uses_per_item(&self) -> f64 {
+        match self.item_count() {
+            0 => 0.0,
+            items => self.uses() as f64 / items as f64,
+        }
+    }
+
+    /// Returns the paths of the crate's modules, other than its root module.
+    fn module_paths(&self) -> impl Iterator<Item = &String> {
+        self.items_by_module.keys().filter(|path| !path.is_empty())
+    }
+}
+
+/// A module only found in the left-hand crate, matched up with one only found in the right-hand one.
+#[derive(Clone, PartialEq, Debug)]
+struct Move {
+    lhs_path: String,
+    rhs_path: String,
+    /// The percentage of the two modules' items found in both (by kind and name).
+    similarity: f64,
+}
+
+#[derive(Clone, PartialEq, Debug)]
+pub(crate) struct Report {
+    lhs: Profile,
+    rhs: Profile,
+    moves: Vec<Move>,
+}
+
+impl Report {
+    pub(crate) fn new(
+        (lhs_krate, lhs_node_idx): (hir::Crate, NodeIndex),
+        (rhs_krate, rhs_node_idx): (hir::Crate, NodeIndex),
+        graph: &Graph<Node, Edge>,
+        min_similarity: u8,
+        db: &RootDatabase,
+    ) -> Self {
+        let lhs = Profile::new(lhs_krate, lhs_node_idx, graph, db);
+        let rhs = Profile::new(rhs_krate, rhs_node_idx, graph, db);
+        let moves = Self::moves(&lhs, &rhs, f64::from(min_similarity));
+
+        Self { lhs, rhs, moves }
+    }
+
+    /// Matches up the modules only found in either crate, pairing up the most similar ones first.
+    ///
+    /// Modules without any items (other than modules) have nothing to be matched by.
+    fn moves(lhs: &Profile, rhs: &Profile, min_similarity: f64) -> Vec<Move> {
+        let lhs_paths = Self::exclusive_modules(lhs, rhs);
+        let rhs_paths = Self::exclusive_modules(rhs, lhs);
+
+        let mut candidates = vec![];
+
+        for lhs_path in &lhs_paths {
+            for rhs_path in &rhs_paths {
+                let (Some(lhs_names), Some(rhs_names)) = (
+                    lhs.item_names_by_module.get(*lhs_path),
+                    rhs.item_names_by_module.get(*rhs_path),
+                ) else {
+                    continue;
+                };
+
+                let shared = lhs_names.intersection(rhs_names).count();
+                let total = lhs_names.union(rhs_names).count();
+                let similarity = 100.0 * shared as f64 / total as f64;
+
+                if similarity >= min_similarity {
+                    candidates.push(Move {
+                        lhs_path: (*lhs_path).clone(),
+                        rhs_path: (*rhs_path).clone(),
+                        similarity,
+                    });
+                }
+            }
+        }
+
+        candidates.sort_by(|lhs, rhs| {
+            rhs.similarity
+                .total_cmp(&lhs.similarity)
+                .then_with(|| (&lhs.lhs_path, &lhs.rhs_path).cmp(&(&rhs.lhs_path, &rhs.rhs_path)))
+        });
+
+        let mut matched_lhs_paths = BTreeSet::new();
+        let mut matched_rhs_paths = BTreeSet::new();
+
+        let mut moves = vec![];
+
+        for candidate in candidates {
+            if matched_lhs_paths.contains(&candidate.lhs_path)
+                || matched_rhs_paths.contains(&candidate.rhs_path)
+            {
+                continue;
+            }
+
+            matched_lhs_paths.insert(candidate.lhs_path.clone());
+            matched_rhs_paths.insert(candidate.rhs_path.clone());
+
+            moves.push(candidate);
+        }
+
+        moves.sort_by(|lhs, rhs| lhs.lhs_path.cmp(&rhs.lhs_path));
+
+        moves
+    }
+
+    /// Returns the metrics to compare, as (label, lhs value, rhs value) rows.
+    fn metrics(&self) -> Vec<(String, String, String)> {
+        let row = |label: &str, metric: &dyn Fn(&Profile) -> String| {
+            (label.to_owned(), metric(&self.lhs), metric(&self.rhs))
+        };
+
+        vec![
+            row("Modules:", &|profile| profile.module_count().to_string()),
+            row("Max depth:", &|profile| profile.max_depth.to_string()),
+            row("Items:", &|profile| profile.item_count().to_string()),
+            row("Uses:", &|profile| profile.uses().to_string()),
+            row("  internal", &|profile| profile.internal_uses.to_string()),
+            row("  external", &|profile| profile.external_uses.to_string()),
+            row("Uses per item:", &|profile| {
+                format!("{:.2}", profile.uses_per_item())
+            }),
+        ]
+    }
+
+    /// Returns the paths of the modules of both crates, along with their number of items in each.
+    fn shared_modules(&self) -> Vec<(String, String, String)> {
+        self.lhs
+            .module_paths()
+            .filter_map(|path| {
+                let rhs_items = self.rhs.items_by_module.get(path)?;
+                let lhs_items = self.lhs.items_by_module[path];
+                Some((
+                    format!("  {path}"),
+                    lhs_items.to_string(),
+                    rhs_items.to_string(),
+                ))
+            })
+            .collect()
+    }
+
+    /// Returns the paths of the modules of `profile` missing from `other`.
+    fn exclusive_modules<'a>(profile: &'a Profile, other: &Profile) -> Vec<&'a String> {
+        profile
+            .module_paths()
+            .filter(|path| !other.items_by_module.contains_key(*path))
+            .collect()
+    }
+}
+
+impl fmt::Display for Report {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let metrics = self.metrics();
+        let shared_modules = self.shared_modules();
+
+        let label_width = metrics
+            .iter()
+            .chain(&shared_modules)
+            .map(|(label, _, _)| label.chars().count())
+            .max()
+            .unwrap_or(0);
+
+        let value_width = [&self.lhs.name, &self.rhs.name]
+            .iter()
+            .map(|name| name.chars().count())
+            .chain(
+                metrics
+                    .iter()
+                    .chain(&shared_modules)
+                    .flat_map(|(_, lhs, rhs)| [lhs.len(), rhs.len()]),
+            )
+            .max()
+            .unwrap_or(0);
+
+        let fmt_row = |f: &mut fmt::Formatter<'_>, (label, lhs, rhs): &(String, String, String)| {
+            writeln!(
+                f,
+                "{label:<label_width$}  {lhs:>value_width$}  {rhs:>value_width$}"
+            )
+        };
+
+        let header = (String::new(), self.lhs.name.clone(), self.rhs.name.clone());
+        fmt_row(f, &header)?;
+
+        for row in &metrics {
+            fmt_row(f, row)?;
+        }
+
+        writeln!(f)?;
+        writeln!(f, "Shared modules ({}):", shared_modules.len())?;
+
+        for row in &shared_modules {
+            fmt_row(f, row)?;
+        }
+
+        writeln!(f)?;
+        writeln!(f, "Moved modules ({}):", self.moves.len())?;
+
+        for Move {
+            lhs_path,
+            rhs_path,
+            similarity,
+        } in &self.moves
+        {
+            writeln!(f, "  {lhs_path} -> {rhs_path} ({similarity:.0}% similar)")?;
+        }
+
+        let moved_paths: BTreeSet<&String> = self
+            .moves
+            .iter()
+            .flat_map(|moved| [&moved.lhs_path, &moved.rhs_path])
+            .collect();
+
+        for (profile, other) in [(&self.lhs, &self.rhs), (&self.rhs, &self.lhs)] {
+            // A path is only ever exclusive to one of the crates, so the moved ones can share a set:
+            let paths: Vec<_> = Self::exclusive_modules(profile, other)
+                .into_iter()
+                .filter(|path| !moved_paths.contains(path))
+                .collect();
+
+            writeln!(f)?;
+            writeln!(f, "Only in {} ({}):", profile.name, paths.len())?;
+
+            for path in paths {
+                writeln!(f, "  {path}")?;
+            }
+        }
+
+        Ok(())
+    }
+}
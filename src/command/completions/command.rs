@@ -0,0 +1,98 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at https://mozilla.org/MPL/2.0/.
+
+use std::io;
+
+use clap::{builder::PossibleValuesParser, CommandFactory as _, Parser};
+
+use crate::options::App;
+
+use super::options::Options;
+
+const BIN_NAME: &str = "cargo-modules";
+
+const LINTS: &[&str] = &[
+    "cycles",
+    "orphans",
+    "glob-imports",
+    "deep-nesting",
+    "unused-pub",
+];
+
+// Most of the value options get parsed via `FromStr`, leaving clap unaware of their values,
+// so they get patched in for the completion scripts to offer them:
+const VALUE_HINTS: &[(&str, &[&str])] = &[
+    ("color", &["auto", "always", "never"]),
+    ("theme", &["dark", "light", "monochrome", "colorblind-safe"]),
+    ("summary", &["text", "json"]),
+    ("message_format", &["human", "quickfix", "github", "json"]),
+    ("sort_by", &["name", "visibility", "kind", "source", "size"]),
+    ("icons", &["none", "nerd", "unicode", "ascii"]),
+    (
+        "layout",
+        &["none", "dot", "neato", "twopi", "circo", "fdp", "sfdp"],
+    ),
+    ("layout_direction", &["TB", "LR", "BT", "RL"]),
+    (
+        "splines",
+        &["none", "line", "polyline", "curved", "ortho", "spline"],
+    ),
+    ("label", &["full", "short"]),
+    ("color_by", &["visibility", "loc", "std", "depth", "none"]),
+    ("cluster_by", &["feature"]),
+    ("group_by", &["crate", "module", "item"]),
+    ("format", &["table", "json", "csv"]),
+    ("report", &["summary", "dependencies", "teams"]),
+    ("graph_format", &["dot", "ascii", "unicode", "review-json"]),
+    ("emit", &["dot", "json", "mermaid"]),
+    ("allow", LINTS),
+    ("warn", LINTS),
+    ("deny", LINTS),
+    ("kind", &["graph", "tree"]),
+];
+
+#[derive(Parser, Clone, PartialEq, Eq, Debug)]
+pub struct Command {
+    #[command(flatten)]
+    pub options: Options,
+}
+
+impl Command {
+    pub fn new(options: Options) -> Self {
+        Self { options }
+    }
+
+    pub(crate) fn sanitize(&mut self) {}
+
+    #[doc(hidden)]
+    pub fn run(self) -> anyhow::Result<()> {
+        let mut app = with_value_hints(App::command());
+
+        clap_complete::generate(self.options.shell, &mut app, BIN_NAME, &mut io::stdout());
+
+        Ok(())
+    }
+}
+
+/// Adds the values of `VALUE_HINTS` to the matching arguments of the command and its sub-commands.
+fn with_value_hints(mut command: clap::Command) -> clap::Command {
+    for (id, values) in VALUE_HINTS {
+        if command.get_arguments().any(|arg| arg.get_id() == id) {
+            command = command.mut_arg(id, |arg| {
+                arg.value_parser(PossibleValuesParser::new(values.iter().copied()))
+            });
+        }
+    }
+
+    let subcommand_names: Vec<String> = command
+        .get_subcommands()
+        .map(|subcommand| subcommand.get_name().to_owned())
+        .collect();
+
+    for name in subcommand_names {
+        command = command.mut_subcommand(name, with_value_hints);
+    }
+
+    command
+}
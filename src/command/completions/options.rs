@@ -0,0 +1,14 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at https://mozilla.org/MPL/2.0/.
+
+use clap::Parser;
+use clap_complete::Shell;
+
+#[derive(Parser, Clone, PartialEq, Eq, Debug)]
+#[group(id = "CompletionsOptions")]
+pub struct Options {
+    /// The shell to generate the completion script for.
+    #[arg(value_name = "SHELL")]
+    pub shell: Shell,
+}
@@ -0,0 +1,77 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at https://mozilla.org/MPL/2.0/.
+
+use clap::Parser;
+use log::trace;
+use ra_ap_hir as hir;
+use ra_ap_ide::RootDatabase;
+
+use crate::{
+    analyzer::LoadOptions,
+    command::dependencies::dep_kinds::DependencyKinds,
+    graph::GraphBuilder,
+    output,
+    progress::{self, Stage},
+    summary,
+};
+
+use super::{graph::CrateGraph, options::Options, unused::Failure};
+
+#[derive(Parser, Clone, PartialEq, Eq, Debug)]
+pub struct Command {
+    #[command(flatten)]
+    pub options: Options,
+}
+
+impl Command {
+    pub fn new(options: Options) -> Self {
+        Self { options }
+    }
+
+    pub(crate) fn sanitize(&mut self) {}
+
+    #[doc(hidden)]
+    pub fn run(self, krates: &[hir::Crate], db: &RootDatabase) -> anyhow::Result<()> {
+        trace!("Building graph ...");
+        progress::stage(Stage::Building);
+
+        let (graph, _crate_node_idxs) = GraphBuilder::new(db, krates).build()?;
+
+        let dependency_kinds = DependencyKinds::new(&self.options.project);
+        let mut crate_graph =
+            CrateGraph::new(&graph, krates, &dependency_kinds, self.options.cfg_test, db);
+
+        if self.options.with_impls {
+            crate_graph = crate_graph.with_impls(krates, db);
+        }
+
+        trace!("Printing graph ...");
+        progress::stage(Stage::Printing);
+
+        let output = crate_graph.to_string();
+
+        summary::record_edges(crate_graph.edge_count());
+
+        match &self.options.output {
+            Some(output_path) => output::write_atomically(output_path, output)?,
+            None => print!("{output}"),
+        }
+
+        if !self.options.deny_unused {
+            return Ok(());
+        }
+
+        match Failure::from_graph(&crate_graph, &self.options.project) {
+            Some(failure) => Err(failure.into()),
+            None => Ok(()),
+        }
+    }
+
+    pub fn load_options(&self) -> LoadOptions {
+        LoadOptions {
+            cfg_test: self.options.cfg_test,
+            sysroot: false,
+        }
+    }
+}
@@ -0,0 +1,345 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at https://mozilla.org/MPL/2.0/.
+
+//! Crate-level dependency graphs, as printed by `crates`.
+//!
+//! Each of the processed crates gets an edge to each of the crates it declares a dependency on,
+//! labeled by the number of its items' uses of the dependency's items (as found in its module graph),
+//! with declared dependencies that aren't used at all getting flagged as such.
+//!
+//! If requested, each crate also gets an "implements" edge to each of the other crates
+//! whose traits it implements, labeled by the number of such impls (and the traits' names).
+
+use std::{
+    collections::{BTreeMap, BTreeSet, HashMap},
+    fmt,
+};
+
+use petgraph::visit::{EdgeRef as _, IntoEdgeReferences as _};
+use ra_ap_hir as hir;
+use ra_ap_ide::RootDatabase;
+
+use crate::{
+    analyzer,
+    command::dependencies::{
+        dep_kinds::{DependencyKind, DependencyKinds},
+        theme::{color_palette, edge_styles, node_styles, EdgeStyle, Stroke},
+    },
+    graph::{Edge, Graph, Node, Relationship},
+};
+
+const INDENTATION: &str = "    ";
+
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+enum CrateKind {
+    /// One of the processed crates.
+    Member,
+    /// A crate depended on by the processed crates (of the given kind, if known).
+    Dependency(Option<DependencyKind>),
+}
+
+#[derive(Clone, PartialEq, Eq, Debug)]
+struct CrateEdge {
+    source: String,
+    target: String,
+    /// The number of uses of the target's items by the source's items.
+    uses: usize,
+}
+
+#[derive(Clone, PartialEq, Eq, Debug)]
+struct ImplEdge {
+    source: String,
+    target: String,
+    /// The number of impls of the target's traits within the source.
+    impls: usize,
+    /// The names of the target's traits implemented within the source.
+    traits: BTreeSet<String>,
+}
+
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub(crate) struct CrateGraph {
+    label: String,
+    nodes: BTreeMap<String, CrateKind>,
+    edges: Vec<CrateEdge>,
+    impl_edges: Vec<ImplEdge>,
+}
+
+impl CrateGraph {
+    /// Builds the crate-level graph of the `krates`, counting the uses of their dependencies in `graph`.
+    ///
+    /// Dev-dependencies are only included when analyzing with `#[cfg(test)]` enabled,
+    /// as the code using them is invisible otherwise.
+    pub(crate) fn new(
+        graph: &Graph<Node, Edge>,
+        krates: &[hir::Crate],
+        dependency_kinds: &DependencyKinds,
+        cfg_test: bool,
+        db: &RootDatabase,
+    ) -> Self {
+        let mut uses: HashMap<(hir::Crate, hir::Crate), usize> = HashMap::new();
+
+        for edge_ref in graph.edge_references() {
+            if edge_ref.weight().kind == Relationship::Owns {
+                continue;
+            }
+
+            let (Some(source), Some(target)) = (
+                analyzer::krate(graph[edge_ref.source()].hir, db),
+                analyzer::krate(graph[edge_ref.target()].hir, db),
+            ) else {
+                continue;
+            };
+
+            if source != target {
+                *uses.entry((source, target)).or_default() += 1;
+            }
+        }
+
+        let mut nodes: BTreeMap<String, CrateKind> = krates
+            .iter()
+            .map(|krate| (analyzer::crate_name(*krate, db), CrateKind::Member))
+            .collect();
+
+        let mut edges = vec![];
+
+        for krate in krates {
+            for dependency in krate.dependencies(db) {
+                // Sysroot crates (`std`, `core` & friends) are implicit dependencies of every crate:
+                if dependency.krate.origin(db).is_lang() {
+                    continue;
+                }
+
+                let name = analyzer::crate_name(dependency.krate, db);
+                let kind = dependency_kinds.get(&name);
+
+                if kind == Some(DependencyKind::Dev) && !cfg_test {
+                    continue;
+                }
+
+                nodes
+                    .entry(name.clone())
+                    .or_insert(CrateKind::Dependency(kind));
+
+                edges.push(CrateEdge {
+                    source: analyzer::crate_name(*krate, db),
+                    target: name,
+                    uses: uses.get(&(*krate, dependency.krate)).copied().unwrap_or(0),
+                });
+            }
+        }
+
+        edges.sort_by(|lhs, rhs| (&lhs.source, &lhs.target).cmp(&(&rhs.source, &rhs.target)));
+        edges.dedup();
+
+        let label = krates
+            .iter()
+            .map(|krate| analyzer::crate_name(*krate, db))
+            .collect::<Vec<_>>()
+            .join(", ");
+
+        Self {
+            label,
+            nodes,
+            edges,
+            impl_edges: vec![],
+        }
+    }
+
+    /// Adds an "implements" edge from each of the `krates` to each of the graph's other crates
+    /// whose traits it implements (e.g. a workspace's "impl" crates implementing its "core" crate's traits).
+    pub(crate) fn with_impls(mut self, krates: &[hir::Crate], db: &RootDatabase) -> Self {
+        let mut impl_edges: BTreeMap<(String, String), ImplEdge> = BTreeMap::new();
+
+        for krate in krates {
+            let source = analyzer::crate_name(*krate, db);
+
+            for impl_hir in hir::Impl::all_in_crate(db, *krate) {
+                let Some(trait_hir) = impl_hir.trait_(db) else {
+                    continue;
+                };
+
+                let trait_krate = trait_hir.module(db).krate();
+
+                if trait_krate == *krate {
+                    continue;
+                }
+
+                let target = analyzer::crate_name(trait_krate, db);
+
+                // Sysroot crates (`std`, `core` & friends) aren't part of the graph, so get no edges:
+                if !self.nodes.contains_key(&target) {
+                    continue;
+                }
+
+                let edge = impl_edges
+                    .entry((source.clone(), target.clone()))
+                    .or_insert_with(|| ImplEdge {
+                        source: source.clone(),
+                        target,
+                        impls: 0,
+                        traits: BTreeSet::new(),
+                    });
+
+                edge.impls += 1;
+                edge.traits
+                    .insert(analyzer::display_name(hir::ModuleDef::Trait(trait_hir), db));
+            }
+        }
+
+        self.impl_edges = impl_edges.into_values().collect();
+        self
+    }
+
+    /// Returns the number of the graph's edges (of all kinds).
+    pub(crate) fn edge_count(&self) -> usize {
+        self.edges.len() + self.impl_edges.len()
+    }
+
+    /// Returns the (crate, dependency) pairs of the declared dependencies that aren't used at all.
+    pub(crate) fn unused_dependencies(&self) -> Vec<(String, String)> {
+        self.edges
+            .iter()
+            .filter(|edge| edge.uses == 0)
+            .map(|edge| (edge.source.clone(), edge.target.clone()))
+            .collect()
+    }
+}
+
+impl fmt::Display for CrateGraph {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let i = INDENTATION;
+        let label = &self.label;
+
+        writeln!(f, "digraph {{")?;
+        writeln!(f)?;
+
+        indoc::writedoc!(
+            f,
+            r#"
+            {i}graph [
+            {i}    label={label:?},
+            {i}    labelloc=t,
+
+            {i}    pad=0.4,
+
+            {i}    layout=dot,
+            {i}    rankdir=LR,
+
+            {i}    fontname="Helvetica",
+            {i}    fontsize="36",
+            {i}];
+            "#,
+        )?;
+
+        writeln!(f)?;
+
+        indoc::writedoc!(
+            f,
+            r#"
+            {i}node [
+            {i}    fontname="monospace",
+            {i}    fontsize="10",
+            {i}    shape="record",
+            {i}    style="filled",
+            {i}];
+            "#,
+        )?;
+
+        writeln!(f)?;
+
+        indoc::writedoc!(
+            f,
+            r#"
+            {i}edge [
+            {i}    fontname="monospace",
+            {i}    fontsize="10",
+            {i}];
+            "#,
+        )?;
+
+        writeln!(f)?;
+
+        let node_styles = node_styles();
+
+        for (name, kind) in &self.nodes {
+            let (kind_name, fill_color, stroke) = match kind {
+                CrateKind::Member => ("crate", node_styles.krate.fill_color, None),
+                CrateKind::Dependency(Some(DependencyKind::Dev)) => (
+                    "dev-dependency",
+                    node_styles.plain.fill_color,
+                    Some(&node_styles.dependency_kind.dev),
+                ),
+                CrateKind::Dependency(Some(DependencyKind::Build)) => (
+                    "build-dependency",
+                    node_styles.plain.fill_color,
+                    Some(&node_styles.dependency_kind.build),
+                ),
+                CrateKind::Dependency(_) => ("dependency", node_styles.plain.fill_color, None),
+            };
+
+            let style = match stroke {
+                Some(stroke) => format!(r#", style="filled,{stroke}""#),
+                None => String::new(),
+            };
+
+            writeln!(
+                f,
+                r#"{i}{name:?} [label="{kind_name}|{name}", fillcolor="{fill_color}"{style}]; // {kind_name:?} node"#
+            )?;
+        }
+
+        writeln!(f)?;
+
+        let used_style = edge_styles().uses;
+        let unused_style = EdgeStyle {
+            color: color_palette().red,
+            stroke: Stroke::Dashed,
+        };
+
+        for edge in &self.edges {
+            let (kind, label, style) = match edge.uses {
+                0 => ("unused", "unused".to_owned(), &unused_style),
+                1 => ("uses", "1 use".to_owned(), &used_style),
+                uses => ("uses", format!("{uses} uses"), &used_style),
+            };
+
+            let CrateEdge { source, target, .. } = edge;
+            let EdgeStyle { color, stroke } = style;
+
+            writeln!(
+                f,
+                r#"{i}{source:?} -> {target:?} [label={label:?}, color="{color}", style="{stroke}"]; // {kind:?} edge"#
+            )?;
+        }
+
+        writeln!(f)?;
+
+        if !self.impl_edges.is_empty() {
+            let color = color_palette().purple;
+            let stroke = Stroke::Solid;
+
+            for edge in &self.impl_edges {
+                let ImplEdge {
+                    source,
+                    target,
+                    impls,
+                    traits,
+                } = edge;
+
+                let noun = if *impls == 1 { "impl" } else { "impls" };
+                let traits = traits.iter().cloned().collect::<Vec<_>>().join(", ");
+                let label = format!("{impls} {noun} ({traits})");
+
+                writeln!(
+                    f,
+                    r#"{i}{source:?} -> {target:?} [label={label:?}, color="{color}", style="{stroke}"]; // "implements" edge"#
+                )?;
+            }
+
+            writeln!(f)?;
+        }
+
+        writeln!(f, "}}")
+    }
+}
@@ -0,0 +1,38 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at https://mozilla.org/MPL/2.0/.
+
+use std::path::PathBuf;
+
+use clap::Parser;
+
+use crate::options::{GeneralOptions, ProjectOptions};
+
+#[derive(Parser, Clone, PartialEq, Eq, Debug)]
+#[group(id = "CratesOptions")]
+pub struct Options {
+    #[command(flatten)]
+    pub general: GeneralOptions,
+
+    #[command(flatten)]
+    pub project: ProjectOptions,
+
+    /// Analyze with `#[cfg(test)]` enabled (i.e as if built via `cargo test`),
+    /// which also includes dev-dependencies in the graph.
+    #[arg(long = "cfg-test")]
+    pub cfg_test: bool,
+
+    /// Fail (with exit code 2) if any declared dependencies aren't used by the analyzed code,
+    /// after printing the graph.
+    #[arg(long = "deny-unused")]
+    pub deny_unused: bool,
+
+    /// Add an "implements" edge from each crate to each of the other crates whose traits it implements,
+    /// labeled by the number of such impls.
+    #[arg(long = "with-impls")]
+    pub with_impls: bool,
+
+    /// Write the output to this file (atomically replacing it) instead of printing it.
+    #[arg(short = 'o', long = "output")]
+    pub output: Option<PathBuf>,
+}
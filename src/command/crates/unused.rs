@@ -0,0 +1,77 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at https://mozilla.org/MPL/2.0/.
+
+//! Unused dependencies, as denied via `crates --deny-unused`.
+
+use std::fmt;
+
+use crate::options::ProjectOptions;
+
+use super::graph::CrateGraph;
+
+/// The declared dependencies none of whose items are used by the crates depending on them.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct Failure {
+    /// The (crate, dependency) pairs of the unused dependencies.
+    dependencies: Vec<(String, String)>,
+    /// The feature flags the crates were analyzed with (e.g. `--features foo`), if any.
+    features: Option<String>,
+}
+
+impl fmt::Display for Failure {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let count = self.dependencies.len();
+        let noun = if count == 1 {
+            "dependency"
+        } else {
+            "dependencies"
+        };
+
+        write!(f, "Found {count} unused {noun}")?;
+
+        if let Some(features) = &self.features {
+            write!(f, " (with `{features}`)")?;
+        }
+
+        write!(f, ":")?;
+
+        for (krate, dependency) in &self.dependencies {
+            write!(f, "\n  {krate} -> {dependency}")?;
+        }
+
+        Ok(())
+    }
+}
+
+impl std::error::Error for Failure {}
+
+impl Failure {
+    /// Returns a failure listing the graph's unused dependencies, if any.
+    pub(crate) fn from_graph(graph: &CrateGraph, options: &ProjectOptions) -> Option<Self> {
+        let dependencies = graph.unused_dependencies();
+
+        if dependencies.is_empty() {
+            return None;
+        }
+
+        let mut flags = vec![];
+
+        if options.all_features {
+            flags.push("--all-features".to_owned());
+        } else {
+            if options.no_default_features {
+                flags.push("--no-default-features".to_owned());
+            }
+
+            if !options.features.is_empty() {
+                flags.push(format!("--features {}", options.features.join(",")));
+            }
+        }
+
+        Some(Self {
+            dependencies,
+            features: (!flags.is_empty()).then(|| flags.join(" ")),
+        })
+    }
+}
@@ -0,0 +1,118 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at https://mozilla.org/MPL/2.0/.
+
+use std::io::{self, BufRead as _, Write};
+
+use clap::Parser;
+use json::JsonValue;
+use log::trace;
+
+use crate::analyzer::{load_workspace, LoadOptions};
+
+use super::{
+    handler::Handler,
+    options::Options,
+    rpc::{write_response, Request},
+};
+
+enum Control {
+    /// Reload the project, then respond to the request with the given id.
+    Reload(Option<JsonValue>),
+    Shutdown(Option<JsonValue>),
+}
+
+#[derive(Parser, Clone, PartialEq, Eq, Debug)]
+pub struct Command {
+    #[command(flatten)]
+    pub options: Options,
+}
+
+impl Command {
+    pub fn new(options: Options) -> Self {
+        Self { options }
+    }
+
+    pub(crate) fn sanitize(&mut self) {}
+
+    #[doc(hidden)]
+    pub fn run(self) -> anyhow::Result<()> {
+        let load_options = self.load_options();
+
+        let stdin = io::stdin();
+        let mut lines = stdin.lock().lines();
+
+        let stdout = io::stdout();
+        let mut writer = stdout.lock();
+
+        let mut reload_id = None;
+
+        loop {
+            trace!("Loading workspace ...");
+
+            let (krates, host, _vfs) =
+                load_workspace(&self.options.general, &self.options.project, &load_options)?;
+
+            if let Some(id) = reload_id.take() {
+                write_response(&mut writer, id, Ok(JsonValue::Boolean(true)))?;
+            }
+
+            let handler = Handler::new(&krates, host.raw_database());
+
+            match Self::serve(&handler, &mut lines, &mut writer)? {
+                Control::Reload(id) => reload_id = id,
+                Control::Shutdown(id) => {
+                    if let Some(id) = id {
+                        write_response(&mut writer, id, Ok(JsonValue::Null))?;
+                    }
+                    return Ok(());
+                }
+            }
+        }
+    }
+
+    /// Answers requests until either reaching the end of input, or a `reload`/`shutdown` request.
+    fn serve(
+        handler: &Handler,
+        lines: &mut impl Iterator<Item = io::Result<String>>,
+        writer: &mut dyn Write,
+    ) -> anyhow::Result<Control> {
+        for line in lines {
+            let line = line?;
+
+            if line.trim().is_empty() {
+                continue;
+            }
+
+            let request = match Request::parse(&line) {
+                Ok(request) => request,
+                Err((id, err)) => {
+                    write_response(writer, id, Err(err))?;
+                    continue;
+                }
+            };
+
+            match request.method.as_str() {
+                "reload" => return Ok(Control::Reload(request.id)),
+                "shutdown" | "exit" => return Ok(Control::Shutdown(request.id)),
+                method => {
+                    let result = handler.handle(method, &request.params);
+
+                    // Notifications (i.e. requests without an id) don't get a response:
+                    if let Some(id) = request.id {
+                        write_response(writer, id, result)?;
+                    }
+                }
+            }
+        }
+
+        Ok(Control::Shutdown(None))
+    }
+
+    pub fn load_options(&self) -> LoadOptions {
+        LoadOptions {
+            cfg_test: self.options.cfg_test,
+            sysroot: false,
+        }
+    }
+}
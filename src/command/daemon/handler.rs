@@ -0,0 +1,283 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at https://mozilla.org/MPL/2.0/.
+
+//! Answering of queries against a loaded project.
+
+use std::{
+    cell::OnceCell,
+    collections::{HashMap, VecDeque},
+};
+
+use clap::{CommandFactory as _, Parser};
+use json::{object, JsonValue};
+use log::trace;
+use petgraph::{graph::NodeIndex, visit::EdgeRef as _};
+use ra_ap_hir as hir;
+use ra_ap_ide::RootDatabase;
+
+use crate::{
+    analyzer,
+    command::{dependencies, serve::export::graph_to_json, structure},
+    graph::{Edge, Graph, GraphBuilder, Node, Relationship},
+    options::{GeneralOptions, ProjectOptions},
+    tree::{Tree, TreeBuilder},
+};
+
+use super::rpc::RpcError;
+
+pub(super) struct Handler<'a> {
+    krates: &'a [hir::Crate],
+    db: &'a RootDatabase,
+    graph: OnceCell<(Graph<Node, Edge>, Vec<NodeIndex>)>,
+}
+
+impl<'a> Handler<'a> {
+    pub fn new(krates: &'a [hir::Crate], db: &'a RootDatabase) -> Self {
+        Self {
+            krates,
+            db,
+            graph: OnceCell::new(),
+        }
+    }
+
+    pub fn handle(&self, method: &str, params: &JsonValue) -> Result<JsonValue, RpcError> {
+        trace!("Handling {method:?} request ...");
+
+        match method {
+            "tree" => self.tree(params),
+            "subgraph" => self.subgraph(params),
+            "why" => self.why(params),
+            "locate" => self.locate(params),
+            _ => Err(RpcError::method_not_found(method)),
+        }
+    }
+
+    /// Returns the (filtered) module tree of each of the project's crates.
+    fn tree(&self, params: &JsonValue) -> Result<JsonValue, RpcError> {
+        let options: structure::options::Options = self.parse_options("structure", params)?;
+
+        let mut trees = JsonValue::new_array();
+
+        for krate in self.krates.iter().cloned() {
+            let tree = TreeBuilder::new(self.db, krate)
+                .build()
+                .map_err(RpcError::internal)?;
+
+            let filter = structure::filter::Filter::new(&options, self.db, krate);
+
+            if let Some(tree) = filter.filter(&tree).map_err(RpcError::internal)? {
+                trees
+                    .push(self.tree_to_json(&tree))
+                    .map_err(RpcError::internal)?;
+            }
+        }
+
+        Ok(trees)
+    }
+
+    /// Returns the (filtered) dependency graph, as exported by the `serve` command.
+    fn subgraph(&self, params: &JsonValue) -> Result<JsonValue, RpcError> {
+        let options: dependencies::options::Options = self.parse_options("dependencies", params)?;
+
+        let (graph, crate_node_idxs) = self.graph()?;
+
+        let filter = dependencies::filter::Filter::new(&options, self.db, self.krates);
+        let graph = filter
+            .filter(graph, crate_node_idxs)
+            .map_err(RpcError::internal)?;
+
+        graph_to_json(&graph, crate_node_idxs, self.krates, self.db).map_err(RpcError::internal)
+    }
+
+    /// Returns the shortest chain of edges leading from `from` to `to`, if any.
+    fn why(&self, params: &JsonValue) -> Result<JsonValue, RpcError> {
+        let from = required_str(params, "from")?;
+        let to = required_str(params, "to")?;
+
+        let (graph, _) = self.graph()?;
+
+        let from_idx = self.find_node(graph, from)?;
+        let to_idx = self.find_node(graph, to)?;
+
+        let mut predecessors: HashMap<NodeIndex, (NodeIndex, Relationship)> = HashMap::new();
+        let mut queue = VecDeque::from([from_idx]);
+
+        while let Some(node_idx) = queue.pop_front() {
+            if node_idx == to_idx {
+                break;
+            }
+
+            for edge_ref in graph.edges(node_idx) {
+                let target_idx = edge_ref.target();
+
+                if target_idx == from_idx || predecessors.contains_key(&target_idx) {
+                    continue;
+                }
+
+                predecessors.insert(target_idx, (node_idx, edge_ref.weight().kind));
+                queue.push_back(target_idx);
+            }
+        }
+
+        if from_idx != to_idx && !predecessors.contains_key(&to_idx) {
+            return Ok(JsonValue::Null);
+        }
+
+        let mut steps = vec![];
+        let mut node_idx = to_idx;
+
+        while let Some((predecessor_idx, edge)) = predecessors.get(&node_idx).copied() {
+            steps.push(object! {
+                path: graph[node_idx].display_path(self.db),
+                via: edge.display_name(),
+            });
+            node_idx = predecessor_idx;
+        }
+
+        steps.push(object! {
+            path: graph[from_idx].display_path(self.db),
+            via: JsonValue::Null,
+        });
+
+        steps.reverse();
+
+        Ok(JsonValue::Array(steps))
+    }
+
+    /// Returns the source location of the item at `path`.
+    fn locate(&self, params: &JsonValue) -> Result<JsonValue, RpcError> {
+        let path = required_str(params, "path")?;
+
+        let (graph, _) = self.graph()?;
+
+        let node_idx = self.find_node(graph, path)?;
+        let hir = graph[node_idx].hir;
+
+        let Some((file_path, line, column)) = analyzer::location(hir, self.db) else {
+            return Ok(JsonValue::Null);
+        };
+
+        Ok(object! {
+            file: file_path.to_string_lossy().into_owned(),
+            line: line,
+            column: column,
+        })
+    }
+
+    fn graph(&self) -> Result<&(Graph<Node, Edge>, Vec<NodeIndex>), RpcError> {
+        if let Some(graph) = self.graph.get() {
+            return Ok(graph);
+        }
+
+        trace!("Building graph ...");
+
+        let graph = GraphBuilder::new(self.db, self.krates)
+            .build()
+            .map_err(RpcError::internal)?;
+
+        Ok(self.graph.get_or_init(|| graph))
+    }
+
+    fn find_node(&self, graph: &Graph<Node, Edge>, path: &str) -> Result<NodeIndex, RpcError> {
+        graph
+            .node_indices()
+            .find(|node_idx| graph[*node_idx].display_path(self.db) == path)
+            .ok_or_else(|| RpcError::invalid_params(format!("No item found at path `{path}`")))
+    }
+
+    fn tree_to_json(&self, tree: &Tree<Node>) -> JsonValue {
+        let node = &tree.node;
+
+        let mut subtrees: Vec<_> = tree.subtrees.iter().collect();
+        subtrees.sort_by_cached_key(|subtree| {
+            (
+                subtree.node.display_name(self.db),
+                subtree.node.stable_id(self.db),
+            )
+        });
+
+        let children: Vec<JsonValue> = subtrees
+            .into_iter()
+            .map(|subtree| self.tree_to_json(subtree))
+            .collect();
+
+        let visibility = if analyzer::moduledef_is_crate(node.hir, self.db) {
+            JsonValue::Null
+        } else {
+            node.visibility(self.db).to_string().into()
+        };
+
+        object! {
+            name: node.display_name(self.db),
+            path: node.display_path(self.db),
+            kind: node.kind_display_name(self.db).to_string(),
+            visibility: visibility,
+            children: children,
+        }
+    }
+
+    /// Parses a request's params as if they were passed as command-line args
+    /// to the corresponding command (e.g. `{"no_fns": true}` as `--no-fns`).
+    fn parse_options<T: Parser>(&self, command: &str, params: &JsonValue) -> Result<T, RpcError> {
+        let mut args = vec![command.to_owned()];
+
+        if !params.is_null() && !params.is_object() {
+            return Err(RpcError::invalid_params("Params must be an object"));
+        }
+
+        // The project (and how it gets loaded) is fixed by the daemon's own command-line,
+        // so such options would go ignored if passed along with a request:
+
+        let fixed_options: Vec<clap::Arg> = ProjectOptions::command()
+            .get_arguments()
+            .chain(GeneralOptions::command().get_arguments())
+            .cloned()
+            .collect();
+
+        for (key, value) in params.entries() {
+            let long = key.replace('_', "-");
+
+            if fixed_options
+                .iter()
+                .any(|arg| arg.get_long() == Some(long.as_str()))
+            {
+                return Err(RpcError::invalid_params(format!(
+                    "Param {key:?} can only be passed to the daemon itself"
+                )));
+            }
+
+            let flag = format!("--{long}");
+
+            let values: Vec<&JsonValue> = match value {
+                JsonValue::Array(values) => values.iter().collect(),
+                value => vec![value],
+            };
+
+            for value in values {
+                match value {
+                    JsonValue::Boolean(true) => args.push(flag.clone()),
+                    JsonValue::Boolean(false) | JsonValue::Null => {}
+                    JsonValue::Short(_) | JsonValue::String(_) | JsonValue::Number(_) => {
+                        args.push(flag.clone());
+                        args.push(value.to_string());
+                    }
+                    _ => {
+                        return Err(RpcError::invalid_params(format!(
+                            "Unsupported value for param {key:?}"
+                        )));
+                    }
+                }
+            }
+        }
+
+        T::try_parse_from(args)
+            .map_err(|err| RpcError::invalid_params(err.render().to_string().trim_end()))
+    }
+}
+
+fn required_str<'p>(params: &'p JsonValue, key: &str) -> Result<&'p str, RpcError> {
+    params[key]
+        .as_str()
+        .ok_or_else(|| RpcError::invalid_params(format!("Missing string param {key:?}")))
+}
@@ -0,0 +1,104 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at https://mozilla.org/MPL/2.0/.
+
+//! Minimal JSON-RPC 2.0 framing, with one message per line.
+
+use std::{
+    fmt,
+    io::{self, Write},
+};
+
+use json::{object, JsonValue};
+
+#[derive(Debug)]
+pub(super) struct RpcError {
+    pub code: i32,
+    pub message: String,
+}
+
+impl RpcError {
+    pub fn parse_error(message: impl fmt::Display) -> Self {
+        Self::new(-32700, message)
+    }
+
+    pub fn invalid_request(message: impl fmt::Display) -> Self {
+        Self::new(-32600, message)
+    }
+
+    pub fn method_not_found(method: &str) -> Self {
+        Self::new(-32601, format!("Unknown method {method:?}"))
+    }
+
+    pub fn invalid_params(message: impl fmt::Display) -> Self {
+        Self::new(-32602, message)
+    }
+
+    pub fn internal(message: impl fmt::Display) -> Self {
+        Self::new(-32000, message)
+    }
+
+    fn new(code: i32, message: impl fmt::Display) -> Self {
+        Self {
+            code,
+            message: format!("{message:#}"),
+        }
+    }
+}
+
+#[derive(Debug)]
+pub(super) struct Request {
+    /// The request's id, or `None` for notifications.
+    pub id: Option<JsonValue>,
+    pub method: String,
+    pub params: JsonValue,
+}
+
+impl Request {
+    pub fn parse(line: &str) -> Result<Self, (JsonValue, RpcError)> {
+        let message =
+            json::parse(line).map_err(|err| (JsonValue::Null, RpcError::parse_error(err)))?;
+
+        let id = message.has_key("id").then(|| message["id"].clone());
+
+        let Some(method) = message["method"].as_str() else {
+            let id = id.unwrap_or(JsonValue::Null);
+            return Err((id, RpcError::invalid_request("Missing method")));
+        };
+
+        Ok(Self {
+            id,
+            method: method.to_owned(),
+            params: message["params"].clone(),
+        })
+    }
+}
+
+pub(super) fn write_response(
+    writer: &mut dyn Write,
+    id: JsonValue,
+    result: Result<JsonValue, RpcError>,
+) -> io::Result<()> {
+    let response = match result {
+        Ok(result) => object! {
+            jsonrpc: "2.0",
+            id: id,
+            result: result,
+        },
+        Err(error) => {
+            let error = object! {
+                code: error.code,
+                message: error.message,
+            };
+
+            object! {
+                jsonrpc: "2.0",
+                id: id,
+                error: error,
+            }
+        }
+    };
+
+    writeln!(writer, "{}", response.dump())?;
+    writer.flush()
+}
@@ -4,8 +4,19 @@
 
 pub mod options;
 
+pub(super) mod anonymize;
 pub(crate) mod command;
 pub(super) mod cycles;
-pub(super) mod filter;
-pub(super) mod printer;
-pub(super) mod theme;
+pub(crate) mod dep_kinds;
+pub mod fail_on;
+pub(crate) mod filter;
+pub(super) mod highlight;
+pub(crate) mod leaves;
+pub(super) mod mermaid;
+pub(crate) mod printer;
+pub(super) mod review;
+pub(super) mod split;
+pub(super) mod template;
+pub(super) mod text;
+pub(crate) mod theme;
+pub(super) mod urls;
@@ -0,0 +1,29 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at https://mozilla.org/MPL/2.0/.
+
+//! Pseudonyms for the names of a graph's nodes, as printed via `--anonymize`.
+
+use std::{
+    collections::hash_map::DefaultHasher,
+    hash::{Hash, Hasher},
+};
+
+/// Returns a stable pseudonym for `name` (e.g. `n4f1c09a2` for `foo`),
+/// such that equal names always map to equal pseudonyms.
+pub(crate) fn pseudonym(name: &str) -> String {
+    let mut hasher = DefaultHasher::new();
+    name.hash(&mut hasher);
+    let hash = hasher.finish();
+
+    format!("n{:08x}", hash as u32)
+}
+
+/// Replaces each segment of `path` (e.g. `foo::bar::Baz`) with its pseudonym,
+/// preserving the path's structure.
+pub(crate) fn anonymize_path(path: &str) -> String {
+    path.split("::")
+        .map(pseudonym)
+        .collect::<Vec<_>>()
+        .join("::")
+}
@@ -2,6 +2,14 @@
 // License, v. 2.0. If a copy of the MPL was not distributed with this
 // file, You can obtain one at https://mozilla.org/MPL/2.0/.
 
+use std::{
+    collections::HashMap,
+    fmt, fs,
+    io::BufWriter,
+    path::{Path, PathBuf},
+};
+
+use anyhow::Context as _;
 use clap::Parser;
 use log::trace;
 use petgraph::graph::NodeIndex;
@@ -9,15 +17,30 @@ use ra_ap_hir as hir;
 use ra_ap_ide::RootDatabase;
 
 use crate::{
-    analyzer::LoadOptions,
+    analyzer::{self, LoadOptions},
+    api::{
+        self,
+        analyzers::{self, Analyzer, NoStd, Requirement},
+    },
+    command::{orphans::scanner, serve::export::graph_to_json, Rendered},
     graph::{Edge, Graph, GraphBuilder, Node},
+    options::MessageFormat,
+    output,
+    progress::{self, Stage},
+    quickfix::{self, Entry, Severity},
+    summary,
+    writer::FmtWriter,
 };
 
 use super::{
-    cycles::tri_color::{CycleDetector, TriColorDepthFirstSearch},
+    cycles, fail_on,
     filter::Filter,
-    options::{LayoutAlgorithm, Options},
+    leaves::{self, MergedLeaves},
+    mermaid::MermaidPrinter,
+    options::{ColorBy, EmitFormat, GraphFormat, LayoutAlgorithm, Options},
     printer::Printer,
+    review, split, template,
+    text::TextPrinter,
 };
 
 #[derive(Parser, Clone, PartialEq, Eq, Debug)]
@@ -34,45 +57,379 @@ impl Command {
     pub(crate) fn sanitize(&mut self) {}
 
     #[doc(hidden)]
-    pub fn run(self, krate: hir::Crate, db: &RootDatabase) -> anyhow::Result<()> {
-        trace!("Building graph ...");
+    pub fn run(self, krates: &[hir::Crate], db: &RootDatabase) -> anyhow::Result<()> {
+        let (graph, crate_node_idxs) = self.build(krates, db)?;
+
+        let stdout = std::io::stdout();
+        let mut writer = FmtWriter::new(BufWriter::new(stdout.lock()));
+
+        if !self.write_files(&graph, &crate_node_idxs, krates, db)? {
+            self.fmt(&mut writer, &graph, &crate_node_idxs, krates, db)?;
+        }
+
+        // The graph only gets checked once it has been printed in full:
+        let checked = self.check_graph(&mut writer, &graph, &crate_node_idxs, krates, db);
+
+        writer.flush()?;
 
-        let builder = GraphBuilder::new(db, krate);
-        let (graph, crate_node_idx) = builder.build()?;
+        checked
+    }
+
+    pub(crate) fn render(
+        &self,
+        krates: &[hir::Crate],
+        db: &RootDatabase,
+    ) -> anyhow::Result<Rendered> {
+        let (graph, crate_node_idxs) = self.build(krates, db)?;
+
+        let mut output = String::new();
+
+        if !self.write_files(&graph, &crate_node_idxs, krates, db)? {
+            self.fmt(&mut output, &graph, &crate_node_idxs, krates, db)?;
+        }
+
+        let checked = self.check_graph(&mut output, &graph, &crate_node_idxs, krates, db);
+
+        Ok(Rendered { output, checked })
+    }
 
+    /// Checks the crate's graph against `--acyclic` and the `--fail-on` conditions, if any,
+    /// without printing the graph itself (e.g. for outputs re-used from the cache).
+    pub(crate) fn check(&self, krates: &[hir::Crate], db: &RootDatabase) -> anyhow::Result<()> {
+        if !self.options.acyclic && self.options.fail_on.is_empty() {
+            return Ok(());
+        }
+
+        let (graph, crate_node_idxs) = self.build(krates, db)?;
+
+        let stdout = std::io::stdout();
+        let mut writer = FmtWriter::new(BufWriter::new(stdout.lock()));
+
+        let checked = self.check_graph(&mut writer, &graph, &crate_node_idxs, krates, db);
+
+        writer.flush()?;
+
+        checked
+    }
+
+    fn check_graph(
+        &self,
+        f: &mut dyn fmt::Write,
+        graph: &Graph<Node, Edge>,
+        crate_node_idxs: &[NodeIndex],
+        krates: &[hir::Crate],
+        db: &RootDatabase,
+    ) -> anyhow::Result<()> {
         if self.options.acyclic {
-            if let Some(cycle) =
-                TriColorDepthFirstSearch::new(&graph).run_from(crate_node_idx, &mut CycleDetector)
-            {
+            trace!("Checking graph for cycles ...");
+
+            if let Some(cycle) = cycles::find_cycle(graph, crate_node_idxs) {
                 assert!(cycle.len() >= 2);
                 let first = graph[cycle[0]].display_path(db);
                 let last = graph[*cycle.last().unwrap()].display_path(db);
-                let drawing = draw_cycle(&graph, cycle, db);
+
+                if self.options.message_format != MessageFormat::Human {
+                    fmt_cycle_quickfix(f, graph, &cycle, &self.options, db)?;
+                    anyhow::bail!("Circular dependency between `{first}` and `{last}`.");
+                }
+
+                let drawing = cycles::draw_cycle(graph, cycle, db);
                 anyhow::bail!("Circular dependency between `{first}` and `{last}`.\n\n{drawing}");
             }
         }
 
+        if self.options.fail_on.is_empty() {
+            return Ok(());
+        }
+
+        trace!("Checking graph ...");
+
+        fail_on::check(&self.options.fail_on, graph, crate_node_idxs, krates, db)
+    }
+
+    /// Writes the graph to the files of `--split-output` or `--emit` (if either was passed),
+    /// returning whether it did so, rather than leaving the graph to be printed.
+    fn write_files(
+        &self,
+        graph: &Graph<Node, Edge>,
+        crate_node_idxs: &[NodeIndex],
+        krates: &[hir::Crate],
+        db: &RootDatabase,
+    ) -> anyhow::Result<bool> {
+        if let Some(dir_path) = &self.options.split_output {
+            self.write_split(dir_path, graph, crate_node_idxs, krates, db)?;
+        } else if !self.options.emit.is_empty() {
+            self.write_emitted(graph, crate_node_idxs, krates, db)?;
+        } else {
+            return Ok(false);
+        }
+
+        Ok(true)
+    }
+
+    fn write_split(
+        &self,
+        dir_path: &Path,
+        graph: &Graph<Node, Edge>,
+        crate_node_idxs: &[NodeIndex],
+        krates: &[hir::Crate],
+        db: &RootDatabase,
+    ) -> anyhow::Result<()> {
+        if self.options.layout == LayoutAlgorithm::None {
+            return Ok(());
+        }
+
+        let requirements = self.requirements(graph, krates, db);
+
+        fs::create_dir_all(dir_path)
+            .with_context(|| format!("Could not create directory {}", dir_path.display()))?;
+
+        trace!("Printing overview graph ...");
+
+        let overview_options = Options {
+            max_depth: Some(1),
+            ..self.options.clone()
+        };
+
+        let filter = Filter::new(&overview_options, db, krates);
+        let overview_graph = filter.filter(graph, crate_node_idxs)?;
+
+        let mut output = String::new();
+        let printer =
+            Printer::new(&overview_options, krates, db).with_requirements(requirements.clone());
+        printer.fmt(&mut output, &overview_graph, crate_node_idxs)?;
+        output::write_atomically(&dir_path.join("index.dot"), output)?;
+
+        trace!("Filtering graph ...");
+        progress::stage(Stage::Filtering);
+
+        let filter = Filter::new(&self.options, db, krates);
+        let graph = filter.filter(graph, crate_node_idxs)?;
+
+        let printer = Printer::new(&self.options, krates, db).with_requirements(requirements);
+
+        for crate_node_idx in crate_node_idxs.iter().cloned() {
+            for module_idx in split::top_level_modules(&graph, crate_node_idx) {
+                let module_path = graph[module_idx].display_path(db);
+
+                trace!("Printing graph of module {module_path} ...");
+
+                let subgraph = split::module_subgraph(&graph, module_idx);
+
+                let mut output = String::new();
+                printer.fmt(&mut output, &subgraph, &[crate_node_idx])?;
+
+                let file_name = format!("{}.dot", module_path.replace("::", "."));
+                output::write_atomically(&dir_path.join(file_name), output)?;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Writes the graph in each of the `--emit` formats, building and filtering it only once.
+    fn write_emitted(
+        &self,
+        graph: &Graph<Node, Edge>,
+        crate_node_idxs: &[NodeIndex],
+        krates: &[hir::Crate],
+        db: &RootDatabase,
+    ) -> anyhow::Result<()> {
+        if self.options.layout == LayoutAlgorithm::None {
+            return Ok(());
+        }
+
+        let requirements = self.requirements(graph, krates, db);
+
+        trace!("Filtering graph ...");
+        progress::stage(Stage::Filtering);
+
+        let filter = Filter::new(&self.options, db, krates);
+        let graph = filter.filter(graph, crate_node_idxs)?;
+
+        progress::stage(Stage::Printing);
+
+        for format in &self.options.emit {
+            trace!("Printing graph as {format} ...");
+
+            let output = match format {
+                EmitFormat::Dot => {
+                    let mut output = String::new();
+                    self.fmt_dot(
+                        &mut output,
+                        graph.clone(),
+                        crate_node_idxs,
+                        requirements.clone(),
+                        krates,
+                        db,
+                    )?;
+                    output
+                }
+                EmitFormat::Json => graph_to_json(&graph, crate_node_idxs, krates, db)?.dump(),
+                EmitFormat::Mermaid => {
+                    let mut output = String::new();
+                    MermaidPrinter::new(db).fmt(&mut output, &graph)?;
+                    output
+                }
+            };
+
+            let file_path =
+                PathBuf::from(self.options.emit_path.replace("{ext}", format.extension()));
+
+            if let Some(dir_path) = file_path
+                .parent()
+                .filter(|path| !path.as_os_str().is_empty())
+            {
+                fs::create_dir_all(dir_path).with_context(|| {
+                    format!("Could not create directory {}", dir_path.display())
+                })?;
+            }
+
+            output::write_atomically(&file_path, output)?;
+        }
+
+        Ok(())
+    }
+
+    fn fmt(
+        &self,
+        f: &mut dyn fmt::Write,
+        graph: &Graph<Node, Edge>,
+        crate_node_idxs: &[NodeIndex],
+        krates: &[hir::Crate],
+        db: &RootDatabase,
+    ) -> anyhow::Result<()> {
         if self.options.layout == LayoutAlgorithm::None {
             return Ok(());
         }
 
+        // Requirements propagate along all of the graph's edges, including any filtered ones:
+        let requirements = self.requirements(graph, krates, db);
+
         trace!("Filtering graph ...");
+        progress::stage(Stage::Filtering);
+
+        let filter = Filter::new(&self.options, db, krates);
+        let graph = filter.filter(graph, crate_node_idxs)?;
 
-        let filter = Filter::new(&self.options, db, krate);
-        let graph = filter.filter(&graph, crate_node_idx)?;
+        if let Some(template_path) = &self.options.template {
+            trace!("Rendering template ...");
+
+            let output = template::render(template_path, &graph, crate_node_idxs, krates, db)?;
+            f.write_str(&output)?;
+
+            return Ok(());
+        }
 
         trace!("Printing graph ...");
+        progress::stage(Stage::Printing);
 
-        let mut string = String::new();
+        if self.options.graph_format == GraphFormat::ReviewJson {
+            let output = review::render(&self.options, &graph, crate_node_idxs, krates, db)?;
+            f.write_str(&output)?;
 
-        let printer = Printer::new(&self.options, krate, db);
-        printer.fmt(&mut string, &graph, crate_node_idx)?;
+            return Ok(());
+        }
 
-        print!("{string}");
+        if self.options.graph_format != GraphFormat::Dot {
+            let printer = TextPrinter::new(self.options.graph_format, db);
+            printer.fmt(f, &graph, crate_node_idxs)?;
+
+            return Ok(());
+        }
+
+        self.fmt_dot(f, graph, crate_node_idxs, requirements, krates, db)
+    }
+
+    fn fmt_dot(
+        &self,
+        f: &mut dyn fmt::Write,
+        mut graph: Graph<Node, Edge>,
+        crate_node_idxs: &[NodeIndex],
+        requirements: HashMap<hir::ModuleDef, Requirement>,
+        krates: &[hir::Crate],
+        db: &RootDatabase,
+    ) -> anyhow::Result<()> {
+        let merged_leaves = match self.options.merge_leaves {
+            Some(max_leaves) => leaves::merge_leaves(&mut graph, max_leaves, db),
+            None => MergedLeaves::new(),
+        };
+
+        let orphans = if self.options.with_orphans {
+            let orphans = scanner::scan_all(db, krates)?;
+            summary::record_orphans(orphans.len());
+            orphans
+        } else {
+            vec![]
+        };
+
+        let printer = Printer::new(&self.options, krates, db)
+            .with_merged_leaves(merged_leaves)
+            .with_requirements(requirements)
+            .with_orphans(orphans);
+        printer.fmt(f, &graph, crate_node_idxs)?;
 
         Ok(())
     }
 
+    /// Builds the (unfiltered) graph.
+    fn build(
+        &self,
+        krates: &[hir::Crate],
+        db: &RootDatabase,
+    ) -> anyhow::Result<(Graph<Node, Edge>, Vec<NodeIndex>)> {
+        trace!("Building graph ...");
+        progress::stage(Stage::Building);
+
+        let mut analyzers: Vec<Box<dyn Analyzer>> = self
+            .options
+            .analyzers
+            .iter()
+            .filter_map(|name| analyzers::by_name(name))
+            .collect();
+
+        let mut builder = GraphBuilder::new(db, krates);
+
+        if !analyzers.is_empty() {
+            builder = builder.with_visitor(&mut analyzers);
+        }
+
+        let (mut graph, crate_node_idxs) = builder.build()?;
+
+        for analyzer in &analyzers {
+            eprintln!("{}", analyzer.report());
+        }
+
+        // Test-only coupling shouldn't count against the crate's architecture, if so requested:
+        if self.options.selection.no_test_uses {
+            graph.retain_edges(|graph, edge_idx| !graph[edge_idx].test_only);
+        }
+
+        Ok((graph, crate_node_idxs))
+    }
+
+    /// Returns the standard library crate each of the graph's nodes depends on,
+    /// if requested via `--color-by std`.
+    fn requirements(
+        &self,
+        graph: &Graph<Node, Edge>,
+        krates: &[hir::Crate],
+        db: &RootDatabase,
+    ) -> HashMap<hir::ModuleDef, Requirement> {
+        if self.options.color_by != ColorBy::Std {
+            return HashMap::new();
+        }
+
+        let mut no_std = NoStd::default();
+        api::visit(graph, krates, db, &mut no_std);
+
+        no_std
+            .requirements()
+            .into_iter()
+            .map(|(node_id, requirement)| (graph[node_id.index()].hir, requirement))
+            .collect()
+    }
+
     pub fn load_options(&self) -> LoadOptions {
         LoadOptions {
             cfg_test: self.options.cfg_test,
@@ -83,18 +440,44 @@ impl Command {
     }
 }
 
-fn draw_cycle(graph: &Graph<Node, Edge>, cycle: Vec<NodeIndex>, db: &RootDatabase) -> String {
-    assert!(!cycle.is_empty());
+/// Writes one quickfix entry per edge of the cycle, located at the edge's source item.
+fn fmt_cycle_quickfix(
+    f: &mut dyn fmt::Write,
+    graph: &Graph<Node, Edge>,
+    cycle: &[NodeIndex],
+    options: &Options,
+    db: &RootDatabase,
+) -> anyhow::Result<()> {
+    let project_dir = quickfix::project_dir(&options.project);
 
-    let first = graph[cycle[0]].display_path(db);
-    let mut drawing = format!("┌> {first}\n");
+    let targets = cycle.iter().skip(1).chain(cycle.first());
 
-    for (i, node) in cycle[1..].iter().enumerate() {
-        let path = graph[*node].display_path(db);
-        drawing += &format!("│  {:>width$}└─> {path}\n", "", width = i * 4);
-    }
+    for (source_idx, target_idx) in cycle.iter().zip(targets) {
+        let source = &graph[*source_idx];
+        let target = &graph[*target_idx];
+
+        let Some((file_path, line, column)) = analyzer::location(source.hir, db) else {
+            continue;
+        };
 
-    drawing += &format!("└──{:─>width$}┘", "", width = (cycle.len() - 1) * 4);
+        let entry = Entry {
+            file_path: quickfix::relative_path(&file_path, &project_dir),
+            line,
+            column,
+            severity: Severity::Error,
+            message: format!(
+                "circular dependency: `{source}` depends on `{target}`",
+                source = source.display_path(db),
+                target = target.display_path(db),
+            ),
+        };
+
+        if options.message_format == MessageFormat::Json {
+            progress::diagnostic(&entry);
+        } else {
+            writeln!(f, "{}", entry.formatted(options.message_format))?;
+        }
+    }
 
-    drawing
+    Ok(())
 }
@@ -4,3 +4,40 @@
 
 #[rustfmt::skip]
 pub mod tri_color;
+
+use petgraph::graph::NodeIndex;
+use ra_ap_ide::RootDatabase;
+
+use crate::graph::{Edge, Graph, Node};
+
+use self::tri_color::{CycleDetector, TriColorDepthFirstSearch};
+
+/// Returns the first cycle found when traversing the graph from its crates' nodes, if any.
+pub(crate) fn find_cycle(
+    graph: &Graph<Node, Edge>,
+    crate_node_idxs: &[NodeIndex],
+) -> Option<Vec<NodeIndex>> {
+    crate_node_idxs.iter().find_map(|crate_node_idx| {
+        TriColorDepthFirstSearch::new(graph).run_from(*crate_node_idx, &mut CycleDetector)
+    })
+}
+
+pub(crate) fn draw_cycle(
+    graph: &Graph<Node, Edge>,
+    cycle: Vec<NodeIndex>,
+    db: &RootDatabase,
+) -> String {
+    assert!(!cycle.is_empty());
+
+    let first = graph[cycle[0]].display_path(db);
+    let mut drawing = format!("┌> {first}\n");
+
+    for (i, node) in cycle[1..].iter().enumerate() {
+        let path = graph[*node].display_path(db);
+        drawing += &format!("│  {:>width$}└─> {path}\n", "", width = i * 4);
+    }
+
+    drawing += &format!("└──{:─>width$}┘", "", width = (cycle.len() - 1) * 4);
+
+    drawing
+}
@@ -0,0 +1,107 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at https://mozilla.org/MPL/2.0/.
+
+//! Kinds of the workspace's dependencies (i.e. normal, dev or build), as obtained from `cargo metadata`.
+
+use std::{collections::HashMap, env, fmt, process};
+
+use log::debug;
+
+use crate::{options::ProjectOptions, quickfix};
+
+#[derive(Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Debug)]
+pub(crate) enum DependencyKind {
+    /// A dev-dependency (i.e. `[dev-dependencies]`), only used by tests, examples and benches.
+    Dev,
+    /// A build-dependency (i.e. `[build-dependencies]`), only used by build scripts.
+    Build,
+    /// A normal dependency (i.e. `[dependencies]`).
+    Normal,
+}
+
+impl fmt::Display for DependencyKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            Self::Dev => "dev",
+            Self::Build => "build",
+            Self::Normal => "normal",
+        };
+        write!(f, "{name}")
+    }
+}
+
+/// The kinds of the workspace members' dependencies, by (canonical) crate name.
+#[derive(Clone, Default, PartialEq, Eq, Debug)]
+pub(crate) struct DependencyKinds(HashMap<String, DependencyKind>);
+
+impl DependencyKinds {
+    /// Obtains the dependency kinds of the project's workspace members,
+    /// defaulting to none if they cannot be determined (e.g. for non-Cargo projects).
+    pub fn new(options: &ProjectOptions) -> Self {
+        let project_dir = quickfix::project_dir(options);
+
+        let cargo = env::var_os("CARGO").unwrap_or_else(|| "cargo".into());
+
+        let mut command = process::Command::new(cargo);
+        command
+            .current_dir(&project_dir)
+            .args(["metadata", "--format-version", "1", "--no-deps"]);
+
+        if options.offline {
+            command.arg("--offline");
+        }
+
+        let Some(metadata) = command
+            .output()
+            .ok()
+            .filter(|output| output.status.success())
+            .and_then(|output| String::from_utf8(output.stdout).ok())
+            .and_then(|stdout| json::parse(&stdout).ok())
+        else {
+            debug!(
+                "Could not determine dependency kinds of {}",
+                project_dir.display()
+            );
+            return Self::default();
+        };
+
+        Self::from_metadata(&metadata)
+    }
+
+    fn from_metadata(metadata: &json::JsonValue) -> Self {
+        let mut kinds: HashMap<String, DependencyKind> = HashMap::new();
+
+        let dependencies = metadata["packages"]
+            .members()
+            .flat_map(|package| package["dependencies"].members());
+
+        for dependency in dependencies {
+            let Some(name) = dependency["rename"]
+                .as_str()
+                .or_else(|| dependency["name"].as_str())
+            else {
+                continue;
+            };
+
+            let kind = match dependency["kind"].as_str() {
+                Some("dev") => DependencyKind::Dev,
+                Some("build") => DependencyKind::Build,
+                _ => DependencyKind::Normal,
+            };
+
+            // A crate depended on in several ways is considered the most "runtime" of them:
+            kinds
+                .entry(name.replace('-', "_"))
+                .and_modify(|existing| *existing = (*existing).max(kind))
+                .or_insert(kind);
+        }
+
+        Self(kinds)
+    }
+
+    /// Returns the kind of the dependency on the crate named `crate_name`, if any.
+    pub fn get(&self, crate_name: &str) -> Option<DependencyKind> {
+        self.0.get(crate_name).copied()
+    }
+}
@@ -0,0 +1,258 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at https://mozilla.org/MPL/2.0/.
+
+//! Quality gates, as checked via `--fail-on`.
+
+use std::{collections::VecDeque, fmt, str::FromStr};
+
+use petgraph::{graph::NodeIndex, visit::EdgeRef as _, Direction};
+use ra_ap_hir as hir;
+use ra_ap_ide::RootDatabase;
+
+use crate::{
+    command::orphans::scanner::Scanner,
+    graph::{Edge, Graph, Node, Relationship},
+    summary,
+};
+
+use super::cycles::tri_color::{CycleDetector, TriColorDepthFirstSearch};
+
+/// The exit code of runs failing any of their `--fail-on` conditions.
+pub const EXIT_CODE: i32 = 2;
+
+/// A condition to fail on (e.g. `cycles`, `orphans`, `orphans>3`, `fan-out>25` or `depth>5`).
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum FailOn {
+    /// Fail if there are any circular dependencies.
+    Cycles,
+    /// Fail if there are more than the given number of orphaned source files.
+    Orphans(usize),
+    /// Fail if any item uses more than the given number of other items.
+    FanOut(usize),
+    /// Fail if any module is nested deeper than the given number of levels
+    /// (with the crate's root module at level 0).
+    Depth(usize),
+}
+
+impl FromStr for FailOn {
+    type Err = &'static str;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (name, max) = match s.split_once('>') {
+            Some((name, max)) => {
+                let max = max
+                    .trim()
+                    .parse()
+                    .map_err(|_| "Expected a non-negative threshold (e.g. `fan-out>25`)")?;
+                (name.trim(), Some(max))
+            }
+            None => (s.trim(), None),
+        };
+
+        match (name, max) {
+            ("cycles", None) => Ok(Self::Cycles),
+            ("orphans", max) => Ok(Self::Orphans(max.unwrap_or(0))),
+            ("fan-out", Some(max)) => Ok(Self::FanOut(max)),
+            ("fan-out", None) => Err("Expected a threshold for `fan-out` (e.g. `fan-out>25`)"),
+            ("depth", Some(max)) => Ok(Self::Depth(max)),
+            ("depth", None) => Err("Expected a threshold for `depth` (e.g. `depth>5`)"),
+            _ => {
+                Err("Unrecognized condition (e.g. cycles, orphans, orphans>N, fan-out>N, depth>N)")
+            }
+        }
+    }
+}
+
+impl fmt::Display for FailOn {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Cycles => f.write_str("cycles"),
+            Self::Orphans(0) => f.write_str("orphans"),
+            Self::Orphans(max) => write!(f, "orphans>{max}"),
+            Self::FanOut(max) => write!(f, "fan-out>{max}"),
+            Self::Depth(max) => write!(f, "depth>{max}"),
+        }
+    }
+}
+
+/// The error of runs failing any of their `--fail-on` conditions.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct Failure {
+    violations: Vec<(FailOn, String)>,
+}
+
+impl fmt::Display for Failure {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let count = self.violations.len();
+        let noun = if count == 1 {
+            "condition"
+        } else {
+            "conditions"
+        };
+
+        write!(f, "Failed {count} `--fail-on` {noun}:")?;
+
+        for (condition, violation) in &self.violations {
+            write!(f, "\n  {condition}: {violation}")?;
+        }
+
+        Ok(())
+    }
+}
+
+impl std::error::Error for Failure {}
+
+/// Checks the (unfiltered) graph against the given conditions,
+/// returning a [`Failure`] listing the violated ones, if any.
+pub(crate) fn check(
+    conditions: &[FailOn],
+    graph: &Graph<Node, Edge>,
+    crate_node_idxs: &[NodeIndex],
+    krates: &[hir::Crate],
+    db: &RootDatabase,
+) -> anyhow::Result<()> {
+    let violations = violations(conditions, graph, crate_node_idxs, krates, db)?;
+
+    if violations.is_empty() {
+        return Ok(());
+    }
+
+    Err(Failure { violations }.into())
+}
+
+/// Returns the given conditions violated by the graph, along with a description of each violation.
+pub(crate) fn violations(
+    conditions: &[FailOn],
+    graph: &Graph<Node, Edge>,
+    crate_node_idxs: &[NodeIndex],
+    krates: &[hir::Crate],
+    db: &RootDatabase,
+) -> anyhow::Result<Vec<(FailOn, String)>> {
+    let mut violations = vec![];
+
+    for condition in conditions.iter().copied() {
+        let violation = match condition {
+            FailOn::Cycles => check_cycles(graph, crate_node_idxs, db),
+            FailOn::Orphans(max) => check_orphans(max, krates, db)?,
+            FailOn::FanOut(max) => check_fan_out(max, graph, db),
+            FailOn::Depth(max) => check_depth(max, graph, crate_node_idxs, db),
+        };
+
+        violations.extend(violation.map(|violation| (condition, violation)));
+    }
+
+    Ok(violations)
+}
+
+fn check_cycles(
+    graph: &Graph<Node, Edge>,
+    crate_node_idxs: &[NodeIndex],
+    db: &RootDatabase,
+) -> Option<String> {
+    crate_node_idxs.iter().find_map(|crate_node_idx| {
+        let cycle =
+            TriColorDepthFirstSearch::new(graph).run_from(*crate_node_idx, &mut CycleDetector)?;
+
+        let first = graph[cycle[0]].display_path(db);
+        let last = graph[*cycle.last()?].display_path(db);
+
+        Some(format!(
+            "Circular dependency between `{first}` and `{last}`"
+        ))
+    })
+}
+
+fn check_orphans(
+    max: usize,
+    krates: &[hir::Crate],
+    db: &RootDatabase,
+) -> anyhow::Result<Option<String>> {
+    let mut count = 0;
+
+    for krate in krates.iter().cloned() {
+        count += Scanner::new(db, krate).scan()?.len();
+    }
+
+    summary::record_orphans(count);
+
+    if count <= max {
+        return Ok(None);
+    }
+
+    Ok(Some(format!("Found {count} orphans")))
+}
+
+fn check_fan_out(max: usize, graph: &Graph<Node, Edge>, db: &RootDatabase) -> Option<String> {
+    let mut offenders: Vec<(usize, String)> = graph
+        .node_indices()
+        .filter_map(|node_idx| {
+            let count = graph
+                .edges_directed(node_idx, Direction::Outgoing)
+                .filter(|edge_ref| edge_ref.weight().kind == Relationship::Uses)
+                .count();
+
+            (count > max).then(|| (count, graph[node_idx].display_path(db)))
+        })
+        .collect();
+
+    if offenders.is_empty() {
+        return None;
+    }
+
+    // Sort by descending count, then by path for stable output:
+    offenders.sort_by(|lhs, rhs| rhs.0.cmp(&lhs.0).then(lhs.1.cmp(&rhs.1)));
+
+    let offenders = offenders
+        .iter()
+        .map(|(count, path)| format!("`{path}` ({count})"))
+        .collect::<Vec<_>>()
+        .join(", ");
+
+    Some(format!("Items using too many others: {offenders}"))
+}
+
+fn check_depth(
+    max: usize,
+    graph: &Graph<Node, Edge>,
+    crate_node_idxs: &[NodeIndex],
+    db: &RootDatabase,
+) -> Option<String> {
+    let mut offenders: Vec<(usize, String)> = vec![];
+
+    // Walk the crates' module trees breadth-first, following their "owns" edges:
+    let mut queue: VecDeque<(NodeIndex, usize)> = crate_node_idxs
+        .iter()
+        .map(|crate_node_idx| (*crate_node_idx, 0))
+        .collect();
+
+    while let Some((node_idx, depth)) = queue.pop_front() {
+        if depth > max {
+            offenders.push((depth, graph[node_idx].display_path(db)));
+        }
+
+        let sub_modules = graph
+            .edges_directed(node_idx, Direction::Outgoing)
+            .filter(|edge_ref| edge_ref.weight().kind == Relationship::Owns)
+            .map(|edge_ref| edge_ref.target())
+            .filter(|node_idx| matches!(graph[*node_idx].hir, hir::ModuleDef::Module(_)))
+            .map(|node_idx| (node_idx, depth + 1));
+
+        queue.extend(sub_modules);
+    }
+
+    if offenders.is_empty() {
+        return None;
+    }
+
+    // Sort by descending depth, then by path for stable output:
+    offenders.sort_by(|lhs, rhs| rhs.0.cmp(&lhs.0).then(lhs.1.cmp(&rhs.1)));
+
+    let offenders = offenders
+        .iter()
+        .map(|(depth, path)| format!("`{path}` ({depth})"))
+        .collect::<Vec<_>>()
+        .join(", ");
+
+    Some(format!("Modules nested too deeply: {offenders}"))
+}
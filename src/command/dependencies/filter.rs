@@ -2,7 +2,10 @@
 // License, v. 2.0. If a copy of the MPL was not distributed with this
 // file, You can obtain one at https://mozilla.org/MPL/2.0/.
 
-use std::collections::{HashMap, HashSet};
+use std::{
+    collections::{HashMap, HashSet},
+    path::Path,
+};
 
 use log::trace;
 use petgraph::{
@@ -16,8 +19,9 @@ use ra_ap_ide_db::RootDatabase;
 use ra_ap_syntax::ast;
 
 use crate::{
-    analyzer,
+    analyzer, glob,
     graph::{Edge, Graph, GraphWalker, Node, Relationship},
+    selector::{EdgeQuery, Selector},
 };
 
 use super::options::Options;
@@ -26,65 +30,83 @@ use super::options::Options;
 pub struct Filter<'a> {
     options: &'a Options,
     db: &'a RootDatabase,
-    krate: hir::Crate,
+    krates: &'a [hir::Crate],
 }
 
 impl<'a> Filter<'a> {
-    pub fn new(options: &'a Options, db: &'a RootDatabase, krate: hir::Crate) -> Self {
-        Self { options, db, krate }
+    pub fn new(options: &'a Options, db: &'a RootDatabase, krates: &'a [hir::Crate]) -> Self {
+        Self {
+            options,
+            db,
+            krates,
+        }
     }
 
     pub fn filter(
         &self,
         graph: &Graph<Node, Edge>,
-        root_idx: NodeIndex,
+        root_idxs: &[NodeIndex],
     ) -> anyhow::Result<Graph<Node, Edge>> {
         const ROOT_DROP_ERR_MSG: &str = "Root module should not be dropped";
 
         let mut graph = graph.clone();
 
-        let focus_on = self
-            .options
-            .focus_on
-            .as_ref()
-            .cloned()
-            .unwrap_or_else(|| self.krate.display_name(self.db).unwrap().to_string());
+        if self.options.selection.collapse_sysroot {
+            trace!("Collapsing sysroot nodes in graph ...");
+            self.collapse_crates(&mut graph, |krate| krate.origin(self.db).is_lang());
+        }
 
-        let syntax = format!("use {focus_on};");
-        let use_tree: ast::UseTree = analyzer::parse_ast(&syntax);
+        if self.options.selection.collapse_externs {
+            trace!("Collapsing extern nodes in graph ...");
+            self.collapse_crates(&mut graph, |krate| !self.krates.contains(&krate));
+        }
 
-        trace!("Searching for focus nodes in graph ...");
+        let focus_node_idxs: Vec<NodeIndex> = match self.options.focus_on.as_ref() {
+            Some(focus_on) => {
+                let focus_node_idxs = self.focus_node_idxs(&graph);
 
-        let focus_node_idxs: Vec<NodeIndex> = graph
-            .node_indices()
-            .filter(|node_idx| {
-                let node = &graph[*node_idx];
-                let path = node.display_path(self.db);
-                analyzer::use_tree_matches_item_path(&use_tree, &path[..])
-            })
-            .collect();
+                if focus_node_idxs.is_empty() {
+                    if analyzer::is_focus_file(focus_on) {
+                        anyhow::bail!("No module found owning file '{focus_on}'");
+                    }
 
-        if focus_node_idxs.is_empty() {
-            anyhow::bail!("No node found matching use tree '{:?}'", focus_on);
-        }
+                    anyhow::bail!("No node found matching use tree '{:?}'", focus_on);
+                }
+
+                focus_node_idxs
+            }
+            None => root_idxs.to_vec(),
+        };
 
         let max_depth = self.options.max_depth.unwrap_or(usize::MAX);
         let nodes_within_max_depth =
             Self::nodes_within_max_depth_from(&graph, max_depth, &focus_node_idxs[..]);
 
         debug_assert!(
-            nodes_within_max_depth.contains(&root_idx),
+            root_idxs
+                .iter()
+                .all(|root_idx| nodes_within_max_depth.contains(root_idx)),
             "{}",
             ROOT_DROP_ERR_MSG
         );
 
+        let deprecated_node_idxs = self
+            .options
+            .only_deprecated
+            .then(|| self.deprecated_nodes_and_owners(&graph));
+
         // Populate stack with nodes in breadth-first order:
         let mut stack: Vec<_> = {
             let mut stack: Vec<_> = Vec::default();
+            let mut visited: HashSet<NodeIndex> = HashSet::default();
 
-            let mut traversal = Bfs::new(&graph, root_idx);
-            while let Some(node_idx) = traversal.next(&graph) {
-                stack.push(node_idx);
+            for root_idx in root_idxs {
+                let mut traversal = Bfs::new(&graph, *root_idx);
+                while let Some(node_idx) = traversal.next(&graph) {
+                    if visited.insert(node_idx) {
+                        stack.push(node_idx);
+                    }
+                }
             }
 
             stack
@@ -104,8 +126,21 @@ impl<'a> Filter<'a> {
                 // Make sure the node's `moduledef` should be retained:
                 should_keep_node &= self.should_retain_moduledef(node.hir);
 
-                // Make sure the root node doesn't get dropped:
-                should_keep_node |= *node_idx == root_idx;
+                // Make sure the node is (or contains) a deprecated item, if requested:
+                if let Some(deprecated_node_idxs) = &deprecated_node_idxs {
+                    should_keep_node &= deprecated_node_idxs.contains(node_idx);
+                }
+
+                // Make sure the node matches the selector, if any:
+                if let Some(Selector::Nodes(expr)) = &self.options.select {
+                    should_keep_node &= expr.matches(node, self.db);
+                }
+
+                // Make sure the root nodes don't get dropped:
+                should_keep_node |= root_idxs.contains(node_idx);
+
+                // Make sure the focus nodes don't get dropped either (e.g. a type despite `--no-types`):
+                should_keep_node |= focus_node_idxs.contains(node_idx);
 
                 should_keep_node
             })
@@ -124,7 +159,7 @@ impl<'a> Filter<'a> {
             let parent_owned_node = |node_idx| {
                 graph
                     .edges_directed(node_idx, Direction::Incoming)
-                    .find(|edge_ref| matches!(edge_ref.weight(), Edge::Owns))
+                    .find(|edge_ref| edge_ref.weight().kind == Relationship::Owns)
                     .map(|edge_ref| edge_ref.source())
             };
 
@@ -184,51 +219,332 @@ impl<'a> Filter<'a> {
 
         // Drop any "uses" edges, if necessary:
         if self.options.selection.no_uses {
-            graph.retain_edges(|graph, edge_idx| graph[edge_idx] == Relationship::Owns);
+            graph.retain_edges(|graph, edge_idx| graph[edge_idx].kind == Relationship::Owns);
+        }
+
+        // Drop any test-only "uses" (and "re-exports") edges, if necessary:
+        if self.options.selection.no_test_uses {
+            graph.retain_edges(|graph, edge_idx| !graph[edge_idx].test_only);
         }
 
         // The edge-reconciliation above may have resulted in redundant edges, so we need to remove those:
+        Self::merge_parallel_edges(&mut graph);
+
+        if self.options.simplify {
+            trace!("Simplifying edges in graph ...");
+            Self::simplify_edges(&mut graph);
+        }
+
+        if let Some(Selector::Edges(query)) = &self.options.select {
+            self.select_edges(&mut graph, query, root_idxs);
+        }
+
+        // The above filters may have created disconnected sub-graphs.
+        // We're only interested in the sub-graphs containing the `root_idxs` though,
+        // so we query the graph for all node reachable from any of them:
+        let nodes_reachable_from_root: HashSet<NodeIndex> = root_idxs
+            .iter()
+            .flat_map(|root_idx| Self::nodes_reachable_from(&graph, *root_idx))
+            .collect();
+
+        debug_assert!(
+            root_idxs
+                .iter()
+                .all(|root_idx| nodes_reachable_from_root.contains(root_idx)),
+            "{}",
+            ROOT_DROP_ERR_MSG
+        );
+
+        // And drop any node that wasn't reachable from any `root`:
+        graph.retain_nodes(|_graph, node_idx| nodes_reachable_from_root.contains(&node_idx));
+
+        debug_assert!(
+            root_idxs
+                .iter()
+                .all(|root_idx| graph.contains_node(*root_idx)),
+            "{}",
+            ROOT_DROP_ERR_MSG
+        );
+
+        Ok(graph)
+    }
+
+    /// Returns the nodes selected by `--focus-on` (if any).
+    pub fn focus_node_idxs(&self, graph: &Graph<Node, Edge>) -> Vec<NodeIndex> {
+        let Some(focus_on) = self.options.focus_on.as_ref() else {
+            return vec![];
+        };
+
+        // Files are focused on via the modules owning them:
+        if analyzer::is_focus_file(focus_on) {
+            let module_paths =
+                analyzer::modules_owning_file(Path::new(focus_on), self.krates, self.db);
+
+            return graph
+                .node_indices()
+                .filter(|node_idx| {
+                    matches!(graph[*node_idx].hir, hir::ModuleDef::Module(_))
+                        && module_paths.contains(&graph[*node_idx].display_path(self.db))
+                })
+                .collect();
+        }
+
+        let syntax = format!("use {focus_on};");
+        let use_tree: ast::UseTree = analyzer::parse_ast(&syntax);
+
+        trace!("Searching for focus nodes in graph ...");
+
+        // Items may also be addressed through any of their re-exports (i.e. `pub use …`):
+        let reexport_paths = analyzer::reexport_paths(self.krates, self.db);
+
+        graph
+            .node_indices()
+            .filter(|node_idx| {
+                let node = &graph[*node_idx];
+                let path = node.display_path(self.db);
+
+                let aliases = reexport_paths.get(&node.hir).into_iter().flatten();
+
+                std::iter::once(&path)
+                    .chain(aliases)
+                    .any(|path| analyzer::use_tree_matches_item_path(&use_tree, path))
+            })
+            .collect()
+    }
+
+    /// Collapses the nodes of all crates matching `predicate` into a single node per crate.
+    fn collapse_crates<P>(&self, graph: &mut Graph<Node, Edge>, predicate: P)
+    where
+        P: Fn(hir::Crate) -> bool,
+    {
+        let collapsible_node_idxs: Vec<(NodeIndex, hir::Crate)> = graph
+            .node_indices()
+            .filter_map(|node_idx| {
+                let krate = analyzer::krate(graph[node_idx].hir, self.db)?;
+                predicate(krate).then_some((node_idx, krate))
+            })
+            .collect();
+
+        // Re-use any already existing nodes for the crates' root modules:
+        let mut crate_node_idxs: HashMap<hir::Crate, NodeIndex> = collapsible_node_idxs
+            .iter()
+            .filter(|(node_idx, krate)| {
+                graph[*node_idx].hir == hir::ModuleDef::Module(krate.root_module())
+            })
+            .cloned()
+            .map(|(node_idx, krate)| (krate, node_idx))
+            .collect();
+
+        for (node_idx, krate) in collapsible_node_idxs {
+            let crate_node_idx = *crate_node_idxs
+                .entry(krate)
+                .or_insert_with(|| graph.add_node(Node::new(krate.root_module().into())));
+
+            if node_idx == crate_node_idx {
+                continue;
+            }
+
+            // Collect edge indices and sources for incoming edges:
+            let pending: Vec<_> = graph
+                .edges_directed(node_idx, Direction::Incoming)
+                .map(|incoming_edge_ref| (incoming_edge_ref.id(), incoming_edge_ref.source()))
+                .collect();
+
+            // Then replace the edge with one where the `target` is the crate, if necessary:
+            for (edge_idx, source_node_idx) in pending {
+                let edge_weight = graph.remove_edge(edge_idx).unwrap();
+
+                if source_node_idx != crate_node_idx {
+                    graph.add_edge(source_node_idx, crate_node_idx, edge_weight);
+                }
+            }
+
+            graph.remove_node(node_idx);
+        }
+    }
 
-        let mut unique_edges: HashMap<(NodeIndex, NodeIndex, Edge), EdgeIndex> = HashMap::new();
+    /// Merges parallel edges of the same kind into single edges (summing up their weights).
+    pub(super) fn merge_parallel_edges(graph: &mut Graph<Node, Edge>) {
+        let mut unique_edges: HashMap<(NodeIndex, NodeIndex, Relationship), (EdgeIndex, Edge)> =
+            HashMap::new();
 
         for edge_ref in graph.edge_references() {
             let source = edge_ref.source();
             let target = edge_ref.target();
-            let weight = edge_ref.weight();
+            let edge = edge_ref.weight();
             let idx = edge_ref.id();
-            unique_edges.entry((source, target, *weight)).or_insert(idx);
+
+            let (_, merged_edge) = unique_edges
+                .entry((source, target, edge.kind))
+                .or_insert_with(|| {
+                    let merged_edge = Edge {
+                        weight: 0,
+                        test_only: true,
+                        ..Edge::new(edge.kind)
+                    };
+                    (idx, merged_edge)
+                });
+
+            merged_edge.weight += edge.weight;
+            merged_edge.test_only &= edge.test_only;
+
+            for target in &edge.targets {
+                if !merged_edge.targets.contains(target) {
+                    merged_edge.targets.push(*target);
+                }
+            }
         }
 
-        // Drop any redundant edges:
+        // Drop any redundant edges, merging them into the remaining ones:
 
         graph.retain_edges(|graph, edge_idx| {
             let (source, target) = graph.edge_endpoints(edge_idx).unwrap();
-            let weight = graph[edge_idx];
-            let idx = unique_edges[&(source, target, weight)];
+            let kind = graph[edge_idx].kind;
+            let (idx, _) = unique_edges[&(source, target, kind)];
             edge_idx == idx
         });
 
-        // The above filters may have created disconnected sub-graphs.
-        // We're only interested in the sub-graph containing the `root_idx` though,
-        // so we query the graph for all node reachable from `root_node`:
-        let nodes_reachable_from_root = Self::nodes_reachable_from(&graph, root_idx);
+        for (idx, merged_edge) in unique_edges.into_values() {
+            graph[idx] = merged_edge;
+        }
+    }
 
-        debug_assert!(
-            nodes_reachable_from_root.contains(&root_idx),
-            "{}",
-            ROOT_DROP_ERR_MSG
-        );
+    /// Drops "owns" edges running parallel to "uses" (or "re-exports") edges,
+    /// as the latter already imply the former's connection,
+    /// and merges parallel "uses" edges into their "re-exports" counterparts.
+    fn simplify_edges(graph: &mut Graph<Node, Edge>) {
+        let mut parallel_edges: HashMap<(NodeIndex, NodeIndex), Vec<EdgeIndex>> = HashMap::new();
 
-        // And drop any node that wasn't reachable from `root`:
-        graph.retain_nodes(|_graph, node_idx| nodes_reachable_from_root.contains(&node_idx));
+        for edge_ref in graph.edge_references() {
+            parallel_edges
+                .entry((edge_ref.source(), edge_ref.target()))
+                .or_default()
+                .push(edge_ref.id());
+        }
 
-        debug_assert!(graph.contains_node(root_idx), "{}", ROOT_DROP_ERR_MSG);
+        let mut redundant_edges: HashSet<EdgeIndex> = HashSet::new();
 
-        Ok(graph)
+        for edge_idxs in parallel_edges.values() {
+            let find = |kind: Relationship| {
+                edge_idxs
+                    .iter()
+                    .copied()
+                    .find(|edge_idx| graph[*edge_idx].kind == kind)
+            };
+
+            let owns = find(Relationship::Owns);
+            let uses = find(Relationship::Uses);
+            let reexports = find(Relationship::ReExports);
+
+            if let Some(owns) = owns {
+                if uses.is_some() || reexports.is_some() {
+                    redundant_edges.insert(owns);
+                }
+            }
+
+            if let (Some(uses), Some(reexports)) = (uses, reexports) {
+                let uses_edge = graph[uses].clone();
+                let reexports_edge = &mut graph[reexports];
+
+                reexports_edge.weight += uses_edge.weight;
+                reexports_edge.test_only &= uses_edge.test_only;
+
+                for target in uses_edge.targets {
+                    if !reexports_edge.targets.contains(&target) {
+                        reexports_edge.targets.push(target);
+                    }
+                }
+
+                redundant_edges.insert(uses);
+            }
+        }
+
+        graph.retain_edges(|_, edge_idx| !redundant_edges.contains(&edge_idx));
+    }
+
+    fn select_edges(
+        &self,
+        graph: &mut Graph<Node, Edge>,
+        query: &EdgeQuery,
+        root_idxs: &[NodeIndex],
+    ) {
+        trace!("Selecting edges in graph ...");
+
+        let selected_edge_idxs: HashSet<EdgeIndex> = graph
+            .edge_references()
+            .filter(|edge_ref| {
+                let source = &graph[edge_ref.source()];
+                let target = &graph[edge_ref.target()];
+                query.matches(edge_ref.weight(), source, target, self.db)
+            })
+            .map(|edge_ref| edge_ref.id())
+            .collect();
+
+        // Keep the selected edges' endpoints, as well as their owning ancestors (for context):
+        let mut nodes_to_keep: HashSet<NodeIndex> = root_idxs.iter().cloned().collect();
+
+        for edge_idx in &selected_edge_idxs {
+            let (source_idx, target_idx) = graph.edge_endpoints(*edge_idx).unwrap();
+
+            for node_idx in [source_idx, target_idx] {
+                let mut node_idx = Some(node_idx);
+
+                while let Some(idx) = node_idx {
+                    nodes_to_keep.insert(idx);
+
+                    node_idx = graph
+                        .edges_directed(idx, Direction::Incoming)
+                        .find(|edge_ref| edge_ref.weight().kind == Relationship::Owns)
+                        .map(|edge_ref| edge_ref.source());
+                }
+            }
+        }
+
+        graph.retain_edges(|graph, edge_idx| {
+            if selected_edge_idxs.contains(&edge_idx) {
+                return true;
+            }
+
+            let (source_idx, target_idx) = graph.edge_endpoints(edge_idx).unwrap();
+
+            graph[edge_idx].kind == Relationship::Owns
+                && nodes_to_keep.contains(&source_idx)
+                && nodes_to_keep.contains(&target_idx)
+        });
+
+        graph.retain_nodes(|_graph, node_idx| nodes_to_keep.contains(&node_idx));
+    }
+
+    /// Returns the deprecated nodes, as well as all of the nodes (transitively) owning them.
+    fn deprecated_nodes_and_owners(&self, graph: &Graph<Node, Edge>) -> HashSet<NodeIndex> {
+        let mut node_idxs = HashSet::default();
+
+        for node_idx in graph.node_indices() {
+            if graph[node_idx].attrs(self.db).deprecated.is_none() {
+                continue;
+            }
+
+            let mut owner_idx = Some(node_idx);
+
+            while let Some(node_idx) = owner_idx {
+                if !node_idxs.insert(node_idx) {
+                    break;
+                }
+
+                owner_idx = graph
+                    .edges_directed(node_idx, Direction::Incoming)
+                    .find(|edge_ref| edge_ref.weight().kind == Relationship::Owns)
+                    .map(|edge_ref| edge_ref.source());
+            }
+        }
+
+        node_idxs
     }
 
     fn should_retain_moduledef(&self, module_def_hir: hir::ModuleDef) -> bool {
-        if self.options.selection.no_externs && self.is_extern(module_def_hir) {
+        if self.is_extern(module_def_hir)
+            && (self.options.selection.no_externs || !self.matches_extern_filter(module_def_hir))
+        {
             return false;
         }
 
@@ -336,7 +652,31 @@ impl<'a> Filter<'a> {
             return true;
         };
 
-        self.krate != import_krate
+        !self.krates.contains(&import_krate)
+    }
+
+    /// Returns whether the (extern) item's crate matches any of the `--extern-filter` patterns,
+    /// with all crates matching in their absence.
+    fn matches_extern_filter(&self, module_def_hir: hir::ModuleDef) -> bool {
+        let patterns = &self.options.selection.extern_filter;
+
+        if patterns.is_empty() {
+            return true;
+        }
+
+        let Some(krate) = analyzer::krate(module_def_hir, self.db) else {
+            return false;
+        };
+
+        let Some(crate_name) = krate.display_name(self.db) else {
+            return false;
+        };
+
+        let crate_name = crate_name.to_string();
+
+        patterns
+            .iter()
+            .any(|pattern| glob::glob_matches(pattern, &crate_name))
     }
 
     fn nodes_reachable_from(
@@ -370,7 +710,7 @@ impl<'a> Filter<'a> {
             // Walks from a node to its ascendants in the graph (i.e. super-items & dependents):
             let mut ascendants_walker = GraphWalker::new(petgraph::Direction::Incoming);
             ascendants_walker.walk_graph(graph, *start_node_idx, |edge, _node, depth| {
-                (*edge == Edge::Owns) || (depth <= max_depth)
+                (edge.kind == Relationship::Owns) || (depth <= max_depth)
             });
             nodes_to_keep.extend(ascendants_walker.nodes_visited);
         }
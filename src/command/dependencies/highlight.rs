@@ -0,0 +1,58 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at https://mozilla.org/MPL/2.0/.
+
+//! Highlighting of the nodes selected via `--focus-on`,
+//! along with the paths leading to them from their crate's root (via `--highlight-path`).
+
+use std::collections::HashSet;
+
+use petgraph::{graph::NodeIndex, visit::EdgeRef as _, Direction};
+use ra_ap_hir as hir;
+
+use crate::graph::{Edge, Graph, Node, Relationship};
+
+#[derive(Clone, Default, Debug)]
+pub(crate) struct Highlights {
+    /// The items of the focus nodes.
+    pub(crate) nodes: HashSet<hir::ModuleDef>,
+    /// The "owns" edges on the paths from the crates' root nodes to the focus nodes,
+    /// as (owner, owned) items.
+    pub(crate) edges: HashSet<(hir::ModuleDef, hir::ModuleDef)>,
+}
+
+impl Highlights {
+    pub(crate) fn new(
+        graph: &Graph<Node, Edge>,
+        focus_node_idxs: &[NodeIndex],
+        with_paths: bool,
+    ) -> Self {
+        let nodes = focus_node_idxs
+            .iter()
+            .map(|node_idx| graph[*node_idx].hir)
+            .collect();
+
+        let mut edges = HashSet::new();
+
+        if with_paths {
+            for focus_node_idx in focus_node_idxs.iter().cloned() {
+                let mut node_idx = focus_node_idx;
+
+                // Items are owned by a single node (if any), making for a single path to the root,
+                // which may however be shared with other focus nodes:
+                while let Some(edge_ref) = graph
+                    .edges_directed(node_idx, Direction::Incoming)
+                    .find(|edge_ref| edge_ref.weight().kind == Relationship::Owns)
+                {
+                    if !edges.insert((graph[edge_ref.source()].hir, graph[node_idx].hir)) {
+                        break;
+                    }
+
+                    node_idx = edge_ref.source();
+                }
+            }
+        }
+
+        Self { nodes, edges }
+    }
+}
@@ -0,0 +1,96 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at https://mozilla.org/MPL/2.0/.
+
+//! Merging of sibling leaf modules, as done via `--merge-leaves`.
+
+use std::collections::HashMap;
+
+use petgraph::{graph::NodeIndex, visit::EdgeRef as _, Direction};
+use ra_ap_hir as hir;
+use ra_ap_ide::RootDatabase;
+
+use crate::graph::{Edge, Graph, Node, Relationship};
+
+use super::filter::Filter;
+
+/// The number of leaf modules merged into each summary node, by the summary node's item.
+pub(crate) type MergedLeaves = HashMap<hir::ModuleDef, usize>;
+
+/// Merges groups of more than `max_leaves` sibling leaf modules (i.e. modules owning no items)
+/// into their first sibling (by path), which then stands for the whole group.
+pub(crate) fn merge_leaves(
+    graph: &mut Graph<Node, Edge>,
+    max_leaves: usize,
+    db: &RootDatabase,
+) -> MergedLeaves {
+    let mut merged = MergedLeaves::new();
+
+    let parent_idxs: Vec<NodeIndex> = graph.node_indices().collect();
+
+    for parent_idx in parent_idxs {
+        // Skip any leaves already merged into their siblings:
+        if !graph.contains_node(parent_idx) {
+            continue;
+        }
+
+        let mut leaf_idxs: Vec<NodeIndex> = owned_nodes(graph, parent_idx)
+            .into_iter()
+            .filter(|node_idx| matches!(graph[*node_idx].hir, hir::ModuleDef::Module(_)))
+            .filter(|node_idx| owned_nodes(graph, *node_idx).is_empty())
+            .collect();
+
+        if leaf_idxs.len() <= max_leaves {
+            continue;
+        }
+
+        leaf_idxs.sort_by_cached_key(|node_idx| graph[*node_idx].display_path(db));
+
+        let summary_idx = leaf_idxs[0];
+
+        for &leaf_idx in &leaf_idxs[1..] {
+            redirect_edges(graph, leaf_idx, summary_idx);
+            graph.remove_node(leaf_idx);
+        }
+
+        merged.insert(graph[summary_idx].hir, leaf_idxs.len());
+    }
+
+    Filter::merge_parallel_edges(graph);
+
+    merged
+}
+
+fn owned_nodes(graph: &Graph<Node, Edge>, node_idx: NodeIndex) -> Vec<NodeIndex> {
+    graph
+        .edges_directed(node_idx, Direction::Outgoing)
+        .filter(|edge_ref| edge_ref.weight().kind == Relationship::Owns)
+        .map(|edge_ref| edge_ref.target())
+        .collect()
+}
+
+/// Moves the node's incoming and outgoing edges over to `summary_idx`,
+/// dropping any that would end up as self-loops.
+fn redirect_edges(graph: &mut Graph<Node, Edge>, node_idx: NodeIndex, summary_idx: NodeIndex) {
+    let outgoing: Vec<(NodeIndex, Edge)> = graph
+        .edges_directed(node_idx, Direction::Outgoing)
+        .map(|edge_ref| (edge_ref.target(), edge_ref.weight().clone()))
+        .collect();
+
+    let incoming: Vec<(NodeIndex, Edge)> = graph
+        .edges_directed(node_idx, Direction::Incoming)
+        .map(|edge_ref| (edge_ref.source(), edge_ref.weight().clone()))
+        .collect();
+
+    for (target_idx, edge) in outgoing {
+        if target_idx != node_idx && target_idx != summary_idx {
+            graph.add_edge(summary_idx, target_idx, edge);
+        }
+    }
+
+    for (source_idx, edge) in incoming {
+        if source_idx != node_idx && source_idx != summary_idx {
+            graph.add_edge(source_idx, summary_idx, edge);
+        }
+    }
+}
@@ -0,0 +1,86 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at https://mozilla.org/MPL/2.0/.
+
+//! Printer for Mermaid flowcharts, as written via `--emit mermaid`.
+//!
+//! Nodes are numbered by their position when sorted by path (rather than by their indices),
+//! so that flowcharts of the same code are identical across runs.
+//! "Owns" edges are drawn as solid arrows, "uses" edges as dotted ones
+//! and "re-exports" edges as thick ones.
+
+use std::{collections::HashMap, fmt};
+
+use petgraph::{
+    graph::NodeIndex,
+    visit::{EdgeRef as _, IntoEdgeReferences as _},
+};
+use ra_ap_ide::RootDatabase;
+
+use crate::graph::{Edge, Graph, Node, Relationship};
+
+pub(crate) struct MermaidPrinter<'a> {
+    db: &'a RootDatabase,
+}
+
+impl<'a> MermaidPrinter<'a> {
+    pub(crate) fn new(db: &'a RootDatabase) -> Self {
+        Self { db }
+    }
+
+    pub(crate) fn fmt(&self, f: &mut dyn fmt::Write, graph: &Graph<Node, Edge>) -> fmt::Result {
+        let mut node_idxs: Vec<NodeIndex> = graph.node_indices().collect();
+        node_idxs.sort_by_cached_key(|node_idx| graph[*node_idx].display_path(self.db));
+
+        let positions: HashMap<NodeIndex, usize> = node_idxs
+            .iter()
+            .enumerate()
+            .map(|(position, node_idx)| (*node_idx, position))
+            .collect();
+
+        writeln!(f, "flowchart LR")?;
+
+        for (position, node_idx) in node_idxs.iter().enumerate() {
+            let node = &graph[*node_idx];
+
+            let label = format!(
+                "{} {}",
+                node.kind_display_name(self.db),
+                node.display_path(self.db)
+            );
+
+            writeln!(f, "    n{position}[\"{}\"]", escaped(&label))?;
+        }
+
+        let mut edges: Vec<(usize, usize, Relationship)> = graph
+            .edge_references()
+            .map(|edge_ref| {
+                (
+                    positions[&edge_ref.source()],
+                    positions[&edge_ref.target()],
+                    edge_ref.weight().kind,
+                )
+            })
+            .collect();
+
+        edges.sort_by_key(|(source, target, kind)| (*source, *target, kind.display_name()));
+        edges.dedup();
+
+        for (source, target, kind) in edges {
+            let arrow = match kind {
+                Relationship::Owns => "-->",
+                Relationship::Uses => "-.->",
+                Relationship::ReExports => "==>",
+            };
+
+            writeln!(f, "    n{source} {arrow} n{target}")?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Escapes the characters Mermaid would otherwise take for the end of a quoted label.
+fn escaped(label: &str) -> String {
+    label.replace('"', "#quot;")
+}
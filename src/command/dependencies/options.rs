@@ -2,11 +2,16 @@
 // License, v. 2.0. If a copy of the MPL was not distributed with this
 // file, You can obtain one at https://mozilla.org/MPL/2.0/.
 
-use std::{fmt::Display, str::FromStr};
+use std::{fmt::Display, path::PathBuf, str::FromStr};
 
 use clap::Parser;
 
-use crate::options::{GeneralOptions, ProjectOptions};
+use crate::{
+    options::{GeneralOptions, MessageFormat, ProjectOptions},
+    selector::Selector,
+};
+
+use super::fail_on::FailOn;
 
 #[derive(Copy, Clone, PartialEq, Eq, Debug)]
 pub enum LayoutAlgorithm {
@@ -50,6 +55,312 @@ impl Display for LayoutAlgorithm {
     }
 }
 
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum LayoutDirection {
+    TopToBottom,
+    LeftToRight,
+    BottomToTop,
+    RightToLeft,
+}
+
+impl FromStr for LayoutDirection {
+    type Err = &'static str;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "TB" => Ok(Self::TopToBottom),
+            "LR" => Ok(Self::LeftToRight),
+            "BT" => Ok(Self::BottomToTop),
+            "RL" => Ok(Self::RightToLeft),
+            _ => Err("Unrecognized layout direction"),
+        }
+    }
+}
+
+impl Display for LayoutDirection {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            Self::TopToBottom => "TB",
+            Self::LeftToRight => "LR",
+            Self::BottomToTop => "BT",
+            Self::RightToLeft => "RL",
+        })
+    }
+}
+
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum Splines {
+    None,
+    Line,
+    Polyline,
+    Curved,
+    Ortho,
+    Spline,
+}
+
+impl FromStr for Splines {
+    type Err = &'static str;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "none" => Ok(Self::None),
+            "line" => Ok(Self::Line),
+            "polyline" => Ok(Self::Polyline),
+            "curved" => Ok(Self::Curved),
+            "ortho" => Ok(Self::Ortho),
+            "spline" => Ok(Self::Spline),
+            _ => Err("Unrecognized splines"),
+        }
+    }
+}
+
+impl Display for Splines {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            Self::None => "none",
+            Self::Line => "line",
+            Self::Polyline => "polyline",
+            Self::Curved => "curved",
+            Self::Ortho => "ortho",
+            Self::Spline => "spline",
+        })
+    }
+}
+
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum ColorBy {
+    /// Fill nodes by their visibility (e.g. green for `pub`, red for private items).
+    Visibility,
+    /// Fill nodes on a gradient by their lines of code (e.g. green for small, red for large items).
+    Loc,
+    /// Fill nodes by the standard library crate they (transitively) depend on
+    /// (i.e. green for `core`, yellow for `alloc`, red for `std`).
+    Std,
+    /// Fill nodes by their depth within the module hierarchy
+    /// (e.g. blue for crates, cyan for their top-level modules).
+    Depth,
+    /// Fill all nodes alike, leaving their visibility to their labels.
+    None,
+}
+
+impl FromStr for ColorBy {
+    type Err = &'static str;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "visibility" => Ok(Self::Visibility),
+            "loc" => Ok(Self::Loc),
+            "std" => Ok(Self::Std),
+            "depth" => Ok(Self::Depth),
+            "none" => Ok(Self::None),
+            _ => Err("Unrecognized color-by"),
+        }
+    }
+}
+
+impl Display for ColorBy {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            Self::Visibility => "visibility",
+            Self::Loc => "loc",
+            Self::Std => "std",
+            Self::Depth => "depth",
+            Self::None => "none",
+        })
+    }
+}
+
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum ClusterBy {
+    /// Group nodes by the Cargo feature gating them (e.g. via `#[cfg(feature = "json")]`),
+    /// with unconditional ones in an "always" cluster.
+    Feature,
+}
+
+impl FromStr for ClusterBy {
+    type Err = &'static str;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "feature" => Ok(Self::Feature),
+            _ => Err("Unrecognized cluster-by"),
+        }
+    }
+}
+
+impl Display for ClusterBy {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            Self::Feature => "feature",
+        })
+    }
+}
+
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum Label {
+    /// Label nodes by their full path (e.g. `my_crate::utils::Foo`).
+    Full,
+    /// Label nodes by their name only (e.g. `Foo`).
+    Short,
+}
+
+impl FromStr for Label {
+    type Err = &'static str;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "full" => Ok(Self::Full),
+            "short" => Ok(Self::Short),
+            _ => Err("Unrecognized label"),
+        }
+    }
+}
+
+impl Display for Label {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            Self::Full => "full",
+            Self::Short => "short",
+        })
+    }
+}
+
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum GraphFormat {
+    /// Graphviz's dot format.
+    Dot,
+    /// A tree of the graph's "owns" edges, annotated with its other edges, drawn in plain ASCII.
+    Ascii,
+    /// A tree of the graph's "owns" edges, annotated with its other edges, drawn via box-drawing characters.
+    Unicode,
+    /// A compact JSON summary of the modules changed by the current diff, for review bots.
+    ReviewJson,
+}
+
+impl FromStr for GraphFormat {
+    type Err = &'static str;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "dot" => Ok(Self::Dot),
+            "ascii" => Ok(Self::Ascii),
+            "unicode" => Ok(Self::Unicode),
+            "review-json" => Ok(Self::ReviewJson),
+            _ => Err("Unrecognized graph format"),
+        }
+    }
+}
+
+impl Display for GraphFormat {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            Self::Dot => "dot",
+            Self::Ascii => "ascii",
+            Self::Unicode => "unicode",
+            Self::ReviewJson => "review-json",
+        })
+    }
+}
+
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum EmitFormat {
+    /// Graphviz's dot format.
+    Dot,
+    /// The JSON format of `serve` (as described by `schema`).
+    Json,
+    /// A Mermaid flowchart.
+    Mermaid,
+}
+
+impl EmitFormat {
+    /// The file extension substituted for `{ext}` in `--emit-path`.
+    pub fn extension(&self) -> &'static str {
+        match self {
+            Self::Dot => "dot",
+            Self::Json => "json",
+            Self::Mermaid => "mmd",
+        }
+    }
+}
+
+impl FromStr for EmitFormat {
+    type Err = &'static str;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "dot" => Ok(Self::Dot),
+            "json" => Ok(Self::Json),
+            "mermaid" => Ok(Self::Mermaid),
+            _ => Err("Unrecognized emit format"),
+        }
+    }
+}
+
+impl Display for EmitFormat {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            Self::Dot => "dot",
+            Self::Json => "json",
+            Self::Mermaid => "mermaid",
+        })
+    }
+}
+
+/// A non-negative distance in inches (e.g. `0.25`), as used by graphviz.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct Inches(String);
+
+impl FromStr for Inches {
+    type Err = &'static str;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.parse::<f64>() {
+            Ok(inches) if inches.is_finite() && inches >= 0.0 => Ok(Self(s.to_owned())),
+            _ => Err("Expected a non-negative number of inches"),
+        }
+    }
+}
+
+impl Display for Inches {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+/// A `key=value` attribute to be passed through to graphviz (e.g. `fontname=Fira Code`).
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct DotAttr {
+    pub key: String,
+    pub value: String,
+}
+
+impl FromStr for DotAttr {
+    type Err = &'static str;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let Some((key, value)) = s.split_once('=') else {
+            return Err("Expected an attribute of the form `key=value`");
+        };
+
+        let key = key.trim();
+
+        if key.is_empty() || !key.chars().all(|c| c.is_ascii_alphanumeric() || c == '_') {
+            return Err("Expected an attribute key consisting of letters, digits and underscores");
+        }
+
+        Ok(Self {
+            key: key.to_owned(),
+            value: value.to_owned(),
+        })
+    }
+}
+
+impl Display for DotAttr {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}={}", self.key, self.value)
+    }
+}
+
 #[derive(Parser, Clone, PartialEq, Eq, Debug)]
 #[group(id = "GenerateSelectionOptions")]
 pub struct Options {
@@ -66,24 +377,257 @@ pub struct Options {
     #[arg(long = "acyclic", conflicts_with = "focus_on")]
     pub acyclic: bool,
 
+    /// The format to report cycles (as detected via `--acyclic`) in
+    /// (e.g. human, quickfix, github, json).
+    #[arg(long = "message-format", default_value = "human")]
+    pub message_format: MessageFormat,
+
+    /// The format to print the graph in (e.g. dot, ascii, unicode, review-json),
+    /// with ascii and unicode drawing it right in the terminal, without requiring Graphviz,
+    /// and review-json summarizing the modules changed by the current diff.
+    #[arg(
+        long = "format",
+        value_name = "FORMAT",
+        default_value = "dot",
+        conflicts_with_all = ["template", "split_output"]
+    )]
+    pub graph_format: GraphFormat,
+
+    /// The git revision to diff against for `--format review-json`
+    /// (e.g. `origin/main`), with the diff including uncommitted and untracked files.
+    #[arg(long = "diff-base", value_name = "REV", default_value = "HEAD")]
+    pub diff_base: String,
+
+    /// Treat the given file as changed for `--format review-json`, instead of querying git.
+    /// May be passed multiple times.
+    #[arg(long = "changed", value_name = "FILE")]
+    pub changed: Vec<PathBuf>,
+
+    /// A graph of the diff's base, as written via `--emit json`,
+    /// for `--format review-json` to report the edges added and removed since.
+    #[arg(long = "baseline", value_name = "FILE")]
+    pub baseline: Option<PathBuf>,
+
     /// The graph layout algorithm to use
     /// (e.g. none, dot, neato, twopi, circo, fdp, sfdp).
-    #[arg(long = "layout", default_value = "neato")]
+    #[arg(
+        long = "layout",
+        visible_alias = "layout-engine",
+        default_value = "neato"
+    )]
     pub layout: LayoutAlgorithm,
 
+    /// The direction to lay out the graph's ranks in
+    /// (e.g. TB, LR, BT, RL).
+    #[arg(long = "layout-direction", default_value = "LR")]
+    pub layout_direction: LayoutDirection,
+
+    /// How to draw the graph's edges
+    /// (e.g. none, line, polyline, curved, ortho, spline).
+    #[arg(long = "splines", default_value = "line")]
+    pub splines: Splines,
+
+    /// Merge edges with a common endpoint into single, partially parallel edges.
+    #[arg(long = "concentrate")]
+    pub concentrate: bool,
+
+    /// The minimum space between adjacent nodes of the same rank, in inches.
+    #[arg(long = "nodesep")]
+    pub nodesep: Option<Inches>,
+
+    /// The minimum space between adjacent ranks, in inches.
+    #[arg(long = "ranksep")]
+    pub ranksep: Option<Inches>,
+
+    /// Render the module hierarchy as nested clusters (instead of "owns" edges),
+    /// as supported by the dot and fdp layouts.
+    #[arg(long = "cluster")]
+    pub cluster: bool,
+
+    /// Group the graph's nodes into clusters by what gates them
+    /// (e.g. feature, for the Cargo features of the analyzed crates).
+    #[arg(
+        long = "cluster-by",
+        value_name = "GROUPING",
+        conflicts_with = "cluster"
+    )]
+    pub cluster_by: Option<ClusterBy>,
+
+    /// Align nodes of the same depth within the module hierarchy on the same rank,
+    /// as supported by the dot layout.
+    #[arg(long = "rank-by-depth", conflicts_with = "cluster")]
+    pub rank_by_depth: bool,
+
+    /// Drop "owns" edges between items already connected by "uses" (or "re-exports") edges,
+    /// and merge parallel "uses" edges into their "re-exports" counterparts.
+    #[arg(long = "simplify", conflicts_with = "cluster")]
+    pub simplify: bool,
+
+    /// What to label the graph's nodes by
+    /// (e.g. full, short).
+    #[arg(long = "label", default_value = "full")]
+    pub label: Label,
+
+    /// Wrap the paths of node labels onto lines of at most the given number of characters
+    /// (breaking them after `::`, truncating any longer segments), keeping the full paths in tooltips.
+    #[arg(long = "label-max-len", value_name = "N")]
+    pub label_max_len: Option<usize>,
+
+    /// What to fill the graph's nodes by
+    /// (e.g. visibility, loc, std, depth, none).
+    #[arg(long = "color-by", default_value = "visibility")]
+    pub color_by: ColorBy,
+
+    /// Append a legend explaining the graph's node colors and edge styles.
+    #[arg(long = "with-legend")]
+    pub with_legend: bool,
+
+    /// Add orphaned source files (i.e. ones not linked via `mod`) as dashed red nodes,
+    /// attached to the modules they'd belong to.
+    #[arg(long = "with-orphans")]
+    pub with_orphans: bool,
+
+    /// Scale the width of "uses" edges by the number of item-level uses they stand for.
+    #[arg(long = "edge-weights")]
+    pub edge_weights: bool,
+
+    /// Label "uses" edges with the names of the items actually being used,
+    /// listing up to the given number of them.
+    #[arg(
+        long = "edge-labels",
+        value_name = "MAX",
+        num_args = 0..=1,
+        default_missing_value = "3"
+    )]
+    pub edge_labels: Option<usize>,
+
+    /// Add the first line of each item's doc comment to its node as a tooltip.
+    #[arg(long = "with-docs")]
+    pub with_docs: bool,
+
+    /// Link each node to its source file (via a `file://` URL), making rendered SVGs clickable.
+    #[arg(long = "with-urls")]
+    pub with_urls: bool,
+
+    /// Link each node to its source via a URL template, with `{rev}`, `{path}` and `{line}` placeholders
+    /// (e.g. `https://github.com/org/repo/blob/{rev}/{path}#L{line}`).
+    #[arg(long = "url-template")]
+    pub url_template: Option<String>,
+
+    /// Replace the names of nodes with stable pseudonyms, preserving the graph's structure
+    /// (e.g. for sharing graphs of proprietary code bases).
+    #[arg(
+        long = "anonymize",
+        conflicts_with_all = ["with_docs", "with_urls", "url_template", "template"]
+    )]
+    pub anonymize: bool,
+
+    /// Add a `key=value` attribute to the graph (e.g. `bgcolor=transparent`).
+    /// May be passed multiple times.
+    #[arg(long = "graph-attr")]
+    pub graph_attrs: Vec<DotAttr>,
+
+    /// Add a `key=value` attribute to all nodes (e.g. `fontname=Fira Code`).
+    /// May be passed multiple times.
+    #[arg(long = "node-attr")]
+    pub node_attrs: Vec<DotAttr>,
+
+    /// Add a `key=value` attribute to all edges (e.g. `penwidth=2`).
+    /// May be passed multiple times.
+    #[arg(long = "edge-attr")]
+    pub edge_attrs: Vec<DotAttr>,
+
     /// Focus the graph on a particular path or use-tree's environment,
-    /// e.g. "foo::bar::{self, baz, blee::*}".
+    /// e.g. "foo::bar::{self, baz, blee::*}",
+    /// or on the module owning a source file, e.g. "src/parser/expr.rs".
     #[arg(long = "focus-on")]
     pub focus_on: Option<String>,
 
+    /// Highlight the paths from the crate's root node to the nodes selected by '--focus-on',
+    /// in addition to the (always highlighted) nodes themselves.
+    #[arg(long = "highlight-path", requires = "focus_on")]
+    pub highlight_path: bool,
+
     /// The maximum depth of the generated graph
     /// relative to the crate's root node, or nodes selected by '--focus-on'.
     #[arg(long = "max-depth")]
     pub max_depth: Option<usize>,
 
+    /// Collapse groups of more than N sibling leaf modules
+    /// into a single summary node (e.g. "… 42 leaf modules").
+    #[arg(long = "merge-leaves", value_name = "N")]
+    pub merge_leaves: Option<usize>,
+
+    /// Only include deprecated items (i.e. `#[deprecated]`),
+    /// along with the modules containing them.
+    #[arg(long = "only-deprecated")]
+    pub only_deprecated: bool,
+
+    /// Select nodes or edges via an expression,
+    /// e.g. `kind == mod && path =~ "net::*"` or `edges(uses) from api to db`.
+    #[arg(long = "select")]
+    pub select: Option<Selector>,
+
+    /// Run a built-in analyzer while building the graph, reporting its findings to stderr
+    /// (e.g. stats, fan-out, no-std). May be passed multiple times.
+    #[arg(
+        long = "analyzer",
+        value_parser = clap::builder::PossibleValuesParser::new(crate::api::analyzers::NAMES)
+    )]
+    pub analyzers: Vec<String>,
+
+    /// Render the graph via a tera template file (exposing its `roots`, `nodes` and `edges`),
+    /// instead of printing it in dot format.
+    #[arg(long = "template")]
+    pub template: Option<PathBuf>,
+
     /// Analyze with `#[cfg(test)]` enabled (i.e as if built via `cargo test`).
     #[arg(long = "cfg-test")]
     pub cfg_test: bool,
+
+    /// Cache the output on disk (in `target/cargo-modules/`), re-using it for as long as the project's files remain unchanged.
+    #[arg(long = "cache", conflicts_with = "template")]
+    pub cache: bool,
+
+    /// Open the output in a viewer (e.g. `xdot` for graphs) instead of printing it.
+    #[arg(long = "open")]
+    pub open: bool,
+
+    /// Write the output to this file (atomically replacing it) instead of printing it.
+    #[arg(short = 'o', long = "output", conflicts_with = "open")]
+    pub output: Option<PathBuf>,
+
+    /// Write one graph per top-level module (plus an `index.dot` overview of them)
+    /// into this directory, instead of printing a single graph.
+    #[arg(
+        long = "split-output",
+        conflicts_with_all = ["output", "open", "cache", "template", "focus_on", "anonymize"]
+    )]
+    pub split_output: Option<PathBuf>,
+
+    /// Write the graph in each of the given formats (e.g. dot,json,mermaid) from a single analysis,
+    /// to the files named by `--emit-path`, instead of printing it.
+    #[arg(
+        long = "emit",
+        value_delimiter = ',',
+        value_name = "FORMATS",
+        conflicts_with_all = ["output", "open", "cache", "template", "split_output"]
+    )]
+    pub emit: Vec<EmitFormat>,
+
+    /// The path of the files written via `--emit`, with `{ext}` replaced by each format's extension
+    /// (i.e. dot, json, mmd).
+    #[arg(
+        long = "emit-path",
+        value_name = "TEMPLATE",
+        default_value = "graph.{ext}"
+    )]
+    pub emit_path: String,
+
+    /// Fail (with exit code 2) if a condition is met, after printing the output
+    /// (e.g. cycles, orphans, orphans>N, fan-out>N, depth>N). May be passed multiple times.
+    #[arg(long = "fail-on", conflicts_with = "cache")]
+    pub fail_on: Vec<FailOn>,
 }
 
 // Important:
@@ -111,6 +655,23 @@ pub struct SelectionOptions {
     #[arg(long = "no-sysroot")]
     pub no_sysroot: bool,
 
+    /// Collapse items of sysroot crates (`std`, `core` & friends) into a single node per crate.
+    #[arg(long = "collapse-sysroot", conflicts_with = "no_sysroot")]
+    pub collapse_sysroot: bool,
+
+    /// Collapse items of extern crates into a single node per crate.
+    #[arg(long = "collapse-externs", conflicts_with = "no_externs")]
+    pub collapse_externs: bool,
+
+    /// Only retain the items of extern crates whose names match the given glob pattern (e.g. `tokio*`).
+    /// May be passed multiple times.
+    #[arg(
+        long = "extern-filter",
+        value_name = "GLOB",
+        conflicts_with = "no_externs"
+    )]
+    pub extern_filter: Vec<String>,
+
     /// Filter out traits (e.g. trait, unsafe trait) from graph.
     #[arg(long = "no-traits")]
     pub no_traits: bool,
@@ -122,4 +683,9 @@ pub struct SelectionOptions {
     /// Filter out "use" edges from graph.
     #[arg(long = "no-uses")]
     pub no_uses: bool,
+
+    /// Filter out "use" edges stemming from test-only code (i.e. `#[cfg(test)]` modules and items,
+    /// or `#[test]` functions, as analyzed via `--cfg-test`) from graph.
+    #[arg(long = "no-test-uses", conflicts_with = "no_uses")]
+    pub no_test_uses: bool,
 }
@@ -4,54 +4,167 @@
 
 //! Printer for displaying crate as a graoh.
 
-use std::fmt::{self, Write};
+use std::{
+    cell::{Cell, OnceCell, RefCell},
+    collections::{BTreeMap, HashMap, HashSet},
+    fmt::{self, Write},
+};
 
 use petgraph::{
     graph::NodeIndex,
-    visit::{IntoNodeReferences, NodeRef},
+    visit::{EdgeRef as _, IntoEdgeReferences as _},
+    Direction,
 };
 use ra_ap_hir as hir;
 use ra_ap_ide::RootDatabase;
 
 use crate::{
     analyzer,
-    graph::{Edge, Graph, Node},
+    api::analyzers::Requirement,
+    colors::styles,
+    command::orphans::orphan::Orphan,
+    graph::{Edge, Graph, Node, Relationship},
     item::ItemVisibility,
+    summary,
 };
 
 use super::{
-    options::Options,
-    theme::{edge_styles, node_styles},
+    anonymize,
+    dep_kinds::{DependencyKind, DependencyKinds},
+    filter::Filter,
+    highlight::Highlights,
+    leaves::MergedLeaves,
+    options::{ClusterBy, ColorBy, DotAttr, Label, Options},
+    theme::{
+        color_palette, depth_node_style, edge_styles, feature_cluster_color, heat_node_style,
+        highlight_color, node_styles, requirement_node_style, EdgeStyle, NodeStyle, Stroke,
+    },
+    urls::NodeUrls,
 };
 
 const INDENTATION: &str = "    ";
 
 pub struct Printer<'a> {
     options: &'a Options,
-    member_krate: hir::Crate,
+    member_krates: &'a [hir::Crate],
     db: &'a RootDatabase,
+    urls: Option<NodeUrls>,
+    dependency_kinds: OnceCell<DependencyKinds>,
+    /// The largest number of lines of code of the graph's internal nodes, as used by `--color-by loc`.
+    max_lines_of_code: Cell<usize>,
+    /// The qualifiers of member crates sharing their name with others (e.g. `bin`).
+    crate_qualifiers: HashMap<hir::Crate, String>,
+    /// The nodes standing for groups of merged leaf modules, as produced by `--merge-leaves`.
+    merged_leaves: MergedLeaves,
+    /// The standard library crates the nodes depend on, as used by `--color-by std`.
+    requirements: HashMap<hir::ModuleDef, Requirement>,
+    /// The focus nodes (and paths leading to them) of the graph being printed.
+    highlights: RefCell<Highlights>,
+    /// The nodes' depths within the module hierarchy, as used by `--color-by depth` and `--rank-by-depth`.
+    depths: RefCell<HashMap<hir::ModuleDef, usize>>,
+    /// The orphans to attach to their would-be parent modules, as requested via `--with-orphans`.
+    orphans: Vec<Orphan>,
 }
 
 impl<'a> Printer<'a> {
-    pub fn new(options: &'a Options, member_krate: hir::Crate, db: &'a RootDatabase) -> Self {
+    pub fn new(
+        options: &'a Options,
+        member_krates: &'a [hir::Crate],
+        db: &'a RootDatabase,
+    ) -> Self {
         Self {
             options,
-            member_krate,
+            member_krates,
             db,
+            urls: NodeUrls::new(options),
+            dependency_kinds: OnceCell::new(),
+            max_lines_of_code: Cell::new(0),
+            crate_qualifiers: crate_qualifiers(member_krates, db),
+            merged_leaves: MergedLeaves::new(),
+            requirements: HashMap::new(),
+            highlights: RefCell::new(Highlights::default()),
+            depths: RefCell::new(HashMap::new()),
+            orphans: vec![],
         }
     }
 
+    pub fn with_merged_leaves(mut self, merged_leaves: MergedLeaves) -> Self {
+        self.merged_leaves = merged_leaves;
+        self
+    }
+
+    pub fn with_requirements(mut self, requirements: HashMap<hir::ModuleDef, Requirement>) -> Self {
+        self.requirements = requirements;
+        self
+    }
+
+    pub fn with_orphans(mut self, orphans: Vec<Orphan>) -> Self {
+        self.orphans = orphans;
+        self
+    }
+
     pub fn fmt(
         &self,
         f: &mut dyn fmt::Write,
         graph: &Graph<Node, Edge>,
-        start_node_idx: NodeIndex,
+        start_node_idxs: &[NodeIndex],
     ) -> Result<(), anyhow::Error> {
-        let root_node = &graph[start_node_idx];
-        let label = root_node.display_path(self.db);
+        let label = start_node_idxs
+            .iter()
+            .map(|start_node_idx| self.node_path(&graph[*start_node_idx]))
+            .collect::<Vec<_>>()
+            .join(", ");
+
+        if self.options.color_by == ColorBy::Loc {
+            let max_lines_of_code = graph
+                .node_weights()
+                .filter(|node| !self.is_external(node))
+                .filter_map(|node| analyzer::lines_of_code(node.hir, self.db))
+                .max()
+                .unwrap_or(0);
+
+            self.max_lines_of_code.set(max_lines_of_code);
+        }
+
+        if self.options.color_by == ColorBy::Depth || self.options.rank_by_depth {
+            *self.depths.borrow_mut() = node_depths(graph);
+        }
+
+        if self.options.focus_on.is_some() {
+            let focus_node_idxs =
+                Filter::new(self.options, self.db, self.member_krates).focus_node_idxs(graph);
+
+            *self.highlights.borrow_mut() =
+                Highlights::new(graph, &focus_node_idxs, self.options.highlight_path);
+        }
+
         let layout = self.options.layout.to_string();
+        let direction = self.options.layout_direction.to_string();
+        let splines = self.options.splines.to_string();
         let i = INDENTATION;
 
+        // Optional tuning attributes, only emitted when explicitly requested:
+
+        let mut tuning = String::new();
+
+        if self.options.concentrate {
+            tuning.push_str(&format!("\n{i}    concentrate=true,"));
+        }
+
+        if let Some(nodesep) = &self.options.nodesep {
+            tuning.push_str(&format!("\n{i}    nodesep={nodesep},"));
+        }
+
+        if let Some(ranksep) = &self.options.ranksep {
+            tuning.push_str(&format!("\n{i}    ranksep={ranksep},"));
+        }
+
+        // Custom attributes, overriding the defaults preceding them:
+
+        let graph_attrs = Self::custom_attrs(&self.options.graph_attrs);
+        let node_attrs = Self::custom_attrs(&self.options.node_attrs);
+        let edge_attrs = Self::custom_attrs(&self.options.edge_attrs);
+
         writeln!(f, "digraph {{")?;
 
         writeln!(f)?;
@@ -69,11 +182,11 @@ impl<'a> Printer<'a> {
             {i}    // [dot, neato, twopi, circo, fdp, sfdp]
             {i}    layout={layout},
             {i}    overlap=false,
-            {i}    splines="line",
-            {i}    rankdir=LR,
+            {i}    splines={splines:?},
+            {i}    rankdir={direction},{tuning}
             
             {i}    fontname="Helvetica", 
-            {i}    fontsize="36",
+            {i}    fontsize="36",{graph_attrs}
             {i}];
             "#,
         )?;
@@ -87,7 +200,7 @@ impl<'a> Printer<'a> {
             {i}    fontname="monospace",
             {i}    fontsize="10",
             {i}    shape="record",
-            {i}    style="filled",
+            {i}    style="filled",{node_attrs}
             {i}];
             "#,
         )?;
@@ -99,82 +212,453 @@ impl<'a> Printer<'a> {
             r#"
             {i}edge [
             {i}    fontname="monospace",
-            {i}    fontsize="10",
+            {i}    fontsize="10",{edge_attrs}
             {i}];
             "#,
         )?;
 
         writeln!(f)?;
 
-        self.fmt_nodes(f, graph)?;
+        if self.options.cluster {
+            self.fmt_clusters(f, graph)?;
+        } else if let Some(ClusterBy::Feature) = self.options.cluster_by {
+            self.fmt_feature_clusters(f, graph)?;
+        } else {
+            self.fmt_nodes(f, graph)?;
+        }
 
         writeln!(f)?;
 
+        if self.options.rank_by_depth {
+            self.fmt_ranks(f, graph)?;
+
+            writeln!(f)?;
+        }
+
         self.fmt_edges(f, graph)?;
 
         writeln!(f)?;
 
+        if !self.orphans.is_empty() {
+            self.fmt_orphans(f, graph)?;
+
+            writeln!(f)?;
+        }
+
+        if self.options.with_legend {
+            self.fmt_legend(f)?;
+
+            writeln!(f)?;
+        }
+
         writeln!(f, "}}")?;
 
         Ok(())
     }
 
+    fn custom_attrs(attrs: &[DotAttr]) -> String {
+        let i = INDENTATION;
+
+        attrs
+            .iter()
+            .fold(String::new(), |mut output, DotAttr { key, value }| {
+                let _ = write!(output, "\n{i}    {key}={value:?},");
+                output
+            })
+    }
+
     fn fmt_nodes(&self, f: &mut dyn fmt::Write, graph: &Graph<Node, Edge>) -> fmt::Result {
-        let mut lines: Vec<_> = graph
-            .node_references()
-            .map(|node_ref| {
-                let node: &Node = node_ref.weight();
+        let i = INDENTATION;
 
-                let id = node.display_path(self.db);
-                let kind = node.kind_display_name(self.db);
+        // Only the (cheap) sort keys get collected up-front,
+        // while the (expensive) lines get formatted and written one by one:
+        let mut keys: Vec<_> = vec![];
+        let mut build_script_keys: BTreeMap<String, Vec<_>> = BTreeMap::new();
 
-                let label = self.node_label(node).unwrap();
-                let attributes = self.node_attributes(node);
+        for node_idx in graph.node_indices() {
+            let node = &graph[node_idx];
+            let id = self.node_path(node);
+            let key = (format!("{id:?}"), node_idx);
 
-                let i = INDENTATION;
+            match analyzer::krate(node.hir, self.db) {
+                Some(krate)
+                    if self.options.project.with_build_script
+                        && analyzer::crate_is_build_script(krate, self.db) =>
+                {
+                    let cluster_id = match id.split_once("::") {
+                        Some((crate_id, _)) => format!("cluster_{crate_id}"),
+                        None => format!("cluster_{id}"),
+                    };
 
-                format!(r#"{i}{id:?} [label={label:?}{attributes}]; // {kind:?} node"#)
-            })
+                    build_script_keys.entry(cluster_id).or_default().push(key);
+                }
+                _ => keys.push(key),
+            }
+        }
+
+        self.fmt_sorted_lines(f, keys, |node_idx| {
+            let line = self.node_line(&graph[node_idx]);
+
+            format!("{i}{line}")
+        })?;
+
+        // Build scripts (as processed via `--with-build-script`) get a cluster of their own,
+        // to keep build-time code apart from the package's other targets:
+        for (cluster_id, keys) in build_script_keys {
+            writeln!(f, "{i}subgraph {cluster_id:?} {{")?;
+            writeln!(f, "{i}{i}label=\"build script\";")?;
+            writeln!(f, "{i}{i}fontsize=\"16\";")?;
+
+            self.fmt_sorted_lines(f, keys, |node_idx| {
+                let line = self.node_line(&graph[node_idx]);
+
+                format!("{i}{i}{line}")
+            })?;
+
+            writeln!(f, "{i}}}")?;
+        }
+
+        Ok(())
+    }
+
+    /// Writes a `rank=same` block per depth within the module hierarchy,
+    /// aligning the nodes of each depth on a rank of their own.
+    fn fmt_ranks(&self, f: &mut dyn fmt::Write, graph: &Graph<Node, Edge>) -> fmt::Result {
+        let i = INDENTATION;
+
+        let mut paths_by_depth: BTreeMap<usize, Vec<String>> = BTreeMap::new();
+
+        for node in graph.node_weights() {
+            if let Some(depth) = self.depths.borrow().get(&node.hir) {
+                paths_by_depth
+                    .entry(*depth)
+                    .or_default()
+                    .push(format!("{:?}", self.node_path(node)));
+            }
+        }
+
+        for (depth, mut paths) in paths_by_depth {
+            paths.sort();
+            paths.dedup();
+
+            writeln!(
+                f,
+                "{i}{{ rank=same; {}; }} // depth {depth} rank",
+                paths.join("; ")
+            )?;
+        }
+
+        Ok(())
+    }
+
+    /// Writes the nodes nested within `subgraph cluster_*` blocks of their owning nodes,
+    /// in place of the "owns" edges between them.
+    fn fmt_clusters(&self, f: &mut dyn fmt::Write, graph: &Graph<Node, Edge>) -> fmt::Result {
+        let mut children: HashMap<NodeIndex, Vec<NodeIndex>> = HashMap::new();
+        let mut owned: HashSet<NodeIndex> = HashSet::new();
+
+        for edge_ref in graph.edge_references() {
+            if edge_ref.weight().kind == Relationship::Owns {
+                children
+                    .entry(edge_ref.source())
+                    .or_default()
+                    .push(edge_ref.target());
+                owned.insert(edge_ref.target());
+            }
+        }
+
+        let roots: Vec<_> = graph
+            .node_indices()
+            .filter(|node_idx| !owned.contains(node_idx))
             .collect();
 
-        lines.sort();
+        for node_idx in self.sorted_by_path(graph, roots) {
+            self.fmt_cluster(f, graph, &children, node_idx, 1)?;
+        }
+
+        Ok(())
+    }
+
+    fn fmt_cluster(
+        &self,
+        f: &mut dyn fmt::Write,
+        graph: &Graph<Node, Edge>,
+        children: &HashMap<NodeIndex, Vec<NodeIndex>>,
+        node_idx: NodeIndex,
+        depth: usize,
+    ) -> fmt::Result {
+        let node = &graph[node_idx];
+        let indentation = INDENTATION.repeat(depth);
+
+        let Some(child_idxs) = children.get(&node_idx) else {
+            return writeln!(f, "{indentation}{}", self.node_line(node));
+        };
 
-        for line in lines {
-            f.write_str(&line)?;
-            f.write_char('\n')?;
+        let id = format!("cluster_{}", self.node_path(node));
+
+        writeln!(f, "{indentation}subgraph {id:?} {{")?;
+        writeln!(f, "{indentation}{INDENTATION}{}", self.node_line(node))?;
+
+        for child_idx in self.sorted_by_path(graph, child_idxs.clone()) {
+            self.fmt_cluster(f, graph, children, child_idx, depth + 1)?;
+        }
+
+        writeln!(f, "{indentation}}}")
+    }
+
+    /// Writes the nodes of member crates within `subgraph cluster_*` blocks
+    /// of the Cargo features gating them, with unconditional ones in an "always" cluster
+    /// (and those of other crates outside of any).
+    fn fmt_feature_clusters(
+        &self,
+        f: &mut dyn fmt::Write,
+        graph: &Graph<Node, Edge>,
+    ) -> fmt::Result {
+        let i = INDENTATION;
+
+        let mut keys: Vec<_> = vec![];
+        let mut always_keys: Vec<_> = vec![];
+        let mut feature_keys: BTreeMap<String, Vec<_>> = BTreeMap::new();
+
+        for node_idx in graph.node_indices() {
+            let node = &graph[node_idx];
+            let key = (format!("{:?}", self.node_path(node)), node_idx);
+
+            let is_member = analyzer::krate(node.hir, self.db)
+                .is_some_and(|krate| self.member_krates.contains(&krate));
+
+            if !is_member {
+                keys.push(key);
+                continue;
+            }
+
+            match analyzer::gating_feature(node.hir, self.db) {
+                Some(feature) => feature_keys.entry(feature).or_default().push(key),
+                None => always_keys.push(key),
+            }
+        }
+
+        let fmt_line = |node_idx: NodeIndex| format!("{i}{}", self.node_line(&graph[node_idx]));
+
+        self.fmt_sorted_lines(f, keys, fmt_line)?;
+
+        if !always_keys.is_empty() {
+            writeln!(f, "{i}subgraph \"cluster_always\" {{")?;
+            writeln!(f, "{i}{i}label=\"always\";")?;
+            writeln!(f, "{i}{i}fontsize=\"16\";")?;
+
+            self.fmt_sorted_lines(f, always_keys, |node_idx| {
+                format!("{i}{}", fmt_line(node_idx))
+            })?;
+
+            writeln!(f, "{i}}}")?;
+        }
+
+        for (index, (feature, keys)) in feature_keys.into_iter().enumerate() {
+            let feature = if self.options.anonymize {
+                anonymize::pseudonym(&feature)
+            } else {
+                feature
+            };
+
+            let id = format!("cluster_feature_{feature}");
+            let label = format!("feature {feature:?}");
+            let color = feature_cluster_color(index);
+
+            writeln!(f, "{i}subgraph {id:?} {{")?;
+            writeln!(f, "{i}{i}label={label:?};")?;
+            writeln!(f, "{i}{i}fontsize=\"16\";")?;
+            writeln!(f, "{i}{i}color=\"{color}\";")?;
+
+            self.fmt_sorted_lines(f, keys, |node_idx| format!("{i}{}", fmt_line(node_idx)))?;
+
+            writeln!(f, "{i}}}")?;
         }
 
         Ok(())
     }
 
+    fn sorted_by_path(
+        &self,
+        graph: &Graph<Node, Edge>,
+        mut node_idxs: Vec<NodeIndex>,
+    ) -> Vec<NodeIndex> {
+        node_idxs.sort_by_cached_key(|node_idx| self.node_path(&graph[*node_idx]));
+        node_idxs
+    }
+
+    /// Returns the node's path, anonymized if requested via `--anonymize`
+    /// and qualified if its crate's name is shared with others.
+    fn node_path(&self, node: &Node) -> String {
+        let path = self.anonymized(node.hir, node.display_path(self.db));
+
+        // Summary nodes stand for all of their parent's leaf modules, not just their own:
+        let path = match (
+            self.merged_leaves.contains_key(&node.hir),
+            path.rsplit_once("::"),
+        ) {
+            (true, Some((parent_path, _))) => format!("{parent_path}::…"),
+            _ => path,
+        };
+
+        let Some(qualifier) = self.crate_qualifier(node) else {
+            return path;
+        };
+
+        match path.split_once("::") {
+            Some((crate_name, relative_path)) => {
+                format!("{crate_name}[{qualifier}]::{relative_path}")
+            }
+            None => format!("{path}[{qualifier}]"),
+        }
+    }
+
+    /// Returns the qualifier of the node's crate, if its name is shared with others.
+    fn crate_qualifier(&self, node: &Node) -> Option<&str> {
+        let krate = analyzer::krate(node.hir, self.db)?;
+        self.crate_qualifiers.get(&krate).map(String::as_str)
+    }
+
+    /// Returns `path` anonymized if requested via `--anonymize`,
+    /// except for those of sysroot crates (`std`, `core` & friends), which have nothing to hide.
+    fn anonymized(&self, hir: hir::ModuleDef, path: String) -> String {
+        if !self.options.anonymize {
+            return path;
+        }
+
+        let is_sysroot =
+            analyzer::krate(hir, self.db).is_some_and(|krate| krate.origin(self.db).is_lang());
+
+        if is_sysroot {
+            path
+        } else {
+            anonymize::anonymize_path(&path)
+        }
+    }
+
+    fn node_line(&self, node: &Node) -> String {
+        let id = self.node_path(node);
+        let kind = node.kind_display_name(self.db);
+
+        let label = self.node_label(node).unwrap();
+        let attributes = self.node_attributes(node);
+
+        format!(r#"{id:?} [label={label:?}{attributes}]; // {kind:?} node"#)
+    }
+
     fn fmt_edges(&self, f: &mut dyn fmt::Write, graph: &Graph<Node, Edge>) -> fmt::Result {
-        let mut lines: Vec<_> = graph.edge_indices().map(|edge_idx| {
+        let keys: Vec<_> = graph
+            .edge_indices()
+            // Clusters already convey the "owns" edges by means of nesting:
+            .filter(|edge_idx| {
+                !(self.options.cluster && graph[*edge_idx].kind == Relationship::Owns)
+            })
+            .map(|edge_idx| {
+                let (source_idx, target_idx) = graph.edge_endpoints(edge_idx).unwrap();
+
+                let source = self.node_path(&graph[source_idx]);
+                let target = self.node_path(&graph[target_idx]);
+
+                (format!("{source:?} -> {target:?}"), edge_idx)
+            })
+            .collect();
+
+        summary::record_edges(keys.len());
+
+        self.fmt_sorted_lines(f, keys, |edge_idx| {
             let edge = &graph[edge_idx];
             let (source_idx, target_idx) = graph.edge_endpoints(edge_idx).unwrap();
 
-            let source = graph[source_idx].display_path(self.db);
-            let target = graph[target_idx].display_path(self.db);
+            let source = self.node_path(&graph[source_idx]);
+            let target = self.node_path(&graph[target_idx]);
 
             let kind = edge.display_name();
 
+            let is_highlighted = self
+                .highlights
+                .borrow()
+                .edges
+                .contains(&(graph[source_idx].hir, graph[target_idx].hir));
+
             let label = self.edge_label(edge);
-            let attributes = self.edge_attributes(edge);
+            let attributes = self.edge_attributes(edge, is_highlighted);
 
-            let constraint = match edge {
-                Edge::Uses => "[constraint=false]",
-                Edge::Owns => "[constraint=true]",
+            let constraint = match edge.kind {
+                Relationship::Uses | Relationship::ReExports => "[constraint=false]",
+                Relationship::Owns => "[constraint=true]",
             };
 
             let i = INDENTATION;
 
             format!(r#"{i}{source:?} -> {target:?} [label={label:?}{attributes}] {constraint}; // {kind:?} edge"#)
-        }).collect();
+        })
+    }
+
+    /// Writes a node for each orphan whose would-be parent module is part of the graph,
+    /// along with a dashed "owns" edge from the latter.
+    fn fmt_orphans(&self, f: &mut dyn fmt::Write, graph: &Graph<Node, Edge>) -> fmt::Result {
+        let parents: HashMap<String, &Node> = graph
+            .node_weights()
+            .filter_map(|node| Some((analyzer::path(node.hir, self.db)?, node)))
+            .collect();
+
+        let i = INDENTATION;
+        let fill_color = node_styles().plain.fill_color;
+        let color = color_palette().red;
+        let stroke = Stroke::Dashed;
+
+        for orphan in &self.orphans {
+            let Some(parent) = parents.get(&orphan.parent_module_path) else {
+                continue;
+            };
+
+            let name = if self.options.anonymize {
+                anonymize::pseudonym(&orphan.name)
+            } else {
+                orphan.name.clone()
+            };
+
+            let source = self.node_path(parent);
+            let target = format!("{source}::{name}");
+            let label = format!("orphan|{name}");
 
-        lines.sort();
+            writeln!(
+                f,
+                r#"{i}{target:?} [label={label:?}, fillcolor="{fill_color}", color="{color}", style="filled,{stroke}"]; // "orphan" node"#
+            )?;
+            writeln!(
+                f,
+                r#"{i}{source:?} -> {target:?} [label="owns", color="{color}", style="{stroke}"] [constraint=true]; // "orphan" edge"#
+            )?;
 
-        for line in lines {
-            f.write_str(&line)?;
-            f.write_char('\n')?;
+            summary::record_edges(1);
+        }
+
+        Ok(())
+    }
+
+    /// Writes the lines produced by `fmt_line` in the order of their keys,
+    /// ordering lines with equal keys by their formatted contents.
+    fn fmt_sorted_lines<I>(
+        &self,
+        f: &mut dyn fmt::Write,
+        mut keys: Vec<(String, I)>,
+        fmt_line: impl Fn(I) -> String,
+    ) -> fmt::Result
+    where
+        I: Copy,
+    {
+        keys.sort_by(|lhs, rhs| lhs.0.cmp(&rhs.0));
+
+        for group in keys.chunk_by(|lhs, rhs| lhs.0 == rhs.0) {
+            let mut lines: Vec<_> = group.iter().map(|(_, idx)| fmt_line(*idx)).collect();
+
+            lines.sort();
+
+            for line in lines {
+                f.write_str(&line)?;
+                f.write_char('\n')?;
+            }
         }
 
         Ok(())
@@ -183,21 +667,79 @@ impl<'a> Printer<'a> {
     fn node_label(&self, node: &Node) -> Result<String, fmt::Error> {
         let mut label = String::new();
 
+        // Only record shapes split labels into fields, all others get theirs split into lines:
+        let separator = match self.kind_style(node, "shape") {
+            Some(shape) if shape != "record" && shape != "Mrecord" => "\n",
+            _ => "|",
+        };
+
         self.fmt_node_header(&mut label, node)?;
-        write!(&mut label, "|")?;
+        write!(&mut label, "{separator}")?;
         self.fmt_node_body(&mut label, node)?;
 
         Ok(label)
     }
 
-    fn fmt_node_header(&self, f: &mut dyn fmt::Write, node: &Node) -> fmt::Result {
+    fn is_external(&self, node: &Node) -> bool {
         let krate = analyzer::krate(node.hir, self.db);
 
-        let is_external = krate != Some(self.member_krate);
+        !krate.is_some_and(|krate| self.member_krates.contains(&krate))
+    }
+
+    /// Returns the kind of dependency the node's (external) crate is, if known.
+    fn dependency_kind(&self, node: &Node) -> Option<DependencyKind> {
+        if !self.is_external(node) {
+            return None;
+        }
+
+        let krate = analyzer::krate(node.hir, self.db)?;
+        let crate_name = analyzer::crate_name(krate, self.db);
+
+        // Obtained lazily, as graphs without any external nodes have no need for them:
+        self.dependency_kinds
+            .get_or_init(|| DependencyKinds::new(&self.options.project))
+            .get(&crate_name)
+    }
+
+    /// Returns the kind under which the node's custom styles are looked up (see `colors::styles`).
+    fn style_kind(&self, node: &Node) -> String {
+        if self.is_external(node) {
+            return "extern".to_owned();
+        }
+
+        // Drop any qualifiers, such as in "async fn" or "unsafe trait":
+        let kind = node.kind_display_name(self.db).to_string();
+
+        match kind.rsplit_once(' ') {
+            Some((_qualifiers, kind)) => kind.to_owned(),
+            None => kind,
+        }
+    }
+
+    fn kind_style(&self, node: &Node, key: &str) -> Option<&'static str> {
+        styles::current()
+            .get(&self.style_kind(node))
+            .iter()
+            .rev()
+            .find(|(attr_key, _)| attr_key == key)
+            .map(|(_, value)| value.as_str())
+    }
+
+    fn fmt_node_header(&self, f: &mut dyn fmt::Write, node: &Node) -> fmt::Result {
+        if self.merged_leaves.contains_key(&node.hir) {
+            return write!(f, "mod");
+        }
+
+        let is_external = self.is_external(node);
         let is_crate = analyzer::moduledef_is_crate(node.hir, self.db);
 
         let visibility = if is_external {
-            Some("external".to_owned())
+            match self.dependency_kind(node) {
+                Some(kind @ (DependencyKind::Dev | DependencyKind::Build)) => {
+                    Some(format!("external {kind}"))
+                }
+                _ => Some("external".to_owned()),
+            }
         } else if is_crate {
             None
         } else {
@@ -206,6 +748,15 @@ impl<'a> Printer<'a> {
 
         let kind = node.kind_display_name(self.db);
 
+        if let Some(deprecated) = node.attrs(self.db).deprecated {
+            write!(f, "#[{deprecated}] ")?;
+        }
+
+        // Only the analyzed crates' modules get warned about (and thus marked) as unresolved:
+        if !is_external && node.is_unresolved(self.db) {
+            write!(f, "unresolved ")?;
+        }
+
         if let Some(visibility) = visibility {
             write!(f, "{visibility} ")?;
         }
@@ -214,7 +765,16 @@ impl<'a> Printer<'a> {
     }
 
     fn fmt_node_body(&self, f: &mut dyn fmt::Write, node: &Node) -> fmt::Result {
-        let path = node.display_path(self.db);
+        if let Some(count) = self.merged_leaves.get(&node.hir) {
+            return write!(f, "… {count} leaf modules");
+        }
+
+        let path = self.node_path(node);
+
+        if self.options.label == Label::Short {
+            let name = path.rsplit("::").next().unwrap_or_default();
+            return write!(f, "{}", self.wrapped(name));
+        }
 
         let refined_path = if self.options.selection.no_externs {
             // Try to drop the crate-name from the path if externs are being filtered:
@@ -227,7 +787,15 @@ impl<'a> Printer<'a> {
             path
         };
 
-        write!(f, "{refined_path}")
+        write!(f, "{}", self.wrapped(&refined_path))
+    }
+
+    /// Returns `path` wrapped onto multiple lines, if requested via `--label-max-len`.
+    fn wrapped(&self, path: &str) -> String {
+        match self.options.label_max_len {
+            Some(max_len) => wrap_path(path, max_len),
+            None => path.to_owned(),
+        }
     }
 
     fn node_attributes(&self, node: &Node) -> String {
@@ -235,33 +803,415 @@ impl<'a> Printer<'a> {
 
         let is_crate = analyzer::moduledef_is_crate(node.hir, self.db);
 
-        let style = if is_crate {
-            styles.krate
-        } else {
-            match &node.visibility(self.db) {
+        let is_merged = self.merged_leaves.contains_key(&node.hir);
+
+        let style = match self.options.color_by {
+            ColorBy::None => styles.plain,
+            _ if is_merged => styles.plain,
+            ColorBy::Loc => self.heat_style(node).unwrap_or(styles.plain),
+            ColorBy::Depth => self.depth_style(node).unwrap_or(styles.plain),
+            ColorBy::Std => match self.requirements.get(&node.hir) {
+                Some(requirement) => requirement_node_style(*requirement),
+                None => styles.plain,
+            },
+            ColorBy::Visibility if is_crate => styles.krate,
+            ColorBy::Visibility if node.attrs(self.db).deprecated.is_some() => styles.deprecated,
+            ColorBy::Visibility => match &node.visibility(self.db) {
                 ItemVisibility::Crate => styles.visibility.pub_crate,
                 ItemVisibility::Module(_) => styles.visibility.pub_module,
                 ItemVisibility::Private => styles.visibility.pub_private,
                 ItemVisibility::Public => styles.visibility.pub_global,
                 ItemVisibility::Super => styles.visibility.pub_super,
+            },
+        };
+
+        let custom_attrs = styles::current().get(&self.style_kind(node));
+
+        let mut attributes = String::new();
+
+        if !custom_attrs.iter().any(|(key, _)| key == "fillcolor") {
+            let _ = write!(attributes, r#", fillcolor="{}""#, style.fill_color);
+        }
+
+        // Graphviz would otherwise identify the nodes of SVG outputs by their position:
+        let id = match self.crate_qualifier(node) {
+            Some(qualifier) => format!("{}-{qualifier}", node.stable_id(self.db)),
+            None => node.stable_id(self.db),
+        };
+        let id = if is_merged {
+            format!("{id}-leaves")
+        } else {
+            id
+        };
+        let _ = write!(attributes, r#", id="{id}""#);
+
+        let stroke = match self.dependency_kind(node) {
+            Some(DependencyKind::Dev) => Some(&styles.dependency_kind.dev),
+            Some(DependencyKind::Build) => Some(&styles.dependency_kind.build),
+            _ => None,
+        };
+
+        if let Some(stroke) = stroke {
+            if !custom_attrs.iter().any(|(key, _)| key == "style") {
+                let _ = write!(attributes, r#", style="filled,{stroke}""#);
+            }
+        }
+
+        // Wrapped labels don't lend themselves to reading (or copying) paths, so they go into tooltips:
+        let path = self.options.label_max_len.map(|_| self.node_path(node));
+
+        let summary = if self.options.with_docs {
+            node.doc_summary(self.db)
+        } else {
+            None
+        };
+
+        let tooltip = match (path, summary) {
+            (Some(path), Some(summary)) => Some(format!("{path}\n{summary}")),
+            (path, summary) => path.or(summary),
+        };
+
+        if let Some(tooltip) = tooltip {
+            let _ = write!(attributes, ", tooltip={tooltip:?}");
+        }
+
+        if let Some(url) = self.urls.as_ref().and_then(|urls| urls.url(node, self.db)) {
+            let _ = write!(attributes, ", URL={url:?}");
+        }
+
+        if self.highlights.borrow().nodes.contains(&node.hir) {
+            let color = highlight_color();
+            let _ = write!(attributes, r#", color="{color}", penwidth="3""#);
+        }
+
+        for (key, value) in custom_attrs {
+            let _ = write!(attributes, ", {key}={value:?}");
+        }
+
+        attributes
+    }
+
+    /// Returns the style of an (internal) node by its lines of code,
+    /// relative to the largest number of lines of code of the graph's nodes.
+    fn heat_style(&self, node: &Node) -> Option<NodeStyle> {
+        if self.is_external(node) {
+            return None;
+        }
+
+        let lines_of_code = analyzer::lines_of_code(node.hir, self.db)?;
+        let max_lines_of_code = self.max_lines_of_code.get().max(1);
+
+        Some(heat_node_style(
+            lines_of_code as f64 / max_lines_of_code as f64,
+        ))
+    }
+
+    /// Returns the style of an (internal) node by its depth within the module hierarchy.
+    fn depth_style(&self, node: &Node) -> Option<NodeStyle> {
+        if self.is_external(node) {
+            return None;
+        }
+
+        let depth = self.depths.borrow().get(&node.hir).copied()?;
+
+        Some(depth_node_style(depth))
+    }
+
+    /// Writes a `subgraph cluster_legend` block with one sample node per node color
+    /// and one sample edge per edge style.
+    fn fmt_legend(&self, f: &mut dyn fmt::Write) -> fmt::Result {
+        let i = INDENTATION;
+
+        let node_styles = node_styles();
+        let edge_styles = edge_styles();
+
+        let nodes = match self.options.color_by {
+            ColorBy::Visibility => vec![
+                ("crate".to_owned(), node_styles.krate),
+                ("pub".to_owned(), node_styles.visibility.pub_global),
+                ("pub(crate)".to_owned(), node_styles.visibility.pub_crate),
+                ("pub(super)".to_owned(), node_styles.visibility.pub_super),
+                ("pub(in path)".to_owned(), node_styles.visibility.pub_module),
+                ("private".to_owned(), node_styles.visibility.pub_private),
+                ("#[deprecated]".to_owned(), node_styles.deprecated),
+            ],
+            ColorBy::Loc => {
+                let max_lines_of_code = self.max_lines_of_code.get();
+
+                vec![
+                    ("0 LOC".to_owned(), heat_node_style(0.0)),
+                    (
+                        format!("{} LOC", max_lines_of_code / 2),
+                        heat_node_style(0.5),
+                    ),
+                    (format!("{max_lines_of_code} LOC"), heat_node_style(1.0)),
+                ]
+            }
+            ColorBy::Std => [Requirement::Core, Requirement::Alloc, Requirement::Std]
+                .into_iter()
+                .map(|requirement| (requirement.to_string(), requirement_node_style(requirement)))
+                .collect(),
+            ColorBy::Depth => {
+                let max_depth = self.depths.borrow().values().max().copied().unwrap_or(0);
+
+                (0..=max_depth)
+                    .map(|depth| (format!("depth {depth}"), depth_node_style(depth)))
+                    .collect()
             }
+            ColorBy::None => vec![],
         };
 
-        format!(r#", fillcolor="{}""#, style.fill_color)
+        let mut edges = vec![
+            ("owns", edge_styles.owns),
+            ("uses", edge_styles.uses.clone()),
+            ("reexports", edge_styles.reexports),
+        ];
+
+        // Test-only edges only exist when analyzing `#[cfg(test)]` code:
+        if self.options.cfg_test {
+            let style = EdgeStyle {
+                stroke: edge_styles.test_only,
+                ..edge_styles.uses
+            };
+
+            edges.push(("uses (test-only)", style));
+        }
+
+        writeln!(f, "{i}subgraph \"cluster_legend\" {{")?;
+        writeln!(f, "{i}{i}label=\"Legend\";")?;
+        writeln!(f, "{i}{i}fontsize=\"16\";")?;
+
+        for (name, style) in nodes {
+            let id = format!("legend::{name}");
+            let fill_color = style.fill_color;
+
+            writeln!(
+                f,
+                r#"{i}{i}{id:?} [label={name:?}, fillcolor="{fill_color}"];"#
+            )?;
+        }
+
+        let dependency_kinds = [
+            ("dev-dependency", &node_styles.dependency_kind.dev),
+            ("build-dependency", &node_styles.dependency_kind.build),
+        ];
+
+        for (name, stroke) in dependency_kinds {
+            let id = format!("legend::{name}");
+            let fill_color = node_styles.plain.fill_color;
+
+            writeln!(
+                f,
+                r#"{i}{i}{id:?} [label={name:?}, fillcolor="{fill_color}", style="filled,{stroke}"];"#
+            )?;
+        }
+
+        // Kinds with custom styles (see `colors::styles`) get a sample node of their own:
+
+        for kind in styles::KINDS {
+            let custom_attrs = styles::current().get(kind);
+
+            if custom_attrs.is_empty() {
+                continue;
+            }
+
+            let id = format!("legend::{kind}");
+            let attributes = custom_attrs
+                .iter()
+                .fold(String::new(), |mut output, (key, value)| {
+                    let _ = write!(output, ", {key}={value:?}");
+                    output
+                });
+
+            writeln!(f, r#"{i}{i}{id:?} [label={kind:?}{attributes}];"#)?;
+        }
+
+        for (name, style) in edges {
+            let source = format!("legend::{name}::source");
+            let target = format!("legend::{name}::target");
+            let (color, stroke) = (style.color, style.stroke);
+
+            writeln!(
+                f,
+                r#"{i}{i}{source:?} [label="", shape="point"]; {target:?} [label="", shape="point"];"#
+            )?;
+            writeln!(
+                f,
+                r#"{i}{i}{source:?} -> {target:?} [label={name:?}, color="{color}", style="{stroke}"];"#
+            )?;
+        }
+
+        writeln!(f, "{i}}}")
     }
 
     fn edge_label(&self, edge: &Edge) -> String {
-        edge.display_name().to_owned()
+        let kind = edge.display_name();
+
+        let Some(max_items) = self.options.edge_labels else {
+            return kind.to_owned();
+        };
+
+        let mut names: Vec<_> = edge
+            .targets
+            .iter()
+            .map(|target| self.anonymized(*target, analyzer::display_name(*target, self.db)))
+            .collect();
+
+        if names.is_empty() {
+            return kind.to_owned();
+        }
+
+        names.sort();
+        names.dedup();
+
+        let count = names.len();
+        names.truncate(max_items);
+
+        let mut label = format!("{kind} {}", names.join(", "));
+
+        if count > names.len() {
+            let _ = write!(label, " (+{} more)", count - names.len());
+        }
+
+        label
     }
 
-    fn edge_attributes(&self, edge: &Edge) -> String {
+    fn edge_attributes(&self, edge: &Edge, is_highlighted: bool) -> String {
         let styles = edge_styles();
 
-        let style = match edge {
-            Edge::Uses { .. } => styles.uses,
-            Edge::Owns => styles.owns,
+        let style = match edge.kind {
+            Relationship::Uses => styles.uses,
+            Relationship::ReExports => styles.reexports,
+            Relationship::Owns => styles.owns,
         };
 
-        format!(r#", color="{}", style="{}""#, style.color, style.stroke)
+        let stroke = if edge.test_only {
+            styles.test_only
+        } else {
+            style.stroke
+        };
+
+        let mut attributes = if is_highlighted {
+            let color = highlight_color();
+            format!(r#", color="{color}", style="{stroke}", penwidth="2""#)
+        } else {
+            format!(r#", color="{}", style="{stroke}""#, style.color)
+        };
+
+        if self.options.edge_weights && edge.kind == Relationship::Uses {
+            let weight = edge.weight;
+            let penwidth = weight.max(1).ilog2() + 1;
+            let _ = write!(attributes, r#", weight="{weight}", penwidth="{penwidth}""#);
+        }
+
+        attributes
+    }
+}
+
+/// Returns the depth of each of the graph's nodes within the module hierarchy,
+/// i.e. the number of "owns" edges leading to it from its root (e.g. its crate's root module).
+/// Wraps `path` onto lines of at most `max_len` characters, breaking it after its `::` separators
+/// and truncating any segments too long to fit on a line of their own (e.g. `my_crate::\nvery_lo…`).
+fn wrap_path(path: &str, max_len: usize) -> String {
+    // Leaving room for at least one character followed by an ellipsis:
+    let max_len = max_len.max(2);
+
+    let segments: Vec<&str> = path.split("::").collect();
+    let count = segments.len();
+
+    let mut lines: Vec<String> = vec![];
+    let mut line = String::new();
+
+    for (pos, segment) in segments.into_iter().enumerate() {
+        let segment = if pos + 1 < count {
+            format!("{segment}::")
+        } else {
+            segment.to_owned()
+        };
+
+        let segment = if segment.chars().count() > max_len {
+            let mut truncated: String = segment.chars().take(max_len - 1).collect();
+            truncated.push('…');
+            truncated
+        } else {
+            segment
+        };
+
+        if !line.is_empty() && line.chars().count() + segment.chars().count() > max_len {
+            lines.push(std::mem::take(&mut line));
+        }
+
+        line.push_str(&segment);
     }
+
+    lines.push(line);
+    lines.join("\n")
+}
+
+fn node_depths(graph: &Graph<Node, Edge>) -> HashMap<hir::ModuleDef, usize> {
+    graph
+        .node_indices()
+        .map(|node_idx| {
+            let mut depth = 0;
+            let mut owned_idx = node_idx;
+
+            while let Some(edge_ref) = graph
+                .edges_directed(owned_idx, Direction::Incoming)
+                .find(|edge_ref| edge_ref.weight().kind == Relationship::Owns)
+            {
+                depth += 1;
+                owned_idx = edge_ref.source();
+            }
+
+            (graph[node_idx].hir, depth)
+        })
+        .collect()
+}
+
+/// Returns qualifiers (e.g. `lib` and `bin`) for member crates sharing their name
+/// with other member crates (e.g. a package's library and binary, as merged via `--merge-targets`),
+/// keeping their equally named nodes from colliding.
+fn crate_qualifiers(
+    member_krates: &[hir::Crate],
+    db: &RootDatabase,
+) -> HashMap<hir::Crate, String> {
+    let mut krates_by_name: HashMap<String, Vec<hir::Crate>> = HashMap::new();
+
+    for krate in member_krates.iter().copied() {
+        krates_by_name
+            .entry(analyzer::crate_name(krate, db))
+            .or_default()
+            .push(krate);
+    }
+
+    let mut qualifiers = HashMap::new();
+
+    for krates in krates_by_name
+        .into_values()
+        .filter(|krates| krates.len() > 1)
+    {
+        let mut used = HashSet::new();
+
+        for krate in krates {
+            let kind = if analyzer::crate_is_bin(krate, db) {
+                "bin"
+            } else {
+                "lib"
+            };
+
+            // Fall back to numbering crates of the same kind (e.g. `bin2`):
+            let qualifier = (1..)
+                .map(|count| match count {
+                    1 => kind.to_owned(),
+                    count => format!("{kind}{count}"),
+                })
+                .find(|qualifier| !used.contains(qualifier))
+                .unwrap();
+
+            used.insert(qualifier.clone());
+            qualifiers.insert(krate, qualifier);
+        }
+    }
+
+    qualifiers
 }
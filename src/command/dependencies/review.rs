@@ -0,0 +1,278 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at https://mozilla.org/MPL/2.0/.
+
+//! Summaries of the modules changed by the current diff, as printed via `--format review-json`
+//! (e.g. for review bots to comment on pull requests with).
+//!
+//! The summary is limited to the changed modules, the edges between them and their direct neighbors
+//! (with the edges of items lifted to the modules containing them), as well as any rule violations
+//! (i.e. failed `--fail-on` conditions and exceeded budgets).
+//! Given a `--baseline` graph, it also lists the module-level edges added and removed since.
+
+use std::{
+    collections::{BTreeMap, BTreeSet, HashMap},
+    fs,
+    path::{Path, PathBuf},
+    process,
+};
+
+use anyhow::Context as _;
+use json::{object, JsonValue};
+use petgraph::graph::NodeIndex;
+use ra_ap_hir as hir;
+use ra_ap_ide::RootDatabase;
+
+use crate::{
+    analyzer,
+    command::{check::budget, serve::export::graph_to_json},
+    graph::{Edge, Graph, Node},
+    quickfix,
+};
+
+use super::{fail_on, options::Options};
+
+/// A module-level edge, as `(source, target, kind)`.
+type ModuleEdge = (String, String, String);
+
+pub(crate) fn render(
+    options: &Options,
+    graph: &Graph<Node, Edge>,
+    crate_node_idxs: &[NodeIndex],
+    krates: &[hir::Crate],
+    db: &RootDatabase,
+) -> anyhow::Result<String> {
+    let project_dir = quickfix::project_dir(&options.project);
+
+    let changed_files = changed_files(options, &project_dir)?;
+    let changed_modules = changed_modules(&changed_files, graph, krates, db);
+
+    let graph_json = graph_to_json(graph, crate_node_idxs, krates, db)?;
+    let edges = module_edges(&graph_json);
+
+    let is_relevant = |(source, target, _): &ModuleEdge| {
+        changed_modules.contains(source) || changed_modules.contains(target)
+    };
+
+    let relevant_edges: Vec<&ModuleEdge> = edges.iter().filter(|edge| is_relevant(edge)).collect();
+
+    let mut modules: BTreeMap<&str, bool> = changed_modules
+        .iter()
+        .map(|path| (path.as_str(), true))
+        .collect();
+
+    for (source, target, _) in &relevant_edges {
+        modules.entry(source).or_insert(false);
+        modules.entry(target).or_insert(false);
+    }
+
+    let mut review = object! {
+        base: options.diff_base.as_str(),
+        changed: changed_modules.iter().map(String::as_str).collect::<Vec<_>>(),
+        modules: modules
+            .into_iter()
+            .map(|(path, changed)| object! { path: path, changed: changed })
+            .collect::<Vec<_>>(),
+        edges: relevant_edges.into_iter().map(edge_json).collect::<Vec<_>>(),
+    };
+
+    if let Some(baseline_path) = &options.baseline {
+        let baseline = fs::read_to_string(baseline_path)
+            .with_context(|| format!("Could not read baseline {}", baseline_path.display()))?;
+        let baseline_json = json::parse(&baseline)
+            .with_context(|| format!("Could not parse baseline {}", baseline_path.display()))?;
+        let baseline_edges = module_edges(&baseline_json);
+
+        review["added_edges"] = edges
+            .difference(&baseline_edges)
+            .map(edge_json)
+            .collect::<Vec<_>>()
+            .into();
+        review["removed_edges"] = baseline_edges
+            .difference(&edges)
+            .map(edge_json)
+            .collect::<Vec<_>>()
+            .into();
+    }
+
+    review["violations"] = violations(options, graph, crate_node_idxs, krates, db)?.into();
+
+    Ok(format!("{}\n", review.pretty(2)))
+}
+
+fn edge_json((source, target, kind): &ModuleEdge) -> JsonValue {
+    object! {
+        source: source.as_str(),
+        target: target.as_str(),
+        kind: kind.as_str(),
+    }
+}
+
+/// Returns the (absolute) paths of the files changed by the current diff,
+/// as either passed via `--changed` or reported by git.
+fn changed_files(options: &Options, project_dir: &Path) -> anyhow::Result<Vec<PathBuf>> {
+    if !options.changed.is_empty() {
+        let current_dir = std::env::current_dir()?;
+
+        return Ok(options
+            .changed
+            .iter()
+            .map(|file_path| canonical(&current_dir.join(file_path)))
+            .collect());
+    }
+
+    let base = options.diff_base.as_str();
+
+    let tracked = git_lines(project_dir, &["diff", "--name-only", "--relative", base]);
+    let untracked = git_lines(project_dir, &["ls-files", "--others", "--exclude-standard"]);
+
+    let (Some(tracked), Some(untracked)) = (tracked, untracked) else {
+        anyhow::bail!("Could not query git for the files changed since `{base}`");
+    };
+
+    Ok(tracked
+        .into_iter()
+        .chain(untracked)
+        .map(|relative_path| canonical(&project_dir.join(relative_path)))
+        .collect())
+}
+
+/// Returns the paths of the crates' modules whose source got changed,
+/// with modules declared inline (i.e. `mod foo { … }`) being sourced from their parent's file.
+fn changed_modules(
+    changed_files: &[PathBuf],
+    graph: &Graph<Node, Edge>,
+    krates: &[hir::Crate],
+    db: &RootDatabase,
+) -> BTreeSet<String> {
+    graph
+        .node_weights()
+        .filter_map(|node| {
+            let hir::ModuleDef::Module(module) = node.hir else {
+                return None;
+            };
+
+            if !krates.contains(&module.krate()) {
+                return None;
+            }
+
+            let file_path = analyzer::module_file_path(module, db)
+                .or_else(|| analyzer::location(node.hir, db).map(|(file_path, _, _)| file_path))?;
+
+            changed_files
+                .contains(&canonical(&file_path))
+                .then(|| node.display_path(db))
+        })
+        .collect()
+}
+
+/// Returns the module-level edges of a graph in the JSON format of `--emit json`,
+/// with the "uses" and "re-exports" edges of items lifted to the modules containing them.
+fn module_edges(graph_json: &JsonValue) -> BTreeSet<ModuleEdge> {
+    let nodes = &graph_json["nodes"];
+    let edges = &graph_json["edges"];
+
+    let is_module = |idx: usize| matches!(nodes[idx]["kind"].as_str(), Some("mod" | "crate"));
+
+    let owners: HashMap<usize, usize> = edges
+        .members()
+        .filter(|edge| edge["kind"] == "owns")
+        .filter_map(|edge| Some((edge["target"].as_usize()?, edge["source"].as_usize()?)))
+        .collect();
+
+    // Items outside of the graph's module trees (e.g. those of external crates)
+    // belong to the module their path points into:
+    let module_path = |mut idx: usize| -> Option<String> {
+        while !is_module(idx) {
+            match owners.get(&idx) {
+                Some(owner_idx) => idx = *owner_idx,
+                None => {
+                    let path = nodes[idx]["path"].as_str()?;
+                    return path.rsplit_once("::").map(|(parent, _)| parent.to_owned());
+                }
+            }
+        }
+
+        nodes[idx]["path"].as_str().map(str::to_owned)
+    };
+
+    edges
+        .members()
+        .filter_map(|edge| {
+            let source_idx = edge["source"].as_usize()?;
+            let target_idx = edge["target"].as_usize()?;
+            let kind = edge["kind"].as_str()?;
+
+            // Modules owning items are implied by the lifted edges, so only sub-modules count:
+            if kind == "owns" && !(is_module(source_idx) && is_module(target_idx)) {
+                return None;
+            }
+
+            let source = module_path(source_idx)?;
+            let target = module_path(target_idx)?;
+
+            (source != target).then(|| (source, target, kind.to_owned()))
+        })
+        .collect()
+}
+
+/// Returns the failed `--fail-on` conditions and exceeded budgets, as JSON objects.
+fn violations(
+    options: &Options,
+    graph: &Graph<Node, Edge>,
+    crate_node_idxs: &[NodeIndex],
+    krates: &[hir::Crate],
+    db: &RootDatabase,
+) -> anyhow::Result<Vec<JsonValue>> {
+    let mut violations = vec![];
+
+    for (condition, message) in
+        fail_on::violations(&options.fail_on, graph, crate_node_idxs, krates, db)?
+    {
+        violations.push(object! {
+            rule: format!("fail-on {condition}"),
+            message: message,
+        });
+    }
+
+    let budgets = budget::load(&options.project.manifest_path)?;
+
+    for report in budget::check(&budgets, graph, krates, db)? {
+        for message in &report.violations {
+            violations.push(object! {
+                rule: format!("budget {}", report.budget.path),
+                message: message.as_str(),
+            });
+        }
+    }
+
+    Ok(violations)
+}
+
+fn canonical(path: &Path) -> PathBuf {
+    fs::canonicalize(path).unwrap_or_else(|_| path.to_owned())
+}
+
+/// Runs `git` with the given `args` in `dir`, returning the lines of its output on success.
+fn git_lines(dir: &Path, args: &[&str]) -> Option<Vec<String>> {
+    let output = process::Command::new("git")
+        .arg("-C")
+        .arg(dir)
+        .args(args)
+        .output()
+        .ok()?;
+
+    if !output.status.success() {
+        return None;
+    }
+
+    let stdout = String::from_utf8(output.stdout).ok()?;
+
+    Some(
+        stdout
+            .lines()
+            .filter(|line| !line.is_empty())
+            .map(str::to_owned)
+            .collect(),
+    )
+}
@@ -0,0 +1,111 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at https://mozilla.org/MPL/2.0/.
+
+//! Splitting of graphs into one graph per top-level module, as written via `--split-output`.
+
+use std::collections::HashSet;
+
+use petgraph::{
+    graph::NodeIndex,
+    visit::{EdgeRef as _, IntoNodeReferences as _},
+    Direction,
+};
+use ra_ap_hir as hir;
+
+use crate::graph::{Edge, Graph, Node, Relationship};
+
+/// Returns the top-level modules of the crate with root node `crate_node_idx`.
+pub(super) fn top_level_modules(
+    graph: &Graph<Node, Edge>,
+    crate_node_idx: NodeIndex,
+) -> Vec<NodeIndex> {
+    let mut module_idxs: Vec<NodeIndex> = owned_nodes(graph, crate_node_idx)
+        .filter(|node_idx| matches!(graph[*node_idx].hir, hir::ModuleDef::Module(_)))
+        .collect();
+
+    module_idxs.sort();
+
+    module_idxs
+}
+
+/// Returns the subgraph of the module with node `module_idx`, consisting of
+/// the module along with all of its sub-items, the items used by them directly,
+/// as well as the (transitive) owners of all these, up to their crates' root nodes.
+pub(super) fn module_subgraph(
+    graph: &Graph<Node, Edge>,
+    module_idx: NodeIndex,
+) -> Graph<Node, Edge> {
+    let mut subtree_idxs: HashSet<NodeIndex> = HashSet::default();
+    let mut stack = vec![module_idx];
+
+    while let Some(node_idx) = stack.pop() {
+        if subtree_idxs.insert(node_idx) {
+            stack.extend(owned_nodes(graph, node_idx));
+        }
+    }
+
+    let used_idxs = subtree_idxs.iter().flat_map(|node_idx| {
+        graph
+            .edges_directed(*node_idx, Direction::Outgoing)
+            .filter(|edge_ref| edge_ref.weight().kind != Relationship::Owns)
+            .map(|edge_ref| edge_ref.target())
+    });
+
+    let mut nodes_to_keep: HashSet<NodeIndex> = HashSet::default();
+    let mut stack: Vec<NodeIndex> = subtree_idxs.iter().cloned().chain(used_idxs).collect();
+
+    while let Some(node_idx) = stack.pop() {
+        if nodes_to_keep.insert(node_idx) {
+            stack.extend(owner_node(graph, node_idx));
+        }
+    }
+
+    let mut subgraph = graph.clone();
+
+    subgraph.retain_nodes(|_, node_idx| nodes_to_keep.contains(&node_idx));
+
+    // Only the module's own dependencies are of interest,
+    // not those between the items it merely uses:
+    subgraph.retain_edges(|subgraph, edge_idx| {
+        let edge = &subgraph[edge_idx];
+        let (source_idx, _) = subgraph.edge_endpoints(edge_idx).expect("edge endpoints");
+
+        edge.kind == Relationship::Owns || subtree_idxs.contains(&source_idx)
+    });
+
+    debug_assert!(subgraph.node_references().count() == nodes_to_keep.len());
+
+    subgraph
+}
+
+pub(crate) fn owned_nodes(
+    graph: &Graph<Node, Edge>,
+    node_idx: NodeIndex,
+) -> impl Iterator<Item = NodeIndex> + '_ {
+    graph
+        .edges_directed(node_idx, Direction::Outgoing)
+        .filter(|edge_ref| edge_ref.weight().kind == Relationship::Owns)
+        .map(|edge_ref| edge_ref.target())
+}
+
+pub(crate) fn owner_node(graph: &Graph<Node, Edge>, node_idx: NodeIndex) -> Option<NodeIndex> {
+    graph
+        .edges_directed(node_idx, Direction::Incoming)
+        .find(|edge_ref| edge_ref.weight().kind == Relationship::Owns)
+        .map(|edge_ref| edge_ref.source())
+}
+
+/// Returns the innermost module containing the node (i.e. the node itself, if it is a module).
+pub(crate) fn containing_module(
+    graph: &Graph<Node, Edge>,
+    node_idx: NodeIndex,
+) -> Option<NodeIndex> {
+    let mut node_idx = node_idx;
+
+    while !matches!(graph[node_idx].hir, hir::ModuleDef::Module(_)) {
+        node_idx = owner_node(graph, node_idx)?;
+    }
+
+    Some(node_idx)
+}
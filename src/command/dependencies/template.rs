@@ -0,0 +1,59 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at https://mozilla.org/MPL/2.0/.
+
+//! Rendering of the graph via user-provided tera templates.
+
+use std::{fs, path::Path};
+
+use anyhow::Context as _;
+use json::JsonValue;
+use petgraph::graph::NodeIndex;
+use ra_ap_hir as hir;
+use ra_ap_ide::RootDatabase;
+
+use crate::{
+    command::serve::export::graph_to_json,
+    graph::{Edge, Graph, Node},
+};
+
+/// Renders the template at `path`, with the graph (as exported by the `serve` command) as its context.
+pub(crate) fn render(
+    path: &Path,
+    graph: &Graph<Node, Edge>,
+    root_idxs: &[NodeIndex],
+    krates: &[hir::Crate],
+    db: &RootDatabase,
+) -> anyhow::Result<String> {
+    let source = fs::read_to_string(path)
+        .with_context(|| format!("Could not read template {}", path.display()))?;
+
+    let json = graph_to_json(graph, root_idxs, krates, db)?;
+
+    let context = tera::Context::from_value(tera_value(&json))?;
+
+    tera::Tera::one_off(&source, &context, false)
+        .with_context(|| format!("Could not render template {}", path.display()))
+}
+
+/// Converts the exported JSON into tera's representation of it.
+fn tera_value(json: &JsonValue) -> tera::Value {
+    match json {
+        JsonValue::Null => tera::Value::Null,
+        JsonValue::Short(string) => tera::Value::String(string.to_string()),
+        JsonValue::String(string) => tera::Value::String(string.clone()),
+        JsonValue::Number(_) => json
+            .as_i64()
+            .map(tera::Value::from)
+            .or_else(|| json.as_u64().map(tera::Value::from))
+            .unwrap_or_else(|| tera::Value::from(json.as_f64().unwrap_or(f64::NAN))),
+        JsonValue::Boolean(boolean) => tera::Value::Bool(*boolean),
+        JsonValue::Object(object) => tera::Value::Object(
+            object
+                .iter()
+                .map(|(key, value)| (key.to_owned(), tera_value(value)))
+                .collect(),
+        ),
+        JsonValue::Array(values) => tera::Value::Array(values.iter().map(tera_value).collect()),
+    }
+}
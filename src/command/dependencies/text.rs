@@ -0,0 +1,191 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at https://mozilla.org/MPL/2.0/.
+
+//! Printer for drawing a graph right in the terminal, as requested via `--format ascii` (or `unicode`).
+//!
+//! The graph's "owns" edges get drawn as a tree, with each node followed by the targets
+//! of its other outgoing edges (e.g. `mod api ──▶ my_crate::service::Service`),
+//! which is good enough for taking a quick look at small graphs without requiring Graphviz.
+
+use std::{
+    collections::{HashMap, HashSet},
+    fmt,
+};
+
+use petgraph::{
+    graph::NodeIndex,
+    visit::{EdgeRef as _, IntoEdgeReferences as _},
+    Direction,
+};
+use ra_ap_ide::RootDatabase;
+
+use crate::graph::{Edge, Graph, Node, Relationship};
+
+use super::options::GraphFormat;
+
+struct Glyphs {
+    branch: &'static str,
+    last_branch: &'static str,
+    pipe: &'static str,
+    space: &'static str,
+    uses: &'static str,
+    reexports: &'static str,
+}
+
+const ASCII_GLYPHS: Glyphs = Glyphs {
+    branch: "|-- ",
+    last_branch: "`-- ",
+    pipe: "|   ",
+    space: "    ",
+    uses: "-->",
+    reexports: "==>",
+};
+
+const UNICODE_GLYPHS: Glyphs = Glyphs {
+    branch: "├── ",
+    last_branch: "└── ",
+    pipe: "│   ",
+    space: "    ",
+    uses: "──▶",
+    reexports: "══▶",
+};
+
+pub(crate) struct TextPrinter<'a> {
+    glyphs: &'static Glyphs,
+    db: &'a RootDatabase,
+}
+
+impl<'a> TextPrinter<'a> {
+    pub(crate) fn new(format: GraphFormat, db: &'a RootDatabase) -> Self {
+        let glyphs = match format {
+            GraphFormat::Ascii => &ASCII_GLYPHS,
+            GraphFormat::Dot | GraphFormat::Unicode | GraphFormat::ReviewJson => &UNICODE_GLYPHS,
+        };
+
+        Self { glyphs, db }
+    }
+
+    pub(crate) fn fmt(
+        &self,
+        f: &mut dyn fmt::Write,
+        graph: &Graph<Node, Edge>,
+        start_node_idxs: &[NodeIndex],
+    ) -> fmt::Result {
+        let mut children: HashMap<NodeIndex, Vec<NodeIndex>> = HashMap::new();
+        let mut owned: HashSet<NodeIndex> = HashSet::new();
+
+        for edge_ref in graph.edge_references() {
+            if edge_ref.weight().kind == Relationship::Owns {
+                children
+                    .entry(edge_ref.source())
+                    .or_default()
+                    .push(edge_ref.target());
+                owned.insert(edge_ref.target());
+            }
+        }
+
+        // Unowned nodes that are merely the targets of other edges (e.g. external items)
+        // only show up as such, rather than as trees of their own:
+        let roots: Vec<_> = graph
+            .node_indices()
+            .filter(|node_idx| !owned.contains(node_idx))
+            .filter(|node_idx| {
+                start_node_idxs.contains(node_idx)
+                    || graph
+                        .edges_directed(*node_idx, Direction::Incoming)
+                        .next()
+                        .is_none()
+            })
+            .collect();
+
+        for node_idx in self.sorted_by_path(graph, roots) {
+            self.fmt_tree(f, graph, &children, node_idx, "", "")?;
+        }
+
+        Ok(())
+    }
+
+    fn fmt_tree(
+        &self,
+        f: &mut dyn fmt::Write,
+        graph: &Graph<Node, Edge>,
+        children: &HashMap<NodeIndex, Vec<NodeIndex>>,
+        node_idx: NodeIndex,
+        branch: &str,
+        indentation: &str,
+    ) -> fmt::Result {
+        let node = &graph[node_idx];
+
+        write!(
+            f,
+            "{indentation}{branch}{} {}",
+            node.kind_display_name(self.db),
+            node.display_name(self.db)
+        )?;
+
+        for (kind, glyph) in [
+            (Relationship::Uses, self.glyphs.uses),
+            (Relationship::ReExports, self.glyphs.reexports),
+        ] {
+            let target_idxs: Vec<_> = graph
+                .edges_directed(node_idx, Direction::Outgoing)
+                .filter(|edge_ref| edge_ref.weight().kind == kind)
+                .map(|edge_ref| edge_ref.target())
+                .collect();
+
+            if target_idxs.is_empty() {
+                continue;
+            }
+
+            let mut targets: Vec<_> = target_idxs
+                .into_iter()
+                .map(|target_idx| graph[target_idx].display_path(self.db))
+                .collect();
+
+            targets.sort();
+            targets.dedup();
+
+            write!(f, " {glyph} {}", targets.join(", "))?;
+        }
+
+        writeln!(f)?;
+
+        let Some(child_idxs) = children.get(&node_idx) else {
+            return Ok(());
+        };
+
+        // The parent's branch continues alongside its children, if followed by any siblings:
+        let indentation = match branch {
+            "" => indentation.to_owned(),
+            branch if branch == self.glyphs.last_branch => {
+                format!("{indentation}{}", self.glyphs.space)
+            }
+            _ => format!("{indentation}{}", self.glyphs.pipe),
+        };
+
+        let child_idxs = self.sorted_by_path(graph, child_idxs.clone());
+        let count = child_idxs.len();
+
+        for (pos, child_idx) in child_idxs.into_iter().enumerate() {
+            let branch = if pos + 1 == count {
+                self.glyphs.last_branch
+            } else {
+                self.glyphs.branch
+            };
+
+            self.fmt_tree(f, graph, children, child_idx, branch, &indentation)?;
+        }
+
+        Ok(())
+    }
+
+    fn sorted_by_path(
+        &self,
+        graph: &Graph<Node, Edge>,
+        mut node_idxs: Vec<NodeIndex>,
+    ) -> Vec<NodeIndex> {
+        node_idxs.sort_by_cached_key(|node_idx| graph[*node_idx].display_path(self.db));
+        node_idxs
+    }
+}
@@ -4,6 +4,11 @@
 
 use std::fmt;
 
+use crate::{
+    api::analyzers::Requirement,
+    colors::theme::{self, Palette, Rgb},
+};
+
 #[derive(Copy, Clone, Debug)]
 pub(crate) struct Color(pub u8, pub u8, pub u8);
 
@@ -14,36 +19,25 @@ impl fmt::Display for Color {
     }
 }
 
-#[derive(Clone, Debug)]
-pub(crate) struct ColorPalette {
-    #[allow(dead_code)]
-    pub purple: Color,
-    pub red: Color,
-    pub orange: Color,
-    pub yellow: Color,
-    pub green: Color,
-    pub cyan: Color,
-    pub blue: Color,
-    pub black: Color,
-    #[allow(dead_code)]
-    pub gray: Color,
-    #[allow(dead_code)]
-    pub white: Color,
+impl Color {
+    /// Returns the color at `t` (in `0.0..=1.0`) of the way from `self` to `other`.
+    fn lerp(self, other: Self, t: f64) -> Self {
+        let component = |from: u8, to: u8| {
+            (f64::from(from) + (f64::from(to) - f64::from(from)) * t).round() as u8
+        };
+
+        Self(
+            component(self.0, other.0),
+            component(self.1, other.1),
+            component(self.2, other.2),
+        )
+    }
 }
 
+pub(crate) type ColorPalette = Palette<Color>;
+
 pub(crate) fn color_palette() -> ColorPalette {
-    ColorPalette {
-        purple: Color(186, 111, 167),
-        red: Color(219, 83, 103),
-        orange: Color(254, 148, 84),
-        yellow: Color(248, 192, 76),
-        green: Color(129, 193, 105),
-        cyan: Color(105, 190, 210),
-        blue: Color(83, 151, 200),
-        black: Color(0, 0, 0),
-        gray: Color(127, 127, 127),
-        white: Color(255, 255, 255),
-    }
+    theme::current().graph.map(|Rgb(r, g, b)| Color(r, g, b))
 }
 
 #[derive(Clone, Debug)]
@@ -66,6 +60,66 @@ impl NodeStyle {
     }
 }
 
+/// Returns the style of a node at `heat` (in `0.0..=1.0`) on a green-yellow-red gradient.
+pub(crate) fn heat_node_style(heat: f64) -> NodeStyle {
+    let color_palette = color_palette();
+    let heat = heat.clamp(0.0, 1.0);
+
+    let fill_color = if heat < 0.5 {
+        color_palette.green.lerp(color_palette.yellow, heat * 2.0)
+    } else {
+        color_palette
+            .yellow
+            .lerp(color_palette.red, (heat - 0.5) * 2.0)
+    };
+
+    NodeStyle::new(fill_color)
+}
+
+/// Returns the style of a node by the standard library crate it depends on
+/// (i.e. green for `core`, yellow for `alloc`, red for `std`).
+pub(crate) fn requirement_node_style(requirement: Requirement) -> NodeStyle {
+    heat_node_style(match requirement {
+        Requirement::Core => 0.0,
+        Requirement::Alloc => 0.5,
+        Requirement::Std => 1.0,
+    })
+}
+
+/// Returns the style of a node at `depth` within the module hierarchy,
+/// cycling through the palette's colors (i.e. blue for crates, cyan for their top-level modules, …).
+pub(crate) fn depth_node_style(depth: usize) -> NodeStyle {
+    NodeStyle::new(cycled_color(depth))
+}
+
+/// Returns the border color of the `index`-th feature's cluster, as drawn via `--cluster-by feature`.
+pub(crate) fn feature_cluster_color(index: usize) -> Color {
+    cycled_color(index)
+}
+
+/// Returns the palette's `index`-th color, cycling through them (i.e. blue, cyan, green, …).
+fn cycled_color(index: usize) -> Color {
+    let color_palette = color_palette();
+
+    let colors = [
+        color_palette.blue,
+        color_palette.cyan,
+        color_palette.green,
+        color_palette.yellow,
+        color_palette.orange,
+        color_palette.red,
+        color_palette.purple,
+    ];
+
+    colors[index % colors.len()]
+}
+
+#[derive(Clone, Debug)]
+pub(crate) struct DependencyKindStyles {
+    pub dev: Stroke,
+    pub build: Stroke,
+}
+
 #[derive(Clone, Debug)]
 pub(crate) struct NodeStyles {
     #[allow(dead_code)]
@@ -73,6 +127,9 @@ pub(crate) struct NodeStyles {
     pub visibility: ItemVisibilityStyles,
     #[allow(dead_code)]
     pub test: NodeStyle,
+    pub deprecated: NodeStyle,
+    pub plain: NodeStyle,
+    pub dependency_kind: DependencyKindStyles,
 }
 
 pub(crate) fn node_styles() -> NodeStyles {
@@ -87,6 +144,12 @@ pub(crate) fn node_styles() -> NodeStyles {
             pub_super: NodeStyle::new(color_palette.orange),
         },
         test: NodeStyle::new(color_palette.cyan),
+        deprecated: NodeStyle::new(color_palette.purple),
+        plain: NodeStyle::new(color_palette.white),
+        dependency_kind: DependencyKindStyles {
+            dev: Stroke::Dashed,
+            build: Stroke::Dotted,
+        },
     }
 }
 
@@ -94,6 +157,7 @@ pub(crate) fn node_styles() -> NodeStyles {
 pub(crate) enum Stroke {
     Solid,
     Dashed,
+    Dotted,
 }
 
 impl fmt::Display for Stroke {
@@ -101,6 +165,7 @@ impl fmt::Display for Stroke {
         let name = match self {
             Self::Solid => "solid",
             Self::Dashed => "dashed",
+            Self::Dotted => "dotted",
         };
         write!(f, "{name}")
     }
@@ -122,6 +187,9 @@ impl EdgeStyle {
 pub(crate) struct EdgeStyles {
     pub owns: EdgeStyle,
     pub uses: EdgeStyle,
+    pub reexports: EdgeStyle,
+    /// The stroke of "uses" (and "re-exports") edges stemming from test-only code exclusively.
+    pub test_only: Stroke,
 }
 
 pub(crate) fn edge_styles() -> EdgeStyles {
@@ -129,5 +197,12 @@ pub(crate) fn edge_styles() -> EdgeStyles {
     EdgeStyles {
         owns: EdgeStyle::new(color_palette.black, Stroke::Solid),
         uses: EdgeStyle::new(color_palette.gray, Stroke::Dashed),
+        reexports: EdgeStyle::new(color_palette.blue, Stroke::Dashed),
+        test_only: Stroke::Dotted,
     }
 }
+
+/// Returns the color of the nodes (and edges) highlighted via `--focus-on` (and `--highlight-path`).
+pub(crate) fn highlight_color() -> Color {
+    color_palette().cyan
+}
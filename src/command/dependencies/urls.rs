@@ -0,0 +1,106 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at https://mozilla.org/MPL/2.0/.
+
+//! URLs of nodes' source locations, as attached to them via `--with-urls` or `--url-template`.
+
+use std::{
+    path::{Path, PathBuf},
+    process,
+};
+
+use log::debug;
+use ra_ap_ide::RootDatabase;
+
+use crate::{analyzer, graph::Node, quickfix};
+
+use super::options::Options;
+
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub(crate) enum NodeUrls {
+    /// Links to the local source files (e.g. `file:///path/to/src/lib.rs`).
+    Files,
+    /// Links to the source files via a template
+    /// (e.g. `https://github.com/org/repo/blob/{rev}/{path}#L{line}`).
+    Template {
+        template: String,
+        /// The directory that `{path}` is relative to (i.e. the repository's root).
+        root_dir: PathBuf,
+        /// The value of `{rev}` (i.e. the repository's current commit).
+        rev: String,
+    },
+}
+
+impl NodeUrls {
+    pub fn new(options: &Options) -> Option<Self> {
+        if let Some(template) = &options.url_template {
+            let project_dir = quickfix::project_dir(&options.project);
+
+            let root_dir = git(&project_dir, &["rev-parse", "--show-toplevel"])
+                .map(PathBuf::from)
+                .unwrap_or_else(|| project_dir.clone());
+            let rev =
+                git(&project_dir, &["rev-parse", "HEAD"]).unwrap_or_else(|| "HEAD".to_owned());
+
+            debug!("Linking nodes relative to {} at {rev}", root_dir.display());
+
+            return Some(Self::Template {
+                template: template.clone(),
+                root_dir,
+                rev,
+            });
+        }
+
+        if options.with_urls {
+            return Some(Self::Files);
+        }
+
+        None
+    }
+
+    /// Returns the URL of the node's source location, if it has any.
+    pub fn url(&self, node: &Node, db: &RootDatabase) -> Option<String> {
+        let (file_path, line, _column) = analyzer::location(node.hir, db)?;
+
+        match self {
+            Self::Files => Some(format!("file://{}", file_path.display())),
+            Self::Template {
+                template,
+                root_dir,
+                rev,
+            } => {
+                let path = quickfix::relative_path(&file_path, root_dir);
+                let path = path
+                    .components()
+                    .map(|component| component.as_os_str().to_string_lossy())
+                    .collect::<Vec<_>>()
+                    .join("/");
+
+                Some(
+                    template
+                        .replace("{rev}", rev)
+                        .replace("{path}", &path)
+                        .replace("{line}", &line.to_string()),
+                )
+            }
+        }
+    }
+}
+
+/// Runs `git` with the given `args` in `dir`, returning its trimmed output on success.
+fn git(dir: &Path, args: &[&str]) -> Option<String> {
+    let output = process::Command::new("git")
+        .arg("-C")
+        .arg(dir)
+        .args(args)
+        .output()
+        .ok()?;
+
+    if !output.status.success() {
+        return None;
+    }
+
+    let stdout = String::from_utf8(output.stdout).ok()?;
+
+    Some(stdout.trim().to_owned())
+}
@@ -0,0 +1,88 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at https://mozilla.org/MPL/2.0/.
+
+use std::{fs, path::Path};
+
+use anyhow::Context as _;
+use clap::Parser;
+use log::trace;
+use petgraph::graph::NodeIndex;
+use ra_ap_hir as hir;
+use ra_ap_ide::RootDatabase;
+
+use crate::{
+    analyzer::LoadOptions,
+    graph::{Edge, Graph, GraphBuilder, Node},
+    output,
+    progress::{self, Stage},
+};
+
+use super::{document::Document, options::Options, wiki};
+
+#[derive(Parser, Clone, PartialEq, Eq, Debug)]
+pub struct Command {
+    #[command(flatten)]
+    pub options: Options,
+}
+
+impl Command {
+    pub fn new(options: Options) -> Self {
+        Self { options }
+    }
+
+    pub(crate) fn sanitize(&mut self) {}
+
+    #[doc(hidden)]
+    pub fn run(self, krates: &[hir::Crate], db: &RootDatabase) -> anyhow::Result<()> {
+        trace!("Building graph ...");
+        progress::stage(Stage::Building);
+
+        let (graph, crate_node_idxs) = GraphBuilder::new(db, krates).build()?;
+
+        if let Some(dir_path) = &self.options.wiki {
+            return Self::write_wiki(dir_path, &graph, &crate_node_idxs, db);
+        }
+
+        let document = Document::new(&graph, &crate_node_idxs, db);
+
+        trace!("Printing document ...");
+        progress::stage(Stage::Printing);
+
+        let output = document.to_string();
+
+        match &self.options.output {
+            Some(output_path) => output::write_atomically(output_path, output),
+            None => {
+                print!("{output}");
+                Ok(())
+            }
+        }
+    }
+
+    fn write_wiki(
+        dir_path: &Path,
+        graph: &Graph<Node, Edge>,
+        crate_node_idxs: &[NodeIndex],
+        db: &RootDatabase,
+    ) -> anyhow::Result<()> {
+        fs::create_dir_all(dir_path)
+            .with_context(|| format!("Could not create directory {}", dir_path.display()))?;
+
+        trace!("Printing notes ...");
+        progress::stage(Stage::Printing);
+
+        for note in wiki::notes(graph, crate_node_idxs, db) {
+            output::write_atomically(&dir_path.join(note.file_name()), note.to_string())?;
+        }
+
+        Ok(())
+    }
+
+    pub fn load_options(&self) -> LoadOptions {
+        LoadOptions {
+            cfg_test: self.options.cfg_test,
+            sysroot: false,
+        }
+    }
+}
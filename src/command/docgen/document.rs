@@ -0,0 +1,392 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at https://mozilla.org/MPL/2.0/.
+
+//! Markdown architecture documents, as generated by `docgen`.
+//!
+//! Each crate gets a module tree, followed by one section per top-level module ("subsystem"),
+//! with its summary, sizes and a Mermaid diagram of its modules and their dependencies.
+
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    fmt,
+};
+
+use petgraph::{graph::NodeIndex, visit::EdgeRef as _, Direction};
+use ra_ap_hir as hir;
+use ra_ap_ide::RootDatabase;
+
+use crate::{
+    analyzer,
+    command::dependencies::split::{containing_module, owned_nodes, owner_node},
+    graph::{Edge, Graph, Node, Relationship},
+    item::ItemVisibility,
+};
+
+/// A line of a crate's module tree.
+#[derive(Clone, PartialEq, Eq, Debug)]
+struct TreeEntry {
+    depth: usize,
+    name: String,
+    summary: Option<String>,
+}
+
+/// A Mermaid flowchart of a subsystem's modules, along with the modules they use.
+#[derive(Clone, Default, PartialEq, Eq, Debug)]
+struct Diagram {
+    /// The labels of the diagram's nodes (i.e. module paths relative to their crate).
+    labels: Vec<String>,
+    /// The "owns" edges between the diagram's nodes, by the nodes' indices within `labels`.
+    owns: BTreeSet<(usize, usize)>,
+    /// The (module-level) "uses" edges between the diagram's nodes.
+    uses: BTreeSet<(usize, usize)>,
+}
+
+/// The section of a top-level module.
+#[derive(Clone, PartialEq, Eq, Debug)]
+struct Subsystem {
+    path: String,
+    summary: Option<String>,
+    /// The number of items (other than modules) within the module and its sub-modules.
+    items: usize,
+    /// The number of public ones among `items`.
+    public_items: usize,
+    /// The number of (transitive) sub-modules.
+    submodules: usize,
+    /// The (non-blank) lines of code of `items`, excluding any items nested within them.
+    lines_of_code: usize,
+    diagram: Diagram,
+}
+
+#[derive(Clone, PartialEq, Eq, Debug)]
+struct CrateSection {
+    name: String,
+    tree: Vec<TreeEntry>,
+    subsystems: Vec<Subsystem>,
+}
+
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub(crate) struct Document {
+    crates: Vec<CrateSection>,
+}
+
+impl Document {
+    pub(crate) fn new(
+        graph: &Graph<Node, Edge>,
+        crate_node_idxs: &[NodeIndex],
+        db: &RootDatabase,
+    ) -> Self {
+        let crates = crate_node_idxs
+            .iter()
+            .map(|crate_node_idx| CrateSection::new(graph, *crate_node_idx, db))
+            .collect();
+
+        Self { crates }
+    }
+}
+
+impl CrateSection {
+    fn new(graph: &Graph<Node, Edge>, crate_node_idx: NodeIndex, db: &RootDatabase) -> Self {
+        let mut tree = vec![];
+        let mut stack = vec![(crate_node_idx, 0)];
+
+        while let Some((module_idx, depth)) = stack.pop() {
+            let node = &graph[module_idx];
+
+            tree.push(TreeEntry {
+                depth,
+                name: node.display_name(db),
+                summary: node.doc_summary(db),
+            });
+
+            // Push in reverse, for the sub-modules to get popped in order of their names:
+            let mut submodule_idxs = sorted_by_path(graph, submodules(graph, module_idx), db);
+            submodule_idxs.reverse();
+
+            stack.extend(
+                submodule_idxs
+                    .into_iter()
+                    .map(|submodule_idx| (submodule_idx, depth + 1)),
+            );
+        }
+
+        let subsystems = sorted_by_path(graph, submodules(graph, crate_node_idx), db)
+            .into_iter()
+            .map(|module_idx| Subsystem::new(graph, crate_node_idx, module_idx, db))
+            .collect();
+
+        Self {
+            name: graph[crate_node_idx].display_name(db),
+            tree,
+            subsystems,
+        }
+    }
+}
+
+impl Subsystem {
+    fn new(
+        graph: &Graph<Node, Edge>,
+        crate_node_idx: NodeIndex,
+        module_idx: NodeIndex,
+        db: &RootDatabase,
+    ) -> Self {
+        let mut module_idxs = vec![];
+        let mut item_idxs = vec![];
+        let mut lines_of_code = 0;
+
+        let mut stack = vec![module_idx];
+
+        while let Some(node_idx) = stack.pop() {
+            let node = &graph[node_idx];
+
+            if matches!(node.hir, hir::ModuleDef::Module(_)) {
+                module_idxs.push(node_idx);
+            } else {
+                item_idxs.push(node_idx);
+
+                // Nested items (e.g. methods) are part of their owners' lines:
+                let is_nested = owner_node(graph, node_idx).is_some_and(|owner_idx| {
+                    !matches!(graph[owner_idx].hir, hir::ModuleDef::Module(_))
+                });
+
+                if !is_nested {
+                    lines_of_code += analyzer::lines_of_code(node.hir, db).unwrap_or(0);
+                }
+            }
+
+            stack.extend(owned_nodes(graph, node_idx));
+        }
+
+        let public_items = item_idxs
+            .iter()
+            .filter(|node_idx| graph[**node_idx].visibility(db) == ItemVisibility::Public)
+            .count();
+
+        let node = &graph[module_idx];
+
+        Self {
+            path: node.display_path(db),
+            summary: node.doc_summary(db),
+            items: item_idxs.len(),
+            public_items,
+            submodules: module_idxs.len() - 1,
+            lines_of_code,
+            diagram: Diagram::new(graph, crate_node_idx, &module_idxs, &item_idxs, db),
+        }
+    }
+}
+
+impl Diagram {
+    fn new(
+        graph: &Graph<Node, Edge>,
+        crate_node_idx: NodeIndex,
+        module_idxs: &[NodeIndex],
+        item_idxs: &[NodeIndex],
+        db: &RootDatabase,
+    ) -> Self {
+        let crate_path = graph[crate_node_idx].display_path(db);
+
+        let label = |node_idx: NodeIndex| {
+            let path = graph[node_idx].display_path(db);
+
+            path.strip_prefix(&format!("{crate_path}::"))
+                .map(str::to_owned)
+                .unwrap_or(path)
+        };
+
+        let mut diagram = Self::default();
+        let mut indices: BTreeMap<NodeIndex, usize> = BTreeMap::new();
+
+        let mut index = |diagram: &mut Self, node_idx: NodeIndex| {
+            *indices.entry(node_idx).or_insert_with(|| {
+                diagram.labels.push(label(node_idx));
+                diagram.labels.len() - 1
+            })
+        };
+
+        for module_idx in sorted_by_path(graph, module_idxs.to_vec(), db) {
+            index(&mut diagram, module_idx);
+        }
+
+        for module_idx in module_idxs {
+            for submodule_idx in submodules(graph, *module_idx) {
+                let edge = (
+                    index(&mut diagram, *module_idx),
+                    index(&mut diagram, submodule_idx),
+                );
+                diagram.owns.insert(edge);
+            }
+        }
+
+        // Lift the "uses" edges of the subsystem's items to their modules,
+        // and those leading out of the subsystem to the top-level modules they lead into:
+        let mut uses: BTreeSet<(NodeIndex, NodeIndex)> = BTreeSet::new();
+
+        for source_idx in module_idxs.iter().chain(item_idxs) {
+            let Some(source_module_idx) = containing_module(graph, *source_idx) else {
+                continue;
+            };
+
+            for edge_ref in graph.edges_directed(*source_idx, Direction::Outgoing) {
+                if edge_ref.weight().kind == Relationship::Owns {
+                    continue;
+                }
+
+                let Some(target_module_idx) = containing_module(graph, edge_ref.target()) else {
+                    continue;
+                };
+
+                let target_module_idx = if module_idxs.contains(&target_module_idx) {
+                    target_module_idx
+                } else {
+                    match top_level_module(graph, crate_node_idx, target_module_idx) {
+                        Some(top_level_idx) => top_level_idx,
+                        None => continue,
+                    }
+                };
+
+                if source_module_idx != target_module_idx {
+                    uses.insert((source_module_idx, target_module_idx));
+                }
+            }
+        }
+
+        let used_idxs: Vec<NodeIndex> = uses
+            .iter()
+            .map(|(_, target_idx)| *target_idx)
+            .filter(|target_idx| !module_idxs.contains(target_idx))
+            .collect();
+
+        for used_idx in sorted_by_path(graph, used_idxs, db) {
+            index(&mut diagram, used_idx);
+        }
+
+        for (source_idx, target_idx) in uses {
+            let edge = (
+                index(&mut diagram, source_idx),
+                index(&mut diagram, target_idx),
+            );
+            diagram.uses.insert(edge);
+        }
+
+        diagram
+    }
+}
+
+impl fmt::Display for Document {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "# Architecture")?;
+        writeln!(f)?;
+        writeln!(
+            f,
+            "<!-- Generated by `cargo modules docgen`. Do not edit manually. -->"
+        )?;
+
+        for krate in &self.crates {
+            writeln!(f)?;
+            write!(f, "{krate}")?;
+        }
+
+        Ok(())
+    }
+}
+
+impl fmt::Display for CrateSection {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "## `{}`", self.name)?;
+        writeln!(f)?;
+        writeln!(f, "### Module tree")?;
+        writeln!(f)?;
+
+        for entry in &self.tree {
+            let indentation = "  ".repeat(entry.depth);
+
+            match &entry.summary {
+                Some(summary) => writeln!(f, "{indentation}- `{}`: {summary}", entry.name)?,
+                None => writeln!(f, "{indentation}- `{}`", entry.name)?,
+            }
+        }
+
+        for subsystem in &self.subsystems {
+            writeln!(f)?;
+            write!(f, "{subsystem}")?;
+        }
+
+        Ok(())
+    }
+}
+
+impl fmt::Display for Subsystem {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "### `{}`", self.path)?;
+        writeln!(f)?;
+
+        if let Some(summary) = &self.summary {
+            writeln!(f, "{summary}")?;
+            writeln!(f)?;
+        }
+
+        writeln!(f, "- Items: {} ({} public)", self.items, self.public_items)?;
+        writeln!(f, "- Sub-modules: {}", self.submodules)?;
+        writeln!(f, "- Lines of code: {}", self.lines_of_code)?;
+        writeln!(f)?;
+        write!(f, "{}", self.diagram)
+    }
+}
+
+impl fmt::Display for Diagram {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "```mermaid")?;
+        writeln!(f, "flowchart LR")?;
+
+        for (index, label) in self.labels.iter().enumerate() {
+            writeln!(f, "    m{index}[\"{label}\"]")?;
+        }
+
+        for (source, target) in &self.owns {
+            writeln!(f, "    m{source} --> m{target}")?;
+        }
+
+        for (source, target) in &self.uses {
+            writeln!(f, "    m{source} -.-> m{target}")?;
+        }
+
+        writeln!(f, "```")
+    }
+}
+
+/// Returns the module's direct sub-modules.
+fn submodules(graph: &Graph<Node, Edge>, module_idx: NodeIndex) -> Vec<NodeIndex> {
+    owned_nodes(graph, module_idx)
+        .filter(|node_idx| matches!(graph[*node_idx].hir, hir::ModuleDef::Module(_)))
+        .collect()
+}
+
+/// Returns the top-level module (i.e. the sub-module of the crate's root module)
+/// containing the module, if it belongs to the crate with root node `crate_node_idx`.
+fn top_level_module(
+    graph: &Graph<Node, Edge>,
+    crate_node_idx: NodeIndex,
+    module_idx: NodeIndex,
+) -> Option<NodeIndex> {
+    let mut module_idx = module_idx;
+
+    loop {
+        let owner_idx = owner_node(graph, module_idx)?;
+
+        if owner_idx == crate_node_idx {
+            return Some(module_idx);
+        }
+
+        module_idx = owner_idx;
+    }
+}
+
+fn sorted_by_path(
+    graph: &Graph<Node, Edge>,
+    mut node_idxs: Vec<NodeIndex>,
+    db: &RootDatabase,
+) -> Vec<NodeIndex> {
+    node_idxs.sort_by_cached_key(|node_idx| graph[*node_idx].display_path(db));
+    node_idxs
+}
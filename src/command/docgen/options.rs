@@ -0,0 +1,32 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at https://mozilla.org/MPL/2.0/.
+
+use std::path::PathBuf;
+
+use clap::Parser;
+
+use crate::options::{GeneralOptions, ProjectOptions};
+
+#[derive(Parser, Clone, PartialEq, Eq, Debug)]
+#[group(id = "DocgenOptions")]
+pub struct Options {
+    #[command(flatten)]
+    pub general: GeneralOptions,
+
+    #[command(flatten)]
+    pub project: ProjectOptions,
+
+    /// Analyze with `#[cfg(test)]` enabled (i.e as if built via `cargo test`).
+    #[arg(long = "cfg-test")]
+    pub cfg_test: bool,
+
+    /// Write the output to this file (atomically replacing it) instead of printing it.
+    #[arg(short = 'o', long = "output")]
+    pub output: Option<PathBuf>,
+
+    /// Write one Markdown note per module into this directory (linking their parents, sub-modules,
+    /// dependencies and dependents via `[[wiki-links]]`, e.g. for Obsidian), instead of a single document.
+    #[arg(long = "wiki", value_name = "DIR", conflicts_with = "output")]
+    pub wiki: Option<PathBuf>,
+}
@@ -0,0 +1,173 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at https://mozilla.org/MPL/2.0/.
+
+//! Markdown notes (one per module) linking each other via wiki-links, as written via `docgen --wiki`.
+//!
+//! Notes are named after their module's path (e.g. `my_crate.net.http.md` for `my_crate::net::http`)
+//! and link to their parent, sub-modules, dependencies and dependents via `[[my_crate.net|my_crate::net]]`,
+//! which tools such as Obsidian turn into a browsable graph (including backlinks).
+
+use std::{
+    collections::{BTreeSet, HashMap, HashSet},
+    fmt,
+};
+
+use petgraph::{
+    graph::NodeIndex,
+    visit::{EdgeRef as _, IntoEdgeReferences as _},
+};
+use ra_ap_hir as hir;
+use ra_ap_ide::RootDatabase;
+
+use crate::{
+    command::dependencies::split::{containing_module, owned_nodes, owner_node},
+    graph::{Edge, Graph, Node, Relationship},
+};
+
+/// The note of a single module.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub(crate) struct Note {
+    path: String,
+    summary: Option<String>,
+    parent: Option<String>,
+    submodules: BTreeSet<String>,
+    dependencies: BTreeSet<String>,
+    dependents: BTreeSet<String>,
+}
+
+impl Note {
+    /// Returns the note's file name (e.g. `my_crate.net.md`).
+    pub(crate) fn file_name(&self) -> String {
+        format!("{}.md", note_name(&self.path))
+    }
+}
+
+/// Returns the notes of the crates' modules, sorted by path.
+pub(crate) fn notes(
+    graph: &Graph<Node, Edge>,
+    crate_node_idxs: &[NodeIndex],
+    db: &RootDatabase,
+) -> Vec<Note> {
+    let mut module_idxs: HashSet<NodeIndex> = HashSet::default();
+    let mut stack: Vec<NodeIndex> = crate_node_idxs.to_vec();
+
+    while let Some(node_idx) = stack.pop() {
+        if matches!(graph[node_idx].hir, hir::ModuleDef::Module(_)) {
+            module_idxs.insert(node_idx);
+        }
+
+        stack.extend(owned_nodes(graph, node_idx));
+    }
+
+    // Lift the "uses" (and "re-exports") edges of the modules' items to the modules themselves:
+    let mut dependencies: HashMap<NodeIndex, HashSet<NodeIndex>> = HashMap::new();
+    let mut dependents: HashMap<NodeIndex, HashSet<NodeIndex>> = HashMap::new();
+
+    for edge_ref in graph.edge_references() {
+        if edge_ref.weight().kind == Relationship::Owns {
+            continue;
+        }
+
+        let (Some(source_idx), Some(target_idx)) = (
+            containing_module(graph, edge_ref.source()),
+            containing_module(graph, edge_ref.target()),
+        ) else {
+            continue;
+        };
+
+        if source_idx == target_idx
+            || !module_idxs.contains(&source_idx)
+            || !module_idxs.contains(&target_idx)
+        {
+            continue;
+        }
+
+        dependencies
+            .entry(source_idx)
+            .or_default()
+            .insert(target_idx);
+        dependents.entry(target_idx).or_default().insert(source_idx);
+    }
+
+    let paths = |node_idxs: Option<&HashSet<NodeIndex>>| -> BTreeSet<String> {
+        node_idxs
+            .into_iter()
+            .flatten()
+            .map(|node_idx| graph[*node_idx].display_path(db))
+            .collect()
+    };
+
+    let mut notes: Vec<Note> = module_idxs
+        .iter()
+        .map(|module_idx| {
+            let node = &graph[*module_idx];
+
+            let submodules = owned_nodes(graph, *module_idx)
+                .filter(|node_idx| module_idxs.contains(node_idx))
+                .map(|node_idx| graph[node_idx].display_path(db))
+                .collect();
+
+            Note {
+                path: node.display_path(db),
+                summary: node.doc_summary(db),
+                parent: owner_node(graph, *module_idx)
+                    .map(|owner_idx| graph[owner_idx].display_path(db)),
+                submodules,
+                dependencies: paths(dependencies.get(module_idx)),
+                dependents: paths(dependents.get(module_idx)),
+            }
+        })
+        .collect();
+
+    notes.sort_by(|lhs, rhs| lhs.path.cmp(&rhs.path));
+
+    notes
+}
+
+impl fmt::Display for Note {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "# `{}`", self.path)?;
+
+        if let Some(summary) = &self.summary {
+            writeln!(f)?;
+            writeln!(f, "{summary}")?;
+        }
+
+        if let Some(parent) = &self.parent {
+            writeln!(f)?;
+            writeln!(f, "Parent: {}", wiki_link(parent))?;
+        }
+
+        let sections = [
+            ("Sub-modules", &self.submodules),
+            ("Dependencies", &self.dependencies),
+            ("Dependents", &self.dependents),
+        ];
+
+        for (heading, paths) in sections {
+            if paths.is_empty() {
+                continue;
+            }
+
+            writeln!(f)?;
+            writeln!(f, "## {heading}")?;
+            writeln!(f)?;
+
+            for path in paths {
+                writeln!(f, "- {}", wiki_link(path))?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Returns the name of the note of the module at `path` (e.g. `my_crate.net` for `my_crate::net`).
+fn note_name(path: &str) -> String {
+    path.replace("::", ".")
+}
+
+fn wiki_link(path: &str) -> String {
+    format!("[[{}|{path}]]", note_name(path))
+}
@@ -0,0 +1,64 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at https://mozilla.org/MPL/2.0/.
+
+use clap::Parser;
+use log::trace;
+use ra_ap_hir as hir;
+use ra_ap_ide::RootDatabase;
+
+use crate::{
+    analyzer::LoadOptions,
+    graph::GraphBuilder,
+    progress::{self, Stage},
+};
+
+use super::{
+    coverage::{Failure, Report},
+    options::Options,
+};
+
+#[derive(Parser, Clone, PartialEq, Eq, Debug)]
+pub struct Command {
+    #[command(flatten)]
+    pub options: Options,
+}
+
+impl Command {
+    pub fn new(options: Options) -> Self {
+        Self { options }
+    }
+
+    pub(crate) fn sanitize(&mut self) {}
+
+    #[doc(hidden)]
+    pub fn run(self, krates: &[hir::Crate], db: &RootDatabase) -> anyhow::Result<()> {
+        trace!("Building graph ...");
+        progress::stage(Stage::Building);
+
+        let (graph, crate_node_idxs) = GraphBuilder::new(db, krates).build()?;
+
+        let report = Report::new(&graph, &crate_node_idxs, db);
+
+        trace!("Printing coverage ...");
+        progress::stage(Stage::Printing);
+
+        print!("{report}");
+
+        if !self.options.deny_undocumented_public {
+            return Ok(());
+        }
+
+        match Failure::from_report(&report) {
+            Some(failure) => Err(failure.into()),
+            None => Ok(()),
+        }
+    }
+
+    pub fn load_options(&self) -> LoadOptions {
+        LoadOptions {
+            cfg_test: self.options.cfg_test,
+            sysroot: false,
+        }
+    }
+}
@@ -0,0 +1,214 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at https://mozilla.org/MPL/2.0/.
+
+//! Documentation coverage of a crate's modules, as printed by `docs`.
+//!
+//! Each module's coverage comprises the module itself, along with its public items
+//! (other than its sub-modules, which have coverages of their own).
+
+use std::fmt;
+
+use petgraph::{graph::NodeIndex, visit::EdgeRef as _, Direction};
+use ra_ap_hir as hir;
+use ra_ap_ide::RootDatabase;
+
+use crate::{
+    graph::{Edge, Graph, Node, Relationship},
+    item::ItemVisibility,
+};
+
+/// A module or public item, as covered by the report.
+#[derive(Clone, PartialEq, Eq, Debug)]
+struct Entry {
+    /// The item's kind (e.g. `mod` or `struct`).
+    kind: String,
+    /// The item's full path (e.g. `my_crate::net::Socket`).
+    path: String,
+    is_public: bool,
+    is_documented: bool,
+}
+
+impl Entry {
+    fn new(node: &Node, db: &RootDatabase) -> Self {
+        Self {
+            kind: node.kind_display_name(db).to_string(),
+            path: node.display_path(db),
+            is_public: node.visibility(db) == ItemVisibility::Public,
+            is_documented: node.doc_summary(db).is_some(),
+        }
+    }
+}
+
+/// The documentation coverage of a single module.
+#[derive(Clone, PartialEq, Eq, Debug)]
+struct ModuleCoverage {
+    path: String,
+    /// The module itself, followed by its public items.
+    entries: Vec<Entry>,
+}
+
+impl ModuleCoverage {
+    fn documented(&self) -> usize {
+        self.entries
+            .iter()
+            .filter(|entry| entry.is_documented)
+            .count()
+    }
+}
+
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub(crate) struct Report {
+    /// The coverages of the crates' modules, sorted by path.
+    modules: Vec<ModuleCoverage>,
+}
+
+impl Report {
+    pub(crate) fn new(
+        graph: &Graph<Node, Edge>,
+        crate_node_idxs: &[NodeIndex],
+        db: &RootDatabase,
+    ) -> Self {
+        let mut modules = vec![];
+
+        for crate_node_idx in crate_node_idxs {
+            let mut module_idxs = vec![*crate_node_idx];
+
+            while let Some(module_idx) = module_idxs.pop() {
+                let mut entries = vec![Entry::new(&graph[module_idx], db)];
+
+                // Walk the module's items (and their own items, e.g. the methods of impls),
+                // stopping at sub-modules, which get covered on their own:
+                let mut item_idxs = owned_nodes(graph, module_idx);
+
+                while let Some(item_idx) = item_idxs.pop() {
+                    let node = &graph[item_idx];
+
+                    if matches!(node.hir, hir::ModuleDef::Module(_)) {
+                        module_idxs.push(item_idx);
+                        continue;
+                    }
+
+                    let entry = Entry::new(node, db);
+
+                    if entry.is_public {
+                        entries.push(entry);
+                    }
+
+                    item_idxs.extend(owned_nodes(graph, item_idx));
+                }
+
+                entries[1..].sort_by(|lhs, rhs| lhs.path.cmp(&rhs.path));
+
+                modules.push(ModuleCoverage {
+                    path: entries[0].path.clone(),
+                    entries,
+                });
+            }
+        }
+
+        modules.sort_by(|lhs, rhs| lhs.path.cmp(&rhs.path));
+
+        Self { modules }
+    }
+
+    fn entries(&self) -> impl Iterator<Item = &Entry> {
+        self.modules.iter().flat_map(|module| &module.entries)
+    }
+}
+
+impl fmt::Display for Report {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "documented  total  coverage  module")?;
+
+        for module in &self.modules {
+            let documented = module.documented();
+            let total = module.entries.len();
+
+            writeln!(
+                f,
+                "{documented:>10}  {total:>5}  {:>7.1}%  {}",
+                percentage(documented, total),
+                module.path
+            )?;
+        }
+
+        let documented = self.entries().filter(|entry| entry.is_documented).count();
+        let total = self.entries().count();
+
+        writeln!(f)?;
+        writeln!(
+            f,
+            "Total: {documented}/{total} documented ({:.1}%)",
+            percentage(documented, total)
+        )?;
+
+        let undocumented: Vec<&Entry> = self
+            .entries()
+            .filter(|entry| !entry.is_documented)
+            .collect();
+
+        if !undocumented.is_empty() {
+            writeln!(f)?;
+            writeln!(f, "Undocumented:")?;
+
+            for entry in undocumented {
+                writeln!(f, "  {} {}", entry.kind, entry.path)?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// The error of runs finding public items lacking doc comments (via `--deny-undocumented-public`).
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct Failure {
+    paths: Vec<String>,
+}
+
+impl fmt::Display for Failure {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let count = self.paths.len();
+        let noun = if count == 1 { "item" } else { "items" };
+
+        write!(f, "Found {count} undocumented public {noun}:")?;
+
+        for path in &self.paths {
+            write!(f, "\n  {path}")?;
+        }
+
+        Ok(())
+    }
+}
+
+impl std::error::Error for Failure {}
+
+impl Failure {
+    /// Returns a failure listing the report's undocumented public items, if any.
+    pub(crate) fn from_report(report: &Report) -> Option<Self> {
+        let paths: Vec<String> = report
+            .entries()
+            .filter(|entry| entry.is_public && !entry.is_documented)
+            .map(|entry| entry.path.clone())
+            .collect();
+
+        (!paths.is_empty()).then_some(Self { paths })
+    }
+}
+
+fn owned_nodes(graph: &Graph<Node, Edge>, node_idx: NodeIndex) -> Vec<NodeIndex> {
+    graph
+        .edges_directed(node_idx, Direction::Outgoing)
+        .filter(|edge_ref| edge_ref.weight().kind == Relationship::Owns)
+        .map(|edge_ref| edge_ref.target())
+        .collect()
+}
+
+fn percentage(part: usize, total: usize) -> f64 {
+    if total == 0 {
+        100.0
+    } else {
+        100.0 * part as f64 / total as f64
+    }
+}
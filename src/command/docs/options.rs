@@ -0,0 +1,26 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at https://mozilla.org/MPL/2.0/.
+
+use clap::Parser;
+
+use crate::options::{GeneralOptions, ProjectOptions};
+
+#[derive(Parser, Clone, PartialEq, Eq, Debug)]
+#[group(id = "DocsOptions")]
+pub struct Options {
+    #[command(flatten)]
+    pub general: GeneralOptions,
+
+    #[command(flatten)]
+    pub project: ProjectOptions,
+
+    /// Fail (with exit code 2) if any public items (or public modules) lack doc comments,
+    /// after printing the report.
+    #[arg(long = "deny-undocumented-public")]
+    pub deny_undocumented_public: bool,
+
+    /// Analyze with `#[cfg(test)]` enabled (i.e as if built via `cargo test`).
+    #[arg(long = "cfg-test")]
+    pub cfg_test: bool,
+}
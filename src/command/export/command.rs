@@ -0,0 +1,63 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at https://mozilla.org/MPL/2.0/.
+
+use clap::Parser;
+use log::trace;
+use ra_ap_hir as hir;
+use ra_ap_ide::RootDatabase;
+
+use crate::{
+    analyzer::LoadOptions,
+    graph::GraphBuilder,
+    output,
+    progress::{self, Stage},
+    quickfix,
+};
+
+use super::{graphml::Document, options::Options};
+
+#[derive(Parser, Clone, PartialEq, Eq, Debug)]
+pub struct Command {
+    #[command(flatten)]
+    pub options: Options,
+}
+
+impl Command {
+    pub fn new(options: Options) -> Self {
+        Self { options }
+    }
+
+    pub(crate) fn sanitize(&mut self) {}
+
+    #[doc(hidden)]
+    pub fn run(self, krates: &[hir::Crate], db: &RootDatabase) -> anyhow::Result<()> {
+        trace!("Building graph ...");
+        progress::stage(Stage::Building);
+
+        let (graph, _crate_node_idxs) = GraphBuilder::new(db, krates).build()?;
+
+        let project_dir = quickfix::project_dir(&self.options.project);
+        let document = Document::new(&graph, krates, &project_dir, db);
+
+        trace!("Printing document ...");
+        progress::stage(Stage::Printing);
+
+        let output = document.to_string();
+
+        match &self.options.output {
+            Some(output_path) => output::write_atomically(output_path, output),
+            None => {
+                print!("{output}");
+                Ok(())
+            }
+        }
+    }
+
+    pub fn load_options(&self) -> LoadOptions {
+        LoadOptions {
+            cfg_test: self.options.cfg_test,
+            sysroot: false,
+        }
+    }
+}
@@ -0,0 +1,214 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at https://mozilla.org/MPL/2.0/.
+
+//! GraphML documents of the module/item graph, as written by `export`.
+//!
+//! GraphML is an open, XML-based graph format understood by graph and code exploration tools
+//! (e.g. yEd, Gephi or Cytoscape), which get each node's path, kind, visibility and source location
+//! (as well as each edge's relationship) attached as data.
+
+use std::{collections::HashMap, fmt, path::Path};
+
+use petgraph::{
+    graph::NodeIndex,
+    visit::{EdgeRef as _, IntoEdgeReferences as _},
+};
+use ra_ap_hir as hir;
+use ra_ap_ide::RootDatabase;
+
+use crate::{
+    analyzer,
+    api::{self, NodeData},
+    graph::{Edge, Graph, Node, Relationship},
+    quickfix,
+};
+
+/// The data keys declared by the document, as `(id, domain, type)`.
+const KEYS: [(&str, &str, &str); 11] = [
+    ("name", "node", "string"),
+    ("path", "node", "string"),
+    ("kind", "node", "string"),
+    ("visibility", "node", "string"),
+    ("crate", "node", "boolean"),
+    ("external", "node", "boolean"),
+    ("stable_id", "node", "string"),
+    ("file", "node", "string"),
+    ("line", "node", "int"),
+    ("relationship", "edge", "string"),
+    ("weight", "edge", "int"),
+];
+
+#[derive(Clone, PartialEq, Eq, Debug)]
+struct DocumentNode {
+    data: NodeData,
+    /// The path of the node's source file, relative to the project's directory.
+    file: Option<String>,
+    /// The (1-based) line of the node's name.
+    line: Option<u32>,
+}
+
+#[derive(Clone, PartialEq, Eq, Debug)]
+struct DocumentEdge {
+    /// The index of the edge's source within the document's nodes.
+    source: usize,
+    /// The index of the edge's target within the document's nodes.
+    target: usize,
+    relationship: Relationship,
+    weight: usize,
+}
+
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub(crate) struct Document {
+    /// The graph's nodes, sorted by path.
+    nodes: Vec<DocumentNode>,
+    /// The graph's edges, sorted by their nodes.
+    edges: Vec<DocumentEdge>,
+}
+
+impl Document {
+    pub(crate) fn new(
+        graph: &Graph<Node, Edge>,
+        krates: &[hir::Crate],
+        project_dir: &Path,
+        db: &RootDatabase,
+    ) -> Self {
+        let mut nodes: Vec<(NodeIndex, DocumentNode)> = graph
+            .node_indices()
+            .map(|node_idx| {
+                let node = &graph[node_idx];
+                let location = analyzer::location(node.hir, db);
+
+                let file = location.as_ref().map(|(file_path, _, _)| {
+                    quickfix::relative_path(file_path, project_dir)
+                        .components()
+                        .map(|component| component.as_os_str().to_string_lossy())
+                        .collect::<Vec<_>>()
+                        .join("/")
+                });
+
+                let document_node = DocumentNode {
+                    data: api::node_data(node, krates, db),
+                    file,
+                    line: location.map(|(_, line, _)| line),
+                };
+
+                (node_idx, document_node)
+            })
+            .collect();
+
+        nodes.sort_by(|(_, lhs), (_, rhs)| {
+            (&lhs.data.path, &lhs.data.kind).cmp(&(&rhs.data.path, &rhs.data.kind))
+        });
+
+        let indices: HashMap<NodeIndex, usize> = nodes
+            .iter()
+            .enumerate()
+            .map(|(index, (node_idx, _))| (*node_idx, index))
+            .collect();
+
+        let mut edges: Vec<DocumentEdge> = graph
+            .edge_references()
+            .map(|edge_ref| DocumentEdge {
+                source: indices[&edge_ref.source()],
+                target: indices[&edge_ref.target()],
+                relationship: edge_ref.weight().kind,
+                weight: edge_ref.weight().weight,
+            })
+            .collect();
+
+        edges.sort_by(|lhs, rhs| {
+            (lhs.source, lhs.target, lhs.relationship.display_name()).cmp(&(
+                rhs.source,
+                rhs.target,
+                rhs.relationship.display_name(),
+            ))
+        });
+
+        Self {
+            nodes: nodes.into_iter().map(|(_, node)| node).collect(),
+            edges,
+        }
+    }
+}
+
+impl fmt::Display for Document {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, r#"<?xml version="1.0" encoding="UTF-8"?>"#)?;
+        writeln!(
+            f,
+            r#"<graphml xmlns="http://graphml.graphdrawing.org/xmlns">"#
+        )?;
+
+        for (id, domain, ty) in KEYS {
+            writeln!(
+                f,
+                r#"  <key id="{id}" for="{domain}" attr.name="{id}" attr.type="{ty}"/>"#
+            )?;
+        }
+
+        writeln!(f, r#"  <graph id="G" edgedefault="directed">"#)?;
+
+        for (index, node) in self.nodes.iter().enumerate() {
+            writeln!(f, r#"    <node id="n{index}">"#)?;
+
+            let data = &node.data;
+
+            write_data(f, "name", &data.name)?;
+            write_data(f, "path", &data.path)?;
+            write_data(f, "kind", &data.kind)?;
+            write_data(f, "visibility", &data.visibility)?;
+            write_data(f, "crate", &data.is_crate.to_string())?;
+            write_data(f, "external", &data.is_external.to_string())?;
+            write_data(f, "stable_id", &data.stable_id)?;
+
+            if let Some(file) = &node.file {
+                write_data(f, "file", file)?;
+            }
+
+            if let Some(line) = node.line {
+                write_data(f, "line", &line.to_string())?;
+            }
+
+            writeln!(f, "    </node>")?;
+        }
+
+        for (index, edge) in self.edges.iter().enumerate() {
+            writeln!(
+                f,
+                r#"    <edge id="e{index}" source="n{}" target="n{}">"#,
+                edge.source, edge.target
+            )?;
+
+            write_data(f, "relationship", edge.relationship.display_name())?;
+            write_data(f, "weight", &edge.weight.to_string())?;
+
+            writeln!(f, "    </edge>")?;
+        }
+
+        writeln!(f, "  </graph>")?;
+        writeln!(f, "</graphml>")
+    }
+}
+
+fn write_data(f: &mut fmt::Formatter<'_>, key: &str, value: &str) -> fmt::Result {
+    writeln!(f, r#"      <data key="{key}">{}</data>"#, escaped(value))
+}
+
+/// Returns `text` with XML's special characters escaped.
+fn escaped(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+
+    for char in text.chars() {
+        match char {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&apos;"),
+            _ => escaped.push(char),
+        }
+    }
+
+    escaped
+}
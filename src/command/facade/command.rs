@@ -0,0 +1,62 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at https://mozilla.org/MPL/2.0/.
+
+use clap::Parser;
+use log::trace;
+use ra_ap_hir as hir;
+use ra_ap_ide::RootDatabase;
+
+use crate::{
+    analyzer::LoadOptions,
+    output,
+    progress::{self, Stage},
+};
+
+use super::{options::Options, report::Facade};
+
+#[derive(Parser, Clone, PartialEq, Eq, Debug)]
+pub struct Command {
+    #[command(flatten)]
+    pub options: Options,
+}
+
+impl Command {
+    pub fn new(options: Options) -> Self {
+        Self { options }
+    }
+
+    pub(crate) fn sanitize(&mut self) {}
+
+    #[doc(hidden)]
+    pub fn run(self, krates: &[hir::Crate], db: &RootDatabase) -> anyhow::Result<()> {
+        trace!("Auditing facades ...");
+        progress::stage(Stage::Building);
+
+        let facades: Vec<Facade> = krates.iter().map(|krate| Facade::new(*krate, db)).collect();
+
+        trace!("Printing facades ...");
+        progress::stage(Stage::Printing);
+
+        let output = facades
+            .iter()
+            .map(Facade::to_string)
+            .collect::<Vec<_>>()
+            .join("\n");
+
+        match &self.options.output {
+            Some(output_path) => output::write_atomically(output_path, output),
+            None => {
+                print!("{output}");
+                Ok(())
+            }
+        }
+    }
+
+    pub fn load_options(&self) -> LoadOptions {
+        LoadOptions {
+            cfg_test: self.options.cfg_test,
+            sysroot: false,
+        }
+    }
+}
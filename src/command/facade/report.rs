@@ -0,0 +1,308 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at https://mozilla.org/MPL/2.0/.
+
+//! Audit of a crate's root `pub use` facade, as printed by `facade`.
+//!
+//! An item is reachable if there's a path of `pub` modules (and `pub use` re-exports)
+//! leading to it from the crate's root, with the public items lacking such a path
+//! being unusable from outside of the crate, despite being declared `pub`.
+
+use std::{
+    collections::{BTreeMap, BTreeSet, HashMap, HashSet, VecDeque},
+    fmt,
+};
+
+use ra_ap_hir::{self as hir, HasVisibility as _, ModuleSource};
+use ra_ap_ide::RootDatabase;
+use ra_ap_syntax::{
+    ast::{self, HasModuleItem as _, HasVisibility as _},
+    AstNode as _,
+};
+
+use crate::{analyzer, item::Item};
+
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub(crate) struct Facade {
+    /// The crate's name.
+    name: String,
+    /// The paths of the (internal) modules whose items get re-exported by the crate's root,
+    /// along with the number of their items re-exported.
+    surfaced_modules: BTreeMap<String, usize>,
+    /// The public items not reachable via any public path, as (kind, path) pairs.
+    unreachable_items: Vec<(String, String)>,
+    /// The items re-exported at multiple public paths, as (kind, path, re-export paths) triples.
+    duplicate_reexports: Vec<(String, String, Vec<String>)>,
+    /// The items of the crate root's glob re-exports shadowed by other items of the same name,
+    /// as (path, shadowing item's path) pairs.
+    shadowed_reexports: Vec<(String, String)>,
+}
+
+impl Facade {
+    pub(crate) fn new(krate: hir::Crate, db: &RootDatabase) -> Self {
+        let name = analyzer::crate_name(krate, db);
+        let root_module = krate.root_module();
+
+        let is_internal =
+            |module_def: hir::ModuleDef| analyzer::krate(module_def, db) == Some(krate);
+
+        // Walk the public paths leading from the crate's root, breadth-first:
+        let mut reexport_paths: HashMap<hir::ModuleDef, BTreeSet<String>> = HashMap::new();
+        let mut reachable: HashSet<hir::ModuleDef> = HashSet::new();
+        let mut visited = HashSet::from([root_module]);
+        let mut queue = VecDeque::from([(root_module, name.clone())]);
+
+        while let Some((module, module_path)) = queue.pop_front() {
+            for (item_name, module_def, is_import) in analyzer::public_scope(module, db) {
+                if !is_internal(module_def) {
+                    continue;
+                }
+
+                let path = format!("{module_path}::{item_name}");
+
+                reachable.insert(module_def);
+
+                if is_import {
+                    reexport_paths
+                        .entry(module_def)
+                        .or_default()
+                        .insert(path.clone());
+                }
+
+                if let hir::ModuleDef::Module(module) = module_def {
+                    if visited.insert(module) {
+                        queue.push_back((module, path));
+                    }
+                }
+            }
+        }
+
+        let mut surfaced_modules: BTreeMap<String, usize> = BTreeMap::new();
+
+        for (_, module_def, is_import) in analyzer::public_scope(root_module, db) {
+            if !is_import || !is_internal(module_def) {
+                continue;
+            }
+
+            let defining_module = match module_def {
+                hir::ModuleDef::Module(module) => module.parent(db),
+                module_def => module_def.module(db),
+            };
+
+            let Some(defining_module) = defining_module else {
+                continue;
+            };
+
+            if defining_module != root_module {
+                let path = analyzer::display_path(hir::ModuleDef::Module(defining_module), db);
+                *surfaced_modules.entry(path).or_default() += 1;
+            }
+        }
+
+        let mut unreachable_items = vec![];
+        let mut modules = vec![root_module];
+
+        while let Some(module) = modules.pop() {
+            modules.extend(module.children(db));
+
+            for module_def in module.declarations(db) {
+                if module_def.visibility(db) != hir::Visibility::Public {
+                    continue;
+                }
+
+                if !reachable.contains(&module_def) {
+                    unreachable_items.push(Self::describe(module_def, db));
+                }
+            }
+        }
+
+        unreachable_items.sort_by(|lhs, rhs| lhs.1.cmp(&rhs.1));
+
+        let mut duplicate_reexports: Vec<(String, String, Vec<String>)> = reexport_paths
+            .into_iter()
+            .filter(|(_, paths)| paths.len() > 1)
+            .map(|(module_def, paths)| {
+                let (kind, path) = Self::describe(module_def, db);
+                (kind, path, paths.into_iter().collect())
+            })
+            .collect();
+
+        duplicate_reexports.sort_by(|lhs, rhs| lhs.1.cmp(&rhs.1));
+
+        let shadowed_reexports = Self::shadowed_reexports(root_module, db);
+
+        Self {
+            name,
+            surfaced_modules,
+            unreachable_items,
+            duplicate_reexports,
+            shadowed_reexports,
+        }
+    }
+
+    /// Returns the items of `root_module`'s glob re-exports (e.g. `pub use self::errors::*`)
+    /// that get shadowed by other items of the same name.
+    ///
+    /// Only globs of the crate's own modules get checked, as found by their literal paths.
+    fn shadowed_reexports(root_module: hir::Module, db: &RootDatabase) -> Vec<(String, String)> {
+        let ModuleSource::SourceFile(source_file) = root_module.definition_source(db).value else {
+            return vec![];
+        };
+
+        let mut glob_paths = vec![];
+
+        for item in source_file.items() {
+            let ast::Item::Use(use_item) = item else {
+                continue;
+            };
+
+            let is_public = use_item
+                .visibility()
+                .is_some_and(|visibility| visibility.syntax().text() == "pub");
+
+            if let (true, Some(use_tree)) = (is_public, use_item.use_tree()) {
+                collect_glob_paths(&use_tree, &[], &mut glob_paths);
+            }
+        }
+
+        let mut scope: HashMap<String, Vec<hir::ModuleDef>> = HashMap::new();
+
+        for (name, scope_def) in root_module.scope(db, None) {
+            if let hir::ScopeDef::ModuleDef(module_def) = scope_def {
+                scope
+                    .entry(name.display(db).to_string())
+                    .or_default()
+                    .push(module_def);
+            }
+        }
+
+        let mut shadowed = BTreeSet::new();
+
+        for glob_path in glob_paths {
+            let Some(glob_module) = resolve_module(root_module, &glob_path, db) else {
+                continue;
+            };
+
+            for (name, module_def, _) in analyzer::public_scope(glob_module, db) {
+                let Some(scope_defs) = scope.get(&name) else {
+                    continue;
+                };
+
+                if scope_defs.contains(&module_def) {
+                    continue;
+                }
+
+                shadowed.insert((
+                    analyzer::display_path(module_def, db),
+                    analyzer::display_path(scope_defs[0], db),
+                ));
+            }
+        }
+
+        shadowed.into_iter().collect()
+    }
+
+    fn describe(module_def: hir::ModuleDef, db: &RootDatabase) -> (String, String) {
+        let item = Item::new(module_def);
+        (
+            item.kind_display_name(db).to_string(),
+            item.display_path(db),
+        )
+    }
+}
+
+/// Collects the paths of the glob imports (e.g. `errors::*`) within `use_tree`.
+fn collect_glob_paths(
+    use_tree: &ast::UseTree,
+    prefix: &[String],
+    glob_paths: &mut Vec<Vec<String>>,
+) {
+    let mut path = prefix.to_vec();
+
+    if let Some(use_path) = use_tree.path() {
+        path.extend(
+            use_path
+                .segments()
+                .map(|segment| segment.syntax().text().to_string()),
+        );
+    }
+
+    if use_tree.star_token().is_some() {
+        glob_paths.push(path);
+    } else if let Some(use_tree_list) = use_tree.use_tree_list() {
+        for use_tree in use_tree_list.use_trees() {
+            collect_glob_paths(&use_tree, &path, glob_paths);
+        }
+    }
+}
+
+/// Resolves the `path` of a module of the crate, relative to its `root_module`.
+fn resolve_module(
+    root_module: hir::Module,
+    path: &[String],
+    db: &RootDatabase,
+) -> Option<hir::Module> {
+    let mut module = root_module;
+
+    for (idx, segment) in path.iter().enumerate() {
+        module = match segment.as_str() {
+            "crate" | "self" if idx == 0 => root_module,
+            segment => module.children(db).find(|child| {
+                analyzer::name(hir::ModuleDef::Module(*child), db).as_deref() == Some(segment)
+            })?,
+        };
+    }
+
+    (module != root_module).then_some(module)
+}
+
+impl fmt::Display for Facade {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "crate {}", self.name)?;
+
+        writeln!(f)?;
+        writeln!(f, "Surfaced modules ({}):", self.surfaced_modules.len())?;
+
+        for (path, items) in &self.surfaced_modules {
+            let noun = if *items == 1 { "item" } else { "items" };
+            writeln!(f, "  {path} ({items} {noun})")?;
+        }
+
+        writeln!(f)?;
+        writeln!(
+            f,
+            "Unreachable public items ({}):",
+            self.unreachable_items.len()
+        )?;
+
+        for (kind, path) in &self.unreachable_items {
+            writeln!(f, "  {kind} {path}")?;
+        }
+
+        writeln!(f)?;
+        writeln!(
+            f,
+            "Duplicate re-exports ({}):",
+            self.duplicate_reexports.len()
+        )?;
+
+        for (kind, path, reexport_paths) in &self.duplicate_reexports {
+            writeln!(f, "  {kind} {path}")?;
+            writeln!(f, "    re-exported as {}", reexport_paths.join(", "))?;
+        }
+
+        writeln!(f)?;
+        writeln!(
+            f,
+            "Shadowed glob re-exports ({}):",
+            self.shadowed_reexports.len()
+        )?;
+
+        for (path, shadowing_path) in &self.shadowed_reexports {
+            writeln!(f, "  {path}")?;
+            writeln!(f, "    shadowed by {shadowing_path}")?;
+        }
+
+        Ok(())
+    }
+}
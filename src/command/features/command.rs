@@ -0,0 +1,88 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at https://mozilla.org/MPL/2.0/.
+
+use clap::Parser;
+use log::trace;
+
+use crate::{
+    analyzer::{load_workspace, LoadOptions},
+    graph::GraphBuilder,
+    output,
+    progress::{self, Stage},
+};
+
+use super::{
+    matrix,
+    options::Options,
+    report::{Report, Snapshot},
+};
+
+#[derive(Parser, Clone, PartialEq, Eq, Debug)]
+pub struct Command {
+    #[command(flatten)]
+    pub options: Options,
+}
+
+impl Command {
+    pub fn new(options: Options) -> Self {
+        Self { options }
+    }
+
+    pub(crate) fn sanitize(&mut self) {}
+
+    #[doc(hidden)]
+    pub fn run(self) -> anyhow::Result<()> {
+        let combinations = if self.options.feature_sets.is_empty() {
+            matrix::powerset(&self.options.project, self.options.depth)?
+        } else {
+            matrix::from_feature_sets(&self.options.feature_sets)
+        };
+
+        let load_options = self.load_options();
+
+        let mut snapshots = vec![];
+
+        // Each combination gets loaded on its own, with only its features activated:
+        for features in &combinations {
+            trace!("Analyzing with features {features:?} ...");
+
+            let mut project_options = self.options.project.clone();
+            project_options.all_features = false;
+            project_options.no_default_features = true;
+            project_options.features = features.clone();
+
+            progress::stage(Stage::Loading);
+
+            let (krates, host, _vfs) =
+                load_workspace(&self.options.general, &project_options, &load_options)?;
+            let db = host.raw_database();
+
+            progress::stage(Stage::Building);
+
+            let (graph, _crate_node_idxs) = GraphBuilder::new(db, &krates).build()?;
+
+            snapshots.push(Snapshot::new(&graph, db));
+        }
+
+        trace!("Printing report ...");
+        progress::stage(Stage::Printing);
+
+        let output = Report::new(&combinations, &snapshots).to_string();
+
+        match &self.options.output {
+            Some(output_path) => output::write_atomically(output_path, output),
+            None => {
+                print!("{output}");
+                Ok(())
+            }
+        }
+    }
+
+    pub fn load_options(&self) -> LoadOptions {
+        LoadOptions {
+            cfg_test: self.options.cfg_test,
+            sysroot: false,
+        }
+    }
+}
@@ -0,0 +1,107 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at https://mozilla.org/MPL/2.0/.
+
+//! Feature combinations to analyze, as obtained from `--feature-set`s or the package's features.
+
+use std::{collections::BTreeSet, env, process};
+
+use crate::{glob, options::ProjectOptions, quickfix};
+
+/// Parses the given `--feature-set`s (e.g. `json,net`) into feature combinations.
+pub(crate) fn from_feature_sets(feature_sets: &[String]) -> Vec<Vec<String>> {
+    feature_sets
+        .iter()
+        .map(|feature_set| {
+            feature_set
+                .split([' ', ','])
+                .filter(|feature| !feature.is_empty())
+                .map(str::to_owned)
+                .collect()
+        })
+        .collect()
+}
+
+/// Returns the combinations of the package's features of up to `depth` features each,
+/// ordered by size (starting with the empty combination).
+///
+/// The `default` feature is left out, as it merely enables a combination of other features.
+pub(crate) fn powerset(options: &ProjectOptions, depth: usize) -> anyhow::Result<Vec<Vec<String>>> {
+    let features = package_features(options)?;
+
+    let mut combinations: Vec<Vec<String>> = vec![vec![]];
+    let mut previous: Vec<(usize, Vec<String>)> = vec![(0, vec![])];
+
+    for _ in 0..depth.min(features.len()) {
+        let mut next = vec![];
+
+        for (start, combination) in &previous {
+            for (idx, feature) in features.iter().enumerate().skip(*start) {
+                let mut combination = combination.clone();
+                combination.push(feature.clone());
+                next.push((idx + 1, combination));
+            }
+        }
+
+        combinations.extend(next.iter().map(|(_, combination)| combination.clone()));
+        previous = next;
+    }
+
+    Ok(combinations)
+}
+
+/// Returns the (sorted) features of the selected package, as obtained from `cargo metadata`.
+fn package_features(options: &ProjectOptions) -> anyhow::Result<Vec<String>> {
+    let project_dir = quickfix::project_dir(options);
+
+    let cargo = env::var_os("CARGO").unwrap_or_else(|| "cargo".into());
+
+    let mut command = process::Command::new(cargo);
+    command
+        .current_dir(&project_dir)
+        .args(["metadata", "--format-version", "1", "--no-deps"]);
+
+    if options.offline {
+        command.arg("--offline");
+    }
+
+    let output = command.output()?;
+
+    if !output.status.success() {
+        anyhow::bail!(
+            "Could not determine features of {}:\n{}",
+            project_dir.display(),
+            String::from_utf8_lossy(&output.stderr).trim_end()
+        );
+    }
+
+    let metadata = json::parse(&String::from_utf8(output.stdout)?)?;
+
+    let packages: Vec<&json::JsonValue> = metadata["packages"]
+        .members()
+        .filter(|package| {
+            let name = package["name"].as_str().unwrap_or_default();
+
+            options.package.is_empty()
+                || options.package.iter().any(|pattern| {
+                    pattern == name || (glob::is_glob(pattern) && glob::glob_matches(pattern, name))
+                })
+        })
+        .collect();
+
+    let [package] = packages[..] else {
+        anyhow::bail!(
+            "Expected a single package to combine the features of (e.g. via `-p my_crate`), found {}",
+            packages.len()
+        );
+    };
+
+    let features = package["features"]
+        .entries()
+        .map(|(feature, _)| feature)
+        .filter(|feature| *feature != "default")
+        .map(str::to_owned)
+        .collect::<BTreeSet<_>>();
+
+    Ok(features.into_iter().collect())
+}
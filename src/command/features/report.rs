@@ -0,0 +1,149 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at https://mozilla.org/MPL/2.0/.
+
+//! Feature-gated items and edges, as reported by `features`.
+//!
+//! Each feature combination gets analyzed on its own, with items (and edges between them)
+//! that are missing from any of the combinations' graphs getting reported
+//! along with the combinations they do exist in.
+
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    fmt,
+};
+
+use petgraph::visit::{EdgeRef as _, IntoEdgeReferences as _};
+use ra_ap_ide::RootDatabase;
+
+use crate::graph::{Edge, Graph, Node, Relationship};
+
+/// The items and edges of a single feature combination's graph, by path.
+#[derive(Clone, Default, PartialEq, Eq, Debug)]
+pub(crate) struct Snapshot {
+    /// The kinds of the graph's items (e.g. `mod`), by path.
+    items: BTreeMap<String, String>,
+    /// The graph's "uses" and "reexports" edges, as (source path, relationship, target path) triples.
+    edges: BTreeSet<(String, &'static str, String)>,
+}
+
+impl Snapshot {
+    pub(crate) fn new(graph: &Graph<Node, Edge>, db: &RootDatabase) -> Self {
+        let items = graph
+            .node_indices()
+            .map(|node_idx| {
+                let node = &graph[node_idx];
+                let kind = node.kind_display_name(db).to_string();
+                (node.display_path(db), kind)
+            })
+            .collect();
+
+        let edges = graph
+            .edge_references()
+            .filter(|edge_ref| edge_ref.weight().kind != Relationship::Owns)
+            .map(|edge_ref| {
+                (
+                    graph[edge_ref.source()].display_path(db),
+                    edge_ref.weight().display_name(),
+                    graph[edge_ref.target()].display_path(db),
+                )
+            })
+            .collect();
+
+        Self { items, edges }
+    }
+}
+
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub(crate) struct Report {
+    /// The analyzed feature combinations, as labels (e.g. `json,net`).
+    combinations: Vec<String>,
+    /// The items missing from some of the combinations,
+    /// along with the indices of the combinations they exist in.
+    items: Vec<(String, Vec<usize>)>,
+    /// The edges missing from some of the combinations,
+    /// along with the indices of the combinations they exist in.
+    edges: Vec<(String, Vec<usize>)>,
+}
+
+impl Report {
+    pub(crate) fn new(combinations: &[Vec<String>], snapshots: &[Snapshot]) -> Self {
+        let combinations = combinations
+            .iter()
+            .map(|features| match features.is_empty() {
+                true => "(none)".to_owned(),
+                false => features.join(","),
+            })
+            .collect();
+
+        let mut items: BTreeMap<String, Vec<usize>> = BTreeMap::new();
+        let mut edges: BTreeMap<String, Vec<usize>> = BTreeMap::new();
+
+        for (idx, snapshot) in snapshots.iter().enumerate() {
+            for (path, kind) in &snapshot.items {
+                items.entry(format!("{kind} {path}")).or_default().push(idx);
+            }
+
+            for (source, relationship, target) in &snapshot.edges {
+                edges
+                    .entry(format!("{source} -> {target} ({relationship})"))
+                    .or_default()
+                    .push(idx);
+            }
+        }
+
+        let gated = |entries: BTreeMap<String, Vec<usize>>| {
+            entries
+                .into_iter()
+                .filter(|(_, idxs)| idxs.len() < snapshots.len())
+                .collect()
+        };
+
+        Self {
+            combinations,
+            items: gated(items),
+            edges: gated(edges),
+        }
+    }
+
+    fn fmt_section(
+        &self,
+        f: &mut fmt::Formatter<'_>,
+        title: &str,
+        entries: &[(String, Vec<usize>)],
+    ) -> fmt::Result {
+        writeln!(f)?;
+        writeln!(f, "{title} ({}):", entries.len())?;
+
+        let width = entries
+            .iter()
+            .map(|(entry, _)| entry.chars().count())
+            .max()
+            .unwrap_or(0);
+
+        for (entry, idxs) in entries {
+            let combinations = idxs
+                .iter()
+                .map(|idx| self.combinations[*idx].as_str())
+                .collect::<Vec<_>>()
+                .join("; ");
+
+            writeln!(f, "  {entry:<width$}  only with: {combinations}")?;
+        }
+
+        Ok(())
+    }
+}
+
+impl fmt::Display for Report {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "Feature combinations ({}):", self.combinations.len())?;
+
+        for combination in &self.combinations {
+            writeln!(f, "  {combination}")?;
+        }
+
+        self.fmt_section(f, "Feature-gated items", &self.items)?;
+        self.fmt_section(f, "Feature-gated edges", &self.edges)
+    }
+}
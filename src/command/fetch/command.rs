@@ -0,0 +1,51 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at https://mozilla.org/MPL/2.0/.
+
+use clap::Parser;
+use log::trace;
+
+use super::{fetcher::Fetcher, options::Options};
+
+#[derive(Parser, Clone, PartialEq, Eq, Debug)]
+pub struct Command {
+    #[command(flatten)]
+    pub options: Options,
+
+    #[command(subcommand)]
+    pub command: Box<crate::command::NestedCommand>,
+}
+
+impl Command {
+    pub fn new(options: Options, command: crate::command::NestedCommand) -> Self {
+        Self {
+            options,
+            command: Box::new(command),
+        }
+    }
+
+    pub(crate) fn sanitize(&mut self) {
+        self.command.sanitize();
+    }
+
+    #[doc(hidden)]
+    pub fn run(self) -> anyhow::Result<()> {
+        trace!("Fetching crate ...");
+
+        let mut command = crate::command::Command::from(*self.command);
+
+        let offline = command.project_options().offline;
+
+        let fetcher = Fetcher::new(&self.options.krate, offline);
+        let crate_dir = fetcher.fetch()?;
+
+        if self.options.general.verbose {
+            eprintln!();
+            eprintln!("Fetched crate into {:?}", crate_dir);
+        }
+
+        command.project_options_mut().manifest_path = crate_dir;
+
+        command.run()
+    }
+}
@@ -0,0 +1,191 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at https://mozilla.org/MPL/2.0/.
+
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    process,
+};
+
+use log::{debug, trace};
+use scopeguard::defer;
+
+use super::options::CrateSpec;
+
+/// Downloads a published crate via cargo and unpacks it into the user's cache directory.
+pub struct Fetcher<'a> {
+    spec: &'a CrateSpec,
+    offline: bool,
+}
+
+impl<'a> Fetcher<'a> {
+    pub fn new(spec: &'a CrateSpec, offline: bool) -> Self {
+        Self { spec, offline }
+    }
+
+    pub fn fetch(&self) -> anyhow::Result<PathBuf> {
+        trace!("Fetching crate ...");
+
+        defer! {
+            trace!("Finished fetching crate.");
+        }
+
+        let CrateSpec { name, version } = self.spec;
+
+        // Partial versions (e.g. `1.0`) resolve to the latest matching version (e.g. `1.0.200`):
+        let version_req = match version {
+            Some(version) => format!("={version}"),
+            None => "*".to_owned(),
+        };
+
+        // Let cargo resolve and download the crate by declaring it as
+        // the sole dependency of an otherwise empty helper project:
+
+        let resolver_dir = tempfile::Builder::new()
+            .prefix("cargo-modules-fetch-")
+            .tempdir()?;
+
+        fs::write(
+            resolver_dir.path().join("Cargo.toml"),
+            format!(
+                indoc::indoc! {
+                    r#"
+                    [package]
+                    name = "cargo-modules-fetch"
+                    version = "0.0.0"
+                    edition = "2021"
+                    publish = false
+
+                    [lib]
+                    path = "lib.rs"
+
+                    [dependencies]
+                    {name} = "{version_req}"
+
+                    [workspace]
+                    "#
+                },
+                name = name,
+                version_req = version_req,
+            ),
+        )?;
+
+        fs::write(resolver_dir.path().join("lib.rs"), "")?;
+
+        let source_dir = self.resolve_source_dir(resolver_dir.path())?;
+        debug!("Resolved crate source: {:?}", source_dir);
+
+        // Registry sources are shared across all projects on the system,
+        // so we analyze a private copy instead of writing into the registry:
+
+        let crate_dir_name = source_dir
+            .file_name()
+            .ok_or_else(|| anyhow::anyhow!("Invalid crate source path {:?}", source_dir))?;
+
+        let fetch_dir = dirs::cache_dir()
+            .ok_or_else(|| anyhow::anyhow!("Could not determine cache directory"))?
+            .join("cargo-modules")
+            .join("fetch");
+
+        fs::create_dir_all(&fetch_dir)?;
+
+        let crate_dir = fetch_dir.join(crate_dir_name);
+
+        if crate_dir.is_dir() {
+            return Ok(crate_dir);
+        }
+
+        // Copies only ever get moved into place once complete, so that interrupted
+        // (or concurrent) runs never leave a partial copy behind for later runs to pick up:
+
+        let partial_dir = tempfile::Builder::new()
+            .prefix(".tmp-")
+            .tempdir_in(&fetch_dir)?;
+
+        copy_dir(&source_dir, partial_dir.path())?;
+
+        match fs::rename(partial_dir.path(), &crate_dir) {
+            Ok(()) => {}
+            // Another run might have beaten us to it:
+            Err(_) if crate_dir.is_dir() => {}
+            Err(err) => return Err(err.into()),
+        }
+
+        Ok(crate_dir)
+    }
+
+    fn resolve_source_dir(&self, resolver_dir: &Path) -> anyhow::Result<PathBuf> {
+        let cargo = std::env::var("CARGO").unwrap_or_else(|_| "cargo".to_owned());
+
+        let mut command = process::Command::new(cargo);
+
+        command
+            .arg("metadata")
+            .arg("--format-version=1")
+            .arg("--manifest-path")
+            .arg(resolver_dir.join("Cargo.toml"))
+            .stderr(process::Stdio::inherit());
+
+        if self.offline {
+            command.arg("--offline");
+        }
+
+        let output = command.output()?;
+
+        if !output.status.success() {
+            anyhow::bail!("Could not fetch crate {:?}", self.display_spec());
+        }
+
+        let metadata = json::parse(std::str::from_utf8(&output.stdout)?)?;
+
+        // The crate is the helper project's one and only direct dependency
+        // (whereas other versions of it might be among its own dependencies):
+
+        let root_id = &metadata["resolve"]["root"];
+
+        let package_id = metadata["resolve"]["nodes"]
+            .members()
+            .find(|node| node["id"] == *root_id)
+            .and_then(|node| node["deps"].members().next())
+            .map(|dep| &dep["pkg"])
+            .ok_or_else(|| anyhow::anyhow!("Could not find crate {:?}", self.display_spec()))?;
+
+        let manifest_path = metadata["packages"]
+            .members()
+            .find(|package| package["id"] == *package_id)
+            .and_then(|package| package["manifest_path"].as_str())
+            .ok_or_else(|| anyhow::anyhow!("Could not find crate {:?}", self.display_spec()))?;
+
+        let source_dir = Path::new(manifest_path)
+            .parent()
+            .ok_or_else(|| anyhow::anyhow!("Invalid manifest path {:?}", manifest_path))?;
+
+        Ok(source_dir.to_path_buf())
+    }
+
+    fn display_spec(&self) -> String {
+        match &self.spec.version {
+            Some(version) => format!("{}@{}", self.spec.name, version),
+            None => self.spec.name.clone(),
+        }
+    }
+}
+
+fn copy_dir(source: &Path, destination: &Path) -> anyhow::Result<()> {
+    fs::create_dir_all(destination)?;
+
+    for entry in fs::read_dir(source)? {
+        let entry = entry?;
+        let source_path = entry.path();
+        let destination_path = destination.join(entry.file_name());
+
+        if entry.file_type()?.is_dir() {
+            copy_dir(&source_path, &destination_path)?;
+        } else {
+            fs::copy(&source_path, &destination_path)?;
+        }
+    }
+
+    Ok(())
+}
@@ -0,0 +1,106 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at https://mozilla.org/MPL/2.0/.
+
+use std::str::FromStr;
+
+use clap::Parser;
+
+use crate::options::GeneralOptions;
+
+/// A published crate's name, optionally along with a (possibly partial) version
+/// (e.g. `serde`, `serde@1` or `serde@1.0.200`).
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct CrateSpec {
+    pub name: String,
+    pub version: Option<String>,
+}
+
+impl FromStr for CrateSpec {
+    type Err = &'static str;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (name, version) = match s.split_once('@') {
+            Some((name, version)) => (name, Some(version)),
+            None => (s, None),
+        };
+
+        if name.is_empty() {
+            return Err("Expected crate name");
+        }
+
+        // The spec ends up in a generated manifest, as well as in file paths,
+        // so anything but valid crate names and versions is rejected upfront:
+
+        if !is_valid_name(name) {
+            return Err("Expected crate name of at most 64 ASCII letters, digits, '-' or '_', starting with a letter");
+        }
+
+        if version.is_some_and(|version| version.is_empty()) {
+            return Err("Expected crate version after '@'");
+        }
+
+        if version.is_some_and(|version| !is_valid_version(version)) {
+            return Err("Expected crate version of the form MAJOR[.MINOR[.PATCH[-PRE][+BUILD]]]");
+        }
+
+        Ok(Self {
+            name: name.to_owned(),
+            version: version.map(|version| version.to_owned()),
+        })
+    }
+}
+
+/// Returns whether `name` is a valid name for a crate published on crates.io.
+fn is_valid_name(name: &str) -> bool {
+    name.len() <= 64
+        && name.starts_with(|c: char| c.is_ascii_alphabetic())
+        && name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+}
+
+/// Returns whether `version` is a valid (possibly partial) semantic version.
+fn is_valid_version(version: &str) -> bool {
+    let (version, build) = match version.split_once('+') {
+        Some((version, build)) => (version, Some(build)),
+        None => (version, None),
+    };
+
+    let (core, pre) = match version.split_once('-') {
+        Some((core, pre)) => (core, Some(pre)),
+        None => (version, None),
+    };
+
+    let numbers: Vec<&str> = core.split('.').collect();
+
+    let is_number = |part: &str| !part.is_empty() && part.chars().all(|c| c.is_ascii_digit());
+    let is_identifiers = |part: &str| {
+        part.split('.').all(|identifier| {
+            !identifier.is_empty()
+                && identifier
+                    .chars()
+                    .all(|c| c.is_ascii_alphanumeric() || c == '-')
+        })
+    };
+
+    // Pre-release and build identifiers only make sense for complete versions:
+    let is_complete = numbers.len() == 3;
+
+    numbers.len() <= 3
+        && numbers.iter().all(|part| is_number(part))
+        && pre.map_or(true, |pre| is_complete && is_identifiers(pre))
+        && build.map_or(true, |build| is_complete && is_identifiers(build))
+}
+
+#[derive(Parser, Clone, PartialEq, Eq, Debug)]
+#[group(id = "FetchOptions")]
+pub struct Options {
+    #[command(flatten)]
+    pub general: GeneralOptions,
+
+    /// The published crate to analyze, as `<NAME>` or `<NAME>@<VERSION>`
+    /// (where partial versions, such as `1.0`, pick the latest matching one).
+    #[arg(value_name = "CRATE")]
+    pub krate: CrateSpec,
+}
@@ -0,0 +1,148 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at https://mozilla.org/MPL/2.0/.
+
+use std::{fmt, io::BufWriter};
+
+use anyhow::Context as _;
+use clap::Parser;
+use log::trace;
+use petgraph::graph::NodeIndex;
+use ra_ap_hir as hir;
+use ra_ap_ide::RootDatabase;
+
+use crate::{
+    analyzer::LoadOptions,
+    command::dependencies::{
+        command::Command as DependenciesCommand,
+        filter::Filter,
+        leaves::{self, MergedLeaves},
+        printer::Printer,
+        template,
+    },
+    graph::{Edge, Graph, GraphBuilder, Node},
+    progress::{self, Stage},
+    writer::FmtWriter,
+};
+
+use super::{dependents, options::Options};
+
+#[derive(Parser, Clone, PartialEq, Eq, Debug)]
+pub struct Command {
+    #[command(flatten)]
+    pub options: Options,
+}
+
+impl Command {
+    pub fn new(options: Options) -> Self {
+        Self { options }
+    }
+
+    pub(crate) fn sanitize(&mut self) {}
+
+    #[doc(hidden)]
+    pub fn run(self, krates: &[hir::Crate], db: &RootDatabase) -> anyhow::Result<()> {
+        let stdout = std::io::stdout();
+        let mut writer = FmtWriter::new(BufWriter::new(stdout.lock()));
+
+        self.fmt(&mut writer, krates, db)?;
+
+        writer.flush()?;
+
+        Ok(())
+    }
+
+    pub(crate) fn render(
+        &self,
+        krates: &[hir::Crate],
+        db: &RootDatabase,
+    ) -> anyhow::Result<String> {
+        let mut string = String::new();
+
+        self.fmt(&mut string, krates, db)?;
+
+        Ok(string)
+    }
+
+    fn fmt(
+        &self,
+        f: &mut dyn fmt::Write,
+        krates: &[hir::Crate],
+        db: &RootDatabase,
+    ) -> anyhow::Result<()> {
+        let options = &self.options.dependencies;
+
+        trace!("Building graph ...");
+        progress::stage(Stage::Building);
+
+        let (graph, crate_node_idxs) = GraphBuilder::new(db, krates).build()?;
+
+        trace!("Filtering graph ...");
+        progress::stage(Stage::Filtering);
+
+        let filter = Filter::new(options, db, krates);
+        let graph = filter.filter(&graph, &crate_node_idxs)?;
+
+        let module_idx = find_module(&graph, &self.options.module, db)?;
+
+        trace!("Collecting dependent modules ...");
+
+        let dependent_idxs =
+            dependents::dependent_modules(&graph, module_idx, self.options.across_crates, db);
+
+        if self.options.list {
+            let mut paths: Vec<String> = dependent_idxs
+                .iter()
+                .map(|node_idx| graph[*node_idx].display_path(db))
+                .collect();
+
+            paths.sort();
+
+            for path in paths {
+                writeln!(f, "{path}")?;
+            }
+
+            return Ok(());
+        }
+
+        let mut graph = dependents::impact_subgraph(&graph, module_idx, &dependent_idxs);
+
+        if let Some(template_path) = &options.template {
+            trace!("Rendering template ...");
+
+            let output = template::render(template_path, &graph, &crate_node_idxs, krates, db)?;
+            f.write_str(&output)?;
+
+            return Ok(());
+        }
+
+        trace!("Printing graph ...");
+        progress::stage(Stage::Printing);
+
+        let merged_leaves = match options.merge_leaves {
+            Some(max_leaves) => leaves::merge_leaves(&mut graph, max_leaves, db),
+            None => MergedLeaves::new(),
+        };
+
+        let printer = Printer::new(options, krates, db).with_merged_leaves(merged_leaves);
+        printer.fmt(f, &graph, &crate_node_idxs)?;
+
+        Ok(())
+    }
+
+    pub fn load_options(&self) -> LoadOptions {
+        DependenciesCommand::new(self.options.dependencies.clone()).load_options()
+    }
+}
+
+fn find_module(
+    graph: &Graph<Node, Edge>,
+    path: &str,
+    db: &RootDatabase,
+) -> anyhow::Result<NodeIndex> {
+    graph
+        .node_indices()
+        .filter(|node_idx| matches!(graph[*node_idx].hir, hir::ModuleDef::Module(_)))
+        .find(|node_idx| graph[*node_idx].display_path(db) == path)
+        .with_context(|| format!("No module found at path `{path}`"))
+}
@@ -0,0 +1,165 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at https://mozilla.org/MPL/2.0/.
+
+//! Extraction of the modules (transitively) depending on a module, as printed by `impact`.
+
+use std::collections::HashSet;
+
+use petgraph::{
+    graph::NodeIndex,
+    visit::{EdgeRef as _, IntoEdgeReferences as _},
+    Direction,
+};
+use ra_ap_hir as hir;
+use ra_ap_ide::RootDatabase;
+
+use crate::{
+    analyzer,
+    command::dependencies::split::{containing_module, owned_nodes, owner_node},
+    graph::{Edge, Graph, Node, Relationship},
+};
+
+/// Returns the modules (transitively) depending on the module with node `module_idx`
+/// (or any of its sub-modules) via "uses" (or "re-exports") edges of their items,
+/// excluding the module's own sub-modules.
+///
+/// Unless `across_crates` is set, only modules of the module's own crate are considered.
+pub(crate) fn dependent_modules(
+    graph: &Graph<Node, Edge>,
+    module_idx: NodeIndex,
+    across_crates: bool,
+    db: &RootDatabase,
+) -> HashSet<NodeIndex> {
+    let changed_idxs = submodules(graph, module_idx);
+    let krate = analyzer::krate(graph[module_idx].hir, db);
+
+    let mut dependent_idxs: HashSet<NodeIndex> = HashSet::default();
+    let mut stack: Vec<NodeIndex> = changed_idxs.iter().copied().collect();
+
+    while let Some(module_idx) = stack.pop() {
+        let user_idxs: Vec<NodeIndex> = module_items(graph, module_idx)
+            .into_iter()
+            .flat_map(|node_idx| {
+                graph
+                    .edges_directed(node_idx, Direction::Incoming)
+                    .filter(|edge_ref| edge_ref.weight().kind != Relationship::Owns)
+                    .map(|edge_ref| edge_ref.source())
+            })
+            .collect();
+
+        for user_idx in user_idxs {
+            let Some(user_module_idx) = containing_module(graph, user_idx) else {
+                continue;
+            };
+
+            if changed_idxs.contains(&user_module_idx) {
+                continue;
+            }
+
+            if !across_crates && analyzer::krate(graph[user_module_idx].hir, db) != krate {
+                continue;
+            }
+
+            if dependent_idxs.insert(user_module_idx) {
+                stack.push(user_module_idx);
+            }
+        }
+    }
+
+    dependent_idxs
+}
+
+/// Returns the subgraph consisting of the module with node `module_idx`,
+/// the "uses" (and "re-exports") edges by which its dependents depend on it (or on each other),
+/// along with the (transitive) owners of their nodes, up to their crates' root nodes.
+pub(crate) fn impact_subgraph(
+    graph: &Graph<Node, Edge>,
+    module_idx: NodeIndex,
+    dependent_idxs: &HashSet<NodeIndex>,
+) -> Graph<Node, Edge> {
+    let changed_idxs = submodules(graph, module_idx);
+
+    let is_affected = |node_idx: NodeIndex| {
+        containing_module(graph, node_idx).is_some_and(|module_idx| {
+            changed_idxs.contains(&module_idx) || dependent_idxs.contains(&module_idx)
+        })
+    };
+
+    let impact_edge_idxs: HashSet<_> = graph
+        .edge_references()
+        .filter(|edge_ref| edge_ref.weight().kind != Relationship::Owns)
+        .filter(|edge_ref| {
+            containing_module(graph, edge_ref.source())
+                .is_some_and(|module_idx| dependent_idxs.contains(&module_idx))
+        })
+        .filter(|edge_ref| is_affected(edge_ref.target()))
+        .map(|edge_ref| edge_ref.id())
+        .collect();
+
+    let impact_node_idxs = impact_edge_idxs.iter().flat_map(|edge_idx| {
+        let (source_idx, target_idx) = graph.edge_endpoints(*edge_idx).expect("edge endpoints");
+        [source_idx, target_idx]
+    });
+
+    let mut nodes_to_keep: HashSet<NodeIndex> = HashSet::default();
+    let mut stack: Vec<NodeIndex> = [module_idx]
+        .into_iter()
+        .chain(dependent_idxs.iter().copied())
+        .chain(impact_node_idxs)
+        .collect();
+
+    while let Some(node_idx) = stack.pop() {
+        if nodes_to_keep.insert(node_idx) {
+            stack.extend(owner_node(graph, node_idx));
+        }
+    }
+
+    let mut subgraph = graph.clone();
+
+    subgraph.retain_nodes(|_, node_idx| nodes_to_keep.contains(&node_idx));
+
+    subgraph.retain_edges(|subgraph, edge_idx| {
+        subgraph[edge_idx].kind == Relationship::Owns || impact_edge_idxs.contains(&edge_idx)
+    });
+
+    subgraph
+}
+
+/// Returns the module along with all of its (transitive) sub-modules.
+fn submodules(graph: &Graph<Node, Edge>, module_idx: NodeIndex) -> HashSet<NodeIndex> {
+    let mut module_idxs: HashSet<NodeIndex> = HashSet::default();
+    let mut stack = vec![module_idx];
+
+    while let Some(node_idx) = stack.pop() {
+        if is_module(graph, node_idx) {
+            module_idxs.insert(node_idx);
+        }
+
+        stack.extend(owned_nodes(graph, node_idx));
+    }
+
+    module_idxs
+}
+
+/// Returns the module along with its (transitively owned) items,
+/// stopping at its sub-modules.
+fn module_items(graph: &Graph<Node, Edge>, module_idx: NodeIndex) -> Vec<NodeIndex> {
+    let mut item_idxs = vec![module_idx];
+    let mut stack: Vec<NodeIndex> = owned_nodes(graph, module_idx).collect();
+
+    while let Some(node_idx) = stack.pop() {
+        if is_module(graph, node_idx) {
+            continue;
+        }
+
+        item_idxs.push(node_idx);
+        stack.extend(owned_nodes(graph, node_idx));
+    }
+
+    item_idxs
+}
+
+fn is_module(graph: &Graph<Node, Edge>, node_idx: NodeIndex) -> bool {
+    matches!(graph[node_idx].hir, hir::ModuleDef::Module(_))
+}
@@ -0,0 +1,27 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at https://mozilla.org/MPL/2.0/.
+
+use clap::Parser;
+
+use crate::command::dependencies::options::Options as DependenciesOptions;
+
+#[derive(Parser, Clone, PartialEq, Eq, Debug)]
+#[group(id = "ImpactOptions")]
+pub struct Options {
+    /// The path of the module to be changed (e.g. `my_crate::db`).
+    #[arg(value_name = "MODULE")]
+    pub module: String,
+
+    /// Include dependent modules of other analyzed crates (e.g. of a workspace's other members),
+    /// rather than only those of the module's own crate.
+    #[arg(long = "across-crates")]
+    pub across_crates: bool,
+
+    /// Print the paths of the dependent modules (one per line), instead of a graph.
+    #[arg(long = "list")]
+    pub list: bool,
+
+    #[command(flatten)]
+    pub dependencies: DependenciesOptions,
+}
@@ -0,0 +1,70 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at https://mozilla.org/MPL/2.0/.
+
+use std::{fmt::Display, path::PathBuf, str::FromStr};
+
+use clap::Parser;
+
+use crate::options::{GeneralOptions, ProjectOptions};
+
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum GroupBy {
+    /// Group imports by the crate of their target.
+    Crate,
+    /// Group imports by the module of their target (or the target itself, if a module).
+    Module,
+    /// Don't group imports, listing each target individually.
+    Item,
+}
+
+impl FromStr for GroupBy {
+    type Err = &'static str;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "crate" => Ok(Self::Crate),
+            "module" => Ok(Self::Module),
+            "item" => Ok(Self::Item),
+            _ => Err("Unrecognized grouping"),
+        }
+    }
+}
+
+impl Display for GroupBy {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            Self::Crate => "crate",
+            Self::Module => "module",
+            Self::Item => "item",
+        })
+    }
+}
+
+#[derive(Parser, Clone, PartialEq, Eq, Debug)]
+#[group(id = "ImportsOptions")]
+pub struct Options {
+    #[command(flatten)]
+    pub general: GeneralOptions,
+
+    #[command(flatten)]
+    pub project: ProjectOptions,
+
+    /// What to aggregate the imports' targets by
+    /// (e.g. crate, module, item).
+    #[arg(long = "group-by", default_value = "crate")]
+    pub group_by: GroupBy,
+
+    /// Omit imports of items of the analyzed crate itself
+    /// (i.e. only list imports of other crates).
+    #[arg(long = "no-internal")]
+    pub no_internal: bool,
+
+    /// Analyze with `#[cfg(test)]` enabled (i.e as if built via `cargo test`).
+    #[arg(long = "cfg-test")]
+    pub cfg_test: bool,
+
+    /// Write the output to this file (atomically replacing it) instead of printing it.
+    #[arg(short = 'o', long = "output")]
+    pub output: Option<PathBuf>,
+}
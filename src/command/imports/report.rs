@@ -0,0 +1,126 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at https://mozilla.org/MPL/2.0/.
+
+//! Aggregation of a crate's `use` imports by their targets, as printed by `imports`.
+
+use std::{
+    collections::{HashMap, HashSet},
+    fmt,
+};
+
+use ra_ap_hir as hir;
+use ra_ap_ide::RootDatabase;
+
+use crate::analyzer;
+
+use super::options::GroupBy;
+
+/// The imports of a single target (or group of targets).
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub(crate) struct Row {
+    /// The target's path (e.g. `serde` or `my_crate::net`).
+    target: String,
+    /// The number of imports of the target.
+    imports: usize,
+    /// The number of distinct modules importing the target.
+    modules: usize,
+}
+
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub(crate) struct Report {
+    rows: Vec<Row>,
+}
+
+impl Report {
+    pub(crate) fn new(
+        krates: &[hir::Crate],
+        group_by: GroupBy,
+        no_internal: bool,
+        db: &RootDatabase,
+    ) -> Self {
+        // The importing modules, by target:
+        let mut importers: HashMap<String, (usize, HashSet<hir::Module>)> = HashMap::new();
+
+        for krate in krates {
+            let mut modules = vec![krate.root_module()];
+
+            while let Some(module) = modules.pop() {
+                modules.extend(module.children(db));
+
+                for target in imports(module, db) {
+                    if no_internal && analyzer::krate(target, db) == Some(*krate) {
+                        continue;
+                    }
+
+                    let Some(key) = group_key(target, group_by, db) else {
+                        continue;
+                    };
+
+                    let (imports, modules) = importers.entry(key).or_default();
+                    *imports += 1;
+                    modules.insert(module);
+                }
+            }
+        }
+
+        let mut rows: Vec<Row> = importers
+            .into_iter()
+            .map(|(target, (imports, modules))| Row {
+                target,
+                imports,
+                modules: modules.len(),
+            })
+            .collect();
+
+        // Sort by descending number of importing modules, then imports, then by path for stable output:
+        rows.sort_by(|lhs, rhs| {
+            rhs.modules
+                .cmp(&lhs.modules)
+                .then(rhs.imports.cmp(&lhs.imports))
+                .then(lhs.target.cmp(&rhs.target))
+        });
+
+        Self { rows }
+    }
+}
+
+impl fmt::Display for Report {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "{:>7}  {:>7}  target", "modules", "imports")?;
+
+        for row in &self.rows {
+            writeln!(f, "{:>7}  {:>7}  {}", row.modules, row.imports, row.target)?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Returns the items imported into the module's scope (i.e. via `use`),
+/// excluding any of the module's own items.
+fn imports(module: hir::Module, db: &RootDatabase) -> Vec<hir::ModuleDef> {
+    let mut seen = HashSet::new();
+
+    module
+        .scope(db, None)
+        .into_iter()
+        .filter_map(|(_name, scope_def)| match scope_def {
+            hir::ScopeDef::ModuleDef(module_def_hir) => Some(module_def_hir),
+            _ => None,
+        })
+        .filter(|module_def_hir| module_def_hir.module(db) != Some(module))
+        .filter(|module_def_hir| !matches!(module_def_hir, hir::ModuleDef::BuiltinType(_)))
+        // Items living in multiple namespaces (e.g. unit structs) get listed once per namespace:
+        .filter(|module_def_hir| seen.insert(*module_def_hir))
+        .collect()
+}
+
+fn group_key(target: hir::ModuleDef, group_by: GroupBy, db: &RootDatabase) -> Option<String> {
+    match group_by {
+        GroupBy::Crate => analyzer::krate(target, db).map(|krate| analyzer::crate_name(krate, db)),
+        GroupBy::Module => analyzer::module(target, db)
+            .map(|module| analyzer::display_path(hir::ModuleDef::Module(module), db)),
+        GroupBy::Item => Some(analyzer::display_path(target, db)),
+    }
+}
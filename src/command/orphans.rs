@@ -8,3 +8,4 @@ pub(crate) mod orphan;
 pub(crate) mod printer;
 pub(crate) mod scanner;
 pub(crate) mod theme;
+pub(crate) mod vcs;
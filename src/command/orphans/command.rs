@@ -2,17 +2,23 @@
 // License, v. 2.0. If a copy of the MPL was not distributed with this
 // file, You can obtain one at https://mozilla.org/MPL/2.0/.
 
+use std::path::Path;
+
 use clap::Parser;
-use log::trace;
+use log::{trace, warn};
 use ra_ap_hir as hir;
 use ra_ap_ide::RootDatabase;
-use ra_ap_vfs::Vfs;
 
-use crate::analyzer::{self, LoadOptions};
+use crate::{
+    analyzer::{self, LoadOptions},
+    glob, output,
+    progress::{self, Stage},
+    quickfix, summary,
+};
 
-use super::{options::Options, printer::Printer};
+use super::{options::Options, orphan::Orphan, printer::Printer, vcs::VcsFilter};
 
-use super::scanner::Scanner;
+use super::scanner;
 
 #[derive(Parser, Clone, PartialEq, Eq, Debug)]
 pub struct Command {
@@ -25,33 +31,95 @@ impl Command {
         Self { options }
     }
 
-    pub(crate) fn sanitize(&mut self) {}
+    pub(crate) fn sanitize(&mut self) {
+        // Checking the workspace means checking each member's library and binary targets:
+        if self.options.workspace {
+            self.options.project.package = vec!["*".to_owned()];
+            self.options.project.merge_targets = true;
+        }
+    }
 
     #[doc(hidden)]
-    pub fn run(self, krate: hir::Crate, db: &RootDatabase, vfs: &Vfs) -> anyhow::Result<()> {
+    pub fn run(self, krates: &[hir::Crate], db: &RootDatabase) -> anyhow::Result<()> {
         trace!("Building tree ...");
+        progress::stage(Stage::Building);
+
+        // A package's library and binary share their name, so only get listed once:
+        let mut crate_names: Vec<_> = vec![];
+
+        for krate in krates {
+            let crate_name = format!("'{}'", analyzer::crate_name(*krate, db));
+            if !crate_names.contains(&crate_name) {
+                crate_names.push(crate_name);
+            }
+        }
+
+        let mut orphans = scanner::scan_all(db, krates)?;
+
+        if !self.options.ignore.is_empty() {
+            let project_dir = quickfix::project_dir(&self.options.project);
+            orphans.retain(|orphan| !self.is_ignored(orphan, &project_dir));
+        }
+
+        if self.options.vcs_ignore {
+            let project_dir = quickfix::project_dir(&self.options.project);
 
-        let crate_name = analyzer::crate_name(krate, db);
+            match VcsFilter::new(&project_dir, self.options.include_untracked) {
+                Some(vcs_filter) => {
+                    orphans.retain(|orphan| !vcs_filter.excludes(&orphan.file_path))
+                }
+                None => {
+                    let message = "Could not query git for ignored files, checking all files";
+                    warn!("{message}");
+                    progress::warning(message);
+                }
+            }
+        }
 
-        let scanner = Scanner::new(db, vfs, krate);
-        let mut orphans = Vec::from_iter(scanner.scan()?);
+        summary::record_orphans(orphans.len());
 
-        orphans.sort_by_cached_key(|orphan| orphan.file_path.clone());
+        progress::stage(Stage::Printing);
 
-        let mut stdout = std::io::stdout();
         let printer = Printer::new(&self.options, db);
-        printer.fmt(&mut stdout, &orphans[..])?;
+
+        match &self.options.output {
+            Some(output_path) => {
+                let mut output = vec![];
+                printer.fmt(&mut output, &orphans[..])?;
+                output::write_atomically(output_path, output)?;
+            }
+            None => {
+                let mut stdout = std::io::stdout();
+                printer.fmt(&mut stdout, &orphans[..])?;
+            }
+        }
 
         if orphans.is_empty() {
             Ok(())
         } else {
             let count = orphans.len();
+            let noun = if crate_names.len() == 1 {
+                "crate"
+            } else {
+                "crates"
+            };
+            let crate_names = crate_names.join(", ");
             Err(anyhow::anyhow!(
-                "Found {count} orphans in crate '{crate_name}'"
+                "Found {count} orphans in {noun} {crate_names}"
             ))
         }
     }
 
+    fn is_ignored(&self, orphan: &Orphan, project_dir: &Path) -> bool {
+        let relative_path = quickfix::relative_path(&orphan.file_path, project_dir);
+        let relative_path = relative_path.to_string_lossy().replace('\\', "/");
+
+        self.options
+            .ignore
+            .iter()
+            .any(|pattern| glob::glob_matches(pattern, &relative_path))
+    }
+
     pub fn load_options(&self) -> LoadOptions {
         LoadOptions {
             cfg_test: self.options.cfg_test,
@@ -2,9 +2,11 @@
 // License, v. 2.0. If a copy of the MPL was not distributed with this
 // file, You can obtain one at https://mozilla.org/MPL/2.0/.
 
+use std::path::PathBuf;
+
 use clap::Parser;
 
-use crate::options::{GeneralOptions, ProjectOptions};
+use crate::options::{GeneralOptions, MessageFormat, ProjectOptions};
 
 #[derive(Parser, Clone, PartialEq, Eq, Debug)]
 #[group(id = "OrphansOptions")]
@@ -15,11 +17,39 @@ pub struct Options {
     #[command(flatten)]
     pub project: ProjectOptions,
 
+    /// Check all of the workspace's member packages (and their library and binary targets) in one run,
+    /// grouping the reported orphans by package.
+    #[arg(long = "workspace", conflicts_with_all = ["package", "target-group"])]
+    pub workspace: bool,
+
+    /// Don't report orphans whose file path (relative to the project's directory) matches this glob
+    /// (e.g. `src/generated/**`). May be passed multiple times.
+    #[arg(long = "ignore")]
+    pub ignore: Vec<String>,
+
+    /// Skip files ignored by git (e.g. via `.gitignore`),
+    /// as well as files not tracked by it yet (e.g. scratch files).
+    #[arg(long = "vcs-ignore")]
+    pub vcs_ignore: bool,
+
+    /// Check files not tracked by git yet, even when passing `--vcs-ignore`.
+    #[arg(long = "include-untracked", requires = "vcs_ignore")]
+    pub include_untracked: bool,
+
     /// Returns a failure code if one or more orphans are found.
     #[arg(long = "deny")]
     pub deny: bool,
 
+    /// The format to report orphans in
+    /// (e.g. human, quickfix, github, json).
+    #[arg(long = "message-format", default_value = "human")]
+    pub message_format: MessageFormat,
+
     /// Analyze with `#[cfg(test)]` enabled (i.e as if built via `cargo test`).
     #[arg(long = "cfg-test")]
     pub cfg_test: bool,
+
+    /// Write the output to this file (atomically replacing it) instead of printing it.
+    #[arg(short = 'o', long = "output")]
+    pub output: Option<PathBuf>,
 }
@@ -10,4 +10,5 @@ pub struct Orphan {
     pub file_path: PathBuf,
     pub parent_module_path: String,
     pub parent_file_path: PathBuf,
+    pub crate_name: String,
 }
@@ -4,9 +4,17 @@
 
 //! Printer for displaying module structure as a tree.
 
+use std::{collections::BTreeMap, path::Path};
+
 use ra_ap_ide::RootDatabase;
 use yansi::Paint as _;
 
+use crate::{
+    options::MessageFormat,
+    progress,
+    quickfix::{self, Entry, Severity},
+};
+
 use super::{options::Options, orphan::Orphan, theme::styles};
 
 pub struct Printer<'a> {
@@ -22,6 +30,10 @@ impl<'a> Printer<'a> {
     }
 
     pub fn fmt(&self, f: &mut dyn std::io::Write, orphans: &[Orphan]) -> Result<(), anyhow::Error> {
+        if self.options.message_format != MessageFormat::Human {
+            return self.fmt_quickfix(f, orphans);
+        }
+
         let styles = styles();
 
         if orphans.is_empty() {
@@ -41,85 +53,160 @@ impl<'a> Printer<'a> {
         })
         .expect("canonical path");
 
-        writeln!(f)?;
-        writeln!(f, "{count} orphans found:", count = orphans.len())?;
-        writeln!(f)?;
+        if !self.options.workspace {
+            writeln!(f)?;
+            writeln!(f, "{count} orphans found:", count = orphans.len())?;
+            writeln!(f)?;
+
+            for orphan in orphans {
+                self.fmt_orphan(f, orphan, &prefix_path)?;
+            }
+
+            return Ok(());
+        }
+
+        // When checking a workspace the orphans get grouped by their package:
+        let mut orphans_by_package: BTreeMap<&str, Vec<&Orphan>> = BTreeMap::new();
 
         for orphan in orphans {
-            let file_path = orphan
-                .file_path
-                .strip_prefix(&prefix_path)
-                .expect("relative path")
-                .to_string_lossy();
-
-            let parent_module_path = &orphan.parent_module_path;
-            let parent_file_path = orphan
-                .parent_file_path
-                .strip_prefix(&prefix_path)
-                .expect("relative path")
-                .to_string_lossy();
-
-            let issue = if self.options.deny {
-                "error".paint(styles.error)
-            } else {
-                "warning".paint(styles.warning)
-            };
+            orphans_by_package
+                .entry(&orphan.crate_name)
+                .or_default()
+                .push(orphan);
+        }
 
+        for (package_name, orphans) in orphans_by_package {
+            writeln!(f)?;
             writeln!(
                 f,
-                "{issue}: orphaned module `{name}` at {file_path}",
-                name = orphan.name,
-                file_path = file_path
+                "{count} orphans found in package `{package_name}`:",
+                count = orphans.len()
             )?;
+            writeln!(f)?;
 
-            writeln!(
-                f,
-                "  {arrow} {parent_file_path}",
-                arrow = "-->".paint(styles.chrome),
-            )?;
+            for orphan in orphans {
+                self.fmt_orphan(f, orphan, &prefix_path)?;
+            }
+        }
 
-            let carets =
-                String::from_utf8(vec![b'^'; parent_file_path.len()]).expect("valid string");
-            writeln!(
-                f,
-                "   {pipe}  {carets} {message}",
-                pipe = "|".paint(styles.chrome),
-                carets = carets.paint(styles.deletion),
-                message = "orphan module not loaded from file".paint(styles.deletion)
-            )?;
+        Ok(())
+    }
 
-            writeln!(f, "   {pipe}", pipe = "|".paint(styles.chrome),)?;
+    fn fmt_orphan(
+        &self,
+        f: &mut dyn std::io::Write,
+        orphan: &Orphan,
+        prefix_path: &Path,
+    ) -> Result<(), anyhow::Error> {
+        let styles = styles();
 
-            writeln!(
-                f,
-                " {help}: consider loading `{orphan_name}` from module `{parent_module_path}`",
-                // eq = styles.chrome.paint("="),
-                help = "help".paint(styles.help),
-                orphan_name = orphan.name,
-                parent_module_path = parent_module_path,
-            )?;
+        let file_path = orphan
+            .file_path
+            .strip_prefix(prefix_path)
+            .expect("relative path")
+            .to_string_lossy();
+
+        let parent_module_path = &orphan.parent_module_path;
+        let parent_file_path = orphan
+            .parent_file_path
+            .strip_prefix(prefix_path)
+            .expect("relative path")
+            .to_string_lossy();
+
+        let issue = if self.options.deny {
+            "error".paint(styles.error)
+        } else {
+            "warning".paint(styles.warning)
+        };
+
+        writeln!(
+            f,
+            "{issue}: orphaned module `{name}` at {file_path}",
+            name = orphan.name,
+            file_path = file_path
+        )?;
+
+        writeln!(
+            f,
+            "  {arrow} {parent_file_path}",
+            arrow = "-->".paint(styles.chrome),
+        )?;
+
+        let carets = String::from_utf8(vec![b'^'; parent_file_path.len()]).expect("valid string");
+        writeln!(
+            f,
+            "   {pipe}  {carets} {message}",
+            pipe = "|".paint(styles.chrome),
+            carets = carets.paint(styles.deletion),
+            message = "orphan module not loaded from file".paint(styles.deletion)
+        )?;
+
+        writeln!(f, "   {pipe}", pipe = "|".paint(styles.chrome),)?;
+
+        writeln!(
+            f,
+            " {help}: consider loading `{orphan_name}` from module `{parent_module_path}`",
+            // eq = styles.chrome.paint("="),
+            help = "help".paint(styles.help),
+            orphan_name = orphan.name,
+            parent_module_path = parent_module_path,
+        )?;
+
+        writeln!(f, "   {pipe}", pipe = "|".paint(styles.chrome),)?;
+
+        writeln!(
+            f,
+            "   {pipe}  {insertion}",
+            pipe = "|".paint(styles.chrome),
+            insertion = format!("mod {};", orphan.name).paint(styles.insertion)
+        )?;
+
+        let plusses = String::from_utf8(vec![b'+'; orphan.name.len() + 5]).expect("valid string");
+        writeln!(
+            f,
+            "   {pipe}  {plusses}",
+            pipe = "|".paint(styles.chrome),
+            plusses = plusses.paint(styles.insertion)
+        )?;
+
+        writeln!(f, "   {pipe}", pipe = "|".paint(styles.chrome),)?;
 
-            writeln!(f, "   {pipe}", pipe = "|".paint(styles.chrome),)?;
+        writeln!(f)?;
 
-            writeln!(
-                f,
-                "   {pipe}  {insertion}",
-                pipe = "|".paint(styles.chrome),
-                insertion = format!("mod {};", orphan.name).paint(styles.insertion)
-            )?;
+        Ok(())
+    }
 
-            let plusses =
-                String::from_utf8(vec![b'+'; orphan.name.len() + 5]).expect("valid string");
-            writeln!(
-                f,
-                "   {pipe}  {plusses}",
-                pipe = "|".paint(styles.chrome),
-                plusses = plusses.paint(styles.insertion)
-            )?;
+    fn fmt_quickfix(
+        &self,
+        f: &mut dyn std::io::Write,
+        orphans: &[Orphan],
+    ) -> Result<(), anyhow::Error> {
+        let project_dir = quickfix::project_dir(&self.options.project);
 
-            writeln!(f, "   {pipe}", pipe = "|".paint(styles.chrome),)?;
+        let severity = if self.options.deny {
+            Severity::Error
+        } else {
+            Severity::Warning
+        };
 
-            writeln!(f)?;
+        for orphan in orphans {
+            let entry = Entry {
+                file_path: quickfix::relative_path(&orphan.file_path, &project_dir),
+                line: 1,
+                column: 1,
+                severity,
+                message: format!(
+                    "orphaned module `{name}`, consider loading it from module `{parent_module_path}`",
+                    name = orphan.name,
+                    parent_module_path = orphan.parent_module_path,
+                ),
+            };
+
+            if self.options.message_format == MessageFormat::Json {
+                progress::diagnostic(&entry);
+            } else {
+                writeln!(f, "{}", entry.formatted(self.options.message_format))?;
+            }
         }
 
         Ok(())
@@ -11,8 +11,9 @@ use std::{
 use hir::ModuleDef;
 use log::{debug, trace};
 use ra_ap_hir::{self as hir, Crate};
+use ra_ap_ide::Edition;
 use ra_ap_ide_db::RootDatabase;
-use ra_ap_vfs::Vfs;
+use ra_ap_syntax::{ast, AstNode, AstToken, SourceFile};
 
 use crate::analyzer;
 
@@ -21,13 +22,12 @@ use super::orphan::Orphan;
 #[derive(Debug)]
 pub struct Scanner<'a> {
     db: &'a RootDatabase,
-    vfs: &'a Vfs,
     krate: hir::Crate,
 }
 
 impl<'a> Scanner<'a> {
-    pub fn new(db: &'a RootDatabase, vfs: &'a Vfs, krate: hir::Crate) -> Self {
-        Self { db, vfs, krate }
+    pub fn new(db: &'a RootDatabase, krate: hir::Crate) -> Self {
+        Self { db, krate }
     }
 
     pub fn scan(mut self) -> anyhow::Result<HashSet<Orphan>> {
@@ -38,6 +38,13 @@ impl<'a> Scanner<'a> {
         Ok(orphans)
     }
 
+    /// Returns the (canonicalized) paths of all files loaded by the crate.
+    fn linked_files(&mut self) -> HashSet<PathBuf> {
+        let mut linked_files = HashSet::new();
+        self.collect_linked_files(self.krate.root_module(), &mut linked_files);
+        linked_files
+    }
+
     fn process_crate(&mut self, krate: Crate) -> HashSet<Orphan> {
         let mut orphans = HashSet::new();
         let mut callback = |orphan| {
@@ -45,17 +52,46 @@ impl<'a> Scanner<'a> {
         };
 
         let root_module = krate.root_module();
-        self.process_module(root_module, &mut callback);
+
+        // Files loaded via `#[path = "…"]` or `include!("…")` don't follow the
+        // conventional layout, so we collect them up-front to exclude them:
+        let mut linked_files = HashSet::new();
+        self.collect_linked_files(root_module, &mut linked_files);
+
+        self.process_module(root_module, &linked_files, &mut callback);
 
         orphans
     }
 
-    fn process_module(&mut self, module_hir: hir::Module, callback: &mut dyn FnMut(Orphan)) {
+    fn collect_linked_files(
+        &mut self,
+        module_hir: hir::Module,
+        linked_files: &mut HashSet<PathBuf>,
+    ) {
+        if let Some(file_path) = analyzer::module_file_path(module_hir, self.db) {
+            linked_files.extend(included_files(&file_path));
+            linked_files.insert(canonicalized(&file_path));
+        }
+
+        for module_def_hir in module_hir.declarations(self.db) {
+            if let hir::ModuleDef::Module(module_hir) = module_def_hir {
+                self.collect_linked_files(module_hir, linked_files);
+            }
+        }
+    }
+
+    fn process_module(
+        &mut self,
+        module_hir: hir::Module,
+        linked_files: &HashSet<PathBuf>,
+        callback: &mut dyn FnMut(Orphan),
+    ) {
         let Some(module_path) = analyzer::path(ModuleDef::Module(module_hir), self.db) else {
             return;
         };
 
-        let file_path = analyzer::module_file(module_hir, self.db, self.vfs);
+        let file_path = analyzer::module_file_path(module_hir, self.db);
+        let crate_name = analyzer::crate_name(self.krate, self.db);
 
         let submodules: Vec<hir::Module> = module_hir
             .declarations(self.db)
@@ -72,21 +108,52 @@ impl<'a> Scanner<'a> {
             .collect();
 
         if let Some(file_path) = file_path {
-            for orphan in orphans_of_module(&module_path, &file_path, &submodule_names) {
+            for orphan in orphans_of_module(
+                &module_path,
+                &file_path,
+                &crate_name,
+                &submodule_names,
+                linked_files,
+            ) {
                 callback(orphan);
             }
         }
 
         for module_hir in submodules {
-            self.process_module(module_hir, callback);
+            self.process_module(module_hir, linked_files, callback);
         }
     }
 }
 
+/// Scans the `krates` as a whole, sorted by file path.
+///
+/// Files loaded by any of the crates (e.g. a library's modules, when scanning its
+/// package's binary alongside it) don't count as orphans of any of the others,
+/// with orphans found by multiple crates getting reported once.
+pub fn scan_all(db: &RootDatabase, krates: &[hir::Crate]) -> anyhow::Result<Vec<Orphan>> {
+    let mut linked_files = HashSet::new();
+    let mut orphans = vec![];
+
+    for krate in krates.iter().cloned() {
+        let mut scanner = Scanner::new(db, krate);
+        linked_files.extend(scanner.linked_files());
+        orphans.extend(scanner.scan()?);
+    }
+
+    orphans.retain(|orphan| !linked_files.contains(&canonicalized(&orphan.file_path)));
+
+    orphans.sort_by_cached_key(|orphan| orphan.file_path.clone());
+    orphans.dedup_by(|lhs, rhs| lhs.file_path == rhs.file_path);
+
+    Ok(orphans)
+}
+
 pub(crate) fn orphans_of_module(
     module_path: &str,
     file_path: &Path,
+    crate_name: &str,
     submodule_names: &HashSet<String>,
+    linked_files: &HashSet<PathBuf>,
 ) -> Vec<Orphan> {
     trace!("Searching for orphans of {module_path:?}");
 
@@ -118,16 +185,69 @@ pub(crate) fn orphans_of_module(
                 return None;
             }
 
+            if linked_files.contains(&canonicalized(&possible_orphan.file_path)) {
+                return None;
+            }
+
             Some(Orphan {
                 name: possible_orphan.name,
                 file_path: possible_orphan.file_path,
                 parent_module_path: module_path.to_owned(),
                 parent_file_path: file_path.to_owned(),
+                crate_name: crate_name.to_owned(),
             })
         })
         .collect()
 }
 
+/// Returns the paths of all files included via `include!("…")` from within the file at `file_path`.
+fn included_files(file_path: &Path) -> Vec<PathBuf> {
+    let Ok(text) = fs::read_to_string(file_path) else {
+        debug!("File {file_path:?} not readable");
+        return vec![];
+    };
+
+    let Some(dir_path) = file_path.parent() else {
+        return vec![];
+    };
+
+    let parse = SourceFile::parse(&text, Edition::CURRENT);
+
+    parse
+        .tree()
+        .syntax()
+        .descendants()
+        .filter_map(ast::MacroCall::cast)
+        .filter(|macro_call| {
+            macro_call
+                .path()
+                .and_then(|path| path.segment())
+                .and_then(|segment| segment.name_ref())
+                .is_some_and(|name_ref| name_ref.text() == "include")
+        })
+        .filter_map(|macro_call| {
+            // Only literal paths can be resolved without expanding the macro:
+            let token_tree = macro_call.token_tree()?;
+            let string = token_tree
+                .syntax()
+                .descendants_with_tokens()
+                .filter_map(|element| element.into_token())
+                .find_map(ast::String::cast)?;
+            let value = string
+                .text()
+                .trim_start_matches('r')
+                .trim_matches('#')
+                .strip_prefix('"')?
+                .strip_suffix('"')?;
+            Some(canonicalized(&dir_path.join(value)))
+        })
+        .collect()
+}
+
+fn canonicalized(path: &Path) -> PathBuf {
+    path.canonicalize().unwrap_or_else(|_| path.to_owned())
+}
+
 fn mod_dir(file_path: &Path) -> Option<PathBuf> {
     let file_stem = file_path.file_stem().and_then(|os_str| os_str.to_str());
     let extension = file_path.extension().and_then(|os_str| os_str.to_str());
@@ -177,6 +297,14 @@ fn possible_orphans_in(read_dir: ReadDir) -> impl Iterator<Item = PossibleOrphan
         let ignored_names = ["lib", "main", "mod"];
         let is_ignored_name = ignored_names.contains(&file_stem);
 
+        // Directories of nested packages (e.g. excluded workspace members)
+        // and target directories (as tagged by cargo) aren't part of the crate:
+        if entry_path.is_dir()
+            && (entry_path.join("Cargo.toml").exists() || entry_path.join("CACHEDIR.TAG").exists())
+        {
+            return None;
+        }
+
         let file_path = if entry_path.is_dir() {
             // If it's a directory, then there might be a 'mod.rs' file within:
             entry_path.join("mod.rs")
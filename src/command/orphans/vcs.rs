@@ -0,0 +1,78 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at https://mozilla.org/MPL/2.0/.
+
+//! Version control status of possible orphans, as consulted via `--vcs-ignore`.
+//!
+//! Files ignored by git (e.g. via `.gitignore`) never count as orphans,
+//! and neither do files that aren't tracked by it yet (e.g. scratch files),
+//! unless asked to via `--include-untracked`.
+
+use std::{
+    path::{Path, PathBuf},
+    process,
+};
+
+#[derive(Clone, Debug)]
+pub(crate) struct VcsFilter {
+    /// The excluded files (and directories, all of whose files are excluded).
+    excluded_paths: Vec<PathBuf>,
+}
+
+impl VcsFilter {
+    /// Queries git for the files to exclude within `dir`,
+    /// returning `None` if `dir` isn't within a git repository.
+    pub(crate) fn new(dir: &Path, include_untracked: bool) -> Option<Self> {
+        let mut excluded_paths = vec![];
+
+        for relative_path in git_ls_files(dir, &["--ignored"])? {
+            excluded_paths.push(dir.join(relative_path));
+        }
+
+        if !include_untracked {
+            for relative_path in git_ls_files(dir, &[])? {
+                excluded_paths.push(dir.join(relative_path));
+            }
+        }
+
+        Some(Self { excluded_paths })
+    }
+
+    pub(crate) fn excludes(&self, file_path: &Path) -> bool {
+        self.excluded_paths
+            .iter()
+            .any(|excluded_path| file_path.starts_with(excluded_path))
+    }
+}
+
+/// Lists the untracked files (and directories) within `dir`, as reported by `git ls-files`,
+/// relative to `dir`.
+fn git_ls_files(dir: &Path, args: &[&str]) -> Option<Vec<String>> {
+    let output = process::Command::new("git")
+        .arg("-C")
+        .arg(dir)
+        .args([
+            "ls-files",
+            "-z",
+            "--others",
+            "--exclude-standard",
+            "--directory",
+        ])
+        .args(args)
+        .output()
+        .ok()?;
+
+    if !output.status.success() {
+        return None;
+    }
+
+    let stdout = String::from_utf8(output.stdout).ok()?;
+
+    Some(
+        stdout
+            .split('\0')
+            .filter(|path| !path.is_empty())
+            .map(|path| path.to_owned())
+            .collect(),
+    )
+}
@@ -0,0 +1,66 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at https://mozilla.org/MPL/2.0/.
+
+use std::{fs, path::PathBuf};
+
+use anyhow::Context as _;
+use clap::Parser;
+use log::trace;
+
+use super::{options::Options, planner::Planner, spec};
+
+#[derive(Parser, Clone, PartialEq, Eq, Debug)]
+pub struct Command {
+    #[command(flatten)]
+    pub options: Options,
+}
+
+impl Command {
+    pub fn new(options: Options) -> Self {
+        Self { options }
+    }
+
+    pub(crate) fn sanitize(&mut self) {}
+
+    #[doc(hidden)]
+    pub fn run(self) -> anyhow::Result<()> {
+        let spec_path = &self.options.spec;
+
+        let text = fs::read_to_string(spec_path)
+            .with_context(|| format!("Could not read spec {spec_path:?}"))?;
+
+        let root = spec::parse(&text)?;
+
+        let root_path = self.root_path();
+
+        trace!("Planning scaffold under {root_path:?} ...");
+
+        let actions = Planner::new(self.options.mod_rs).plan(&root, &root_path)?;
+
+        for action in &actions {
+            println!("{action}");
+
+            if !self.options.dry_run {
+                action.apply()?;
+            }
+        }
+
+        Ok(())
+    }
+
+    fn root_path(&self) -> PathBuf {
+        if let Some(root_path) = &self.options.root {
+            return root_path.clone();
+        }
+
+        let lib_path = PathBuf::from("src/lib.rs");
+        let main_path = PathBuf::from("src/main.rs");
+
+        if !lib_path.exists() && main_path.exists() {
+            main_path
+        } else {
+            lib_path
+        }
+    }
+}
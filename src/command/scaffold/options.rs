@@ -0,0 +1,29 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at https://mozilla.org/MPL/2.0/.
+
+use std::path::PathBuf;
+
+use clap::Parser;
+
+#[derive(Parser, Clone, PartialEq, Eq, Debug)]
+#[group(id = "ScaffoldOptions")]
+pub struct Options {
+    /// The module hierarchy to create, in the tree format printed by `structure`
+    /// (items other than modules are ignored).
+    #[arg(value_name = "SPEC")]
+    pub spec: PathBuf,
+
+    /// The crate's root file to scaffold the modules under
+    /// (defaults to `src/lib.rs`, or `src/main.rs` if there's no library).
+    #[arg(long = "root")]
+    pub root: Option<PathBuf>,
+
+    /// Place modules with sub-modules in `foo/mod.rs` files, instead of `foo.rs`.
+    #[arg(long = "mod-rs")]
+    pub mod_rs: bool,
+
+    /// Print the files that would be created or updated, without touching any of them.
+    #[arg(long = "dry-run")]
+    pub dry_run: bool,
+}
@@ -0,0 +1,199 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at https://mozilla.org/MPL/2.0/.
+
+//! Planning of the files to create (or update) for scaffolding a module hierarchy.
+
+use std::{
+    collections::{HashMap, HashSet},
+    fmt, fs,
+    path::{Path, PathBuf},
+};
+
+use ra_ap_ide::Edition;
+use ra_ap_syntax::{
+    ast::{self, HasModuleItem as _, HasName as _},
+    SourceFile,
+};
+
+use crate::output;
+
+use super::spec::ModuleSpec;
+
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub(crate) enum Action {
+    /// Create a new module file, declaring its sub-modules.
+    Create {
+        path: PathBuf,
+        declarations: Vec<String>,
+    },
+    /// Append the declarations of missing sub-modules to an existing module file.
+    Declare {
+        path: PathBuf,
+        declarations: Vec<String>,
+    },
+}
+
+impl Action {
+    pub(crate) fn apply(&self) -> anyhow::Result<()> {
+        match self {
+            Self::Create { path, declarations } => {
+                if let Some(dir_path) = path.parent() {
+                    fs::create_dir_all(dir_path)?;
+                }
+
+                let contents: String = declarations
+                    .iter()
+                    .map(|declaration| format!("{declaration}\n"))
+                    .collect();
+
+                output::write_atomically(path, contents)
+            }
+            Self::Declare { path, declarations } => {
+                let mut contents = fs::read_to_string(path)?;
+
+                if !contents.is_empty() && !contents.ends_with('\n') {
+                    contents.push('\n');
+                }
+
+                for declaration in declarations {
+                    contents.push_str(&format!("{declaration}\n"));
+                }
+
+                output::write_atomically(path, contents)
+            }
+        }
+    }
+}
+
+impl fmt::Display for Action {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Create { path, .. } => write!(f, "create {}", path.display()),
+            Self::Declare { path, declarations } => {
+                write!(f, "update {}: {}", path.display(), declarations.join(" "))
+            }
+        }
+    }
+}
+
+#[derive(Debug)]
+pub(crate) struct Planner {
+    mod_rs: bool,
+}
+
+impl Planner {
+    pub(crate) fn new(mod_rs: bool) -> Self {
+        Self { mod_rs }
+    }
+
+    /// Returns the actions for scaffolding the root module's sub-modules under `root_path`
+    /// (in depth-first order), skipping any modules that already exist.
+    pub(crate) fn plan(&self, root: &ModuleSpec, root_path: &Path) -> anyhow::Result<Vec<Action>> {
+        let dir_path = root_path.parent().unwrap_or(Path::new(""));
+
+        let mut actions = vec![];
+        self.plan_module(root, root_path, dir_path, &mut actions)?;
+
+        Ok(actions)
+    }
+
+    fn plan_module(
+        &self,
+        module: &ModuleSpec,
+        file_path: &Path,
+        dir_path: &Path,
+        actions: &mut Vec<Action>,
+    ) -> anyhow::Result<()> {
+        // Inline modules (i.e. `mod foo { … }`) have no files of their own to scaffold into:
+        let mut inline = HashSet::new();
+
+        if file_path.exists() {
+            let declared = declared_modules(file_path)?;
+
+            inline.extend(
+                declared
+                    .iter()
+                    .filter(|(_, is_inline)| **is_inline)
+                    .map(|(name, _)| name.clone()),
+            );
+
+            let declarations: Vec<String> = module
+                .children
+                .iter()
+                .filter(|child| !declared.contains_key(child.file_stem()))
+                .map(ModuleSpec::declaration)
+                .collect();
+
+            if !declarations.is_empty() {
+                actions.push(Action::Declare {
+                    path: file_path.to_owned(),
+                    declarations,
+                });
+            }
+        } else {
+            actions.push(Action::Create {
+                path: file_path.to_owned(),
+                declarations: module
+                    .children
+                    .iter()
+                    .map(ModuleSpec::declaration)
+                    .collect(),
+            });
+        }
+
+        for child in &module.children {
+            if inline.contains(child.file_stem()) {
+                continue;
+            }
+
+            let (child_file_path, child_dir_path) = self.child_paths(child, dir_path);
+            self.plan_module(child, &child_file_path, &child_dir_path, actions)?;
+        }
+
+        Ok(())
+    }
+
+    /// Returns the paths of the child's file and of the directory holding its own sub-modules,
+    /// preferring any already existing file (i.e. `foo.rs` or `foo/mod.rs`).
+    fn child_paths(&self, child: &ModuleSpec, dir_path: &Path) -> (PathBuf, PathBuf) {
+        let stem = child.file_stem();
+
+        let child_dir_path = dir_path.join(stem);
+
+        let file_path = dir_path.join(format!("{stem}.rs"));
+        let mod_rs_file_path = child_dir_path.join("mod.rs");
+
+        let file_path = if file_path.exists() {
+            file_path
+        } else if mod_rs_file_path.exists() || (self.mod_rs && !child.children.is_empty()) {
+            mod_rs_file_path
+        } else {
+            file_path
+        };
+
+        (file_path, child_dir_path)
+    }
+}
+
+/// Returns the names of the modules declared at the top level of the file (e.g. `mod foo;`),
+/// along with whether they are declared inline.
+fn declared_modules(file_path: &Path) -> anyhow::Result<HashMap<String, bool>> {
+    let text = fs::read_to_string(file_path)?;
+
+    let parse = SourceFile::parse(&text, Edition::CURRENT);
+
+    let names = parse
+        .tree()
+        .items()
+        .filter_map(|item| match item {
+            ast::Item::Module(module) => {
+                let name = module.name()?.text().trim_start_matches("r#").to_owned();
+                Some((name, module.item_list().is_some()))
+            }
+            _ => None,
+        })
+        .collect();
+
+    Ok(names)
+}
@@ -0,0 +1,144 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at https://mozilla.org/MPL/2.0/.
+
+//! Parsing of module hierarchies, as printed by `structure`:
+//!
+//! ```plain
+//! crate my_crate
+//! └── mod net: pub
+//!     ├── mod http: pub
+//!     └── mod tcp: pub(crate)
+//! ```
+
+/// A module to be scaffolded, along with its sub-modules.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub(crate) struct ModuleSpec {
+    pub(crate) name: String,
+    /// The module's visibility (e.g. `pub(crate)`), if other than private.
+    pub(crate) visibility: Option<String>,
+    pub(crate) children: Vec<ModuleSpec>,
+}
+
+impl ModuleSpec {
+    fn new(name: String, visibility: Option<String>) -> Self {
+        Self {
+            name,
+            visibility,
+            children: vec![],
+        }
+    }
+
+    /// Returns the declaration of the module within its parent (e.g. `pub mod net;`).
+    pub(crate) fn declaration(&self) -> String {
+        match &self.visibility {
+            Some(visibility) => format!("{visibility} mod {};", self.name),
+            None => format!("mod {};", self.name),
+        }
+    }
+
+    /// Returns the name of the module's file (e.g. `r#unsafe` is stored in `unsafe.rs`).
+    pub(crate) fn file_stem(&self) -> &str {
+        self.name.trim_start_matches("r#")
+    }
+}
+
+/// Parses the spec into the (unnamed) crate root module and its sub-modules,
+/// ignoring any items other than modules.
+pub(crate) fn parse(text: &str) -> anyhow::Result<ModuleSpec> {
+    // The crate root sits at level 0, with its modules starting at level 1
+    // (even if the spec omits the `crate …` line):
+    let mut stack: Vec<(usize, ModuleSpec)> = vec![(0, ModuleSpec::new(String::new(), None))];
+
+    for (line_idx, line) in text.lines().enumerate() {
+        let Some((depth, module)) = parse_line(line) else {
+            continue;
+        };
+
+        let Some(module) = module else {
+            // The `crate …` line itself:
+            continue;
+        };
+
+        let level = depth + 1;
+
+        while stack.len() > 1
+            && stack
+                .last()
+                .is_some_and(|(top_level, _)| *top_level >= level)
+        {
+            pop_into_parent(&mut stack);
+        }
+
+        if module.name.is_empty() {
+            anyhow::bail!("Expected a module name on line {}: {line:?}", line_idx + 1);
+        }
+
+        stack.push((level, module));
+    }
+
+    while stack.len() > 1 {
+        pop_into_parent(&mut stack);
+    }
+
+    let (_, root) = stack.pop().unwrap();
+
+    if root.children.is_empty() {
+        anyhow::bail!("No modules found in spec (e.g. `└── mod net: pub`)");
+    }
+
+    Ok(root)
+}
+
+fn pop_into_parent(stack: &mut Vec<(usize, ModuleSpec)>) {
+    let (_, module) = stack.pop().unwrap();
+    let (_, parent) = stack.last_mut().unwrap();
+    parent.children.push(module);
+}
+
+/// Returns the line's depth along with its module (or `None` for the `crate …` line),
+/// or `None` altogether if the line doesn't declare a module.
+fn parse_line(line: &str) -> Option<(usize, Option<ModuleSpec>)> {
+    const TREE_CHARS: [char; 5] = [' ', '│', '├', '└', '─'];
+
+    let content = line.trim_start_matches(TREE_CHARS);
+
+    // Each level of the tree is indented by four characters (e.g. `│   ` or `├── `):
+    let depth = (line.chars().count() - content.chars().count()) / 4;
+
+    // Skip any icons (e.g. `[m]`) and attributes (e.g. `#[deprecated]`) preceding the keyword:
+    let mut words = content.split_whitespace();
+    let keyword = words.find(|word| matches!(*word, "mod" | "crate"))?;
+
+    if keyword == "crate" {
+        return Some((depth, None));
+    }
+
+    let rest = content
+        .split_once(" mod ")
+        .map_or(content, |(_, rest)| rest);
+    let rest = rest.strip_prefix("mod ").unwrap_or(rest);
+
+    let (name, visibility) = match rest.split_once(':') {
+        Some((name, suffix)) => (name, Some(suffix)),
+        None => (rest.split_whitespace().next().unwrap_or_default(), None),
+    };
+
+    // Drop any trailing annotations (e.g. ` @ src/foo.rs`, ` // docs` or ` (3 fns)`):
+    let visibility = visibility
+        .map(|suffix| {
+            [" @ ", " // ", " ("]
+                .iter()
+                .fold(suffix, |suffix, separator| {
+                    suffix.split(separator).next().unwrap_or_default()
+                })
+                .trim()
+        })
+        .filter(|visibility| visibility.starts_with("pub") && *visibility != "pub(self)")
+        .map(str::to_owned);
+
+    Some((
+        depth,
+        Some(ModuleSpec::new(name.trim().to_owned(), visibility)),
+    ))
+}
@@ -0,0 +1,38 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at https://mozilla.org/MPL/2.0/.
+
+use clap::Parser;
+
+use super::options::{Options, SchemaKind};
+
+// The schemas' `$id`s carry the version of their output format,
+// which needs to be bumped on any incompatible change:
+const GRAPH_SCHEMA: &str = include_str!("graph.schema.json");
+const TREE_SCHEMA: &str = include_str!("tree.schema.json");
+
+#[derive(Parser, Clone, PartialEq, Eq, Debug)]
+pub struct Command {
+    #[command(flatten)]
+    pub options: Options,
+}
+
+impl Command {
+    pub fn new(options: Options) -> Self {
+        Self { options }
+    }
+
+    pub(crate) fn sanitize(&mut self) {}
+
+    #[doc(hidden)]
+    pub fn run(self) -> anyhow::Result<()> {
+        let schema = match self.options.kind {
+            SchemaKind::Graph => GRAPH_SCHEMA,
+            SchemaKind::Tree => TREE_SCHEMA,
+        };
+
+        print!("{schema}");
+
+        Ok(())
+    }
+}
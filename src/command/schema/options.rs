@@ -0,0 +1,44 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at https://mozilla.org/MPL/2.0/.
+
+use std::{fmt::Display, str::FromStr};
+
+use clap::Parser;
+
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum SchemaKind {
+    Graph,
+    Tree,
+}
+
+impl FromStr for SchemaKind {
+    type Err = &'static str;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "graph" => Ok(Self::Graph),
+            "tree" => Ok(Self::Tree),
+            _ => Err("Unrecognized schema"),
+        }
+    }
+}
+
+impl Display for SchemaKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            Self::Graph => "graph",
+            Self::Tree => "tree",
+        })
+    }
+}
+
+#[derive(Parser, Clone, PartialEq, Eq, Debug)]
+#[group(id = "SchemaOptions")]
+pub struct Options {
+    /// The output to print the schema of
+    /// (e.g. graph, as served by `serve` and `daemon`'s `subgraph`,
+    /// or tree, as returned by `daemon`'s `tree`).
+    #[arg(value_name = "OUTPUT")]
+    pub kind: SchemaKind,
+}
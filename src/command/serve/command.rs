@@ -0,0 +1,72 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at https://mozilla.org/MPL/2.0/.
+
+use std::{
+    sync::{Arc, RwLock},
+    time::Duration,
+};
+
+use clap::Parser;
+use log::trace;
+
+use crate::{analyzer::LoadOptions, command::watch::command::watch_project};
+
+use super::{
+    export::graph_json,
+    options::Options,
+    server::{self, Snapshot},
+};
+
+#[derive(Parser, Clone, PartialEq, Eq, Debug)]
+pub struct Command {
+    #[command(flatten)]
+    pub options: Options,
+}
+
+impl Command {
+    pub fn new(options: Options) -> Self {
+        Self { options }
+    }
+
+    pub(crate) fn sanitize(&mut self) {}
+
+    #[doc(hidden)]
+    pub fn run(self) -> anyhow::Result<()> {
+        let snapshot = Arc::new(RwLock::new(Snapshot {
+            generation: 0,
+            graph: Err("Analyzing project ...".to_owned()),
+        }));
+
+        server::spawn(&self.options.address, snapshot.clone())?;
+
+        eprintln!("Serving viewer on http://{}/", self.options.address);
+
+        let load_options = self.load_options();
+        let debounce = Duration::from_millis(self.options.debounce);
+
+        watch_project(
+            &self.options.general,
+            &self.options.project,
+            &load_options,
+            debounce,
+            |krates, host| {
+                trace!("Updating graph ...");
+
+                let graph =
+                    graph_json(krates, host.raw_database()).map_err(|err| format!("{err:?}"));
+
+                let mut snapshot = snapshot.write().unwrap();
+                snapshot.generation += 1;
+                snapshot.graph = graph;
+            },
+        )
+    }
+
+    pub fn load_options(&self) -> LoadOptions {
+        LoadOptions {
+            cfg_test: self.options.cfg_test,
+            sysroot: false,
+        }
+    }
+}
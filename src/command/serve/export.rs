@@ -0,0 +1,155 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at https://mozilla.org/MPL/2.0/.
+
+//! Export of the dependency graph as JSON, as consumed by the viewer.
+
+use std::collections::HashMap;
+
+use json::{object, JsonValue};
+use log::trace;
+use petgraph::graph::NodeIndex;
+use ra_ap_hir as hir;
+use ra_ap_ide::RootDatabase;
+
+use crate::{
+    analyzer,
+    command::dependencies::theme::node_styles,
+    graph::{Edge, Graph, GraphBuilder, Node, Relationship},
+    item::ItemVisibility,
+};
+
+pub(super) fn graph_json(krates: &[hir::Crate], db: &RootDatabase) -> anyhow::Result<String> {
+    trace!("Building graph ...");
+
+    let builder = GraphBuilder::new(db, krates);
+    let (graph, crate_node_idxs) = builder.build()?;
+
+    trace!("Exporting graph ...");
+
+    let json = graph_to_json(&graph, &crate_node_idxs, krates, db)?;
+
+    Ok(json.dump())
+}
+
+pub(crate) fn graph_to_json(
+    graph: &Graph<Node, Edge>,
+    root_idxs: &[NodeIndex],
+    krates: &[hir::Crate],
+    db: &RootDatabase,
+) -> anyhow::Result<JsonValue> {
+    // Nodes and edges are sorted (by path, then ID), rather than ordered by their indices,
+    // so that exports of the same code are identical across runs:
+    let mut node_idxs: Vec<NodeIndex> = graph.node_indices().collect();
+    node_idxs.sort_by_cached_key(|node_idx| {
+        let node = &graph[*node_idx];
+        (node.display_path(db), node.stable_id(db))
+    });
+
+    // Nodes are referenced by their position within `nodes`,
+    // since the graph's node indices are not guaranteed to be contiguous:
+    let positions: HashMap<NodeIndex, usize> = node_idxs
+        .iter()
+        .enumerate()
+        .map(|(position, node_idx)| (*node_idx, position))
+        .collect();
+
+    let mut nodes = JsonValue::new_array();
+
+    for node_idx in node_idxs {
+        let node = &graph[node_idx];
+
+        let is_external =
+            analyzer::krate(node.hir, db).map_or(true, |krate| !krates.contains(&krate));
+
+        let mut node_json = object! {
+            id: node.stable_id(db),
+            name: node.display_name(db),
+            path: node.display_path(db),
+            kind: node.kind_display_name(db).to_string(),
+            visibility: node.visibility(db).to_string(),
+            color: node_color(node, db),
+            external: is_external,
+        };
+
+        // Only modules (of the analyzed crates) rust-analyzer failed to fully resolve
+        // get to list their problems:
+        let resolution_errors = node.resolution_errors(db);
+
+        if !is_external && !resolution_errors.is_empty() {
+            node_json["unresolved"] = resolution_errors.into();
+        }
+
+        nodes.push(node_json)?;
+    }
+
+    let mut edge_idxs: Vec<_> = graph.edge_indices().collect();
+    edge_idxs.sort_by_cached_key(|edge_idx| {
+        let (source_idx, target_idx) = graph.edge_endpoints(*edge_idx).unwrap();
+        let kind = graph[*edge_idx].display_name();
+        (positions[&source_idx], positions[&target_idx], kind)
+    });
+
+    let mut edges = JsonValue::new_array();
+
+    for edge_idx in edge_idxs {
+        let (source_idx, target_idx) = graph.edge_endpoints(edge_idx).unwrap();
+        let edge = &graph[edge_idx];
+
+        let mut edge_json = object! {
+            source: positions[&source_idx],
+            target: positions[&target_idx],
+            kind: edge.display_name(),
+            weight: edge.weight,
+            test_only: edge.test_only,
+        };
+
+        // Link "uses" (and "re-exports") edges back to the code they stem from:
+        if edge.kind != Relationship::Owns {
+            let mut spans = JsonValue::new_array();
+
+            for (file_path, line, column) in
+                analyzer::reference_locations(graph[source_idx].hir, &edge.targets, db)
+            {
+                spans.push(object! {
+                    file: file_path.to_string_lossy().into_owned(),
+                    line: line,
+                    column: column,
+                })?;
+            }
+
+            edge_json["spans"] = spans;
+        }
+
+        edges.push(edge_json)?;
+    }
+
+    let roots: Vec<usize> = root_idxs
+        .iter()
+        .map(|node_idx| positions[node_idx])
+        .collect();
+
+    Ok(object! {
+        roots: roots,
+        nodes: nodes,
+        edges: edges,
+    })
+}
+
+fn node_color(node: &Node, db: &RootDatabase) -> String {
+    let styles = node_styles();
+
+    let style = if analyzer::moduledef_is_crate(node.hir, db) {
+        styles.krate
+    } else {
+        match &node.visibility(db) {
+            ItemVisibility::Crate => styles.visibility.pub_crate,
+            ItemVisibility::Module(_) => styles.visibility.pub_module,
+            ItemVisibility::Private => styles.visibility.pub_private,
+            ItemVisibility::Public => styles.visibility.pub_global,
+            ItemVisibility::Super => styles.visibility.pub_super,
+        }
+    };
+
+    style.fill_color.to_string()
+}
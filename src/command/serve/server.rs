@@ -0,0 +1,67 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at https://mozilla.org/MPL/2.0/.
+
+//! A minimal HTTP server exposing the viewer page and the most recent graph.
+
+use std::{
+    io::Cursor,
+    sync::{Arc, RwLock},
+    thread,
+};
+
+use json::object;
+use log::debug;
+use tiny_http::{Header, Response, Server};
+
+const VIEWER_HTML: &str = include_str!("viewer.html");
+
+#[derive(Debug)]
+pub(super) struct Snapshot {
+    /// Incremented whenever the graph gets re-analyzed.
+    pub generation: u64,
+    pub graph: Result<String, String>,
+}
+
+pub(super) type SharedSnapshot = Arc<RwLock<Snapshot>>;
+
+pub(super) fn spawn(address: &str, snapshot: SharedSnapshot) -> anyhow::Result<()> {
+    let server = Server::http(address)
+        .map_err(|err| anyhow::anyhow!("Could not listen on {address}: {err}"))?;
+
+    thread::spawn(move || {
+        for request in server.incoming_requests() {
+            let response = respond_to(request.url(), &snapshot);
+
+            if let Err(err) = request.respond(response) {
+                debug!("Could not respond to request: {err}");
+            }
+        }
+    });
+
+    Ok(())
+}
+
+fn respond_to(url: &str, snapshot: &SharedSnapshot) -> Response<Cursor<Vec<u8>>> {
+    let path = url.split('?').next().unwrap_or_default();
+
+    let snapshot = snapshot.read().unwrap();
+
+    match path {
+        "/" | "/index.html" => response(VIEWER_HTML, "text/html; charset=utf-8"),
+        "/graph.json" => match &snapshot.graph {
+            Ok(graph) => response(graph.as_str(), "application/json"),
+            Err(err) => {
+                let error = object! { error: err.as_str() };
+                response(error.dump(), "application/json").with_status_code(500)
+            }
+        },
+        "/generation" => response(snapshot.generation.to_string(), "text/plain"),
+        _ => response("Not Found", "text/plain").with_status_code(404),
+    }
+}
+
+fn response(body: impl Into<String>, content_type: &str) -> Response<Cursor<Vec<u8>>> {
+    let header = Header::from_bytes("Content-Type", content_type).unwrap();
+    Response::from_string(body).with_header(header)
+}
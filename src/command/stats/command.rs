@@ -0,0 +1,168 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at https://mozilla.org/MPL/2.0/.
+
+use clap::Parser;
+use json::JsonValue;
+use log::trace;
+use petgraph::graph::NodeIndex;
+use ra_ap_hir as hir;
+use ra_ap_ide::RootDatabase;
+
+use crate::{
+    analyzer::LoadOptions,
+    graph::{Edge, Graph, GraphBuilder, Node},
+    output,
+    progress::{self, Stage},
+    quickfix, summary,
+};
+
+use super::{
+    dependencies::ModuleDependencies,
+    options::{Options, StatsFormat, StatsReport},
+    summary::Summary,
+    teams::TeamMatrix,
+};
+
+#[derive(Parser, Clone, PartialEq, Eq, Debug)]
+pub struct Command {
+    #[command(flatten)]
+    pub options: Options,
+}
+
+impl Command {
+    pub fn new(options: Options) -> Self {
+        Self { options }
+    }
+
+    pub(crate) fn sanitize(&mut self) {}
+
+    #[doc(hidden)]
+    pub fn run(self, krates: &[hir::Crate], db: &RootDatabase) -> anyhow::Result<()> {
+        trace!("Building graph ...");
+        progress::stage(Stage::Building);
+
+        let (graph, crate_node_idxs) = GraphBuilder::new(db, krates).build()?;
+
+        let output = match self.options.report {
+            StatsReport::Summary => self.summaries(krates, &graph, &crate_node_idxs, db)?,
+            StatsReport::Dependencies => self.dependencies(krates, &graph, db)?,
+            StatsReport::Teams => self.teams(krates, &graph, db)?,
+        };
+
+        match &self.options.output {
+            Some(output_path) => output::write_atomically(output_path, output),
+            None => {
+                print!("{output}");
+                Ok(())
+            }
+        }
+    }
+
+    fn summaries(
+        &self,
+        krates: &[hir::Crate],
+        graph: &Graph<Node, Edge>,
+        crate_node_idxs: &[NodeIndex],
+        db: &RootDatabase,
+    ) -> anyhow::Result<String> {
+        let summaries = krates
+            .iter()
+            .zip(crate_node_idxs)
+            .map(|(krate, crate_node_idx)| Summary::new(*krate, graph, *crate_node_idx, db))
+            .collect::<anyhow::Result<Vec<_>>>()?;
+
+        summary::record_orphans(summaries.iter().map(Summary::orphans).sum());
+
+        trace!("Printing statistics ...");
+        progress::stage(Stage::Printing);
+
+        let output = match self.options.format {
+            StatsFormat::Table => summaries
+                .iter()
+                .map(Summary::to_string)
+                .collect::<Vec<_>>()
+                .join("\n"),
+            StatsFormat::Json => {
+                let json: Vec<JsonValue> = summaries.iter().map(Summary::to_json).collect();
+                format!("{}\n", JsonValue::from(json).pretty(2))
+            }
+            StatsFormat::Csv => {
+                anyhow::bail!("Format `csv` is only supported by the `teams` report")
+            }
+        };
+
+        Ok(output)
+    }
+
+    fn dependencies(
+        &self,
+        krates: &[hir::Crate],
+        graph: &Graph<Node, Edge>,
+        db: &RootDatabase,
+    ) -> anyhow::Result<String> {
+        let modules = ModuleDependencies::collect(
+            krates,
+            graph,
+            self.options.top,
+            self.options.focus_on.as_deref(),
+            db,
+        )?;
+
+        trace!("Printing dependencies ...");
+        progress::stage(Stage::Printing);
+
+        let output = match self.options.format {
+            StatsFormat::Table => modules
+                .iter()
+                .map(ModuleDependencies::to_string)
+                .collect::<Vec<_>>()
+                .join("\n"),
+            StatsFormat::Json => {
+                let json: Vec<JsonValue> =
+                    modules.iter().map(ModuleDependencies::to_json).collect();
+                format!("{}\n", JsonValue::from(json).pretty(2))
+            }
+            StatsFormat::Csv => {
+                anyhow::bail!("Format `csv` is only supported by the `teams` report")
+            }
+        };
+
+        Ok(output)
+    }
+
+    fn teams(
+        &self,
+        krates: &[hir::Crate],
+        graph: &Graph<Node, Edge>,
+        db: &RootDatabase,
+    ) -> anyhow::Result<String> {
+        let project_dir = quickfix::project_dir(&self.options.project);
+
+        let matrix = TeamMatrix::collect(
+            krates,
+            graph,
+            &self.options.project.manifest_path,
+            &project_dir,
+            db,
+        )?;
+
+        trace!("Printing teams ...");
+        progress::stage(Stage::Printing);
+
+        let output = match self.options.format {
+            StatsFormat::Table => matrix.to_string(),
+            StatsFormat::Json => format!("{}\n", matrix.to_json().pretty(2)),
+            StatsFormat::Csv => matrix.to_csv(),
+        };
+
+        Ok(output)
+    }
+
+    pub fn load_options(&self) -> LoadOptions {
+        LoadOptions {
+            cfg_test: self.options.cfg_test,
+            sysroot: false,
+        }
+    }
+}
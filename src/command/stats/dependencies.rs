@@ -0,0 +1,157 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at https://mozilla.org/MPL/2.0/.
+
+//! The heaviest dependencies of each module, as printed by `stats dependencies`.
+//!
+//! Each "uses" edge gets attributed to the modules containing its source and target,
+//! with a module's dependencies on other modules ranked by the number of distinct items they're used for.
+
+use std::{
+    collections::{BTreeMap, HashMap, HashSet},
+    fmt,
+};
+
+use json::{object, JsonValue};
+use petgraph::visit::{EdgeRef as _, IntoEdgeReferences as _};
+use ra_ap_hir as hir;
+use ra_ap_ide::RootDatabase;
+
+use crate::{
+    analyzer,
+    graph::{Edge, Graph, Node, Relationship},
+};
+
+/// The (top-ranked) dependencies of a single module.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub(crate) struct ModuleDependencies {
+    /// The module's path (e.g. `my_crate::api`).
+    module: String,
+    /// The paths of the modules depended on, along with the number of their items used,
+    /// ranked by descending number of items.
+    dependencies: Vec<(String, usize)>,
+    /// The number of further dependencies, beyond the top-ranked ones.
+    omitted: usize,
+}
+
+impl ModuleDependencies {
+    /// Collects the dependencies of the `krates`' modules, keeping the `top` heaviest ones of each.
+    ///
+    /// Only the module at `focus_on` (if any) gets reported.
+    pub(crate) fn collect(
+        krates: &[hir::Crate],
+        graph: &Graph<Node, Edge>,
+        top: usize,
+        focus_on: Option<&str>,
+        db: &RootDatabase,
+    ) -> anyhow::Result<Vec<Self>> {
+        // The distinct items used, by (source module, target module):
+        let mut used_items: HashMap<(hir::Module, hir::Module), HashSet<hir::ModuleDef>> =
+            HashMap::new();
+
+        for edge_ref in graph.edge_references() {
+            if edge_ref.weight().kind != Relationship::Uses {
+                continue;
+            }
+
+            let source = graph[edge_ref.source()].hir;
+            let target = graph[edge_ref.target()].hir;
+
+            let (Some(source_module), Some(target_module)) =
+                (analyzer::module(source, db), analyzer::module(target, db))
+            else {
+                continue;
+            };
+
+            if source_module == target_module || !krates.contains(&source_module.krate()) {
+                continue;
+            }
+
+            used_items
+                .entry((source_module, target_module))
+                .or_default()
+                .insert(target);
+        }
+
+        let mut dependencies_by_module: BTreeMap<String, Vec<(String, usize)>> = BTreeMap::new();
+
+        for ((source_module, target_module), items) in used_items {
+            let module_path = analyzer::display_path(hir::ModuleDef::Module(source_module), db);
+            let dependency_path = analyzer::display_path(hir::ModuleDef::Module(target_module), db);
+
+            dependencies_by_module
+                .entry(module_path)
+                .or_default()
+                .push((dependency_path, items.len()));
+        }
+
+        if let Some(focus_on) = focus_on {
+            let is_known = graph.node_indices().any(|node_idx| {
+                let hir = graph[node_idx].hir;
+                matches!(hir, hir::ModuleDef::Module(_))
+                    && analyzer::path(hir, db).as_deref() == Some(focus_on)
+            });
+
+            if !is_known {
+                anyhow::bail!("No module found for path `{focus_on}`");
+            }
+
+            dependencies_by_module.retain(|module_path, _| module_path == focus_on);
+        }
+
+        let modules = dependencies_by_module
+            .into_iter()
+            .map(|(module, mut dependencies)| {
+                // Sort by descending number of items, then by path for stable output:
+                dependencies.sort_by(|lhs, rhs| rhs.1.cmp(&lhs.1).then(lhs.0.cmp(&rhs.0)));
+
+                let omitted = dependencies.len().saturating_sub(top);
+                dependencies.truncate(top);
+
+                Self {
+                    module,
+                    dependencies,
+                    omitted,
+                }
+            })
+            .collect();
+
+        Ok(modules)
+    }
+
+    pub(crate) fn to_json(&self) -> JsonValue {
+        let dependencies: Vec<JsonValue> = self
+            .dependencies
+            .iter()
+            .map(|(module, items)| {
+                object! {
+                    "module": module.as_str(),
+                    "items": *items,
+                }
+            })
+            .collect();
+
+        object! {
+            "module": self.module.as_str(),
+            "dependencies": dependencies,
+            "omitted": self.omitted,
+        }
+    }
+}
+
+impl fmt::Display for ModuleDependencies {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "module {}", self.module)?;
+        writeln!(f, "  {:>5}  dependency", "items")?;
+
+        for (module, items) in &self.dependencies {
+            writeln!(f, "  {items:>5}  {module}")?;
+        }
+
+        if self.omitted > 0 {
+            writeln!(f, "  ... and {} more", self.omitted)?;
+        }
+
+        Ok(())
+    }
+}
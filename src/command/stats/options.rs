@@ -0,0 +1,113 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at https://mozilla.org/MPL/2.0/.
+
+use std::{fmt::Display, path::PathBuf, str::FromStr};
+
+use clap::Parser;
+
+use crate::options::{GeneralOptions, ProjectOptions};
+
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum StatsFormat {
+    /// A human-readable table.
+    Table,
+    /// A JSON array, with one object per crate.
+    Json,
+    /// Comma-separated values (for the `teams` report).
+    Csv,
+}
+
+impl FromStr for StatsFormat {
+    type Err = &'static str;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "table" => Ok(Self::Table),
+            "json" => Ok(Self::Json),
+            "csv" => Ok(Self::Csv),
+            _ => Err("Unrecognized format"),
+        }
+    }
+}
+
+impl Display for StatsFormat {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            Self::Table => "table",
+            Self::Json => "json",
+            Self::Csv => "csv",
+        })
+    }
+}
+
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum StatsReport {
+    /// A structural summary of each crate.
+    Summary,
+    /// The heaviest dependencies of each module.
+    Dependencies,
+    /// The number of items each team's modules use of each other team's modules.
+    Teams,
+}
+
+impl FromStr for StatsReport {
+    type Err = &'static str;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "summary" => Ok(Self::Summary),
+            "dependencies" => Ok(Self::Dependencies),
+            "teams" => Ok(Self::Teams),
+            _ => Err("Unrecognized report"),
+        }
+    }
+}
+
+impl Display for StatsReport {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            Self::Summary => "summary",
+            Self::Dependencies => "dependencies",
+            Self::Teams => "teams",
+        })
+    }
+}
+
+#[derive(Parser, Clone, PartialEq, Eq, Debug)]
+#[group(id = "StatsOptions")]
+pub struct Options {
+    #[command(flatten)]
+    pub general: GeneralOptions,
+
+    #[command(flatten)]
+    pub project: ProjectOptions,
+
+    /// The statistics to print
+    /// (e.g. summary, dependencies, teams).
+    #[arg(value_name = "REPORT", default_value = "summary")]
+    pub report: StatsReport,
+
+    /// The number of heaviest dependencies to list per module
+    /// (for the `dependencies` report).
+    #[arg(long = "top", default_value = "10")]
+    pub top: usize,
+
+    /// List only the dependencies of the module at this path
+    /// (for the `dependencies` report).
+    #[arg(long = "focus-on")]
+    pub focus_on: Option<String>,
+
+    /// The format to print the statistics in
+    /// (e.g. table, json, csv).
+    #[arg(long = "format", default_value = "table")]
+    pub format: StatsFormat,
+
+    /// Analyze with `#[cfg(test)]` enabled (i.e as if built via `cargo test`).
+    #[arg(long = "cfg-test")]
+    pub cfg_test: bool,
+
+    /// Write the output to this file (atomically replacing it) instead of printing it.
+    #[arg(short = 'o', long = "output")]
+    pub output: Option<PathBuf>,
+}
@@ -0,0 +1,191 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at https://mozilla.org/MPL/2.0/.
+
+//! Structural statistics of a crate, as printed by `stats`.
+
+use std::{
+    collections::{BTreeMap, HashSet, VecDeque},
+    fmt,
+};
+
+use json::{object, JsonValue};
+use petgraph::{
+    algo::tarjan_scc,
+    graph::NodeIndex,
+    visit::{EdgeRef as _, IntoEdgeReferences as _},
+    Direction,
+};
+use ra_ap_hir as hir;
+use ra_ap_ide::RootDatabase;
+
+use crate::{
+    analyzer,
+    command::orphans::scanner::Scanner,
+    graph::{Edge, Graph, Node, Relationship},
+};
+
+/// The edge kinds to report (even if the crate has no edges of a kind).
+const EDGE_KINDS: [Relationship; 3] = [
+    Relationship::Owns,
+    Relationship::Uses,
+    Relationship::ReExports,
+];
+
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub(crate) struct Summary {
+    /// The crate's name.
+    name: String,
+    /// The number of modules per depth (with the crate's root module at depth 0).
+    modules_by_depth: Vec<usize>,
+    /// The number of edges originating from the crate's nodes, per kind.
+    edges: BTreeMap<&'static str, usize>,
+    /// The number of orphaned source files.
+    orphans: usize,
+    /// The number of cycles (i.e. strongly connected components of more than a single node).
+    cycles: usize,
+    /// The number of nodes of the largest strongly connected component.
+    largest_scc: usize,
+}
+
+impl Summary {
+    /// Returns the number of orphaned source files.
+    pub(crate) fn orphans(&self) -> usize {
+        self.orphans
+    }
+
+    pub(crate) fn new(
+        krate: hir::Crate,
+        graph: &Graph<Node, Edge>,
+        crate_node_idx: NodeIndex,
+        db: &RootDatabase,
+    ) -> anyhow::Result<Self> {
+        let name = analyzer::crate_name(krate, db);
+
+        let crate_node_idxs: HashSet<NodeIndex> = graph
+            .node_indices()
+            .filter(|node_idx| analyzer::krate(graph[*node_idx].hir, db) == Some(krate))
+            .collect();
+
+        let modules_by_depth = Self::modules_by_depth(graph, crate_node_idx);
+
+        let mut edges: BTreeMap<&'static str, usize> = EDGE_KINDS
+            .iter()
+            .map(|kind| (kind.display_name(), 0))
+            .collect();
+
+        for edge_ref in graph.edge_references() {
+            if crate_node_idxs.contains(&edge_ref.source()) {
+                *edges
+                    .entry(edge_ref.weight().kind.display_name())
+                    .or_default() += 1;
+            }
+        }
+
+        let orphans = Scanner::new(db, krate).scan()?.len();
+
+        let sccs: Vec<Vec<NodeIndex>> = tarjan_scc(graph)
+            .into_iter()
+            .filter(|scc| {
+                scc.iter()
+                    .any(|node_idx| crate_node_idxs.contains(node_idx))
+            })
+            .filter(|scc| scc.len() > 1 || graph.find_edge(scc[0], scc[0]).is_some())
+            .collect();
+
+        let cycles = sccs.len();
+        let largest_scc = sccs.iter().map(Vec::len).max().unwrap_or(0);
+
+        Ok(Self {
+            name,
+            modules_by_depth,
+            edges,
+            orphans,
+            cycles,
+            largest_scc,
+        })
+    }
+
+    fn modules_by_depth(graph: &Graph<Node, Edge>, crate_node_idx: NodeIndex) -> Vec<usize> {
+        let mut modules_by_depth: Vec<usize> = vec![];
+
+        // Walk the crate's module tree breadth-first, following its "owns" edges:
+        let mut queue = VecDeque::from([(crate_node_idx, 0)]);
+
+        while let Some((node_idx, depth)) = queue.pop_front() {
+            if !matches!(graph[node_idx].hir, hir::ModuleDef::Module(_)) {
+                continue;
+            }
+
+            if modules_by_depth.len() <= depth {
+                modules_by_depth.resize(depth + 1, 0);
+            }
+
+            modules_by_depth[depth] += 1;
+
+            let children = graph
+                .edges_directed(node_idx, Direction::Outgoing)
+                .filter(|edge_ref| edge_ref.weight().kind == Relationship::Owns)
+                .map(|edge_ref| (edge_ref.target(), depth + 1));
+
+            queue.extend(children);
+        }
+
+        modules_by_depth
+    }
+
+    fn module_count(&self) -> usize {
+        self.modules_by_depth.iter().sum()
+    }
+
+    fn max_depth(&self) -> usize {
+        self.modules_by_depth.len().saturating_sub(1)
+    }
+
+    pub(crate) fn to_json(&self) -> JsonValue {
+        let mut edges = JsonValue::new_object();
+
+        for (kind, count) in &self.edges {
+            edges[*kind] = (*count).into();
+        }
+
+        object! {
+            "crate": self.name.as_str(),
+            "modules": self.module_count(),
+            "max_depth": self.max_depth(),
+            "modules_by_depth": self.modules_by_depth.clone(),
+            "edges": edges,
+            "orphans": self.orphans,
+            "cycles": self.cycles,
+            "largest_scc": self.largest_scc,
+        }
+    }
+}
+
+impl fmt::Display for Summary {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "crate {}", self.name)?;
+
+        writeln!(f, "  Modules:     {:>5}", self.module_count())?;
+        writeln!(f, "  Max depth:   {:>5}", self.max_depth())?;
+
+        writeln!(f, "  Modules by depth:")?;
+
+        for (depth, count) in self.modules_by_depth.iter().enumerate() {
+            writeln!(f, "    {depth:>3}:       {count:>5}")?;
+        }
+
+        let edge_count: usize = self.edges.values().sum();
+        writeln!(f, "  Edges:       {edge_count:>5}")?;
+
+        for (kind, count) in &self.edges {
+            writeln!(f, "    {kind:<9}  {count:>5}")?;
+        }
+
+        writeln!(f, "  Orphans:     {:>5}", self.orphans)?;
+        writeln!(f, "  Cycles:      {:>5}", self.cycles)?;
+        writeln!(f, "  Largest SCC: {:>5}", self.largest_scc)?;
+
+        Ok(())
+    }
+}
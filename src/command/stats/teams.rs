@@ -0,0 +1,424 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at https://mozilla.org/MPL/2.0/.
+
+//! The coupling between teams, as printed by `stats teams`.
+//!
+//! Each module is owned by the team configured for it (or its closest ancestor)
+//! via a config's `[owners]` table:
+//!
+//! ```toml
+//! [owners]
+//! "crate" = "@platform"
+//! "crate::net" = "@networking"
+//! ```
+//!
+//! Modules without a configured owner fall back to the first owner of the last pattern
+//! matching their file within the project's `CODEOWNERS` file (if any).
+//!
+//! Each "uses" edge gets attributed to the teams owning the modules containing
+//! its source and target, with the coupling of two teams being the number of distinct items
+//! used across their modules.
+
+use std::{
+    collections::{BTreeMap, BTreeSet, HashMap, HashSet},
+    fmt, fs,
+    path::{Path, PathBuf},
+};
+
+use anyhow::{bail, Context as _};
+use json::{object, JsonValue};
+use petgraph::visit::{EdgeRef as _, IntoEdgeReferences as _};
+use ra_ap_hir as hir;
+use ra_ap_ide::RootDatabase;
+
+use crate::{
+    analyzer,
+    command::check::budget::{is_same_or_descendant, resolve_paths},
+    config,
+    glob::glob_matches,
+    graph::{Edge, Graph, Node, Relationship},
+};
+
+/// The team of modules owned by no one.
+const UNOWNED: &str = "(unowned)";
+
+/// The label of the matrix's corner, reading as "rows use columns".
+const CORNER: &str = "from \\ to";
+
+/// The locations searched for a `CODEOWNERS` file, relative to the repository's root.
+const CODEOWNERS_PATHS: [&str; 3] = ["CODEOWNERS", ".github/CODEOWNERS", "docs/CODEOWNERS"];
+
+/// The number of items the modules of each team use of each team's modules.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub(crate) struct TeamMatrix {
+    /// The teams owning any of the analyzed modules, sorted by name.
+    teams: Vec<String>,
+    /// The number of distinct items used, by (source team, target team).
+    counts: BTreeMap<(String, String), usize>,
+}
+
+impl TeamMatrix {
+    /// Collects the coupling between the teams owning the `krates`' modules,
+    /// as configured for the project at `manifest_path`.
+    pub(crate) fn collect(
+        krates: &[hir::Crate],
+        graph: &Graph<Node, Edge>,
+        manifest_path: &Path,
+        project_dir: &Path,
+        db: &RootDatabase,
+    ) -> anyhow::Result<Self> {
+        let modules: Vec<hir::Module> = graph
+            .node_weights()
+            .filter_map(|node| match node.hir {
+                hir::ModuleDef::Module(module) if krates.contains(&module.krate()) => Some(module),
+                _ => None,
+            })
+            .collect();
+
+        let owners = Owners::load(manifest_path, project_dir, krates, &modules, db)?;
+
+        let teams_by_module: HashMap<hir::Module, String> = modules
+            .iter()
+            .map(|module| (*module, owners.team_of(*module, db)))
+            .collect();
+
+        // The distinct items used, by (source module, target module):
+        let mut used_items: HashMap<(hir::Module, hir::Module), HashSet<hir::ModuleDef>> =
+            HashMap::new();
+
+        for edge_ref in graph.edge_references() {
+            if edge_ref.weight().kind != Relationship::Uses {
+                continue;
+            }
+
+            let source = graph[edge_ref.source()].hir;
+            let target = graph[edge_ref.target()].hir;
+
+            let (Some(source_module), Some(target_module)) =
+                (analyzer::module(source, db), analyzer::module(target, db))
+            else {
+                continue;
+            };
+
+            // Only the modules of the analyzed crates have owners:
+            if source_module == target_module
+                || !teams_by_module.contains_key(&source_module)
+                || !teams_by_module.contains_key(&target_module)
+            {
+                continue;
+            }
+
+            used_items
+                .entry((source_module, target_module))
+                .or_default()
+                .insert(target);
+        }
+
+        let mut counts: BTreeMap<(String, String), usize> = BTreeMap::new();
+
+        for ((source_module, target_module), items) in used_items {
+            let source_team = teams_by_module[&source_module].clone();
+            let target_team = teams_by_module[&target_module].clone();
+
+            *counts.entry((source_team, target_team)).or_default() += items.len();
+        }
+
+        let teams: BTreeSet<String> = teams_by_module.into_values().collect();
+
+        Ok(Self {
+            teams: teams.into_iter().collect(),
+            counts,
+        })
+    }
+
+    fn count(&self, source_team: &str, target_team: &str) -> usize {
+        self.counts
+            .get(&(source_team.to_owned(), target_team.to_owned()))
+            .copied()
+            .unwrap_or(0)
+    }
+
+    pub(crate) fn to_json(&self) -> JsonValue {
+        let edges: Vec<JsonValue> = self
+            .counts
+            .iter()
+            .map(|((source_team, target_team), count)| {
+                object! {
+                    "from": source_team.as_str(),
+                    "to": target_team.as_str(),
+                    "count": *count,
+                }
+            })
+            .collect();
+
+        object! {
+            "teams": self.teams.iter().map(String::as_str).collect::<Vec<_>>(),
+            "edges": edges,
+        }
+    }
+
+    pub(crate) fn to_csv(&self) -> String {
+        let mut csv = String::new();
+
+        let header: Vec<String> = std::iter::once(CORNER)
+            .chain(self.teams.iter().map(String::as_str))
+            .map(csv_field)
+            .collect();
+
+        csv.push_str(&header.join(","));
+        csv.push('\n');
+
+        for source_team in &self.teams {
+            let row: Vec<String> = std::iter::once(csv_field(source_team))
+                .chain(
+                    self.teams
+                        .iter()
+                        .map(|target_team| self.count(source_team, target_team).to_string()),
+                )
+                .collect();
+
+            csv.push_str(&row.join(","));
+            csv.push('\n');
+        }
+
+        csv
+    }
+}
+
+impl fmt::Display for TeamMatrix {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let label_width = self
+            .teams
+            .iter()
+            .map(String::len)
+            .chain([CORNER.len()])
+            .max()
+            .unwrap_or_default();
+
+        let column_widths: Vec<usize> = self
+            .teams
+            .iter()
+            .map(|target_team| {
+                self.teams
+                    .iter()
+                    .map(|source_team| self.count(source_team, target_team).to_string().len())
+                    .chain([target_team.len()])
+                    .max()
+                    .unwrap_or_default()
+            })
+            .collect();
+
+        write!(f, "{CORNER:<label_width$}")?;
+
+        for (team, width) in self.teams.iter().zip(&column_widths) {
+            write!(f, "  {team:>width$}")?;
+        }
+
+        writeln!(f)?;
+
+        for source_team in &self.teams {
+            write!(f, "{source_team:<label_width$}")?;
+
+            for (target_team, width) in self.teams.iter().zip(&column_widths) {
+                let count = self.count(source_team, target_team);
+                write!(f, "  {count:>width$}")?;
+            }
+
+            writeln!(f)?;
+        }
+
+        Ok(())
+    }
+}
+
+/// The ownership of modules, as configured and/or declared by a `CODEOWNERS` file.
+struct Owners {
+    /// The configured (full) module paths, along with their teams.
+    configured: Vec<(String, String)>,
+    codeowners: Option<CodeOwners>,
+}
+
+impl Owners {
+    fn load(
+        manifest_path: &Path,
+        project_dir: &Path,
+        krates: &[hir::Crate],
+        modules: &[hir::Module],
+        db: &RootDatabase,
+    ) -> anyhow::Result<Self> {
+        let crate_names: Vec<String> = krates
+            .iter()
+            .map(|krate| analyzer::crate_name(*krate, db))
+            .collect();
+
+        let module_paths: HashSet<String> = modules
+            .iter()
+            .map(|module| analyzer::display_path(hir::ModuleDef::Module(*module), db))
+            .collect();
+
+        let mut configured = vec![];
+
+        if let Some((config_path, config)) = config::load(manifest_path)? {
+            if let Some(table) = config.get("owners") {
+                let context = || format!("Invalid config in {}", config_path.display());
+
+                let table = table
+                    .as_table()
+                    .context("Expected a table of module paths for `owners`")
+                    .with_context(context)?;
+
+                configured = table
+                    .iter()
+                    .map(|(path, team)| {
+                        let Some(team) = team.as_str() else {
+                            bail!("Expected a team for owner of `{path}`");
+                        };
+
+                        let module_path = resolve_paths(path, &crate_names)
+                            .into_iter()
+                            .find(|module_path| module_paths.contains(module_path))
+                            .with_context(|| format!("Unknown module `{path}` in owners"))?;
+
+                        Ok((module_path, team.to_owned()))
+                    })
+                    .collect::<anyhow::Result<Vec<_>>>()
+                    .with_context(context)?;
+            }
+        }
+
+        Ok(Self {
+            configured,
+            codeowners: CodeOwners::find(project_dir)?,
+        })
+    }
+
+    /// Returns the team owning `module`, with the most specific owner taking precedence.
+    fn team_of(&self, module: hir::Module, db: &RootDatabase) -> String {
+        let module_path = analyzer::display_path(hir::ModuleDef::Module(module), db);
+
+        let configured = self
+            .configured
+            .iter()
+            .filter(|(path, _)| is_same_or_descendant(path, &module_path))
+            .max_by_key(|(path, _)| path.len());
+
+        if let Some((_, team)) = configured {
+            return team.clone();
+        }
+
+        self.codeowners
+            .as_ref()
+            .and_then(|codeowners| codeowners.team_of(module, db))
+            .unwrap_or_else(|| UNOWNED.to_owned())
+    }
+}
+
+/// The rules of a `CODEOWNERS` file.
+struct CodeOwners {
+    /// The directory the file's patterns are relative to.
+    root_dir: PathBuf,
+    /// The file's patterns, along with their first owner, in order of declaration.
+    rules: Vec<(String, String)>,
+}
+
+impl CodeOwners {
+    /// Finds the `CODEOWNERS` file of the repository containing `project_dir`, if any.
+    fn find(project_dir: &Path) -> anyhow::Result<Option<Self>> {
+        for dir in project_dir.ancestors() {
+            for relative_path in CODEOWNERS_PATHS {
+                let path = dir.join(relative_path);
+
+                if !path.is_file() {
+                    continue;
+                }
+
+                let contents = fs::read_to_string(&path)
+                    .with_context(|| format!("Could not read {}", path.display()))?;
+
+                return Ok(Some(Self {
+                    root_dir: dir.to_owned(),
+                    rules: parse_rules(&contents),
+                }));
+            }
+
+            // A repository's `CODEOWNERS` file never lies beyond its root:
+            if dir.join(".git").exists() {
+                break;
+            }
+        }
+
+        Ok(None)
+    }
+
+    /// Returns the team owning the file of `module`, with modules declared inline
+    /// (i.e. `mod foo { … }`) being owned along with their parent's file.
+    fn team_of(&self, module: hir::Module, db: &RootDatabase) -> Option<String> {
+        let mut module = module;
+
+        let file_path = loop {
+            match analyzer::module_file_path(module, db) {
+                Some(file_path) => break file_path,
+                None => module = module.parent(db)?,
+            }
+        };
+
+        let file_path = fs::canonicalize(&file_path).unwrap_or(file_path);
+        let relative_path = file_path.strip_prefix(&self.root_dir).ok()?;
+
+        let relative_path = relative_path
+            .components()
+            .map(|component| component.as_os_str().to_string_lossy())
+            .collect::<Vec<_>>()
+            .join("/");
+
+        // As with git, the last matching pattern takes precedence:
+        self.rules
+            .iter()
+            .rev()
+            .find(|(pattern, _)| codeowners_matches(pattern, &relative_path))
+            .map(|(_, team)| team.clone())
+    }
+}
+
+/// Parses the rules of a `CODEOWNERS` file, skipping comments and patterns without owners.
+fn parse_rules(contents: &str) -> Vec<(String, String)> {
+    contents
+        .lines()
+        .map(|line| line.split_once('#').map_or(line, |(rule, _)| rule))
+        .filter_map(|rule| {
+            let mut fields = rule.split_whitespace();
+            let pattern = fields.next()?;
+            let owner = fields.next()?;
+            Some((pattern.to_owned(), owner.to_owned()))
+        })
+        .collect()
+}
+
+/// Returns whether a `CODEOWNERS` pattern matches the file at `path` (relative to the root).
+fn codeowners_matches(pattern: &str, path: &str) -> bool {
+    // Patterns containing a (non-trailing) slash are anchored to the root,
+    // while all others may match at any depth:
+    let is_anchored = pattern.trim_end_matches('/').contains('/');
+
+    let mut pattern = pattern.trim_start_matches('/').to_owned();
+
+    if !is_anchored {
+        pattern = format!("**/{pattern}");
+    }
+
+    if pattern.ends_with('/') {
+        pattern.push_str("**");
+    }
+
+    // Patterns of directories own all the files within them:
+    glob_matches(&pattern, path) || glob_matches(&format!("{pattern}/**"), path)
+}
+
+fn csv_field(field: &str) -> String {
+    if field.contains([',', '"', '\n']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_owned()
+    }
+}
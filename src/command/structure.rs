@@ -4,9 +4,11 @@
 
 pub mod options;
 
+pub(super) mod budget;
 pub(super) mod command;
 pub(super) mod filter;
+pub(super) mod icons;
 pub(crate) mod printer;
-pub(super) mod theme;
+pub(crate) mod theme;
 
 type Node = crate::item::Item;
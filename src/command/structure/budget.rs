@@ -0,0 +1,89 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at https://mozilla.org/MPL/2.0/.
+
+//! Collapsing of trees that exceed a given node budget.
+
+use std::collections::HashMap;
+
+use ra_ap_hir as hir;
+
+use crate::tree::Tree;
+
+use super::Node;
+
+/// The number of descendants that got collapsed into a given node.
+pub type Collapsed = HashMap<hir::ModuleDef, usize>;
+
+#[derive(Debug)]
+pub struct Budget {
+    max_nodes: usize,
+}
+
+impl Budget {
+    pub fn new(max_nodes: usize) -> Self {
+        Self { max_nodes }
+    }
+
+    /// Collapses the deepest levels of `tree` into their parents
+    /// until the remaining tree fits into the node budget.
+    ///
+    /// The crate's root node and its immediate children are always retained.
+    pub fn apply(&self, tree: Tree<Node>, collapsed: &mut Collapsed) -> Tree<Node> {
+        let mut nodes_per_depth: Vec<usize> = vec![];
+        Self::count_nodes_per_depth(&tree, 0, &mut nodes_per_depth);
+
+        let mut total_nodes = 0;
+        let mut max_depth = 0;
+
+        for (depth, nodes) in nodes_per_depth.into_iter().enumerate() {
+            total_nodes += nodes;
+
+            if total_nodes > self.max_nodes {
+                break;
+            }
+
+            max_depth = depth;
+        }
+
+        Self::collapse(tree, 0, max_depth.max(1), collapsed)
+    }
+
+    fn collapse(
+        tree: Tree<Node>,
+        depth: usize,
+        max_depth: usize,
+        collapsed: &mut Collapsed,
+    ) -> Tree<Node> {
+        if depth < max_depth {
+            let subtrees = tree
+                .subtrees
+                .into_iter()
+                .map(|subtree| Self::collapse(subtree, depth + 1, max_depth, collapsed))
+                .collect();
+
+            return Tree::new(tree.node, subtrees);
+        }
+
+        let descendants = tree.count_descendants();
+
+        // Add to any descendants that got collapsed into the node before (e.g. via `--max-depth`):
+        if descendants > 0 {
+            *collapsed.entry(tree.node.hir).or_default() += descendants;
+        }
+
+        Tree::new(tree.node, vec![])
+    }
+
+    fn count_nodes_per_depth(tree: &Tree<Node>, depth: usize, nodes_per_depth: &mut Vec<usize>) {
+        if nodes_per_depth.len() <= depth {
+            nodes_per_depth.push(0);
+        }
+
+        nodes_per_depth[depth] += 1;
+
+        for subtree in &tree.subtrees {
+            Self::count_nodes_per_depth(subtree, depth + 1, nodes_per_depth);
+        }
+    }
+}
@@ -2,16 +2,29 @@
 // License, v. 2.0. If a copy of the MPL was not distributed with this
 // file, You can obtain one at https://mozilla.org/MPL/2.0/.
 
-use std::fmt::Write;
+use std::{fmt, io::BufWriter};
 
 use clap::Parser;
 use log::trace;
 use ra_ap_hir as hir;
 use ra_ap_ide::RootDatabase;
 
-use crate::{analyzer::LoadOptions, tree::TreeBuilder};
-
-use super::{filter::Filter, options::Options, printer::Printer};
+use crate::{
+    analyzer::{self, LoadOptions},
+    command::{dependencies::cycles, orphans::scanner, Rendered},
+    graph::GraphBuilder,
+    progress::{self, Stage},
+    summary,
+    tree::TreeBuilder,
+    writer::FmtWriter,
+};
+
+use super::{
+    budget::{Budget, Collapsed},
+    filter::Filter,
+    options::Options,
+    printer::Printer,
+};
 
 #[derive(Parser, Clone, PartialEq, Eq, Debug)]
 pub struct Command {
@@ -27,27 +40,120 @@ impl Command {
     pub(crate) fn sanitize(&mut self) {}
 
     #[doc(hidden)]
-    pub fn run(self, krate: hir::Crate, db: &RootDatabase) -> anyhow::Result<()> {
-        trace!("Building tree ...");
+    pub fn run(self, krates: &[hir::Crate], db: &RootDatabase) -> anyhow::Result<()> {
+        let stdout = std::io::stdout();
+        let mut writer = FmtWriter::new(BufWriter::new(stdout.lock()));
 
-        let builder = TreeBuilder::new(db, krate);
-        let tree = builder.build()?;
+        self.fmt(&mut writer, krates, db)?;
 
-        trace!("Filtering tree ...");
+        writer.flush()?;
 
-        let filter = Filter::new(&self.options, db, krate);
-        let tree = filter.filter(&tree)?;
+        // The graph only gets checked once the tree has been printed in full:
+        self.check(krates, db)
+    }
 
-        trace!("Printing tree ...");
+    /// Checks the crate's graph for cycles, if requested via `--acyclic`.
+    pub(crate) fn check(&self, krates: &[hir::Crate], db: &RootDatabase) -> anyhow::Result<()> {
+        if !self.options.acyclic {
+            return Ok(());
+        }
+
+        trace!("Checking graph ...");
+
+        let (graph, crate_node_idxs) = GraphBuilder::new(db, krates).build()?;
+
+        let Some(cycle) = cycles::find_cycle(&graph, &crate_node_idxs) else {
+            return Ok(());
+        };
+
+        let first = graph[cycle[0]].display_path(db);
+        let last = graph[*cycle.last().unwrap()].display_path(db);
+
+        let drawing = cycles::draw_cycle(&graph, cycle, db);
+        anyhow::bail!("Circular dependency between `{first}` and `{last}`.\n\n{drawing}");
+    }
+
+    pub(crate) fn render(
+        &self,
+        krates: &[hir::Crate],
+        db: &RootDatabase,
+    ) -> anyhow::Result<Rendered> {
+        let mut output = String::new();
+
+        self.fmt(&mut output, krates, db)?;
+
+        let checked = self.check(krates, db);
+
+        Ok(Rendered { output, checked })
+    }
+
+    fn fmt(
+        &self,
+        f: &mut dyn fmt::Write,
+        krates: &[hir::Crate],
+        db: &RootDatabase,
+    ) -> anyhow::Result<()> {
+        let mut trees = vec![];
+        let mut collapsed = Collapsed::default();
 
-        let mut string = String::new();
+        for krate in krates.iter().cloned() {
+            trace!("Building tree ...");
+            progress::stage(Stage::Building);
 
-        writeln!(&mut string)?;
+            let builder = TreeBuilder::new(db, krate);
+            let unfiltered_tree = builder.build()?;
 
-        let printer = Printer::new(&self.options, db);
-        printer.fmt(&mut string, &tree)?;
+            trace!("Filtering tree ...");
+            progress::stage(Stage::Filtering);
 
-        print!("{string}");
+            let filter = Filter::new(&self.options, db, krate);
+            let Some(mut tree) = filter.filter(&unfiltered_tree)? else {
+                continue;
+            };
+
+            filter.collapse_truncated(&unfiltered_tree, &tree, &mut collapsed);
+
+            if let Some(max_nodes) = self.options.max_nodes {
+                trace!("Collapsing tree ...");
+
+                let budget = Budget::new(max_nodes);
+                tree = budget.apply(tree, &mut collapsed);
+            }
+
+            let focus_items = filter.focus_items(&tree);
+
+            trees.push((tree, focus_items));
+        }
+
+        if trees.is_empty() {
+            if let Some(focus_on) = &self.options.focus_on {
+                if analyzer::is_focus_file(focus_on) {
+                    anyhow::bail!("No module found owning file '{focus_on}'");
+                }
+
+                anyhow::bail!("No node found matching use tree '{:?}'", focus_on);
+            }
+        }
+
+        let orphans = if self.options.with_orphans {
+            let orphans = scanner::scan_all(db, krates)?;
+            summary::record_orphans(orphans.len());
+            orphans
+        } else {
+            vec![]
+        };
+
+        trace!("Printing tree ...");
+        progress::stage(Stage::Printing);
+
+        for (tree, focus_items) in trees {
+            writeln!(f)?;
+
+            let printer = Printer::new(&self.options, &collapsed, db)
+                .with_focus_items(focus_items)
+                .with_orphans(&orphans);
+            printer.fmt(f, &tree)?;
+        }
 
         Ok(())
     }
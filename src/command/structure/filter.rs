@@ -2,46 +2,180 @@
 // License, v. 2.0. If a copy of the MPL was not distributed with this
 // file, You can obtain one at https://mozilla.org/MPL/2.0/.
 
+use std::{
+    collections::{HashMap, HashSet},
+    path::Path,
+};
+
 use ra_ap_hir::{self as hir};
 use ra_ap_ide_db::RootDatabase;
 use ra_ap_syntax::ast;
 
 use crate::{analyzer, tree::Tree};
 
-use super::{options::Options, Node};
+use super::{budget::Collapsed, options::Options, Node};
 
 #[derive(Debug)]
 pub struct Filter<'a> {
     options: &'a Options,
     db: &'a RootDatabase,
     krate: hir::Crate,
+    /// The paths under which items are re-exported, as they may get focused on via any of them.
+    reexport_paths: HashMap<hir::ModuleDef, Vec<String>>,
+    /// The use tree to focus on (with a file's path resolved to that of the module owning it),
+    /// or `None` if focusing on a file not owned by any of the crate's modules.
+    focus_on: Option<String>,
 }
 
 impl<'a> Filter<'a> {
     pub fn new(options: &'a Options, db: &'a RootDatabase, krate: hir::Crate) -> Self {
-        Self { options, db, krate }
+        let reexport_paths = if options.focus_on.is_some() {
+            analyzer::reexport_paths(&[krate], db)
+        } else {
+            HashMap::new()
+        };
+
+        let focus_on = match options.focus_on.as_deref() {
+            Some(focus_on) if analyzer::is_focus_file(focus_on) => {
+                analyzer::modules_owning_file(Path::new(focus_on), &[krate], db)
+                    .into_iter()
+                    .next()
+            }
+            Some(focus_on) => Some(focus_on.to_owned()),
+            // Nameless crates get the same placeholder name as their crate node:
+            None => Some(
+                krate
+                    .display_name(db)
+                    .map_or_else(|| "_".to_owned(), |name| name.to_string()),
+            ),
+        };
+
+        Self {
+            options,
+            db,
+            krate,
+            reexport_paths,
+            focus_on,
+        }
     }
 
-    pub fn filter(&self, tree: &Tree<Node>) -> anyhow::Result<Tree<Node>> {
-        let focus_on = self
-            .options
-            .focus_on
-            .as_ref()
-            .cloned()
-            .unwrap_or_else(|| self.krate.display_name(self.db).unwrap().to_string());
+    pub fn filter(&self, tree: &Tree<Node>) -> anyhow::Result<Option<Tree<Node>>> {
+        if self.focus_on.is_none() {
+            return Ok(None);
+        }
 
-        let syntax = format!("use {focus_on};");
-        let use_tree: ast::UseTree = analyzer::parse_ast(&syntax);
+        let use_tree = self.focus_tree();
 
         let max_depth = self.options.max_depth.unwrap_or(usize::MAX);
 
-        let tree = self
-            .filter_tree(tree, None, max_depth, &use_tree)
-            .expect("root tree");
+        let tree = self.filter_tree(tree, None, max_depth, &use_tree);
 
         Ok(tree)
     }
 
+    /// Returns the items of the (filtered) tree's nodes selected by `--focus-on` (if any),
+    /// along with those of the nodes on the paths leading to them from the tree's root.
+    pub fn focus_items(
+        &self,
+        tree: &Tree<Node>,
+    ) -> (HashSet<hir::ModuleDef>, HashSet<hir::ModuleDef>) {
+        let mut focus_items = HashSet::new();
+        let mut path_items = HashSet::new();
+
+        if self.options.focus_on.is_some() {
+            let use_tree = self.focus_tree();
+            self.collect_focus_items(tree, &use_tree, &mut focus_items, &mut path_items);
+        }
+
+        (focus_items, path_items)
+    }
+
+    /// Collects the focus items within `tree`, returning whether it contains any.
+    fn collect_focus_items(
+        &self,
+        tree: &Tree<Node>,
+        focus_tree: &ast::UseTree,
+        focus_items: &mut HashSet<hir::ModuleDef>,
+        path_items: &mut HashSet<hir::ModuleDef>,
+    ) -> bool {
+        let mut contains_focus_items = false;
+
+        for subtree in &tree.subtrees {
+            contains_focus_items |=
+                self.collect_focus_items(subtree, focus_tree, focus_items, path_items);
+        }
+
+        if contains_focus_items {
+            path_items.insert(tree.node.hir);
+        }
+
+        if self.is_focus_tree(&tree.node, focus_tree) {
+            focus_items.insert(tree.node.hir);
+            return true;
+        }
+
+        contains_focus_items
+    }
+
+    /// Records the number of descendants that `filtered` (as obtained from `filter`)
+    /// lost to `--max-depth`, for each of its nodes at which it got truncated.
+    pub fn collapse_truncated(
+        &self,
+        tree: &Tree<Node>,
+        filtered: &Tree<Node>,
+        collapsed: &mut Collapsed,
+    ) {
+        if self.options.max_depth.is_none() {
+            return;
+        }
+
+        let use_tree = self.focus_tree();
+
+        let Some(untruncated) = self.filter_tree(tree, None, usize::MAX, &use_tree) else {
+            return;
+        };
+
+        Self::collapse_truncated_subtree(&untruncated, filtered, collapsed);
+    }
+
+    fn collapse_truncated_subtree(
+        untruncated: &Tree<Node>,
+        truncated: &Tree<Node>,
+        collapsed: &mut Collapsed,
+    ) {
+        if truncated.subtrees.is_empty() {
+            let descendants = untruncated.count_descendants();
+
+            if descendants > 0 {
+                collapsed.insert(truncated.node.hir, descendants);
+            }
+
+            return;
+        }
+
+        for truncated_subtree in &truncated.subtrees {
+            let untruncated_subtree = untruncated
+                .subtrees
+                .iter()
+                .find(|subtree| subtree.node.hir == truncated_subtree.node.hir);
+
+            if let Some(untruncated_subtree) = untruncated_subtree {
+                Self::collapse_truncated_subtree(untruncated_subtree, truncated_subtree, collapsed);
+            }
+        }
+    }
+
+    fn focus_tree(&self) -> ast::UseTree {
+        // Filters focusing on files owned by other crates never get to build any focus trees:
+        let focus_on = self
+            .focus_on
+            .as_deref()
+            .expect("focus tree of file not owned by crate");
+
+        let syntax = format!("use {focus_on};");
+        analyzer::parse_ast(&syntax)
+    }
+
     fn filter_tree(
         &self,
         tree: &Tree<Node>,
@@ -49,13 +183,12 @@ impl<'a> Filter<'a> {
         max_depth: usize,
         focus_tree: &ast::UseTree,
     ) -> Option<Tree<Node>> {
-        let path = tree.node.display_path(self.db);
-
-        let is_focus_tree = analyzer::use_tree_matches_item_path(focus_tree, &path);
+        let is_focus_tree = self.is_focus_tree(&tree.node, focus_tree);
 
         let depth = if is_focus_tree { Some(0) } else { depth };
 
-        let should_be_retained = self.should_retain_moduledef(tree.node.hir);
+        // Focus nodes get retained regardless (e.g. a type despite `--no-types`):
+        let should_be_retained = is_focus_tree || self.should_retain_moduledef(tree.node.hir);
 
         let subtree_contains_focus_tree = tree
             .subtrees
@@ -83,6 +216,12 @@ impl<'a> Filter<'a> {
             return None;
         }
 
+        let is_deprecated = tree.node.attrs(self.db).deprecated.is_some();
+
+        if self.options.only_deprecated && !is_deprecated && subtrees.is_empty() {
+            return None;
+        }
+
         if let Some(depth) = depth {
             if depth > max_depth {
                 return None;
@@ -98,9 +237,7 @@ impl<'a> Filter<'a> {
     }
 
     fn is_or_contains_focus_tree(&self, tree: &Tree<Node>, focus_tree: &ast::UseTree) -> bool {
-        let path = tree.node.display_path(self.db);
-
-        if analyzer::use_tree_matches_item_path(focus_tree, &path) {
+        if self.is_focus_tree(&tree.node, focus_tree) {
             return true;
         }
 
@@ -109,6 +246,17 @@ impl<'a> Filter<'a> {
             .any(|tree| self.is_or_contains_focus_tree(tree, focus_tree))
     }
 
+    /// Returns whether the node's path (or that of any of its re-exports) matches `focus_tree`.
+    fn is_focus_tree(&self, node: &Node, focus_tree: &ast::UseTree) -> bool {
+        let path = node.display_path(self.db);
+
+        let aliases = self.reexport_paths.get(&node.hir).into_iter().flatten();
+
+        std::iter::once(&path)
+            .chain(aliases)
+            .any(|path| analyzer::use_tree_matches_item_path(focus_tree, path))
+    }
+
     fn should_retain_moduledef(&self, module_def_hir: hir::ModuleDef) -> bool {
         if self.is_extern(module_def_hir) {
             return false;
@@ -0,0 +1,64 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at https://mozilla.org/MPL/2.0/.
+
+//! Icons marking the kinds of items in tree outputs, as selected via `--icons`.
+
+use super::options::Icons;
+
+/// Returns the icon of items of `kind` (e.g. `mod`, `struct`, `fn`), if any.
+pub(super) fn icon(icons: Icons, kind: &str) -> Option<&'static str> {
+    let icon = match icons {
+        Icons::None => return None,
+        // Codicons, as included in Nerd Fonts:
+        Icons::Nerd => match kind {
+            "crate" => "\u{eb29}",
+            "mod" => "\u{ea8b}",
+            "struct" | "union" => "\u{ea91}",
+            "enum" => "\u{ea95}",
+            "variant" => "\u{eb5e}",
+            "trait" => "\u{eb61}",
+            "fn" => "\u{ea8c}",
+            "type" => "\u{ea92}",
+            "const" => "\u{eb5d}",
+            "static" => "\u{ea88}",
+            "macro" => "\u{eb66}",
+            "builtin" => "\u{eb62}",
+            _ => return None,
+        },
+        Icons::Unicode => match kind {
+            "crate" => "⬢",
+            "mod" => "▤",
+            "struct" => "■",
+            "union" => "◧",
+            "enum" => "◆",
+            "variant" => "◇",
+            "trait" => "◉",
+            "fn" => "ƒ",
+            "type" => "τ",
+            "const" => "π",
+            "static" => "σ",
+            "macro" => "μ",
+            "builtin" => "β",
+            _ => return None,
+        },
+        Icons::Ascii => match kind {
+            "crate" => "[c]",
+            "mod" => "[m]",
+            "struct" => "[S]",
+            "union" => "[U]",
+            "enum" => "[E]",
+            "variant" => "[v]",
+            "trait" => "[T]",
+            "fn" => "[f]",
+            "type" => "[t]",
+            "const" => "[C]",
+            "static" => "[s]",
+            "macro" => "[!]",
+            "builtin" => "[b]",
+            _ => return None,
+        },
+    };
+
+    Some(icon)
+}
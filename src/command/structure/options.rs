@@ -2,17 +2,21 @@
 // License, v. 2.0. If a copy of the MPL was not distributed with this
 // file, You can obtain one at https://mozilla.org/MPL/2.0/.
 
-use std::{fmt::Display, str::FromStr};
+use std::{fmt::Display, path::PathBuf, str::FromStr};
 
 use clap::Parser;
 
-use crate::options::{GeneralOptions, ProjectOptions};
+use crate::options::{GeneralOptions, MessageFormat, ProjectOptions};
 
 #[derive(Copy, Clone, PartialEq, Eq, Debug)]
 pub enum SortBy {
     Name,
     Visibility,
     Kind,
+    /// Declaration order, i.e. by file and position within it.
+    Source,
+    /// Number of descendants, in descending order.
+    Size,
 }
 
 impl FromStr for SortBy {
@@ -23,6 +27,8 @@ impl FromStr for SortBy {
             "name" => Ok(Self::Name),
             "visibility" => Ok(Self::Visibility),
             "kind" => Ok(Self::Kind),
+            "source" => Ok(Self::Source),
+            "size" => Ok(Self::Size),
             _ => Err("Unrecognized sort order"),
         }
     }
@@ -34,6 +40,44 @@ impl Display for SortBy {
             Self::Name => "name",
             Self::Visibility => "visibility",
             Self::Kind => "kind",
+            Self::Source => "source",
+            Self::Size => "size",
+        })
+    }
+}
+
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum Icons {
+    None,
+    /// Nerd Font glyphs (requiring a patched font).
+    Nerd,
+    /// Unicode glyphs.
+    Unicode,
+    /// ASCII tags (e.g. `[m]`, `[S]`, `[f]`).
+    Ascii,
+}
+
+impl FromStr for Icons {
+    type Err = &'static str;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "none" => Ok(Self::None),
+            "nerd" => Ok(Self::Nerd),
+            "unicode" => Ok(Self::Unicode),
+            "ascii" => Ok(Self::Ascii),
+            _ => Err("Unrecognized icons"),
+        }
+    }
+}
+
+impl Display for Icons {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            Self::None => "none",
+            Self::Nerd => "nerd",
+            Self::Unicode => "unicode",
+            Self::Ascii => "ascii",
         })
     }
 }
@@ -51,7 +95,7 @@ pub struct Options {
     pub selection: SelectionOptions,
 
     /// The sorting order to use
-    /// (e.g. name, visibility, kind).
+    /// (e.g. name, visibility, kind, source, size).
     #[arg(long = "sort-by", default_value = "name")]
     pub sort_by: SortBy,
 
@@ -60,18 +104,78 @@ pub struct Options {
     pub sort_reversed: bool,
 
     /// Focus the graph on a particular path or use-tree's environment,
-    /// e.g. "foo::bar::{self, baz, blee::*}".
+    /// e.g. "foo::bar::{self, baz, blee::*}",
+    /// or on the module owning a source file, e.g. "src/parser/expr.rs".
     #[arg(long = "focus-on")]
     pub focus_on: Option<String>,
 
+    /// Highlight the paths from the crate's root node to the nodes selected by '--focus-on',
+    /// in addition to the (always highlighted) nodes themselves.
+    #[arg(long = "highlight-path", requires = "focus_on")]
+    pub highlight_path: bool,
+
     /// The maximum depth of the generated graph
     /// relative to the crate's root node, or nodes selected by '--focus-on'.
     #[arg(long = "max-depth")]
     pub max_depth: Option<usize>,
 
+    /// Only include deprecated items (i.e. `#[deprecated]`),
+    /// along with the modules containing them.
+    #[arg(long = "only-deprecated")]
+    pub only_deprecated: bool,
+
+    /// The maximum number of nodes to print per crate,
+    /// collapsing deeper levels of the tree into their parents as needed.
+    #[arg(long = "max-nodes")]
+    pub max_nodes: Option<usize>,
+
+    /// Append the first line of each item's doc comment to its line.
+    #[arg(long = "with-docs")]
+    pub with_docs: bool,
+
+    /// Append the number of each module's children by kind to its line
+    /// (e.g. `(2 submods, 3 structs, 12 fns)`).
+    #[arg(long = "with-counts")]
+    pub with_counts: bool,
+
+    /// Mark each item with an icon of its kind
+    /// (e.g. none, nerd, unicode, ascii).
+    #[arg(long = "icons", default_value = "none")]
+    pub icons: Icons,
+
+    /// Append the source file of each file module (relative to the project's directory) to its line.
+    #[arg(long = "with-paths")]
+    pub with_paths: bool,
+
+    /// Append orphaned source files (i.e. ones not linked via `mod`) to the modules they'd belong to.
+    #[arg(long = "with-orphans")]
+    pub with_orphans: bool,
+
+    /// Require the crate's dependencies to be acyclic,
+    /// failing with the first cycle found (after printing the tree).
+    #[arg(long = "acyclic")]
+    pub acyclic: bool,
+
+    /// The format to report progress in
+    /// (e.g. human, json).
+    #[arg(long = "message-format", default_value = "human")]
+    pub message_format: MessageFormat,
+
     /// Analyze with `#[cfg(test)]` enabled (i.e as if built via `cargo test`).
     #[arg(long = "cfg-test")]
     pub cfg_test: bool,
+
+    /// Cache the output on disk (in `target/cargo-modules/`), re-using it for as long as the project's files remain unchanged.
+    #[arg(long = "cache")]
+    pub cache: bool,
+
+    /// Open the output in a viewer (e.g. `xdot` for graphs) instead of printing it.
+    #[arg(long = "open")]
+    pub open: bool,
+
+    /// Write the output to this file (atomically replacing it) instead of printing it.
+    #[arg(short = 'o', long = "output", conflicts_with = "open")]
+    pub output: Option<PathBuf>,
 }
 
 // Important:
@@ -4,14 +4,25 @@
 
 //! Printer for displaying module structure as a tree.
 
-use std::fmt;
+use std::{
+    cmp::Reverse,
+    collections::{HashMap, HashSet},
+    fmt,
+    path::{Path, PathBuf},
+};
 
+use ra_ap_hir as hir;
 use ra_ap_ide::RootDatabase;
 use yansi::Paint as _;
 
-use crate::{analyzer, item::ItemVisibility, tree::Tree};
+use crate::{
+    analyzer, colors::styles::KINDS, command::orphans::orphan::Orphan, item::ItemVisibility,
+    quickfix, tree::Tree,
+};
 
 use super::{
+    budget::Collapsed,
+    icons,
     options::{Options, SortBy},
     theme::styles,
     Node,
@@ -23,14 +34,48 @@ struct Twig {
 }
 
 pub struct Printer<'a> {
-    #[allow(dead_code)]
     options: &'a Options,
+    collapsed: &'a Collapsed,
     db: &'a RootDatabase,
+    project_dir: PathBuf,
+    /// The items of the nodes selected by `--focus-on`.
+    focus_items: HashSet<hir::ModuleDef>,
+    /// The items of the nodes on the paths leading to the focus nodes.
+    path_items: HashSet<hir::ModuleDef>,
+    /// The orphans found by `--with-orphans`, by the paths of the modules they'd belong to.
+    orphans: HashMap<String, Vec<Orphan>>,
 }
 
 impl<'a> Printer<'a> {
-    pub fn new(options: &'a Options, db: &'a RootDatabase) -> Self {
-        Self { options, db }
+    pub fn new(options: &'a Options, collapsed: &'a Collapsed, db: &'a RootDatabase) -> Self {
+        Self {
+            options,
+            collapsed,
+            db,
+            project_dir: quickfix::project_dir(&options.project),
+            focus_items: HashSet::new(),
+            path_items: HashSet::new(),
+            orphans: HashMap::new(),
+        }
+    }
+
+    pub fn with_focus_items(
+        mut self,
+        (focus_items, path_items): (HashSet<hir::ModuleDef>, HashSet<hir::ModuleDef>),
+    ) -> Self {
+        self.focus_items = focus_items;
+        self.path_items = path_items;
+        self
+    }
+
+    pub fn with_orphans(mut self, orphans: &[Orphan]) -> Self {
+        for orphan in orphans {
+            self.orphans
+                .entry(orphan.parent_module_path.clone())
+                .or_default()
+                .push(orphan.clone());
+        }
+        self
     }
 
     pub fn fmt(&self, f: &mut dyn fmt::Write, tree: &Tree<Node>) -> Result<(), anyhow::Error> {
@@ -46,12 +91,23 @@ impl<'a> Printer<'a> {
     ) -> Result<(), anyhow::Error> {
         self.fmt_branch(f, &twigs[..])?;
         self.fmt_subtree(f, tree)?;
+        self.fmt_tree_unresolved(f, tree)?;
+        self.fmt_tree_path(f, tree)?;
+        self.fmt_tree_collapsed(f, tree)?;
+        self.fmt_tree_counts(f, tree)?;
+        self.fmt_tree_docs(f, tree)?;
         writeln!(f)?;
 
         let mut subtrees = tree.subtrees.clone();
 
-        // Sort the children by name for easier visual scanning of output:
-        subtrees.sort_by_cached_key(|tree: &Tree<Node>| tree.node.display_name(self.db));
+        // Sort the children by name for easier visual scanning of output
+        // (breaking ties between equally named ones by their IDs, for deterministic output):
+        subtrees.sort_by_cached_key(|tree: &Tree<Node>| {
+            (
+                tree.node.display_name(self.db),
+                tree.node.stable_id(self.db),
+            )
+        });
 
         // The default sorting functions in Rust are stable, so we can use it to re-sort,
         // resulting in a list that's sorted prioritizing whatever we re-sort by.
@@ -67,13 +123,33 @@ impl<'a> Printer<'a> {
             SortBy::Kind => {
                 subtrees.sort_by_cached_key(|tree| tree.node.kind_ordering(self.db));
             }
+            SortBy::Source => {
+                // Items without a location of their own (e.g. builtins) go last:
+                subtrees.sort_by_cached_key(|tree| {
+                    let location = analyzer::location(tree.node.hir, self.db);
+                    (location.is_none(), location)
+                });
+            }
+            SortBy::Size => {
+                // Descendants that got collapsed (via `--max-nodes`) still count towards the size:
+                subtrees.sort_by_cached_key(|tree| {
+                    let collapsed = self.collapsed.get(&tree.node.hir).copied().unwrap_or(0);
+                    Reverse(tree.count_descendants() + collapsed)
+                });
+            }
         }
 
         if self.options.sort_reversed {
             subtrees.reverse();
         }
 
-        let count = subtrees.len();
+        // Orphans go last, after the module's actual children:
+        let orphans = analyzer::path(tree.node.hir, self.db)
+            .and_then(|path| self.orphans.get(&path))
+            .map(Vec::as_slice)
+            .unwrap_or_default();
+
+        let count = subtrees.len() + orphans.len();
         for (pos, tree) in subtrees.into_iter().enumerate() {
             let is_last = pos + 1 == count;
             twigs.push(Twig { is_last });
@@ -81,10 +157,36 @@ impl<'a> Printer<'a> {
             twigs.pop();
         }
 
+        for (pos, orphan) in orphans.iter().enumerate() {
+            let is_last = pos + 1 == orphans.len();
+            twigs.push(Twig { is_last });
+            self.fmt_branch(f, &twigs[..])?;
+            self.fmt_orphan(f, orphan)?;
+            writeln!(f)?;
+            twigs.pop();
+        }
+
         Ok(())
     }
 
+    fn fmt_orphan(&self, f: &mut dyn fmt::Write, orphan: &Orphan) -> fmt::Result {
+        let styles = styles();
+        let orphan_style = styles.orphan;
+        let path_style = styles.path;
+
+        let path = format!("@ {}", self.relative_path(&orphan.file_path));
+
+        write!(
+            f,
+            "{} {} {}",
+            "orphan".paint(orphan_style),
+            orphan.name.paint(orphan_style),
+            path.paint(path_style)
+        )
+    }
+
     fn fmt_subtree(&self, f: &mut dyn fmt::Write, tree: &Tree<Node>) -> fmt::Result {
+        self.fmt_tree_icon(f, tree)?;
         self.fmt_tree_kind(f, tree)?;
         write!(f, " ")?;
         self.fmt_tree_name(f, tree)?;
@@ -105,6 +207,21 @@ impl<'a> Printer<'a> {
         Ok(())
     }
 
+    fn fmt_tree_icon(&self, f: &mut dyn fmt::Write, tree: &Tree<Node>) -> fmt::Result {
+        // Drop any qualifiers, such as in "async fn" or "unsafe trait":
+        let kind = tree.node.kind_display_name(self.db).to_string();
+        let kind = kind.rsplit(' ').next().unwrap_or_default();
+
+        let Some(icon) = icons::icon(self.options.icons, kind) else {
+            return Ok(());
+        };
+
+        let styles = styles();
+        let kind_style = styles.kind;
+
+        write!(f, "{} ", icon.paint(kind_style))
+    }
+
     fn fmt_tree_kind(&self, f: &mut dyn fmt::Write, tree: &Tree<Node>) -> fmt::Result {
         let styles = styles();
         let kind_style = styles.kind;
@@ -151,13 +268,30 @@ impl<'a> Printer<'a> {
     fn fmt_tree_name(&self, f: &mut dyn fmt::Write, tree: &Tree<Node>) -> fmt::Result {
         let styles = styles();
 
-        let name_style = styles.name;
+        let name_style = if self.focus_items.contains(&tree.node.hir) {
+            styles.focus
+        } else if self.options.highlight_path && self.path_items.contains(&tree.node.hir) {
+            styles.focus_path
+        } else {
+            styles.name
+        };
 
         write!(f, "{}", tree.node.display_name(self.db).paint(name_style))?;
 
         Ok(())
     }
 
+    fn fmt_tree_unresolved(&self, f: &mut dyn fmt::Write, tree: &Tree<Node>) -> fmt::Result {
+        if !tree.node.is_unresolved(self.db) {
+            return Ok(());
+        }
+
+        let styles = styles();
+        let unresolved_style = styles.unresolved;
+
+        write!(f, " {}", "[unresolved]".paint(unresolved_style))
+    }
+
     fn fmt_tree_attrs(&self, f: &mut dyn fmt::Write, tree: &Tree<Node>) -> fmt::Result {
         let styles = styles();
         let attr_chrome_style = styles.attr_chrome;
@@ -165,11 +299,25 @@ impl<'a> Printer<'a> {
 
         let mut is_first = true;
 
+        if let Some(deprecated_attr) = &tree.node.attrs(self.db).deprecated {
+            let prefix = "#[".paint(attr_chrome_style);
+            let deprecated = deprecated_attr.paint(styles.deprecated);
+            let suffix = "]".paint(attr_chrome_style);
+
+            write!(f, "{prefix}{deprecated}{suffix}")?;
+
+            is_first = false;
+        }
+
         if let Some(test_attr) = &tree.node.attrs(self.db).test {
             let prefix = "#[".paint(attr_chrome_style);
             let cfg = test_attr.paint(attr_style);
             let suffix = "]".paint(attr_chrome_style);
 
+            if !is_first {
+                write!(f, ", ")?;
+            }
+
             write!(f, "{prefix}{cfg}{suffix}")?;
 
             is_first = false;
@@ -192,6 +340,113 @@ impl<'a> Printer<'a> {
         Ok(())
     }
 
+    fn fmt_tree_path(&self, f: &mut dyn fmt::Write, tree: &Tree<Node>) -> fmt::Result {
+        if !self.options.with_paths {
+            return Ok(());
+        }
+
+        let hir::ModuleDef::Module(module) = tree.node.hir else {
+            return Ok(());
+        };
+
+        let Some(file_path) = analyzer::module_file_path(module, self.db) else {
+            return Ok(());
+        };
+
+        let styles = styles();
+        let path_style = styles.path;
+
+        let path = format!("@ {}", self.relative_path(&file_path));
+
+        write!(f, " {}", path.paint(path_style))
+    }
+
+    fn fmt_tree_collapsed(&self, f: &mut dyn fmt::Write, tree: &Tree<Node>) -> fmt::Result {
+        let Some(count) = self.collapsed.get(&tree.node.hir) else {
+            return Ok(());
+        };
+
+        let styles = styles();
+        let collapsed_style = styles.collapsed;
+
+        let items = if *count == 1 { "item" } else { "items" };
+        let collapsed = format!("(+{count} collapsed {items})");
+
+        write!(f, " {}", collapsed.paint(collapsed_style))
+    }
+
+    fn fmt_tree_counts(&self, f: &mut dyn fmt::Write, tree: &Tree<Node>) -> fmt::Result {
+        if !self.options.with_counts || tree.subtrees.is_empty() {
+            return Ok(());
+        }
+
+        let mut counts: Vec<(String, usize)> = vec![];
+
+        for subtree in &tree.subtrees {
+            // Drop any qualifiers, such as in "async fn" or "unsafe trait":
+            let kind = subtree.node.kind_display_name(self.db).to_string();
+            let kind = kind.rsplit(' ').next().unwrap_or_default().to_owned();
+
+            match counts.iter_mut().find(|(existing, _)| *existing == kind) {
+                Some((_, count)) => *count += 1,
+                None => counts.push((kind, 1)),
+            }
+        }
+
+        counts.sort_by_key(|(kind, _)| {
+            KINDS
+                .iter()
+                .position(|known| known == kind)
+                .unwrap_or(KINDS.len())
+        });
+
+        let counts = counts
+            .into_iter()
+            .map(|(kind, count)| {
+                let kind = match kind.as_str() {
+                    "mod" => "submod".to_owned(),
+                    _ => kind,
+                };
+                let suffix = if count == 1 { "" } else { "s" };
+                format!("{count} {kind}{suffix}")
+            })
+            .collect::<Vec<_>>()
+            .join(", ");
+
+        let styles = styles();
+        let counts_style = styles.counts;
+
+        let counts = format!("({counts})");
+
+        write!(f, " {}", counts.paint(counts_style))
+    }
+
+    fn fmt_tree_docs(&self, f: &mut dyn fmt::Write, tree: &Tree<Node>) -> fmt::Result {
+        if !self.options.with_docs {
+            return Ok(());
+        }
+
+        let Some(summary) = tree.node.doc_summary(self.db) else {
+            return Ok(());
+        };
+
+        let styles = styles();
+        let docs_style = styles.docs;
+
+        let docs = format!("// {summary}");
+
+        write!(f, " {}", docs.paint(docs_style))
+    }
+
+    /// Returns the file's path relative to the project's directory, with forward slashes on all platforms.
+    fn relative_path(&self, file_path: &Path) -> String {
+        quickfix::relative_path(file_path, &self.project_dir)
+            .components()
+            .map(|component| component.as_os_str().to_string_lossy())
+            .collect::<Vec<_>>()
+            .join("/")
+    }
+
     fn fmt_branch(&self, f: &mut dyn fmt::Write, twigs: &[Twig]) -> fmt::Result {
         let styles = styles();
         let branch_style = styles.branch;
@@ -21,9 +21,18 @@ pub(crate) struct Styles {
     pub name: Style,
     pub visibility: VisibilityStyles,
     pub attr: Style,
+    pub deprecated: Style,
     pub colon: Style,
     pub attr_chrome: Style,
     pub branch: Style,
+    pub collapsed: Style,
+    pub counts: Style,
+    pub path: Style,
+    pub docs: Style,
+    pub focus: Style,
+    pub focus_path: Style,
+    pub orphan: Style,
+    pub unresolved: Style,
 }
 
 pub(crate) fn styles() -> Styles {
@@ -39,8 +48,17 @@ pub(crate) fn styles() -> Styles {
             pub_super: Style::default().fg(color_palette.orange),
         },
         attr: Style::default().fg(color_palette.cyan),
+        deprecated: Style::default().fg(color_palette.purple),
         colon: Style::default().dim(),
         attr_chrome: Style::default().dim(),
         branch: Style::default().dim(),
+        collapsed: Style::default().dim(),
+        counts: Style::default().dim(),
+        path: Style::default().dim(),
+        docs: Style::default().dim(),
+        focus: Style::default().fg(color_palette.cyan).bold().underline(),
+        focus_path: Style::default().fg(color_palette.cyan),
+        orphan: Style::default().fg(color_palette.red),
+        unresolved: Style::default().fg(color_palette.red),
     }
 }
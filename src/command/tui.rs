@@ -0,0 +1,11 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at https://mozilla.org/MPL/2.0/.
+
+pub(super) mod command;
+pub(super) mod explorer;
+pub(crate) mod options;
+pub(super) mod theme;
+pub(super) mod ui;
+
+type Node = crate::item::Item;
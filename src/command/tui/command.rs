@@ -0,0 +1,227 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at https://mozilla.org/MPL/2.0/.
+
+use std::{
+    env,
+    io::{self, IsTerminal as _, Stdout},
+    path::Path,
+    process,
+};
+
+use anyhow::Context as _;
+use clap::Parser;
+use crossterm::{
+    event::{self, Event, KeyCode, KeyEventKind, KeyModifiers},
+    execute,
+    terminal::{disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen},
+};
+use log::trace;
+use ra_ap_hir as hir;
+use ra_ap_ide::RootDatabase;
+use ra_ap_vfs::Vfs;
+use ratatui::{backend::CrosstermBackend, Terminal};
+use scopeguard::defer;
+
+use crate::{
+    analyzer::LoadOptions,
+    command::structure::{
+        self,
+        filter::Filter,
+        options::{Icons, SortBy},
+    },
+    graph::GraphBuilder,
+    options::MessageFormat,
+    tree::TreeBuilder,
+};
+
+use super::{
+    explorer::Explorer,
+    options::Options,
+    ui::{self, UiState},
+};
+
+type TuiTerminal = Terminal<CrosstermBackend<Stdout>>;
+
+#[derive(Parser, Clone, PartialEq, Eq, Debug)]
+pub struct Command {
+    #[command(flatten)]
+    pub options: Options,
+}
+
+impl Command {
+    pub fn new(options: Options) -> Self {
+        Self { options }
+    }
+
+    pub(crate) fn sanitize(&mut self) {}
+
+    #[doc(hidden)]
+    pub fn run(self, krates: &[hir::Crate], db: &RootDatabase, vfs: &Vfs) -> anyhow::Result<()> {
+        if !io::stdout().is_terminal() {
+            anyhow::bail!("The `tui` command requires an interactive terminal");
+        }
+
+        let structure_options = self.structure_options();
+
+        let mut trees = vec![];
+
+        for krate in krates.iter().cloned() {
+            trace!("Building tree ...");
+
+            let builder = TreeBuilder::new(db, krate);
+            let tree = builder.build()?;
+
+            trace!("Filtering tree ...");
+
+            let filter = Filter::new(&structure_options, db, krate);
+            if let Some(tree) = filter.filter(&tree)? {
+                trees.push(tree);
+            }
+        }
+
+        trace!("Building graph ...");
+
+        let builder = GraphBuilder::new(db, krates);
+        let (graph, _crate_node_idxs) = builder.build()?;
+
+        let mut explorer = Explorer::new(db, trees, graph);
+
+        enter_terminal()?;
+
+        defer! {
+            let _ = leave_terminal();
+        }
+
+        let mut terminal = Terminal::new(CrosstermBackend::new(io::stdout()))?;
+        terminal.clear()?;
+
+        Self::event_loop(&mut terminal, &mut explorer, vfs)
+    }
+
+    fn event_loop(
+        terminal: &mut TuiTerminal,
+        explorer: &mut Explorer,
+        vfs: &Vfs,
+    ) -> anyhow::Result<()> {
+        let mut state = UiState::default();
+
+        loop {
+            terminal.draw(|frame| ui::draw(frame, explorer, &mut state))?;
+
+            let Event::Key(key) = event::read()? else {
+                continue;
+            };
+
+            if key.kind != KeyEventKind::Press {
+                continue;
+            }
+
+            if key.modifiers.contains(KeyModifiers::CONTROL) && key.code == KeyCode::Char('c') {
+                return Ok(());
+            }
+
+            if explorer.search().is_some() {
+                match key.code {
+                    KeyCode::Char(char) => explorer.push_search_char(char),
+                    KeyCode::Backspace => explorer.pop_search_char(),
+                    KeyCode::Enter => explorer.submit_search(),
+                    KeyCode::Esc => explorer.cancel_search(),
+                    _ => {}
+                }
+
+                continue;
+            }
+
+            match key.code {
+                KeyCode::Char('q') | KeyCode::Esc => return Ok(()),
+                KeyCode::Up | KeyCode::Char('k') => explorer.move_up(),
+                KeyCode::Down | KeyCode::Char('j') => explorer.move_down(),
+                KeyCode::Left | KeyCode::Char('h') => explorer.collapse(),
+                KeyCode::Right | KeyCode::Char('l') => explorer.expand(),
+                KeyCode::Enter | KeyCode::Char(' ') => explorer.activate(),
+                KeyCode::Tab => explorer.next_pane(),
+                KeyCode::BackTab => explorer.previous_pane(),
+                KeyCode::Char('/') => explorer.start_search(),
+                KeyCode::Char('n') => explorer.find_next(),
+                KeyCode::Char('o') => match explorer.selected_file(vfs) {
+                    Some(path) => {
+                        if let Err(err) = open_in_editor(terminal, &path) {
+                            explorer.set_status(format!("{err:#}"));
+                        }
+                    }
+                    None => explorer.set_status("No file found for selected item".to_owned()),
+                },
+                _ => {}
+            }
+        }
+    }
+
+    fn structure_options(&self) -> structure::options::Options {
+        structure::options::Options {
+            general: self.options.general.clone(),
+            project: self.options.project.clone(),
+            selection: self.options.selection.clone(),
+            sort_by: SortBy::Name,
+            sort_reversed: false,
+            focus_on: None,
+            highlight_path: false,
+            max_depth: None,
+            only_deprecated: false,
+            max_nodes: None,
+            with_docs: false,
+            with_counts: false,
+            icons: Icons::None,
+            with_paths: false,
+            with_orphans: false,
+            acyclic: false,
+            message_format: MessageFormat::Human,
+            cfg_test: self.options.cfg_test,
+            cache: false,
+            open: false,
+            output: None,
+        }
+    }
+
+    pub fn load_options(&self) -> LoadOptions {
+        LoadOptions {
+            cfg_test: self.options.cfg_test,
+            sysroot: false,
+        }
+    }
+}
+
+fn enter_terminal() -> io::Result<()> {
+    enable_raw_mode()?;
+    execute!(io::stdout(), EnterAlternateScreen)
+}
+
+fn leave_terminal() -> io::Result<()> {
+    disable_raw_mode()?;
+    execute!(io::stdout(), LeaveAlternateScreen)
+}
+
+/// Opens `path` in the user's `$VISUAL` (or `$EDITOR`), suspending the UI meanwhile.
+fn open_in_editor(terminal: &mut TuiTerminal, path: &Path) -> anyhow::Result<()> {
+    let editor = env::var("VISUAL")
+        .or_else(|_| env::var("EDITOR"))
+        .unwrap_or_else(|_| "vi".to_owned());
+
+    let mut args = editor.split_whitespace();
+    let program = args.next().context("No editor configured")?;
+
+    leave_terminal()?;
+
+    let status = process::Command::new(program).args(args).arg(path).status();
+
+    enter_terminal()?;
+    terminal.clear()?;
+
+    let status = status.with_context(|| format!("Could not launch editor `{editor}`"))?;
+
+    if !status.success() {
+        anyhow::bail!("Editor `{editor}` exited with {status}");
+    }
+
+    Ok(())
+}
@@ -0,0 +1,445 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at https://mozilla.org/MPL/2.0/.
+
+//! The (terminal-agnostic) state of the interactive explorer.
+
+use std::{
+    collections::{HashMap, HashSet},
+    path::PathBuf,
+};
+
+use petgraph::{graph::NodeIndex, visit::EdgeRef as _, Direction};
+use ra_ap_hir as hir;
+use ra_ap_ide::RootDatabase;
+use ra_ap_vfs::Vfs;
+
+use crate::{
+    analyzer,
+    graph::{Edge, Graph, Relationship},
+    tree::Tree,
+};
+
+use super::Node;
+
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub(super) enum Pane {
+    Tree,
+    Dependencies,
+    Dependents,
+}
+
+#[derive(Clone, Debug)]
+pub(super) struct Row {
+    pub hir: hir::ModuleDef,
+    /// Whether the row itself and each of its ancestors is the last of its siblings.
+    pub twigs: Vec<bool>,
+    pub is_expandable: bool,
+    pub is_expanded: bool,
+}
+
+pub(super) struct Explorer<'a> {
+    db: &'a RootDatabase,
+    trees: Vec<Tree<Node>>,
+    graph: Graph<Node, Edge>,
+    node_idxs: HashMap<hir::ModuleDef, NodeIndex>,
+    parents: HashMap<hir::ModuleDef, hir::ModuleDef>,
+    paths: Vec<(hir::ModuleDef, String)>,
+    expanded: HashSet<hir::ModuleDef>,
+    rows: Vec<Row>,
+    selected: usize,
+    pane: Pane,
+    related_selected: usize,
+    search: Option<String>,
+    query: String,
+    status: String,
+}
+
+impl<'a> Explorer<'a> {
+    pub fn new(db: &'a RootDatabase, mut trees: Vec<Tree<Node>>, graph: Graph<Node, Edge>) -> Self {
+        for tree in trees.iter_mut() {
+            Self::sort_tree(tree, db);
+        }
+
+        let node_idxs = graph
+            .node_indices()
+            .map(|node_idx| (graph[node_idx].hir, node_idx))
+            .collect();
+
+        let mut parents = HashMap::default();
+        let mut paths = vec![];
+
+        for tree in &trees {
+            Self::index_tree(tree, db, &mut parents, &mut paths);
+        }
+
+        // Crates start out expanded, everything else collapsed:
+        let expanded = trees.iter().map(|tree| tree.node.hir).collect();
+
+        let mut explorer = Self {
+            db,
+            trees,
+            graph,
+            node_idxs,
+            parents,
+            paths,
+            expanded,
+            rows: vec![],
+            selected: 0,
+            pane: Pane::Tree,
+            related_selected: 0,
+            search: None,
+            query: String::new(),
+            status: String::new(),
+        };
+
+        explorer.rebuild_rows();
+
+        explorer
+    }
+
+    pub fn db(&self) -> &'a RootDatabase {
+        self.db
+    }
+
+    pub fn rows(&self) -> &[Row] {
+        &self.rows[..]
+    }
+
+    pub fn selected(&self) -> usize {
+        self.selected
+    }
+
+    pub fn selected_row(&self) -> Option<&Row> {
+        self.rows.get(self.selected)
+    }
+
+    pub fn pane(&self) -> Pane {
+        self.pane
+    }
+
+    pub fn related_selected(&self) -> usize {
+        self.related_selected
+    }
+
+    pub fn search(&self) -> Option<&str> {
+        self.search.as_deref()
+    }
+
+    pub fn status(&self) -> &str {
+        &self.status
+    }
+
+    pub fn set_status(&mut self, status: String) {
+        self.status = status;
+    }
+
+    /// Items used by the selected item.
+    pub fn dependencies(&self) -> Vec<hir::ModuleDef> {
+        self.related(Direction::Outgoing)
+    }
+
+    /// Items using the selected item.
+    pub fn dependents(&self) -> Vec<hir::ModuleDef> {
+        self.related(Direction::Incoming)
+    }
+
+    pub fn move_up(&mut self) {
+        match self.pane {
+            Pane::Tree => self.select(self.selected.saturating_sub(1)),
+            Pane::Dependencies | Pane::Dependents => {
+                self.related_selected = self.related_selected.saturating_sub(1);
+            }
+        }
+    }
+
+    pub fn move_down(&mut self) {
+        match self.pane {
+            Pane::Tree => self.select(self.selected + 1),
+            Pane::Dependencies | Pane::Dependents => {
+                let count = self.focused_related().len();
+                self.related_selected = (self.related_selected + 1).min(count.saturating_sub(1));
+            }
+        }
+    }
+
+    /// Collapses the selected item, or selects its parent if already collapsed.
+    pub fn collapse(&mut self) {
+        let Some(row) = self.selected_row() else {
+            return;
+        };
+
+        let hir = row.hir;
+
+        if row.is_expanded {
+            self.expanded.remove(&hir);
+            self.rebuild_rows();
+        } else if let Some(parent) = self.parents.get(&hir).copied() {
+            self.select_hir(parent);
+        }
+    }
+
+    /// Expands the selected item, or selects its first child if already expanded.
+    pub fn expand(&mut self) {
+        let Some(row) = self.selected_row() else {
+            return;
+        };
+
+        if !row.is_expandable {
+            return;
+        }
+
+        if row.is_expanded {
+            self.select(self.selected + 1);
+        } else {
+            self.expanded.insert(row.hir);
+            self.rebuild_rows();
+        }
+    }
+
+    /// Toggles the selected item, or jumps to the selected dependency/dependent.
+    pub fn activate(&mut self) {
+        match self.pane {
+            Pane::Tree => {
+                if self.selected_row().is_some_and(|row| row.is_expanded) {
+                    self.collapse();
+                } else {
+                    self.expand();
+                }
+            }
+            Pane::Dependencies | Pane::Dependents => {
+                if let Some(hir) = self.focused_related().get(self.related_selected).copied() {
+                    self.reveal(hir);
+                }
+            }
+        }
+    }
+
+    pub fn next_pane(&mut self) {
+        self.focus_pane(match self.pane {
+            Pane::Tree => Pane::Dependencies,
+            Pane::Dependencies => Pane::Dependents,
+            Pane::Dependents => Pane::Tree,
+        });
+    }
+
+    pub fn previous_pane(&mut self) {
+        self.focus_pane(match self.pane {
+            Pane::Tree => Pane::Dependents,
+            Pane::Dependencies => Pane::Tree,
+            Pane::Dependents => Pane::Dependencies,
+        });
+    }
+
+    pub fn start_search(&mut self) {
+        self.search = Some(String::new());
+    }
+
+    pub fn push_search_char(&mut self, char: char) {
+        if let Some(search) = self.search.as_mut() {
+            search.push(char);
+        }
+    }
+
+    pub fn pop_search_char(&mut self) {
+        if let Some(search) = self.search.as_mut() {
+            search.pop();
+        }
+    }
+
+    pub fn cancel_search(&mut self) {
+        self.search = None;
+    }
+
+    pub fn submit_search(&mut self) {
+        let Some(search) = self.search.take() else {
+            return;
+        };
+
+        self.query = search;
+        self.find_next();
+    }
+
+    /// Selects the next item (in tree order) whose path contains the last search query.
+    pub fn find_next(&mut self) {
+        if self.query.is_empty() {
+            return;
+        }
+
+        let start = self
+            .selected_row()
+            .and_then(|row| self.paths.iter().position(|(hir, _)| *hir == row.hir))
+            .map_or(0, |position| position + 1);
+
+        let count = self.paths.len();
+
+        let found = (0..count)
+            .map(|offset| &self.paths[(start + offset) % count])
+            .find(|(_, path)| path.contains(&self.query))
+            .map(|(hir, _)| *hir);
+
+        match found {
+            Some(hir) => self.reveal(hir),
+            None => self.status = format!("No item matching {:?}", self.query),
+        }
+    }
+
+    /// Returns the file containing the selected item.
+    pub fn selected_file(&self, vfs: &Vfs) -> Option<PathBuf> {
+        let row = self.selected_row()?;
+        let mut module = analyzer::module(row.hir, self.db)?;
+
+        // Inline modules don't have a file of their own,
+        // so we fall back to the file of their closest file-based ancestor:
+        loop {
+            if let Some(path) = analyzer::module_file(module, self.db, vfs) {
+                return Some(path);
+            }
+
+            module = module.parent(self.db)?;
+        }
+    }
+
+    fn reveal(&mut self, hir: hir::ModuleDef) {
+        if !self.paths.iter().any(|(path_hir, _)| *path_hir == hir) {
+            let path = analyzer::display_path(hir, self.db);
+            self.status = format!("Item `{path}` is not part of the tree");
+            return;
+        }
+
+        let mut current = hir;
+        while let Some(parent) = self.parents.get(&current).copied() {
+            self.expanded.insert(parent);
+            current = parent;
+        }
+
+        self.rebuild_rows();
+        self.select_hir(hir);
+        self.pane = Pane::Tree;
+    }
+
+    fn focus_pane(&mut self, pane: Pane) {
+        self.pane = pane;
+        self.related_selected = 0;
+    }
+
+    fn focused_related(&self) -> Vec<hir::ModuleDef> {
+        match self.pane {
+            Pane::Tree => vec![],
+            Pane::Dependencies => self.dependencies(),
+            Pane::Dependents => self.dependents(),
+        }
+    }
+
+    fn related(&self, direction: Direction) -> Vec<hir::ModuleDef> {
+        let Some(node_idx) = self
+            .selected_row()
+            .and_then(|row| self.node_idxs.get(&row.hir))
+            .copied()
+        else {
+            return vec![];
+        };
+
+        let mut related: Vec<_> = self
+            .graph
+            .edges_directed(node_idx, direction)
+            .filter(|edge_ref| edge_ref.weight().kind == Relationship::Uses)
+            .map(|edge_ref| match direction {
+                Direction::Outgoing => self.graph[edge_ref.target()].hir,
+                Direction::Incoming => self.graph[edge_ref.source()].hir,
+            })
+            .collect();
+
+        related.sort_by_cached_key(|hir| analyzer::display_path(*hir, self.db));
+        related.dedup();
+
+        related
+    }
+
+    fn select(&mut self, index: usize) {
+        let index = index.min(self.rows.len().saturating_sub(1));
+
+        if index != self.selected {
+            self.related_selected = 0;
+        }
+
+        self.selected = index;
+        self.status.clear();
+    }
+
+    fn select_hir(&mut self, hir: hir::ModuleDef) {
+        if let Some(index) = self.rows.iter().position(|row| row.hir == hir) {
+            self.select(index);
+        }
+    }
+
+    fn rebuild_rows(&mut self) {
+        let selected_hir = self.selected_row().map(|row| row.hir);
+
+        let mut rows = vec![];
+
+        let count = self.trees.len();
+        for (pos, tree) in self.trees.iter().enumerate() {
+            let mut twigs = vec![pos + 1 == count];
+            Self::push_rows(tree, &self.expanded, &mut twigs, &mut rows);
+        }
+
+        self.rows = rows;
+
+        match selected_hir {
+            Some(hir) => self.select_hir(hir),
+            None => self.select(0),
+        }
+    }
+
+    fn push_rows(
+        tree: &Tree<Node>,
+        expanded: &HashSet<hir::ModuleDef>,
+        twigs: &mut Vec<bool>,
+        rows: &mut Vec<Row>,
+    ) {
+        let is_expanded = expanded.contains(&tree.node.hir);
+
+        rows.push(Row {
+            hir: tree.node.hir,
+            twigs: twigs.clone(),
+            is_expandable: !tree.subtrees.is_empty(),
+            is_expanded,
+        });
+
+        if !is_expanded {
+            return;
+        }
+
+        let count = tree.subtrees.len();
+        for (pos, subtree) in tree.subtrees.iter().enumerate() {
+            twigs.push(pos + 1 == count);
+            Self::push_rows(subtree, expanded, twigs, rows);
+            twigs.pop();
+        }
+    }
+
+    fn sort_tree(tree: &mut Tree<Node>, db: &RootDatabase) {
+        // Sort the children by name, just like the `structure` command does:
+        tree.subtrees
+            .sort_by_cached_key(|tree| tree.node.display_name(db));
+
+        for subtree in tree.subtrees.iter_mut() {
+            Self::sort_tree(subtree, db);
+        }
+    }
+
+    fn index_tree(
+        tree: &Tree<Node>,
+        db: &RootDatabase,
+        parents: &mut HashMap<hir::ModuleDef, hir::ModuleDef>,
+        paths: &mut Vec<(hir::ModuleDef, String)>,
+    ) {
+        paths.push((tree.node.hir, tree.node.display_path(db)));
+
+        for subtree in &tree.subtrees {
+            parents.insert(subtree.node.hir, tree.node.hir);
+            Self::index_tree(subtree, db, parents, paths);
+        }
+    }
+}
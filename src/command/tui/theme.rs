@@ -0,0 +1,57 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at https://mozilla.org/MPL/2.0/.
+
+//! Conversion of the (`yansi`-based) CLI styles into their terminal UI equivalents.
+
+use ratatui::style::{Color, Modifier, Style};
+
+pub(super) fn style(style: yansi::Style) -> Style {
+    let mut tui_style = Style::default();
+
+    if let Some(foreground) = style.foreground {
+        tui_style = tui_style.fg(color(foreground));
+    }
+
+    if let Some(background) = style.background {
+        tui_style = tui_style.bg(color(background));
+    }
+
+    tui_style
+}
+
+pub(super) fn dim() -> Style {
+    Style::default().add_modifier(Modifier::DIM)
+}
+
+pub(super) fn selected() -> Style {
+    Style::default().add_modifier(Modifier::REVERSED)
+}
+
+pub(super) fn focused_border() -> Style {
+    Style::default().add_modifier(Modifier::BOLD)
+}
+
+fn color(color: yansi::Color) -> Color {
+    match color {
+        yansi::Color::Primary => Color::Reset,
+        yansi::Color::Fixed(index) => Color::Indexed(index),
+        yansi::Color::Rgb(red, green, blue) => Color::Rgb(red, green, blue),
+        yansi::Color::Black => Color::Black,
+        yansi::Color::Red => Color::Red,
+        yansi::Color::Green => Color::Green,
+        yansi::Color::Yellow => Color::Yellow,
+        yansi::Color::Blue => Color::Blue,
+        yansi::Color::Magenta => Color::Magenta,
+        yansi::Color::Cyan => Color::Cyan,
+        yansi::Color::White => Color::Gray,
+        yansi::Color::BrightBlack => Color::DarkGray,
+        yansi::Color::BrightRed => Color::LightRed,
+        yansi::Color::BrightGreen => Color::LightGreen,
+        yansi::Color::BrightYellow => Color::LightYellow,
+        yansi::Color::BrightBlue => Color::LightBlue,
+        yansi::Color::BrightMagenta => Color::LightMagenta,
+        yansi::Color::BrightCyan => Color::LightCyan,
+        yansi::Color::BrightWhite => Color::White,
+    }
+}
@@ -0,0 +1,239 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at https://mozilla.org/MPL/2.0/.
+
+//! Rendering of the explorer's state into the terminal.
+
+use ra_ap_hir as hir;
+use ratatui::{
+    layout::{Constraint, Direction, Layout, Rect},
+    text::{Line, Span},
+    widgets::{Block, Borders, List, ListItem, ListState, Paragraph},
+    Frame,
+};
+
+use crate::{
+    analyzer,
+    command::structure::theme::styles,
+    item::{Item, ItemVisibility},
+};
+
+use super::{
+    explorer::{Explorer, Pane, Row},
+    theme,
+};
+
+const HELP: &str = "q: quit | ↑/↓: move | ←/→: collapse/expand | tab: switch pane | enter: toggle/jump | /: search | n: next match | o: open file";
+
+#[derive(Default)]
+pub(super) struct UiState {
+    tree: ListState,
+    dependencies: ListState,
+    dependents: ListState,
+}
+
+pub(super) fn draw(frame: &mut Frame, explorer: &Explorer, state: &mut UiState) {
+    let [main_area, status_area] = split(
+        frame.size(),
+        Direction::Vertical,
+        [Constraint::Min(1), Constraint::Length(1)],
+    );
+
+    let [tree_area, related_area] = split(
+        main_area,
+        Direction::Horizontal,
+        [Constraint::Percentage(60), Constraint::Percentage(40)],
+    );
+
+    let [dependencies_area, dependents_area] = split(
+        related_area,
+        Direction::Vertical,
+        [Constraint::Percentage(50), Constraint::Percentage(50)],
+    );
+
+    draw_tree(frame, tree_area, explorer, &mut state.tree);
+
+    draw_related(
+        frame,
+        dependencies_area,
+        explorer,
+        Pane::Dependencies,
+        &explorer.dependencies(),
+        &mut state.dependencies,
+    );
+
+    draw_related(
+        frame,
+        dependents_area,
+        explorer,
+        Pane::Dependents,
+        &explorer.dependents(),
+        &mut state.dependents,
+    );
+
+    draw_status(frame, status_area, explorer);
+}
+
+fn draw_tree(frame: &mut Frame, area: Rect, explorer: &Explorer, state: &mut ListState) {
+    let items: Vec<ListItem> = explorer
+        .rows()
+        .iter()
+        .map(|row| ListItem::new(tree_line(row, explorer)))
+        .collect();
+
+    let list = List::new(items)
+        .block(block(" Modules ", explorer.pane() == Pane::Tree))
+        .highlight_style(theme::selected());
+
+    state.select(Some(explorer.selected()));
+
+    frame.render_stateful_widget(list, area, state);
+}
+
+fn draw_related(
+    frame: &mut Frame,
+    area: Rect,
+    explorer: &Explorer,
+    pane: Pane,
+    related: &[hir::ModuleDef],
+    state: &mut ListState,
+) {
+    let items: Vec<ListItem> = related
+        .iter()
+        .map(|hir| ListItem::new(related_line(*hir, explorer)))
+        .collect();
+
+    let title = match pane {
+        Pane::Tree => unreachable!(),
+        Pane::Dependencies => format!(" Uses ({}) ", related.len()),
+        Pane::Dependents => format!(" Used by ({}) ", related.len()),
+    };
+
+    let is_focused = explorer.pane() == pane;
+
+    let list = List::new(items)
+        .block(block(&title, is_focused))
+        .highlight_style(theme::selected());
+
+    if is_focused {
+        state.select(Some(explorer.related_selected()));
+    } else {
+        state.select(None);
+    }
+
+    frame.render_stateful_widget(list, area, state);
+}
+
+fn draw_status(frame: &mut Frame, area: Rect, explorer: &Explorer) {
+    let line = if let Some(search) = explorer.search() {
+        Line::from(format!("/{search}"))
+    } else if !explorer.status().is_empty() {
+        Line::from(explorer.status().to_owned())
+    } else {
+        Line::from(Span::styled(HELP, theme::dim()))
+    };
+
+    frame.render_widget(Paragraph::new(line), area);
+}
+
+fn tree_line<'a>(row: &Row, explorer: &Explorer) -> Line<'a> {
+    let db = explorer.db();
+    let item = Item::new(row.hir);
+
+    let styles = styles();
+
+    let marker = match (row.is_expandable, row.is_expanded) {
+        (true, true) => "▾ ",
+        (true, false) => "▸ ",
+        (false, _) => "  ",
+    };
+
+    let mut spans = vec![
+        Span::styled(branch_prefix(&row.twigs), theme::style(styles.branch)),
+        Span::raw(marker),
+        Span::styled(
+            item.kind_display_name(db).to_string(),
+            theme::style(styles.kind),
+        ),
+        Span::raw(" "),
+        Span::styled(item.display_name(db), theme::style(styles.name)),
+    ];
+
+    if !analyzer::moduledef_is_crate(row.hir, db) {
+        let visibility = item.visibility(db);
+
+        let visibility_styles = styles.visibility;
+        let visibility_style = match &visibility {
+            ItemVisibility::Crate => visibility_styles.pub_crate,
+            ItemVisibility::Module(_) => visibility_styles.pub_module,
+            ItemVisibility::Private => visibility_styles.pub_private,
+            ItemVisibility::Public => visibility_styles.pub_global,
+            ItemVisibility::Super => visibility_styles.pub_super,
+        };
+
+        spans.push(Span::styled(":", theme::style(styles.colon)));
+        spans.push(Span::raw(" "));
+        spans.push(Span::styled(
+            visibility.to_string(),
+            theme::style(visibility_style),
+        ));
+    }
+
+    Line::from(spans)
+}
+
+fn related_line<'a>(hir: hir::ModuleDef, explorer: &Explorer) -> Line<'a> {
+    let db = explorer.db();
+    let item = Item::new(hir);
+
+    let styles = styles();
+
+    Line::from(vec![
+        Span::styled(
+            item.kind_display_name(db).to_string(),
+            theme::style(styles.kind),
+        ),
+        Span::raw(" "),
+        Span::styled(item.display_path(db), theme::style(styles.name)),
+    ])
+}
+
+/// Returns a row's prefix, mirroring the branches printed by the `structure` command.
+fn branch_prefix(twigs: &[bool]) -> String {
+    let mut string = String::new();
+
+    // First level is crate level, which doesn't get a branch:
+    if let [_trunk, branches @ .., leaf] = twigs {
+        for is_last in branches {
+            string.push_str(if *is_last { "    " } else { "│   " });
+        }
+        string.push_str(if *leaf { "└── " } else { "├── " });
+    }
+
+    string
+}
+
+fn block(title: &str, is_focused: bool) -> Block<'static> {
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .title(title.to_owned());
+
+    if is_focused {
+        block.border_style(theme::focused_border())
+    } else {
+        block.border_style(theme::dim())
+    }
+}
+
+fn split<const N: usize>(
+    area: Rect,
+    direction: Direction,
+    constraints: [Constraint; N],
+) -> [Rect; N] {
+    let areas = Layout::default()
+        .direction(direction)
+        .constraints(constraints)
+        .split(area);
+
+    std::array::from_fn(|index| areas[index])
+}
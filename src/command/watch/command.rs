@@ -0,0 +1,289 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at https://mozilla.org/MPL/2.0/.
+
+use std::{
+    collections::HashSet,
+    fs,
+    io::IsTerminal as _,
+    path::{Path, PathBuf},
+    sync::mpsc,
+    time::Duration,
+};
+
+use clap::{Parser, Subcommand};
+use log::{debug, trace};
+use notify::{RecursiveMode, Watcher as _};
+use ra_ap_hir as hir;
+use ra_ap_ide::AnalysisHost;
+use ra_ap_ide_db::ChangeWithProcMacros;
+use ra_ap_paths::{AbsPathBuf, Utf8PathBuf};
+use ra_ap_vfs::{Vfs, VfsPath};
+
+use crate::{
+    analyzer::{load_workspace, LoadOptions},
+    command::{
+        dependencies::command::Command as DependenciesCommand,
+        structure::command::Command as StructureCommand, NestedCommand, Rendered,
+    },
+    options::{GeneralOptions, ProjectOptions},
+    output,
+};
+
+use super::options::Options;
+
+const IGNORED_DIR_NAMES: [&str; 2] = ["target", ".git"];
+
+#[derive(Parser, Clone, PartialEq, Eq, Debug)]
+pub struct Command {
+    #[command(flatten)]
+    pub options: Options,
+
+    #[command(subcommand)]
+    pub command: WatchedCommand,
+}
+
+/// The commands that can be re-run on changes
+/// (i.e. those whose output can be rendered in full, for replacing the previous one).
+#[derive(Subcommand, Clone, PartialEq, Eq, Debug)]
+pub enum WatchedCommand {
+    #[command(
+        name = "structure",
+        about = "Prints a crate's hierarchical structure as a tree."
+    )]
+    Structure(Box<StructureCommand>),
+
+    #[command(
+        name = "dependencies",
+        about = "Prints a crate's internal dependencies as a graph."
+    )]
+    Dependencies(Box<DependenciesCommand>),
+}
+
+impl From<WatchedCommand> for NestedCommand {
+    fn from(command: WatchedCommand) -> Self {
+        match command {
+            WatchedCommand::Structure(command) => Self::Structure(*command),
+            WatchedCommand::Dependencies(command) => Self::Dependencies(*command),
+        }
+    }
+}
+
+impl Command {
+    pub fn new(options: Options, command: WatchedCommand) -> Self {
+        Self { options, command }
+    }
+
+    pub(crate) fn sanitize(&mut self) {
+        match &mut self.command {
+            WatchedCommand::Structure(command) => command.sanitize(),
+            WatchedCommand::Dependencies(command) => command.sanitize(),
+        }
+    }
+
+    #[doc(hidden)]
+    pub fn run(self) -> anyhow::Result<()> {
+        let command = NestedCommand::from(self.command);
+
+        let general_options = command.general_options();
+        let project_options = command.project_options();
+        let load_options = command.load_options();
+
+        let debounce = Duration::from_millis(self.options.debounce);
+
+        watch_project(
+            general_options,
+            project_options,
+            &load_options,
+            debounce,
+            |krates, host| Self::emit(&command, krates, host),
+        )
+    }
+
+    pub(crate) fn output_path(&self) -> Option<&Path> {
+        match &self.command {
+            WatchedCommand::Structure(command) => command.options.output.as_deref(),
+            WatchedCommand::Dependencies(command) => command.options.output.as_deref(),
+        }
+    }
+
+    pub(crate) fn general_options(&self) -> &GeneralOptions {
+        match &self.command {
+            WatchedCommand::Structure(command) => &command.options.general,
+            WatchedCommand::Dependencies(command) => &command.options.general,
+        }
+    }
+
+    pub(crate) fn project_options(&self) -> &ProjectOptions {
+        match &self.command {
+            WatchedCommand::Structure(command) => &command.options.project,
+            WatchedCommand::Dependencies(command) => &command.options.project,
+        }
+    }
+
+    pub(crate) fn project_options_mut(&mut self) -> &mut ProjectOptions {
+        match &mut self.command {
+            WatchedCommand::Structure(command) => &mut command.options.project,
+            WatchedCommand::Dependencies(command) => &mut command.options.project,
+        }
+    }
+
+    fn emit(command: &NestedCommand, krates: &[hir::Crate], host: &AnalysisHost) {
+        trace!("Emitting output ...");
+
+        let Rendered { output, checked } = match command.render(krates, host.raw_database()) {
+            Ok(rendered) => rendered,
+            Err(err) => {
+                eprintln!("Error: {err:?}");
+                return;
+            }
+        };
+
+        match command.output_path() {
+            Some(output_path) => {
+                if let Err(err) = output::write_atomically(output_path, output) {
+                    eprintln!("Error: {err:?}");
+                }
+            }
+            None => {
+                if std::io::stdout().is_terminal() {
+                    // Clear screen and move cursor to top-left corner:
+                    print!("\x1b[2J\x1b[H");
+                }
+                print!("{output}");
+            }
+        }
+
+        if let Err(err) = checked {
+            eprintln!("Error: {err:?}");
+        }
+    }
+}
+
+/// Loads the project, invoking `emit` once initially
+/// and then again whenever any of the project's files change.
+pub(crate) fn watch_project(
+    general_options: &GeneralOptions,
+    project_options: &ProjectOptions,
+    load_options: &LoadOptions,
+    debounce: Duration,
+    mut emit: impl FnMut(&[hir::Crate], &AnalysisHost),
+) -> anyhow::Result<()> {
+    let (mut krates, mut host, mut vfs) =
+        load_workspace(general_options, project_options, load_options)?;
+
+    emit(&krates, &host);
+
+    let project_path = project_options.manifest_path.canonicalize()?;
+    let project_dir = if project_path.is_file() {
+        project_path
+            .parent()
+            .map(Path::to_path_buf)
+            .unwrap_or(project_path)
+    } else {
+        project_path
+    };
+
+    let (sender, receiver) = mpsc::channel();
+    let mut watcher = notify::recommended_watcher(sender)?;
+    watcher.watch(&project_dir, RecursiveMode::Recursive)?;
+
+    loop {
+        // Block until something changes, then collect any further changes
+        // that arrive shortly after, to avoid re-analyzing on every keystroke:
+
+        let mut changed_paths: HashSet<PathBuf> = HashSet::new();
+
+        let event = receiver.recv()?;
+        changed_paths.extend(event?.paths);
+
+        while let Ok(event) = receiver.recv_timeout(debounce) {
+            changed_paths.extend(event?.paths);
+        }
+
+        changed_paths.retain(|path| is_relevant_path(path, &project_dir));
+
+        if changed_paths.is_empty() {
+            continue;
+        }
+
+        debug!("Changed files: {changed_paths:#?}");
+
+        if let Err(reason) = apply_changes(&mut host, &mut vfs, &changed_paths) {
+            debug!("Reloading workspace: {reason}");
+
+            match load_workspace(general_options, project_options, load_options) {
+                Ok((new_krates, new_host, new_vfs)) => {
+                    krates = new_krates;
+                    host = new_host;
+                    vfs = new_vfs;
+                }
+                Err(err) => {
+                    eprintln!("Error: {err:?}");
+                    continue;
+                }
+            }
+        }
+
+        emit(&krates, &host);
+    }
+}
+
+fn is_relevant_path(path: &Path, project_dir: &Path) -> bool {
+    let Ok(relative_path) = path.strip_prefix(project_dir) else {
+        return false;
+    };
+
+    let is_ignored = relative_path.components().any(|component| {
+        IGNORED_DIR_NAMES
+            .iter()
+            .any(|ignored_name| component.as_os_str() == *ignored_name)
+    });
+
+    if is_ignored {
+        return false;
+    }
+
+    let file_name = path.file_name().and_then(|file_name| file_name.to_str());
+    let extension = path.extension().and_then(|extension| extension.to_str());
+
+    extension == Some("rs") || matches!(file_name, Some("Cargo.toml" | "Cargo.lock"))
+}
+
+/// Applies modifications of already known source files to the analysis host in-place.
+///
+/// Returns the reason for why a full reload is required otherwise
+/// (e.g. if files got added or removed, or manifests changed).
+fn apply_changes(
+    host: &mut AnalysisHost,
+    vfs: &mut Vfs,
+    changed_paths: &HashSet<PathBuf>,
+) -> Result<(), String> {
+    let mut change = ChangeWithProcMacros::new();
+
+    for path in changed_paths {
+        if path.extension().and_then(|extension| extension.to_str()) != Some("rs") {
+            return Err(format!("manifest {path:?} changed"));
+        }
+
+        let Some(utf8_path_buf) = Utf8PathBuf::from_path_buf(path.clone()).ok() else {
+            return Err(format!("non-UTF-8 path {path:?}"));
+        };
+        let vfs_path = VfsPath::from(AbsPathBuf::assert(utf8_path_buf));
+
+        let Some(file_id) = vfs.file_id(&vfs_path) else {
+            return Err(format!("file {path:?} is unknown"));
+        };
+
+        let Ok(text) = fs::read_to_string(path) else {
+            return Err(format!("file {path:?} is unreadable"));
+        };
+
+        vfs.set_file_contents(vfs_path, Some(text.clone().into_bytes()));
+        change.change_file(file_id, Some(text));
+    }
+
+    host.apply_change(change);
+
+    Ok(())
+}
@@ -0,0 +1,13 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at https://mozilla.org/MPL/2.0/.
+
+use clap::Parser;
+
+#[derive(Parser, Clone, PartialEq, Eq, Debug)]
+#[group(id = "WatchOptions")]
+pub struct Options {
+    /// Time to wait for further file changes before re-analyzing, in milliseconds.
+    #[arg(long = "debounce", default_value = "200")]
+    pub debounce: u64,
+}
@@ -0,0 +1,285 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at https://mozilla.org/MPL/2.0/.
+
+//! Project-wide default options, as provided via a `.cargo-modules.toml` file
+//! (or a `[package.metadata.cargo-modules]` table in `Cargo.toml`).
+//!
+//! Each table of the config is named after a command and maps that command's
+//! (long) flags to their default values:
+//!
+//! ```toml
+//! [structure]
+//! no-fns = true
+//! max-depth = 2
+//!
+//! [dependencies]
+//! no-externs = true
+//! analyzer = ["stats", "fan-out"]
+//! ```
+//!
+//! Custom color themes can be defined via `[themes.<name>]` tables (see `colors::theme`),
+//! per-kind node styles via `[styles.<kind>]` tables (see `colors::styles`),
+//! and per-module dependency budgets via `[budgets."<path>"]` tables (see `command::check::budget`).
+//!
+//! The defaults get injected right after the command's name on the command-line,
+//! unless passed explicitly (in which case they replace the defaults, including any lists of values)
+//! or conflicting with options passed explicitly (e.g. `cache = true` with `--emit`).
+//! Flags turned on by the config can be turned off again via their negated counterpart
+//! (e.g. `--no-acyclic` for `acyclic = true`, or `--fns` for `no-fns = true`).
+
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+use anyhow::{bail, Context as _};
+use clap::{parser::ValueSource, ArgMatches};
+use log::debug;
+
+use crate::options::{self, App};
+
+/// The name of the project's config file.
+pub const FILE_NAME: &str = ".cargo-modules.toml";
+
+/// Returns `args` with the project's configured defaults injected, if any.
+pub fn args_with_defaults(args: Vec<String>) -> anyhow::Result<Vec<String>> {
+    let mut app = App::command_with_negations();
+
+    // Building the command populates its groups with their args (see `are_conflicting`):
+    app.build();
+
+    // Invalid args (as well as `--help` & co.) get reported by the subsequent actual parsing:
+
+    let Ok(matches) = app.clone().try_get_matches_from(&args) else {
+        return Ok(args);
+    };
+
+    let mut subcommands: Vec<(&str, &ArgMatches)> = vec![];
+    let mut current_matches = &matches;
+
+    while let Some((name, sub_matches)) = current_matches.subcommand() {
+        subcommands.push((name, sub_matches));
+        current_matches = sub_matches;
+    }
+
+    // Fetched crates are not subject to the current project's config:
+
+    if matches!(subcommands.first(), None | Some(("fetch", _))) {
+        return Ok(args);
+    }
+
+    let Some(manifest_path) = current_matches
+        .try_get_one::<PathBuf>("manifest_path")
+        .ok()
+        .flatten()
+    else {
+        return Ok(args);
+    };
+
+    let Some((config_path, config)) = load(manifest_path)? else {
+        return Ok(args);
+    };
+
+    debug!("Applying defaults from {}", config_path.display());
+
+    let context = || format!("Invalid config in {}", config_path.display());
+
+    validate(&app, &config).with_context(context)?;
+
+    // Find each (sub)command's position on the command-line:
+
+    let mut positions: Vec<(usize, &str)> = vec![];
+    let mut start = 1;
+
+    for &(name, _) in &subcommands {
+        let Some(offset) = args[start..].iter().position(|arg| arg == name) else {
+            return Ok(args);
+        };
+        positions.push((start + offset, name));
+        start += offset + 1;
+    }
+
+    let mut args = args;
+    let mut command = &app;
+
+    let mut insertions: Vec<(usize, Vec<String>)> = vec![];
+
+    for ((position, name), (_, sub_matches)) in positions.into_iter().zip(&subcommands) {
+        command = command
+            .find_subcommand(name)
+            .expect("matched subcommand should exist");
+
+        if let Some(table) = config.get(name) {
+            let defaults = defaults_for(command, sub_matches, table).with_context(context)?;
+            insertions.push((position + 1, defaults));
+        }
+    }
+
+    for (index, defaults) in insertions.into_iter().rev() {
+        args.splice(index..index, defaults);
+    }
+
+    debug!("Arguments (with defaults): {:?}", args);
+
+    Ok(args)
+}
+
+/// Loads the config of the project at `manifest_path`, if it has any.
+pub(crate) fn load(manifest_path: &Path) -> anyhow::Result<Option<(PathBuf, toml::Table)>> {
+    let project_dir = if manifest_path.is_file() {
+        manifest_path.parent().expect("parent directory")
+    } else {
+        manifest_path
+    };
+
+    let config_path = project_dir.join(FILE_NAME);
+
+    if config_path.is_file() {
+        let config = read_toml(&config_path)?;
+        return Ok(Some((config_path, config)));
+    }
+
+    let cargo_toml_path = project_dir.join("Cargo.toml");
+
+    if !cargo_toml_path.is_file() {
+        return Ok(None);
+    }
+
+    let cargo_toml = read_toml(&cargo_toml_path)?;
+
+    let config = ["package", "workspace"].into_iter().find_map(|key| {
+        cargo_toml
+            .get(key)?
+            .get("metadata")?
+            .get("cargo-modules")?
+            .as_table()
+            .cloned()
+    });
+
+    Ok(config.map(|config| (cargo_toml_path, config)))
+}
+
+fn read_toml(path: &Path) -> anyhow::Result<toml::Table> {
+    let contents =
+        fs::read_to_string(path).with_context(|| format!("Could not read {}", path.display()))?;
+
+    toml::from_str(&contents).with_context(|| format!("Could not parse {}", path.display()))
+}
+
+fn validate(app: &clap::Command, config: &toml::Table) -> anyhow::Result<()> {
+    for (name, value) in config {
+        // Custom color themes (see `colors::theme`), node styles (see `colors::styles`),
+        // module budgets (see `command::check::budget`) and owners (see `command::stats::teams`):
+
+        if ["themes", "styles", "budgets", "owners"].contains(&name.as_str()) {
+            continue;
+        }
+
+        if app.find_subcommand(name).is_none() {
+            bail!("Unknown command `{name}`");
+        }
+
+        if !value.is_table() {
+            bail!("Expected a table of options for command `{name}`");
+        }
+    }
+
+    Ok(())
+}
+
+/// Converts the config's table of options for `command` into command-line args,
+/// skipping any options passed explicitly (as per `matches`).
+fn defaults_for(
+    command: &clap::Command,
+    matches: &ArgMatches,
+    table: &toml::Value,
+) -> anyhow::Result<Vec<String>> {
+    let name = command.get_name();
+    let table = table.as_table().expect("validated table");
+
+    let mut args = vec![];
+
+    let explicit_args: Vec<&clap::Arg> = command
+        .get_arguments()
+        .filter(|arg| matches.value_source(arg.get_id().as_str()) == Some(ValueSource::CommandLine))
+        .collect();
+
+    for (key, value) in table {
+        let is_known = command
+            .get_arguments()
+            .any(|arg| arg.get_long() == Some(key.as_str()));
+
+        if !is_known {
+            bail!("Unknown option `{key}` for command `{name}`");
+        }
+
+        let is_explicit = |long: &str| {
+            command
+                .get_arguments()
+                .filter(|arg| arg.get_long() == Some(long))
+                .any(|arg| {
+                    matches.value_source(arg.get_id().as_str()) == Some(ValueSource::CommandLine)
+                })
+        };
+
+        // Explicitly passed options (or their negations) replace the config's defaults:
+        if is_explicit(key) || is_explicit(&options::negated_flag(key)) {
+            continue;
+        }
+
+        // Defaults conflicting with explicitly passed options (e.g. `cache = true` with `--emit`)
+        // make way for the latter, rather than failing the parsing of the command-line:
+        let is_conflicting = command
+            .get_arguments()
+            .filter(|arg| arg.get_long() == Some(key.as_str()))
+            .any(|arg| {
+                explicit_args
+                    .iter()
+                    .any(|explicit_arg| are_conflicting(command, arg, explicit_arg))
+            });
+
+        if is_conflicting {
+            debug!("Skipping default `{key}` of command `{name}`, as it conflicts with the command-line");
+            continue;
+        }
+
+        let values: Vec<&toml::Value> = match value {
+            toml::Value::Array(values) => values.iter().collect(),
+            value => vec![value],
+        };
+
+        for value in values {
+            match value {
+                toml::Value::Boolean(true) => args.push(format!("--{key}")),
+                toml::Value::Boolean(false) => {}
+                toml::Value::String(string) => args.push(format!("--{key}={string}")),
+                toml::Value::Integer(integer) => args.push(format!("--{key}={integer}")),
+                toml::Value::Float(float) => args.push(format!("--{key}={float}")),
+                _ => bail!("Unsupported value for option `{key}` of command `{name}`"),
+            }
+        }
+    }
+
+    Ok(args)
+}
+
+/// Returns whether `arg` and `other` can't be passed together, be it due to a `conflicts_with`
+/// (on either of them) or due to their sharing a group of mutually exclusive args (e.g. `--lib` and `--bin`).
+fn are_conflicting(command: &clap::Command, arg: &clap::Arg, other: &clap::Arg) -> bool {
+    let conflicts_with = |arg: &clap::Arg, other: &clap::Arg| {
+        command
+            .get_arg_conflicts_with(arg)
+            .iter()
+            .any(|conflicting_arg| conflicting_arg.get_id() == other.get_id())
+    };
+
+    // `ArgGroup::is_multiple` needlessly takes `&mut self`, hence the clone:
+    let share_exclusive_group = command.get_groups().any(|group| {
+        !group.clone().is_multiple()
+            && group.get_args().any(|id| id == arg.get_id())
+            && group.get_args().any(|id| id == other.get_id())
+    });
+
+    conflicts_with(arg, other) || conflicts_with(other, arg) || share_exclusive_group
+}
@@ -0,0 +1,60 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at https://mozilla.org/MPL/2.0/.
+
+//! Minimal glob matching for package names and file paths.
+//!
+//! Supported syntax:
+//!
+//! - `?` matches any single character, except `/`.
+//! - `*` matches any sequence of characters, except `/`.
+//! - `**` matches any sequence of characters, including `/`.
+
+pub(crate) fn is_glob(pattern: &str) -> bool {
+    pattern.contains(['*', '?'])
+}
+
+pub(crate) fn glob_matches(pattern: &str, text: &str) -> bool {
+    let pattern: Vec<char> = pattern.chars().collect();
+    let text: Vec<char> = text.chars().collect();
+
+    matches(&pattern[..], &text[..])
+}
+
+fn matches(pattern: &[char], text: &[char]) -> bool {
+    match pattern {
+        [] => text.is_empty(),
+        ['*', '*', rest @ ..] => {
+            // A `**/` may also match zero directories:
+            if let ['/', tail @ ..] = rest {
+                if matches(tail, text) {
+                    return true;
+                }
+            }
+
+            (0..=text.len()).any(|idx| matches(rest, &text[idx..]))
+        }
+        ['*', rest @ ..] => {
+            for idx in 0..=text.len() {
+                if matches(rest, &text[idx..]) {
+                    return true;
+                }
+
+                // A single `*` never crosses a directory boundary:
+                if text.get(idx) == Some(&'/') {
+                    break;
+                }
+            }
+
+            false
+        }
+        ['?', rest @ ..] => match text {
+            [head, tail @ ..] => *head != '/' && matches(rest, tail),
+            [] => false,
+        },
+        [expected, rest @ ..] => match text {
+            [head, tail @ ..] => head == expected && matches(rest, tail),
+            [] => false,
+        },
+    }
+}
@@ -5,6 +5,7 @@
 use std::fmt;
 
 use petgraph::stable_graph::StableGraph;
+use ra_ap_hir as hir;
 
 use crate::item::Item;
 
@@ -16,11 +17,55 @@ pub(crate) use self::{builder::GraphBuilder, walker::GraphWalker};
 pub type Graph<N, E> = StableGraph<N, E>;
 
 pub type Node = Item;
-pub type Edge = Relationship;
+
+/// A relationship between two nodes.
+#[derive(Clone, Eq, PartialEq, Debug)]
+pub struct Edge {
+    pub kind: Relationship,
+    /// The number of item-level relationships the edge stands for,
+    /// as edges of filtered-out nodes get merged into their parents' edges.
+    pub weight: usize,
+    /// The items actually being used (for "uses" edges),
+    /// which may have since been merged into the edge's target.
+    pub targets: Vec<hir::ModuleDef>,
+    /// Whether the edge stems from test-only code (i.e. `#[cfg(test)]` modules and items,
+    /// or `#[test]` functions) exclusively.
+    pub test_only: bool,
+}
+
+impl Edge {
+    pub fn new(kind: Relationship) -> Self {
+        Self {
+            kind,
+            weight: 1,
+            targets: vec![],
+            test_only: false,
+        }
+    }
+
+    pub fn uses(target: hir::ModuleDef) -> Self {
+        Self {
+            targets: vec![target],
+            ..Self::new(Relationship::Uses)
+        }
+    }
+
+    pub fn reexports(target: hir::ModuleDef) -> Self {
+        Self {
+            targets: vec![target],
+            ..Self::new(Relationship::ReExports)
+        }
+    }
+
+    pub fn display_name(&self) -> &'static str {
+        self.kind.display_name()
+    }
+}
 
 #[derive(Copy, Clone, Hash, Eq, PartialEq, Debug)]
 pub enum Relationship {
     Uses,
+    ReExports,
     Owns,
 }
 
@@ -28,6 +73,7 @@ impl Relationship {
     pub fn display_name(&self) -> &'static str {
         match self {
             Self::Uses => "uses",
+            Self::ReExports => "reexports",
             Self::Owns => "owns",
         }
     }
@@ -37,6 +83,7 @@ impl fmt::Display for Relationship {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         let name = match self {
             Self::Uses => "Uses",
+            Self::ReExports => "ReExports",
             Self::Owns => "Owns",
         };
         write!(f, "{name}")
@@ -4,17 +4,23 @@
 
 use std::collections::{HashMap, HashSet};
 
+use anyhow::Context as _;
 use hir::HirDisplay;
 use log::{debug, trace};
 use petgraph::graph::{EdgeIndex, NodeIndex};
 use ra_ap_hir::{self as hir};
 use ra_ap_hir_def::{self as hir_def};
 use ra_ap_hir_ty::{self as hir_ty, db::HirDatabase as _, TyExt as _};
-use ra_ap_ide_db::{self as ide_db};
+use ra_ap_ide_db::{
+    self as ide_db,
+    base_db::salsa::{Cancelled, ParallelDatabase as _},
+};
+use rayon::prelude::*;
 use scopeguard::defer;
 
 use crate::{
     analyzer,
+    api::{self, EdgeData, NodeId, Visitor},
     graph::{Edge, Graph, Node, Relationship},
     item::Item,
 };
@@ -26,42 +32,125 @@ struct Dependency {
     target_hir: hir::ModuleDef,
 }
 
-#[derive(Debug)]
 pub struct GraphBuilder<'a> {
     db: &'a ide_db::RootDatabase,
-    krate: hir::Crate,
+    krates: &'a [hir::Crate],
     graph: Graph<Node, Edge>,
     nodes: HashMap<hir::ModuleDef, NodeIndex>,
     edges: HashMap<(NodeIndex, Relationship, NodeIndex), EdgeIndex>,
+    visitor: Option<&'a mut dyn Visitor>,
 }
 
 impl<'a> GraphBuilder<'a> {
-    pub fn new(db: &'a ide_db::RootDatabase, krate: hir::Crate) -> Self {
+    pub fn new(db: &'a ide_db::RootDatabase, krates: &'a [hir::Crate]) -> Self {
         let graph = Graph::default();
         let nodes = HashMap::default();
         let edges = HashMap::default();
 
         Self {
             db,
-            krate,
+            krates,
             graph,
             nodes,
             edges,
+            visitor: None,
         }
     }
 
-    pub fn build(mut self) -> anyhow::Result<(Graph<Node, Edge>, NodeIndex)> {
+    /// Notifies `visitor` of each node and edge as they get added to the graph.
+    pub fn with_visitor(mut self, visitor: &'a mut dyn Visitor) -> Self {
+        self.visitor = Some(visitor);
+        self
+    }
+
+    pub fn build(mut self) -> anyhow::Result<(Graph<Node, Edge>, Vec<NodeIndex>)> {
         trace!("Scanning project...");
 
         defer! {
             trace!("Finished canning project");
         }
 
-        let node_idx = self
-            .process_crate(self.krate)
-            .expect("graph node for crate root module");
+        let krates = self.krates;
+
+        self.prime_inference_caches();
+
+        let node_idxs = krates
+            .iter()
+            .map(|krate| {
+                self.process_crate(*krate).with_context(|| {
+                    let crate_name = analyzer::crate_name(*krate, self.db);
+                    format!("Could not add crate `{crate_name}` to graph")
+                })
+            })
+            .collect::<anyhow::Result<_>>()?;
+
+        Ok((self.graph, node_idxs))
+    }
+
+    /// Type inference of function bodies dominates the time spent building the graph,
+    /// so we run it up-front on all available threads, leaving the (sequential)
+    /// graph traversal with nothing but look-ups into the database's caches.
+    ///
+    /// With only a single thread available there is nothing to be gained from this,
+    /// so priming gets skipped (e.g. for `RAYON_NUM_THREADS=1`).
+    fn prime_inference_caches(&self) {
+        let thread_count = rayon::current_num_threads();
+
+        if thread_count <= 1 {
+            trace!("Skipping priming of inference caches on a single thread.");
+            return;
+        }
+
+        trace!("Priming inference caches...");
+
+        defer! {
+            trace!("Finished priming inference caches.");
+        }
+
+        let mut functions: Vec<hir::Function> = vec![];
 
-        Ok((self.graph, node_idx))
+        for krate in self.krates {
+            for module_hir in krate.modules(self.db) {
+                for declaration in module_hir.declarations(self.db) {
+                    if let hir::ModuleDef::Function(function_hir) = declaration {
+                        functions.push(function_hir);
+                    }
+                }
+            }
+
+            for impl_hir in hir::Impl::all_in_crate(self.db, *krate) {
+                for item in impl_hir.items(self.db) {
+                    if let hir::AssocItem::Function(function_hir) = item {
+                        functions.push(function_hir);
+                    }
+                }
+            }
+        }
+
+        let chunk_size = functions.len().div_ceil(thread_count).max(1);
+
+        // The database itself can't be shared across threads, but its snapshots can be sent:
+        let chunks: Vec<_> = functions
+            .chunks(chunk_size)
+            .map(|chunk| (chunk.to_vec(), self.db.snapshot()))
+            .collect();
+
+        // Priming is merely an optimization, so rather than unwinding the whole build
+        // a cancelled snapshot just stops priming its chunk, leaving the remaining
+        // bodies to get inferred lazily by the graph traversal:
+        chunks.into_par_iter().for_each(|(functions, db)| {
+            let result = Cancelled::catch(|| {
+                for function_hir in functions {
+                    let def_with_body = hir::DefWithBody::from(function_hir);
+                    let def_with_body_id: hir_def::DefWithBodyId = def_with_body.into();
+                    let _ = db.infer(def_with_body_id);
+                }
+            });
+
+            if let Err(cancelled) = result {
+                debug!("Priming of inference caches got cancelled: {cancelled}");
+            }
+        });
     }
 
     fn process_crate(&mut self, crate_hir: hir::Crate) -> Option<NodeIndex> {
@@ -95,7 +184,7 @@ impl<'a> GraphBuilder<'a> {
             };
 
             for impl_item_idx in self.process_impl(impl_hir) {
-                self.add_edge(impl_ty_idx, impl_item_idx, Edge::Owns);
+                self.add_edge(impl_ty_idx, impl_item_idx, Edge::new(Relationship::Owns));
             }
         }
 
@@ -214,10 +303,12 @@ impl<'a> GraphBuilder<'a> {
                     continue;
                 };
 
-                self.add_edge(node_idx, declaration_idx, Edge::Owns);
+                self.add_edge(node_idx, declaration_idx, Edge::new(Relationship::Owns));
             }
         }
 
+        let reexports = analyzer::reexports(module_hir, self.db);
+
         for (_name, scope_hir) in module_hir.scope(self.db, None) {
             let hir::ScopeDef::ModuleDef(scope_module_hir) = scope_hir else {
                 // Skip everything but module-defs:
@@ -230,9 +321,36 @@ impl<'a> GraphBuilder<'a> {
                 continue;
             }
 
+            // Re-exports get their own kind of edge:
+            if reexports.contains(&scope_module_hir) {
+                continue;
+            }
+
             dependencies_callback(scope_module_hir);
         }
 
+        if let Some(node_idx) = node_idx {
+            let test_only = analyzer::is_test_only(module_hir.into(), self.db);
+
+            for reexport_hir in reexports {
+                // Check if definition is a child of `module`:
+                if reexport_hir.module(self.db) == Some(module_hir) {
+                    continue;
+                }
+
+                let Some(reexport_idx) = self.add_node_if_necessary(reexport_hir) else {
+                    continue;
+                };
+
+                let edge = Edge {
+                    test_only,
+                    ..Edge::reexports(reexport_hir)
+                };
+
+                self.add_edge(node_idx, reexport_idx, edge);
+            }
+        }
+
         node_idx
     }
 
@@ -712,12 +830,19 @@ impl<'a> GraphBuilder<'a> {
             trace!("Finished adding outgoing 'use' edges for node {depender_idx:?}");
         }
 
+        let test_only = analyzer::is_test_only(self.graph[depender_idx].hir, self.db);
+
         for dependency_hir in dependencies {
-            let Some(dependency_hir) = self.add_node_if_necessary(dependency_hir) else {
+            let Some(dependency_idx) = self.add_node_if_necessary(dependency_hir) else {
                 continue;
             };
 
-            self.add_edge(depender_idx, dependency_hir, Edge::Uses);
+            let edge = Edge {
+                test_only,
+                ..Edge::uses(dependency_hir)
+            };
+
+            self.add_edge(depender_idx, dependency_idx, edge);
         }
     }
 
@@ -746,6 +871,8 @@ impl<'a> GraphBuilder<'a> {
                 let node_idx = self.graph.add_node(node);
                 self.nodes.insert(module_def_hir, node_idx);
 
+                self.visit_node(node_idx);
+
                 Some(node_idx)
             }
         }
@@ -765,7 +892,7 @@ impl<'a> GraphBuilder<'a> {
         let target_path = self.graph[target_idx].display_path(self.db);
         let edge_name = edge.display_name();
 
-        let edge_id = (source_idx, edge, target_idx);
+        let edge_id = (source_idx, edge.kind, target_idx);
 
         trace!("Adding edge: {source_path} --({edge_name})-> {target_path}...");
 
@@ -781,13 +908,43 @@ impl<'a> GraphBuilder<'a> {
             }
             None => {
                 // Otherwise add an edge:
-                let edge_idx = self.graph.add_edge(source_idx, target_idx, edge);
+                let edge_idx = self.graph.add_edge(source_idx, target_idx, edge.clone());
                 self.edges.insert(edge_id, edge_idx);
 
+                self.visit_edge(source_idx, target_idx, edge);
+
                 edge_idx
             }
         };
 
         Some(edge_idx)
     }
+
+    fn visit_node(&mut self, node_idx: NodeIndex) {
+        let Some(visitor) = self.visitor.as_deref_mut() else {
+            return;
+        };
+
+        let node = &self.graph[node_idx];
+        let node_id = NodeId::from_index(node_idx);
+        let node_data = api::node_data(node, self.krates, self.db);
+
+        match node.hir {
+            hir::ModuleDef::Module(_) => visitor.visit_module(node_id, &node_data),
+            _ => visitor.visit_item(node_id, &node_data),
+        }
+    }
+
+    fn visit_edge(&mut self, source_idx: NodeIndex, target_idx: NodeIndex, edge: Edge) {
+        let Some(visitor) = self.visitor.as_deref_mut() else {
+            return;
+        };
+
+        visitor.visit_edge(&EdgeData {
+            source: NodeId::from_index(source_idx),
+            target: NodeId::from_index(target_idx),
+            kind: edge.kind.into(),
+            weight: edge.weight,
+        });
+    }
 }
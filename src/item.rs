@@ -2,13 +2,16 @@
 // License, v. 2.0. If a copy of the MPL was not distributed with this
 // file, You can obtain one at https://mozilla.org/MPL/2.0/.
 
+use std::hash::Hasher as _;
+
+use fnv::FnvHasher;
 use ra_ap_hir::{self as hir};
 use ra_ap_ide_db::RootDatabase;
 
 use crate::analyzer;
 
 pub(crate) use self::{
-    attr::{ItemAttrs, ItemCfgAttr, ItemTestAttr},
+    attr::{ItemAttrs, ItemCfgAttr, ItemDeprecatedAttr, ItemTestAttr},
     kind_display_name::ItemKindDisplayName,
     kind_ordering::ItemKindOrdering,
     visibility::ItemVisibility,
@@ -45,6 +48,10 @@ impl Item {
         ItemKindDisplayName::new(self, db)
     }
 
+    pub fn doc_summary(&self, db: &RootDatabase) -> Option<String> {
+        analyzer::doc_summary(self.hir, db)
+    }
+
     pub fn display_name(&self, db: &RootDatabase) -> String {
         analyzer::display_name(self.hir, db)
     }
@@ -52,4 +59,41 @@ impl Item {
     pub fn display_path(&self, db: &RootDatabase) -> String {
         analyzer::display_path(self.hir, db)
     }
+
+    /// Returns the problems rust-analyzer ran into while resolving the item (if a module),
+    /// which leave its contents only partially known.
+    pub fn resolution_errors(&self, db: &RootDatabase) -> Vec<String> {
+        match self.hir {
+            hir::ModuleDef::Module(module_hir) => analyzer::resolution_errors(module_hir, db),
+            _ => vec![],
+        }
+    }
+
+    pub fn is_unresolved(&self, db: &RootDatabase) -> bool {
+        !self.resolution_errors(db).is_empty()
+    }
+
+    /// Returns an identifier derived from the item's crate name, path and kind (e.g. `n5c5ab3d21e6a9c0f`),
+    /// which (unlike a graph's node indices) stays the same across runs and crate versions.
+    pub fn stable_id(&self, db: &RootDatabase) -> String {
+        // Drop any qualifiers, such as in "async fn" or "unsafe trait",
+        // as these don't change the item's identity:
+        let kind = self.kind_display_name(db).to_string();
+        let kind = kind.rsplit(' ').next().unwrap_or_default();
+
+        // The crate's version is left out, as identifiers would change with every release otherwise:
+        let krate = analyzer::krate(self.hir, db)
+            .map(|krate| analyzer::crate_name(krate, db))
+            .unwrap_or_default();
+
+        // FNV-1a (unlike `DefaultHasher`) is fully specified,
+        // making the identifier independent of the toolchain used:
+        let mut hasher = FnvHasher::default();
+        for field in [&krate[..], &self.display_path(db), kind] {
+            hasher.write(field.as_bytes());
+            hasher.write_u8(0xff);
+        }
+
+        format!("n{:016x}", hasher.finish())
+    }
 }
@@ -84,20 +84,35 @@ impl fmt::Display for ItemTestAttr {
     }
 }
 
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct ItemDeprecatedAttr;
+
+impl fmt::Display for ItemDeprecatedAttr {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "deprecated")
+    }
+}
+
 #[derive(Clone, PartialEq, Debug)]
 pub struct ItemAttrs {
     pub cfgs: Vec<ItemCfgAttr>,
     pub test: Option<ItemTestAttr>,
+    pub deprecated: Option<ItemDeprecatedAttr>,
 }
 
 impl ItemAttrs {
     pub fn new(item: &Item, db: &RootDatabase) -> ItemAttrs {
         let cfgs: Vec<_> = analyzer::cfg_attrs(item.hir, db);
         let test = analyzer::test_attr(item.hir, db);
-        Self { cfgs, test }
+        let deprecated = analyzer::deprecated_attr(item.hir, db);
+        Self {
+            cfgs,
+            test,
+            deprecated,
+        }
     }
 
     pub fn is_empty(&self) -> bool {
-        self.test.is_none() && self.cfgs.is_empty()
+        self.test.is_none() && self.deprecated.is_none() && self.cfgs.is_empty()
     }
 }
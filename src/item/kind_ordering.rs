@@ -10,7 +10,7 @@ use ra_ap_ide_db::RootDatabase;
 use super::Item;
 
 #[derive(Clone, PartialEq, Eq, Debug)]
-pub(crate) enum ItemKindOrdering {
+pub enum ItemKindOrdering {
     Module {
         is_crate_root: bool,
     },
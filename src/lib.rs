@@ -2,11 +2,25 @@
 // License, v. 2.0. If a copy of the MPL was not distributed with this
 // file, You can obtain one at https://mozilla.org/MPL/2.0/.
 
+pub mod api;
 pub mod command;
+pub mod config;
 pub mod options;
+pub mod rustc_wrapper;
 
 pub(crate) mod analyzer;
+pub(crate) mod cache;
 pub(crate) mod colors;
+pub(crate) mod glob;
 pub(crate) mod graph;
 pub(crate) mod item;
+pub(crate) mod output;
+pub(crate) mod progress;
+pub(crate) mod quickfix;
+pub(crate) mod selector;
+pub(crate) mod summary;
+pub(crate) mod timings;
 pub(crate) mod tree;
+pub(crate) mod viewer;
+pub(crate) mod worktree;
+pub(crate) mod writer;
@@ -2,14 +2,28 @@
 // License, v. 2.0. If a copy of the MPL was not distributed with this
 // file, You can obtain one at https://mozilla.org/MPL/2.0/.
 
-use std::env;
+use std::{env, process};
 
-use clap::Parser;
 use log::debug;
 
-use cargo_modules::options::App;
+use cargo_modules::{
+    command::{
+        check::{budget, lints},
+        crates::unused,
+        dependencies::fail_on,
+        docs::coverage,
+    },
+    config,
+    options::App,
+    rustc_wrapper,
+};
 
 fn main() -> anyhow::Result<()> {
+    // Build scripts get run with this very binary wrapping `rustc`:
+    if env::var_os(rustc_wrapper::ENV_VAR).is_some() {
+        process::exit(rustc_wrapper::run());
+    }
+
     env_logger::init_from_env({
         let env = env_logger::Env::default();
         let key = env_logger::DEFAULT_FILTER_ENV;
@@ -20,12 +34,28 @@ fn main() -> anyhow::Result<()> {
     let args: Vec<_> = env::args().collect();
     debug!("Arguments: {:?}", args);
 
-    if env::var("NO_COLOR").is_ok() {
-        debug!("Disabling color output");
-        yansi::disable();
-    }
+    let args = App::args_with_default_command(args);
+    let args = config::args_with_defaults(args)?;
 
-    let app = App::parse();
+    let app = App::parse_with_negations(args);
     let command = app.sanitized_command();
-    command.run()
+
+    // Failed `--fail-on` conditions (as well as exceeded budgets, denied lints, undocumented public items
+    // or unused dependencies) get an exit code of their own, for CI to tell them apart
+    // from the tool itself failing:
+    if let Err(err) = command.run() {
+        if err.is::<fail_on::Failure>()
+            || err.is::<budget::Failure>()
+            || err.is::<lints::Failure>()
+            || err.is::<coverage::Failure>()
+            || err.is::<unused::Failure>()
+        {
+            eprintln!("Error: {err:?}");
+            process::exit(fail_on::EXIT_CODE);
+        }
+
+        return Err(err);
+    }
+
+    Ok(())
 }
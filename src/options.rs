@@ -2,13 +2,15 @@
 // License, v. 2.0. If a copy of the MPL was not distributed with this
 // file, You can obtain one at https://mozilla.org/MPL/2.0/.
 
-use std::path::PathBuf;
+use std::{fmt::Display, path::PathBuf, str::FromStr};
 
-use clap::{ArgGroup, Parser};
+use clap::{Arg, ArgAction, ArgGroup, CommandFactory as _, FromArgMatches as _, Parser};
 
 use crate::command::Command;
 
 #[derive(Parser, Clone, PartialEq, Eq, Debug)]
+// Allows for explicitly passed flags to override the project config's defaults:
+#[command(args_override_self = true)]
 pub struct App {
     #[arg(hide = true, value_parser = clap::builder::PossibleValuesParser::new(["modules"]))]
     pub dummy: Option<String>,
@@ -17,7 +19,45 @@ pub struct App {
     pub command: Command,
 }
 
+/// The command run by a bare `cargo modules` (as well as one given only options, e.g. `--lib`).
+const DEFAULT_COMMAND: &str = "structure";
+
 impl App {
+    /// Inserts the default command into `args`, unless they already contain a command (or ask for help).
+    pub fn args_with_default_command(mut args: Vec<String>) -> Vec<String> {
+        // Skip the binary, as well as the `modules` passed along by `cargo modules`:
+        let mut index = 1.min(args.len());
+
+        if args.get(index).is_some_and(|arg| arg == "modules") {
+            index += 1;
+        }
+
+        let is_missing = match args.get(index) {
+            None => true,
+            Some(arg) => arg.starts_with('-') && !matches!(arg.as_str(), "-h" | "--help"),
+        };
+
+        if is_missing {
+            args.insert(index, DEFAULT_COMMAND.to_owned());
+        }
+
+        args
+    }
+
+    /// Parses `args`, accepting a (hidden) negated counterpart of each of the commands' flags
+    /// (e.g. `--no-acyclic` for `--acyclic`, or `--fns` for `--no-fns`), which turns the flag off again
+    /// (e.g. if turned on by the project's config).
+    pub fn parse_with_negations(args: Vec<String>) -> Self {
+        let matches = Self::command_with_negations().get_matches_from(args);
+
+        Self::from_arg_matches(&matches).unwrap_or_else(|err| err.exit())
+    }
+
+    /// Returns the command-line interface, along with the negated counterparts of its flags.
+    pub fn command_with_negations() -> clap::Command {
+        with_negations(Self::command())
+    }
+
     pub fn sanitized_command(self) -> Command {
         let mut command = self.command;
         command.sanitize();
@@ -25,6 +65,39 @@ impl App {
     }
 }
 
+/// Returns the name of the flag negating the flag `long` (e.g. `no-acyclic` for `acyclic`).
+pub(crate) fn negated_flag(long: &str) -> String {
+    match long.strip_prefix("no-") {
+        Some(long) => long.to_owned(),
+        None => format!("no-{long}"),
+    }
+}
+
+fn with_negations(command: clap::Command) -> clap::Command {
+    let negations: Vec<Arg> = command
+        .get_arguments()
+        .filter(|arg| matches!(arg.get_action(), ArgAction::SetTrue))
+        .filter_map(|arg| {
+            let negated = negated_flag(arg.get_long()?);
+
+            // Flags that already have an explicit counterpart keep it:
+            let is_taken = command
+                .get_arguments()
+                .any(|arg| arg.get_long() == Some(negated.as_str()));
+
+            (!is_taken).then(|| {
+                Arg::new(format!("{}-negated", arg.get_id()))
+                    .long(negated)
+                    .action(ArgAction::SetTrue)
+                    .hide(true)
+                    .overrides_with(arg.get_id())
+            })
+        })
+        .collect();
+
+    command.args(negations).mut_subcommands(with_negations)
+}
+
 #[derive(Parser, Clone, PartialEq, Eq, Debug)]
 #[group(id = "ProjectOptions")]
 #[command(group = ArgGroup::new("target-group"))]
@@ -37,9 +110,34 @@ pub struct ProjectOptions {
     #[arg(long = "bin", group = "target-group")]
     pub bin: Option<String>,
 
+    /// Process only the specified example.
+    #[arg(long = "example", group = "target-group")]
+    pub example: Option<String>,
+
+    /// Process only the specified integration test.
+    #[arg(long = "test", group = "target-group")]
+    pub test: Option<String>,
+
+    /// Process only the specified bench.
+    #[arg(long = "bench", group = "target-group")]
+    pub bench: Option<String>,
+
+    /// Process only this package's build script.
+    #[arg(long = "build-script", group = "target-group")]
+    pub build_script: bool,
+
+    /// Process the package's library and binaries as a single merged graph.
+    #[arg(long = "merge-targets", group = "target-group")]
+    pub merge_targets: bool,
+
+    /// Additionally process the package's build script (if any), as a crate of its own.
+    #[arg(long = "with-build-script", conflicts_with = "build_script")]
+    pub with_build_script: bool,
+
     /// Package to process (see `cargo help pkgid`).
+    /// May be passed multiple times and accepts glob patterns (e.g. `my-org-*`).
     #[arg(short = 'p', long = "package")]
-    pub package: Option<String>,
+    pub package: Vec<String>,
 
     /// Do not activate the `default` feature.
     #[arg(long = "no-default-features")]
@@ -49,18 +147,56 @@ pub struct ProjectOptions {
     #[arg(long = "all-features")]
     pub all_features: bool,
 
-    /// List of features to activate.
-    /// This will be ignored if `--cargo-all-features` is provided.
+    /// Space or comma separated list of features to activate.
+    /// May be passed multiple times and is ignored if `--all-features` is provided.
     #[arg(long = "features")]
     pub features: Vec<String>,
 
-    /// Analyze for target triple.
+    /// Analyze for target triple (e.g. `x86_64-pc-windows-msvc`), resolving `#[cfg(target_os = …)]` & co. accordingly.
     #[arg(long = "target")]
     pub target: Option<String>,
 
-    /// Path to Cargo.toml.
+    /// Rustup toolchain (e.g. `nightly-2024-06-01`) whose sysroot to analyze against,
+    /// rather than the one selected for the project by default.
+    #[arg(long = "toolchain", conflicts_with = "sysroot")]
+    pub toolchain: Option<String>,
+
+    /// Path to the sysroot (as printed by `rustc --print sysroot`) to analyze against.
+    #[arg(long = "sysroot")]
+    pub sysroot: Option<PathBuf>,
+
+    /// Expand local path dependencies into module trees of their own, rather than treating them as external.
+    #[arg(long = "expand-path-deps")]
+    pub expand_path_deps: bool,
+
+    /// Skip analysis of dependency crates (faster, but items of external crates will be missing).
+    #[arg(long = "no-deps")]
+    pub no_deps: bool,
+
+    /// Skip expansion of procedural macros (faster, but items generated by them will be missing).
+    #[arg(long = "no-proc-macros")]
+    pub no_proc_macros: bool,
+
+    /// Skip running build scripts (faster, but code included from `OUT_DIR` will be missing).
+    #[arg(long = "no-build-scripts", conflicts_with = "with_out_dirs")]
+    pub no_build_scripts: bool,
+
+    /// Expand `include!`s of code generated by build scripts into `OUT_DIR` (slower, as it requires loading the sysroot crates).
+    #[arg(long = "with-out-dirs")]
+    pub with_out_dirs: bool,
+
+    /// Run without accessing the network, failing if anything would need downloading.
+    #[arg(long = "offline")]
+    pub offline: bool,
+
+    /// Path to Cargo.toml (or rust-project.json).
     #[arg(long = "manifest-path", default_value = ".")]
     pub manifest_path: PathBuf,
+
+    /// Analyze the project as of the given git revision (e.g. `v2.0`),
+    /// as checked out into a temporary worktree, leaving the working directory untouched.
+    #[arg(long = "at", value_name = "REV")]
+    pub at: Option<String>,
 }
 
 #[derive(Parser, Clone, PartialEq, Eq, Debug)]
@@ -69,4 +205,125 @@ pub struct GeneralOptions {
     /// Use verbose output.
     #[arg(long = "verbose")]
     pub verbose: bool,
+
+    /// Report how long each stage of the analysis took (e.g. loading, building, printing) to stderr.
+    #[arg(long = "timings")]
+    pub timings: bool,
+
+    /// Report a summary of the run (modules analyzed, edges emitted, warnings, orphans, elapsed time) to stderr, as either text or json.
+    #[arg(
+        long = "summary",
+        value_name = "FORMAT",
+        num_args = 0..=1,
+        default_missing_value = "text"
+    )]
+    pub summary: Option<SummaryFormat>,
+
+    /// Color theme to use (dark, light, monochrome, colorblind-safe),
+    /// or the name of a theme defined in the project's `.cargo-modules.toml`.
+    #[arg(long = "theme", default_value = "dark")]
+    pub theme: String,
+
+    /// When to color the output: `auto` colors it only if printing to a terminal
+    /// (and neither `NO_COLOR` nor `CLICOLOR_FORCE` say otherwise).
+    #[arg(long = "color", default_value = "auto")]
+    pub color: ColorChoice,
+}
+
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum ColorChoice {
+    Auto,
+    Always,
+    Never,
+}
+
+impl FromStr for ColorChoice {
+    type Err = &'static str;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "auto" => Ok(Self::Auto),
+            "always" => Ok(Self::Always),
+            "never" => Ok(Self::Never),
+            _ => Err("Unrecognized color choice"),
+        }
+    }
+}
+
+impl Display for ColorChoice {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            Self::Auto => "auto",
+            Self::Always => "always",
+            Self::Never => "never",
+        })
+    }
+}
+
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum SummaryFormat {
+    /// A human-readable block of counts.
+    Text,
+    /// A single JSON object.
+    Json,
+}
+
+impl FromStr for SummaryFormat {
+    type Err = &'static str;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "text" => Ok(Self::Text),
+            "json" => Ok(Self::Json),
+            _ => Err("Unrecognized summary format"),
+        }
+    }
+}
+
+impl Display for SummaryFormat {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            Self::Text => "text",
+            Self::Json => "json",
+        })
+    }
+}
+
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum MessageFormat {
+    /// Human-readable diagnostics.
+    Human,
+    /// One `file:line:col: message` entry per line, as understood by editors' quickfix lists.
+    Quickfix,
+    /// One `::error file=…,line=…::message` workflow command per line,
+    /// as shown inline on pull requests by GitHub Actions.
+    Github,
+    /// One JSON object per line, streaming progress and diagnostic events to stderr
+    /// (e.g. `{"event":"stage","stage":"building"}`).
+    Json,
+}
+
+impl FromStr for MessageFormat {
+    type Err = &'static str;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "human" => Ok(Self::Human),
+            "quickfix" => Ok(Self::Quickfix),
+            "github" => Ok(Self::Github),
+            "json" => Ok(Self::Json),
+            _ => Err("Unrecognized message format"),
+        }
+    }
+}
+
+impl Display for MessageFormat {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            Self::Human => "human",
+            Self::Quickfix => "quickfix",
+            Self::Github => "github",
+            Self::Json => "json",
+        })
+    }
 }
@@ -0,0 +1,37 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at https://mozilla.org/MPL/2.0/.
+
+//! Writing of command outputs to files, as requested via `--output`.
+
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+use anyhow::Context as _;
+use log::debug;
+
+/// Writes `contents` to the file at `path`, replacing any existing file atomically
+/// (i.e. readers of `path` see either its previous or its new contents, never a partial write).
+pub(crate) fn write_atomically(path: &Path, contents: impl AsRef<[u8]>) -> anyhow::Result<()> {
+    let temp_path = temp_path(path);
+
+    debug!("Writing output to {path:?}");
+
+    // Renaming is only atomic within the same file system,
+    // which is why the temporary file is placed right next to its destination:
+    if let Err(err) = fs::write(&temp_path, contents).and_then(|_| fs::rename(&temp_path, path)) {
+        let _ = fs::remove_file(&temp_path);
+        return Err(err).with_context(|| format!("Could not write output to {}", path.display()));
+    }
+
+    Ok(())
+}
+
+fn temp_path(path: &Path) -> PathBuf {
+    let mut file_name = path.file_name().unwrap_or_default().to_os_string();
+    file_name.push(format!(".{}.tmp", std::process::id()));
+
+    path.with_file_name(file_name)
+}
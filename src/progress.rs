@@ -0,0 +1,115 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at https://mozilla.org/MPL/2.0/.
+
+//! Progress and diagnostic events, as streamed to stderr via `--message-format json`
+//! (one JSON object per line, e.g. `{"event":"stage","stage":"building"}`).
+
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use json::{object, JsonValue};
+
+use crate::{quickfix::Entry, summary, timings};
+
+static IS_ENABLED: AtomicBool = AtomicBool::new(false);
+
+/// Enables the streaming of events, which are dropped otherwise.
+pub(crate) fn enable() {
+    IS_ENABLED.store(true, Ordering::Relaxed);
+}
+
+fn is_enabled() -> bool {
+    IS_ENABLED.load(Ordering::Relaxed)
+}
+
+/// The stages an analysis passes through.
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub(crate) enum Stage {
+    /// Loading the project (i.e. running `cargo metadata`, build scripts, etc.).
+    Loading,
+    /// Loading the crates' sources into rust-analyzer's database
+    /// (with most of their analysis happening lazily while building).
+    Analyzing,
+    /// Building the tree or graph.
+    Building,
+    /// Filtering the tree or graph.
+    Filtering,
+    /// Printing the output.
+    Printing,
+}
+
+impl Stage {
+    pub(crate) fn name(self) -> &'static str {
+        match self {
+            Self::Loading => "loading",
+            Self::Analyzing => "analyzing",
+            Self::Building => "building",
+            Self::Filtering => "filtering",
+            Self::Printing => "printing",
+        }
+    }
+}
+
+/// Reports that the analysis entered the given stage.
+pub(crate) fn stage(stage: Stage) {
+    timings::enter(stage);
+
+    emit(object! {
+        event: "stage",
+        stage: stage.name(),
+    });
+}
+
+/// Reports a message of the project loader (e.g. "metadata", "Building build-artifacts").
+pub(crate) fn loading(message: &str) {
+    emit(object! {
+        event: "loading",
+        message: message,
+    });
+}
+
+/// Reports a (non-fatal) warning.
+pub(crate) fn warning(message: &str) {
+    summary::record_warning();
+
+    emit(object! {
+        event: "warning",
+        message: message,
+    });
+}
+
+/// Reports a module that rust-analyzer failed to fully resolve (e.g. due to a missing file),
+/// either as a warning event or (unless streaming events) as a plain warning.
+pub(crate) fn unresolved(module: &str, message: &str) {
+    summary::record_warning();
+
+    if !is_enabled() {
+        eprintln!("warning: unresolved module `{module}`: {message}");
+        return;
+    }
+
+    emit(object! {
+        event: "warning",
+        kind: "unresolved",
+        module: module,
+        message: message,
+    });
+}
+
+/// Reports a diagnostic (e.g. an orphan or an edge of a cycle).
+pub(crate) fn diagnostic(entry: &Entry) {
+    emit(object! {
+        event: "diagnostic",
+        severity: entry.severity.to_string(),
+        file: entry.file_path.display().to_string(),
+        line: entry.line,
+        column: entry.column,
+        message: entry.message.as_str(),
+    });
+}
+
+fn emit(event: JsonValue) {
+    if is_enabled() {
+        eprintln!("{}", event.dump());
+    }
+}
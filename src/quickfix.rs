@@ -0,0 +1,122 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at https://mozilla.org/MPL/2.0/.
+
+//! Entries in the `file:line:col: message` format understood by editors' quickfix lists
+//! (or the `::error file=…,line=…::message` format of GitHub Actions' workflow commands).
+
+use std::{
+    fmt,
+    path::{Path, PathBuf},
+};
+
+use crate::options::{MessageFormat, ProjectOptions};
+
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub(crate) enum Severity {
+    Error,
+    Warning,
+}
+
+impl fmt::Display for Severity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::Error => "error",
+            Self::Warning => "warning",
+        })
+    }
+}
+
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub(crate) struct Entry {
+    pub file_path: PathBuf,
+    /// The 1-based line.
+    pub line: u32,
+    /// The 1-based column.
+    pub column: u32,
+    pub severity: Severity,
+    pub message: String,
+}
+
+impl fmt::Display for Entry {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{file_path}:{line}:{column}: {severity}: {message}",
+            file_path = self.file_path.display(),
+            line = self.line,
+            column = self.column,
+            severity = self.severity,
+            message = self.message,
+        )
+    }
+}
+
+impl Entry {
+    /// Returns the entry formatted as per `message_format`,
+    /// with anything but `github` falling back to the quickfix format.
+    pub(crate) fn formatted(&self, message_format: MessageFormat) -> FormattedEntry<'_> {
+        FormattedEntry {
+            entry: self,
+            message_format,
+        }
+    }
+}
+
+pub(crate) struct FormattedEntry<'a> {
+    entry: &'a Entry,
+    message_format: MessageFormat,
+}
+
+impl fmt::Display for FormattedEntry<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let entry = self.entry;
+
+        if self.message_format != MessageFormat::Github {
+            return write!(f, "{entry}");
+        }
+
+        // See: https://docs.github.com/en/actions/using-workflows/workflow-commands-for-github-actions
+        let file_path = entry.file_path.display().to_string();
+
+        write!(
+            f,
+            "::{severity} file={file_path},line={line},col={column}::{message}",
+            severity = entry.severity,
+            file_path = escape_property(&file_path),
+            line = entry.line,
+            column = entry.column,
+            message = escape_data(&entry.message),
+        )
+    }
+}
+
+fn escape_data(data: &str) -> String {
+    data.replace('%', "%25")
+        .replace('\r', "%0D")
+        .replace('\n', "%0A")
+}
+
+fn escape_property(property: &str) -> String {
+    escape_data(property)
+        .replace(':', "%3A")
+        .replace(',', "%2C")
+}
+
+/// Returns the (canonical) directory of the project selected by `project_options`.
+pub(crate) fn project_dir(project_options: &ProjectOptions) -> PathBuf {
+    let manifest_path = project_options.manifest_path.as_path();
+
+    let project_dir = if manifest_path.is_file() {
+        manifest_path.parent().expect("parent directory")
+    } else {
+        manifest_path
+    };
+
+    std::fs::canonicalize(project_dir).unwrap_or_else(|_| project_dir.to_owned())
+}
+
+/// Returns `path` relative to `base_dir`, if possible.
+pub(crate) fn relative_path(path: &Path, base_dir: &Path) -> PathBuf {
+    path.strip_prefix(base_dir).unwrap_or(path).to_owned()
+}
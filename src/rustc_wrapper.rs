@@ -0,0 +1,44 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at https://mozilla.org/MPL/2.0/.
+
+//! The `rustc` wrapper that build scripts get run with prior to the analysis
+//! (as rust-analyzer points cargo's `RUSTC_WRAPPER` to the running binary itself),
+//! which only compiles build scripts and proc-macros, rather than checking every crate.
+
+use std::{env, ffi::OsString, process};
+
+/// The environment variable rust-analyzer sets for invocations of the wrapper.
+pub const ENV_VAR: &str = "RA_RUSTC_WRAPPER";
+
+/// Runs `rustc` with the arguments passed by cargo (if needed), returning its exit code.
+pub fn run() -> i32 {
+    let mut args = env::args_os().skip(1);
+
+    let Some(rustc) = args.next() else {
+        eprintln!("Error: Expected rustc to wrap");
+        return 102;
+    };
+
+    let args: Vec<OsString> = args.collect();
+
+    // `cargo check` invokes `rustc` with `--emit=metadata` for each of the crates it checks,
+    // which can be skipped (unless invoked by a build script, which cargo sets `CARGO_CFG_*` for):
+    let is_check = args.iter().any(|arg| {
+        let arg = arg.to_string_lossy();
+        arg.starts_with("--emit=") && arg.contains("metadata") && !arg.contains("link")
+    });
+    let is_invoked_by_build_script = env::var_os("CARGO_CFG_TARGET_ARCH").is_some();
+
+    if is_check && !is_invoked_by_build_script {
+        return 0;
+    }
+
+    match process::Command::new(rustc).args(args).status() {
+        Ok(status) => status.code().unwrap_or(102),
+        Err(err) => {
+            eprintln!("Error: {err}");
+            102
+        }
+    }
+}
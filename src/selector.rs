@@ -0,0 +1,432 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at https://mozilla.org/MPL/2.0/.
+
+//! Expression language for selecting nodes and edges of a graph.
+//!
+//! Supported syntax:
+//!
+//! - `<field> <op> <value>` compares one of a node's fields against a value,
+//!   where `<field>` is one of `kind`, `visibility`, `path`, `name` or `crate`,
+//!   and `<op>` is one of `==`, `!=`, `=~` (glob match) or `!~` (glob mismatch).
+//! - `<expr> && <expr>`, `<expr> || <expr>`, `!<expr>` and `(<expr>)` combine node expressions.
+//! - `edges(<kind>, ...) [from <path>] [to <path>]` selects edges of the given kinds
+//!   (i.e. `uses`, `owns`), whose source/target lie within the given paths.
+//!
+//! Values may be given as bare words (e.g. `mod`, `foo::bar`) or as quoted strings (e.g. `"pub(crate)"`).
+
+use std::{fmt, str::FromStr};
+
+use ra_ap_ide::RootDatabase;
+
+use crate::{
+    analyzer,
+    glob::glob_matches,
+    graph::{Edge, Node, Relationship},
+};
+
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub enum Selector {
+    Nodes(NodeExpr),
+    Edges(EdgeQuery),
+}
+
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub enum NodeExpr {
+    And(Box<NodeExpr>, Box<NodeExpr>),
+    Or(Box<NodeExpr>, Box<NodeExpr>),
+    Not(Box<NodeExpr>),
+    Compare {
+        field: Field,
+        op: Operator,
+        value: String,
+    },
+}
+
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum Field {
+    Kind,
+    Visibility,
+    Path,
+    Name,
+    Crate,
+}
+
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum Operator {
+    Equal,
+    NotEqual,
+    Matches,
+    NotMatches,
+}
+
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct EdgeQuery {
+    pub kinds: Vec<Relationship>,
+    pub from: Option<String>,
+    pub to: Option<String>,
+}
+
+impl NodeExpr {
+    pub fn matches(&self, node: &Node, db: &RootDatabase) -> bool {
+        match self {
+            Self::And(lhs, rhs) => lhs.matches(node, db) && rhs.matches(node, db),
+            Self::Or(lhs, rhs) => lhs.matches(node, db) || rhs.matches(node, db),
+            Self::Not(expr) => !expr.matches(node, db),
+            Self::Compare { field, op, value } => {
+                let is_match = match op {
+                    Operator::Equal | Operator::NotEqual => field.equals(node, value, db),
+                    Operator::Matches | Operator::NotMatches => field.glob_matches(node, value, db),
+                };
+
+                match op {
+                    Operator::Equal | Operator::Matches => is_match,
+                    Operator::NotEqual | Operator::NotMatches => !is_match,
+                }
+            }
+        }
+    }
+}
+
+impl Field {
+    fn equals(&self, node: &Node, value: &str, db: &RootDatabase) -> bool {
+        match self {
+            Self::Kind => node.kind_display_name(db).to_string() == canonical_kind(value),
+            Self::Visibility => node.visibility(db).to_string() == canonical_visibility(value),
+            Self::Path => candidate_paths(node, db).iter().any(|path| path == value),
+            Self::Name => node.display_name(db) == value,
+            Self::Crate => crate_name(node, db).as_deref() == Some(value),
+        }
+    }
+
+    fn glob_matches(&self, node: &Node, pattern: &str, db: &RootDatabase) -> bool {
+        match self {
+            Self::Kind => glob_matches(pattern, &node.kind_display_name(db).to_string()),
+            Self::Visibility => glob_matches(pattern, &node.visibility(db).to_string()),
+            Self::Path => candidate_paths(node, db)
+                .iter()
+                .any(|path| glob_matches(pattern, path)),
+            Self::Name => glob_matches(pattern, &node.display_name(db)),
+            Self::Crate => {
+                crate_name(node, db).is_some_and(|crate_name| glob_matches(pattern, &crate_name))
+            }
+        }
+    }
+}
+
+impl EdgeQuery {
+    pub fn matches(&self, edge: &Edge, source: &Node, target: &Node, db: &RootDatabase) -> bool {
+        if !self.kinds.contains(&edge.kind) {
+            return false;
+        }
+
+        let is_within = |pattern: &Option<String>, node: &Node| match pattern {
+            Some(pattern) => is_within_path(node, pattern, db),
+            None => true,
+        };
+
+        is_within(&self.from, source) && is_within(&self.to, target)
+    }
+}
+
+impl FromStr for Selector {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let tokens = tokenize(s)?;
+        let mut parser = Parser {
+            tokens,
+            position: 0,
+        };
+
+        let selector = if parser.peek_word("edges") {
+            Selector::Edges(parser.parse_edge_query()?)
+        } else {
+            Selector::Nodes(parser.parse_or()?)
+        };
+
+        if let Some(token) = parser.next() {
+            return Err(format!("Unexpected {token} in selector"));
+        }
+
+        Ok(selector)
+    }
+}
+
+#[derive(Clone, PartialEq, Eq, Debug)]
+enum Token {
+    Word(String),
+    String(String),
+    OpenParen,
+    CloseParen,
+    Comma,
+    And,
+    Or,
+    Not,
+    Operator(Operator),
+}
+
+impl fmt::Display for Token {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Word(word) => write!(f, "`{word}`"),
+            Self::String(string) => write!(f, "{string:?}"),
+            Self::OpenParen => write!(f, "`(`"),
+            Self::CloseParen => write!(f, "`)`"),
+            Self::Comma => write!(f, "`,`"),
+            Self::And => write!(f, "`&&`"),
+            Self::Or => write!(f, "`||`"),
+            Self::Not => write!(f, "`!`"),
+            Self::Operator(Operator::Equal) => write!(f, "`==`"),
+            Self::Operator(Operator::NotEqual) => write!(f, "`!=`"),
+            Self::Operator(Operator::Matches) => write!(f, "`=~`"),
+            Self::Operator(Operator::NotMatches) => write!(f, "`!~`"),
+        }
+    }
+}
+
+fn tokenize(s: &str) -> Result<Vec<Token>, String> {
+    let mut tokens = vec![];
+    let mut chars = s.chars().peekable();
+
+    while let Some(char) = chars.next() {
+        let token = match char {
+            char if char.is_whitespace() => continue,
+            '(' => Token::OpenParen,
+            ')' => Token::CloseParen,
+            ',' => Token::Comma,
+            '&' if chars.next_if_eq(&'&').is_some() => Token::And,
+            '|' if chars.next_if_eq(&'|').is_some() => Token::Or,
+            '=' if chars.next_if_eq(&'=').is_some() => Token::Operator(Operator::Equal),
+            '=' if chars.next_if_eq(&'~').is_some() => Token::Operator(Operator::Matches),
+            '!' if chars.next_if_eq(&'=').is_some() => Token::Operator(Operator::NotEqual),
+            '!' if chars.next_if_eq(&'~').is_some() => Token::Operator(Operator::NotMatches),
+            '!' => Token::Not,
+            '"' => {
+                let mut string = String::new();
+                loop {
+                    match chars.next() {
+                        Some('"') => break,
+                        Some('\\') => match chars.next() {
+                            Some(char) => string.push(char),
+                            None => return Err("Unterminated string in selector".to_owned()),
+                        },
+                        Some(char) => string.push(char),
+                        None => return Err("Unterminated string in selector".to_owned()),
+                    }
+                }
+                Token::String(string)
+            }
+            char if is_word_char(char) => {
+                let mut word = String::from(char);
+                while let Some(char) = chars.next_if(|char| is_word_char(*char)) {
+                    word.push(char);
+                }
+                Token::Word(word)
+            }
+            char => return Err(format!("Unexpected character `{char}` in selector")),
+        };
+
+        tokens.push(token);
+    }
+
+    Ok(tokens)
+}
+
+fn is_word_char(char: char) -> bool {
+    char.is_alphanumeric() || matches!(char, '_' | ':' | '*' | '?' | '#')
+}
+
+struct Parser {
+    tokens: Vec<Token>,
+    position: usize,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.position)
+    }
+
+    fn peek_word(&self, word: &str) -> bool {
+        matches!(self.peek(), Some(Token::Word(peeked)) if peeked == word)
+    }
+
+    fn next(&mut self) -> Option<Token> {
+        let token = self.tokens.get(self.position).cloned();
+        self.position += 1;
+        token
+    }
+
+    fn expect(&mut self, expected: Token) -> Result<(), String> {
+        match self.next() {
+            Some(token) if token == expected => Ok(()),
+            Some(token) => Err(format!("Expected {expected}, found {token} in selector")),
+            None => Err(format!("Expected {expected}, found end of selector")),
+        }
+    }
+
+    fn parse_or(&mut self) -> Result<NodeExpr, String> {
+        let mut expr = self.parse_and()?;
+
+        while self.peek() == Some(&Token::Or) {
+            self.next();
+            expr = NodeExpr::Or(Box::new(expr), Box::new(self.parse_and()?));
+        }
+
+        Ok(expr)
+    }
+
+    fn parse_and(&mut self) -> Result<NodeExpr, String> {
+        let mut expr = self.parse_unary()?;
+
+        while self.peek() == Some(&Token::And) {
+            self.next();
+            expr = NodeExpr::And(Box::new(expr), Box::new(self.parse_unary()?));
+        }
+
+        Ok(expr)
+    }
+
+    fn parse_unary(&mut self) -> Result<NodeExpr, String> {
+        match self.peek() {
+            Some(Token::Not) => {
+                self.next();
+                Ok(NodeExpr::Not(Box::new(self.parse_unary()?)))
+            }
+            Some(Token::OpenParen) => {
+                self.next();
+                let expr = self.parse_or()?;
+                self.expect(Token::CloseParen)?;
+                Ok(expr)
+            }
+            _ => self.parse_comparison(),
+        }
+    }
+
+    fn parse_comparison(&mut self) -> Result<NodeExpr, String> {
+        let field = match self.next() {
+            Some(Token::Word(word)) => match word.as_str() {
+                "kind" => Field::Kind,
+                "visibility" => Field::Visibility,
+                "path" => Field::Path,
+                "name" => Field::Name,
+                "crate" => Field::Crate,
+                _ => {
+                    return Err(format!(
+                        "Unknown field `{word}` in selector (expected kind, visibility, path, name or crate)"
+                    ))
+                }
+            },
+            Some(token) => return Err(format!("Expected field, found {token} in selector")),
+            None => return Err("Expected field, found end of selector".to_owned()),
+        };
+
+        let op = match self.next() {
+            Some(Token::Operator(op)) => op,
+            Some(token) => return Err(format!("Expected operator, found {token} in selector")),
+            None => return Err("Expected operator, found end of selector".to_owned()),
+        };
+
+        let value = self.parse_value()?;
+
+        Ok(NodeExpr::Compare { field, op, value })
+    }
+
+    fn parse_edge_query(&mut self) -> Result<EdgeQuery, String> {
+        self.next();
+        self.expect(Token::OpenParen)?;
+
+        let mut kinds = vec![];
+
+        loop {
+            let kind = match self.parse_value()?.as_str() {
+                "uses" => Relationship::Uses,
+                "reexports" => Relationship::ReExports,
+                "owns" => Relationship::Owns,
+                kind => {
+                    return Err(format!(
+                        "Unknown edge kind `{kind}` in selector (expected uses, reexports or owns)"
+                    ))
+                }
+            };
+
+            kinds.push(kind);
+
+            match self.next() {
+                Some(Token::Comma) => continue,
+                Some(Token::CloseParen) => break,
+                Some(token) => {
+                    return Err(format!("Expected `,` or `)`, found {token} in selector"))
+                }
+                None => return Err("Expected `)`, found end of selector".to_owned()),
+            }
+        }
+
+        let mut from = None;
+        let mut to = None;
+
+        if self.peek_word("from") {
+            self.next();
+            from = Some(self.parse_value()?);
+        }
+
+        if self.peek_word("to") {
+            self.next();
+            to = Some(self.parse_value()?);
+        }
+
+        Ok(EdgeQuery { kinds, from, to })
+    }
+
+    fn parse_value(&mut self) -> Result<String, String> {
+        match self.next() {
+            Some(Token::Word(value) | Token::String(value)) => Ok(value),
+            Some(token) => Err(format!("Expected value, found {token} in selector")),
+            None => Err("Expected value, found end of selector".to_owned()),
+        }
+    }
+}
+
+fn canonical_kind(kind: &str) -> &str {
+    match kind {
+        "module" => "mod",
+        "function" => "fn",
+        _ => kind,
+    }
+}
+
+fn canonical_visibility(visibility: &str) -> &str {
+    match visibility {
+        "public" => "pub",
+        "crate" => "pub(crate)",
+        "super" => "pub(super)",
+        "private" => "pub(self)",
+        _ => visibility,
+    }
+}
+
+fn crate_name(node: &Node, db: &RootDatabase) -> Option<String> {
+    analyzer::krate(node.hir, db).map(|krate| analyzer::crate_name(krate, db))
+}
+
+/// Returns the node's path, as well as its path relative to its crate
+/// (allowing for `foo::bar` to be matched via either `crate_name::foo::bar` or `foo::bar`).
+fn candidate_paths(node: &Node, db: &RootDatabase) -> Vec<String> {
+    let path = node.display_path(db);
+
+    let relative_path = path
+        .split_once("::")
+        .map(|(_crate_name, relative_path)| relative_path.to_owned());
+
+    std::iter::once(path).chain(relative_path).collect()
+}
+
+/// Returns `true` if the node's path matches `pattern`, or is a descendant of it.
+fn is_within_path(node: &Node, pattern: &str, db: &RootDatabase) -> bool {
+    candidate_paths(node, db).iter().any(|path| {
+        glob_matches(pattern, path)
+            || path
+                .strip_prefix(pattern)
+                .is_some_and(|suffix| suffix.starts_with("::"))
+    })
+}
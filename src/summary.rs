@@ -0,0 +1,139 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at https://mozilla.org/MPL/2.0/.
+
+//! A summary of a command's run, as reported to stderr via `--summary`:
+//!
+//! ```plain
+//! Summary:
+//!   modules          12
+//!   edges            34
+//!   warnings          1
+//!   orphans           2
+//!   elapsed      2.847s
+//! ```
+//!
+//! Or, via `--summary json`, as a single JSON object
+//! (e.g. `{"modules":12,"edges":34,"warnings":1,"orphans":2,"elapsed":2.847}`).
+
+use std::{fmt, sync::Mutex, time::Instant};
+
+use json::object;
+
+use crate::options::SummaryFormat;
+
+static SUMMARY: Mutex<Option<Summary>> = Mutex::new(None);
+
+#[derive(Debug)]
+struct Summary {
+    format: SummaryFormat,
+    /// When the command was started.
+    started: Instant,
+    /// The number of modules of the analyzed crates.
+    modules: usize,
+    /// The number of edges emitted by the command's graphs.
+    edges: usize,
+    /// The number of (non-fatal) warnings reported.
+    warnings: usize,
+    /// The number of orphans found, if the command looked for any.
+    orphans: Option<usize>,
+}
+
+impl Summary {
+    fn to_json(&self) -> String {
+        let elapsed = (self.started.elapsed().as_secs_f64() * 1000.0).round() / 1000.0;
+
+        object! {
+            modules: self.modules,
+            edges: self.edges,
+            warnings: self.warnings,
+            orphans: self.orphans,
+            elapsed: elapsed,
+        }
+        .dump()
+    }
+}
+
+impl fmt::Display for Summary {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let orphans = match self.orphans {
+            Some(orphans) => orphans.to_string(),
+            None => "-".to_owned(),
+        };
+
+        writeln!(f, "Summary:")?;
+        writeln!(f, "  {:<10} {:>8}", "modules", self.modules)?;
+        writeln!(f, "  {:<10} {:>8}", "edges", self.edges)?;
+        writeln!(f, "  {:<10} {:>8}", "warnings", self.warnings)?;
+        writeln!(f, "  {:<10} {:>8}", "orphans", orphans)?;
+        writeln!(
+            f,
+            "  {:<10} {:>8.3}s",
+            "elapsed",
+            self.started.elapsed().as_secs_f64()
+        )
+    }
+}
+
+/// Starts recording the command's counts,
+/// which get reported to stderr in the given format once the returned guard gets dropped.
+pub(crate) fn start(format: SummaryFormat) -> Reporter {
+    *SUMMARY.lock().unwrap() = Some(Summary {
+        format,
+        started: Instant::now(),
+        modules: 0,
+        edges: 0,
+        warnings: 0,
+        orphans: None,
+    });
+
+    Reporter
+}
+
+/// Returns whether a summary is being recorded (i.e. whether counts are worth computing).
+pub(crate) fn is_enabled() -> bool {
+    SUMMARY.lock().unwrap().is_some()
+}
+
+fn update(f: impl FnOnce(&mut Summary)) {
+    if let Some(summary) = SUMMARY.lock().unwrap().as_mut() {
+        f(summary);
+    }
+}
+
+/// Records the number of modules of the analyzed crates.
+pub(crate) fn record_modules(count: usize) {
+    update(|summary| summary.modules = count);
+}
+
+/// Records that a graph with the given number of edges got emitted.
+pub(crate) fn record_edges(count: usize) {
+    update(|summary| summary.edges += count);
+}
+
+/// Records that a (non-fatal) warning got reported.
+pub(crate) fn record_warning() {
+    update(|summary| summary.warnings += 1);
+}
+
+/// Records the number of orphans found.
+pub(crate) fn record_orphans(count: usize) {
+    update(|summary| summary.orphans = Some(count));
+}
+
+/// Reports the recorded summary to stderr when dropped (i.e. even if the command failed).
+#[derive(Debug)]
+pub(crate) struct Reporter;
+
+impl Drop for Reporter {
+    fn drop(&mut self) {
+        let Some(summary) = SUMMARY.lock().unwrap().take() else {
+            return;
+        };
+
+        match summary.format {
+            SummaryFormat::Text => eprint!("{summary}"),
+            SummaryFormat::Json => eprintln!("{}", summary.to_json()),
+        }
+    }
+}
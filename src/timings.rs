@@ -0,0 +1,99 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at https://mozilla.org/MPL/2.0/.
+
+//! Timings of an analysis's stages, as reported to stderr via `--timings`:
+//!
+//! ```plain
+//! Timings:
+//!   loading       0.412s
+//!   analyzing     2.301s
+//!   building      0.120s
+//!   filtering     0.004s
+//!   printing      0.010s
+//!   total         2.847s
+//! ```
+
+use std::{
+    fmt,
+    sync::Mutex,
+    time::{Duration, Instant},
+};
+
+use crate::progress::Stage;
+
+static TIMINGS: Mutex<Option<Timings>> = Mutex::new(None);
+
+#[derive(Debug)]
+struct Timings {
+    /// The stages passed through so far (in order), along with how long each took.
+    stages: Vec<(Stage, Duration)>,
+    /// The current stage, along with when it was entered.
+    current: Option<(Stage, Instant)>,
+}
+
+impl Timings {
+    /// Ends the current stage (if any), adding its duration to that of the stage's earlier runs.
+    fn finish_stage(&mut self) {
+        let Some((stage, started)) = self.current.take() else {
+            return;
+        };
+
+        let elapsed = started.elapsed();
+
+        match self.stages.iter_mut().find(|(other, _)| *other == stage) {
+            Some((_, duration)) => *duration += elapsed,
+            None => self.stages.push((stage, elapsed)),
+        }
+    }
+}
+
+impl fmt::Display for Timings {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "Timings:")?;
+
+        for (stage, duration) in &self.stages {
+            writeln!(f, "  {:<10} {:>8.3}s", stage.name(), duration.as_secs_f64())?;
+        }
+
+        let total: Duration = self.stages.iter().map(|(_, duration)| *duration).sum();
+        writeln!(f, "  {:<10} {:>8.3}s", "total", total.as_secs_f64())
+    }
+}
+
+/// Starts recording the durations of the stages entered from now on,
+/// which get reported to stderr once the returned guard gets dropped.
+pub(crate) fn start() -> Reporter {
+    *TIMINGS.lock().unwrap() = Some(Timings {
+        stages: vec![],
+        current: None,
+    });
+
+    Reporter
+}
+
+/// Records that the analysis entered the given stage, ending the previous one.
+pub(crate) fn enter(stage: Stage) {
+    let mut timings = TIMINGS.lock().unwrap();
+
+    if let Some(timings) = timings.as_mut() {
+        timings.finish_stage();
+        timings.current = Some((stage, Instant::now()));
+    }
+}
+
+/// Reports the recorded timings to stderr when dropped (i.e. even if the analysis failed).
+#[derive(Debug)]
+pub(crate) struct Reporter;
+
+impl Drop for Reporter {
+    fn drop(&mut self) {
+        let Some(mut timings) = TIMINGS.lock().unwrap().take() else {
+            return;
+        };
+
+        timings.finish_stage();
+
+        eprint!("{timings}");
+    }
+}
@@ -20,4 +20,12 @@ impl<N> Tree<N> {
     pub fn push_subtree(&mut self, subtree: Tree<N>) {
         self.subtrees.push(subtree);
     }
+
+    /// Returns the number of the tree's (direct and indirect) descendants.
+    pub fn count_descendants(&self) -> usize {
+        self.subtrees
+            .iter()
+            .map(|subtree| 1 + subtree.count_descendants())
+            .sum()
+    }
 }
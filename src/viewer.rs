@@ -0,0 +1,220 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at https://mozilla.org/MPL/2.0/.
+
+//! Opening of rendered output in an external viewer (as requested via `--open`).
+
+use std::{
+    fs, io,
+    path::{Path, PathBuf},
+    process::{self, Stdio},
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use anyhow::Context as _;
+use log::debug;
+
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub(crate) enum Format {
+    Text,
+    Dot,
+}
+
+impl Format {
+    fn extension(&self) -> &'static str {
+        match self {
+            Self::Text => "txt",
+            Self::Dot => "dot",
+        }
+    }
+}
+
+/// The environment variable naming a viewer command to use instead of the default ones
+/// (e.g. `CARGO_MODULES_VIEWER="code --wait"`), which gets passed the output file's path.
+const VIEWER_ENV_VAR: &str = "CARGO_MODULES_VIEWER";
+
+/// The viewer an output gets opened in.
+#[derive(Clone, PartialEq, Eq, Debug)]
+enum Viewer {
+    /// The command given via `CARGO_MODULES_VIEWER`.
+    Custom(String),
+    Xdot,
+    /// Graphviz' `dot`, rendering the graph to SVG, which then gets opened in the default browser.
+    DotToSvg,
+    /// The platform's default application for the file's type.
+    Default,
+}
+
+/// Writes `output` to a temporary file and opens it in a viewer appropriate for its format.
+///
+/// Graphs get opened in `xdot` (if installed), or otherwise rendered to SVG via graphviz'
+/// `dot` and opened in the default browser. Everything else gets opened in the platform's
+/// default application for its file type.
+pub(crate) fn open(output: &str, format: Format) -> anyhow::Result<()> {
+    let path = temp_path(format.extension())?;
+
+    fs::write(&path, output)
+        .with_context(|| format!("Could not write output to {}", path.display()))?;
+
+    debug!("Wrote output to {}", path.display());
+
+    let custom_viewer = std::env::var(VIEWER_ENV_VAR).ok();
+    let viewer = choose_viewer(format, custom_viewer.as_deref(), is_installed);
+
+    debug!("Opening output in {viewer:?}");
+
+    match viewer {
+        Viewer::Custom(command) => open_with_custom(&command, &path),
+        Viewer::Xdot => {
+            spawn(process::Command::new("xdot").arg(&path)).context("Could not launch `xdot`")
+        }
+        Viewer::DotToSvg => open_as_svg(&path),
+        Viewer::Default => open_with_default(&path),
+    }
+}
+
+/// Chooses the viewer for outputs of the given format, preferring the `custom_viewer` (if any),
+/// and otherwise falling back from `xdot` to `dot` to the default application for graphs.
+fn choose_viewer(
+    format: Format,
+    custom_viewer: Option<&str>,
+    is_installed: impl Fn(&str) -> bool,
+) -> Viewer {
+    if let Some(custom_viewer) = custom_viewer.filter(|command| !command.trim().is_empty()) {
+        return Viewer::Custom(custom_viewer.to_owned());
+    }
+
+    match format {
+        Format::Text => Viewer::Default,
+        Format::Dot if is_installed("xdot") => Viewer::Xdot,
+        Format::Dot if is_installed("dot") => Viewer::DotToSvg,
+        Format::Dot => Viewer::Default,
+    }
+}
+
+/// Returns whether `program` can be found within any of the `PATH`'s directories.
+fn is_installed(program: &str) -> bool {
+    let Some(paths) = std::env::var_os("PATH") else {
+        return false;
+    };
+
+    std::env::split_paths(&paths).any(|dir_path| {
+        let path = dir_path.join(program);
+        path.is_file() || (cfg!(windows) && path.with_extension("exe").is_file())
+    })
+}
+
+fn open_with_custom(command: &str, path: &Path) -> anyhow::Result<()> {
+    let mut args = command.split_whitespace();
+    let program = args.next().context("No viewer configured")?;
+
+    spawn(process::Command::new(program).args(args).arg(path))
+        .with_context(|| format!("Could not launch viewer `{command}`"))
+}
+
+fn open_as_svg(path: &Path) -> anyhow::Result<()> {
+    let svg_path = path.with_extension("svg");
+
+    let status = process::Command::new("dot")
+        .arg("-Tsvg")
+        .arg("-o")
+        .arg(&svg_path)
+        .arg(path)
+        .status()
+        .context("Could not launch graphviz' `dot`")?;
+
+    if !status.success() {
+        anyhow::bail!("Graphviz' `dot` exited with {status}");
+    }
+
+    open_with_default(&svg_path)
+}
+
+fn open_with_default(path: &Path) -> anyhow::Result<()> {
+    let mut command = if cfg!(target_os = "macos") {
+        process::Command::new("open")
+    } else if cfg!(windows) {
+        let mut command = process::Command::new("cmd");
+        command.args(["/C", "start", ""]);
+        command
+    } else {
+        process::Command::new("xdg-open")
+    };
+
+    command.arg(path);
+
+    spawn(&mut command)
+        .with_context(|| format!("Could not open {} in default viewer", path.display()))
+}
+
+/// Launches the viewer without waiting for it to be closed.
+fn spawn(command: &mut process::Command) -> io::Result<()> {
+    command
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .spawn()
+        .map(|_child| ())
+}
+
+fn temp_path(extension: &str) -> anyhow::Result<PathBuf> {
+    let dir = std::env::temp_dir().join("cargo-modules").join("open");
+
+    fs::create_dir_all(&dir)
+        .with_context(|| format!("Could not create directory {}", dir.display()))?;
+
+    let timestamp = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |duration| duration.as_millis());
+
+    let file_name = format!("output-{}-{timestamp}.{extension}", process::id());
+
+    Ok(dir.join(file_name))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn custom_viewer_overrides_defaults() {
+        let viewer = choose_viewer(Format::Dot, Some("code --wait"), |_| true);
+
+        assert_eq!(viewer, Viewer::Custom("code --wait".to_owned()));
+    }
+
+    #[test]
+    fn blank_custom_viewer_is_ignored() {
+        let viewer = choose_viewer(Format::Text, Some("  "), |_| true);
+
+        assert_eq!(viewer, Viewer::Default);
+    }
+
+    #[test]
+    fn graphs_prefer_xdot() {
+        let viewer = choose_viewer(Format::Dot, None, |_| true);
+
+        assert_eq!(viewer, Viewer::Xdot);
+    }
+
+    #[test]
+    fn graphs_fall_back_to_dot() {
+        let viewer = choose_viewer(Format::Dot, None, |program| program == "dot");
+
+        assert_eq!(viewer, Viewer::DotToSvg);
+    }
+
+    #[test]
+    fn graphs_fall_back_to_default() {
+        let viewer = choose_viewer(Format::Dot, None, |_| false);
+
+        assert_eq!(viewer, Viewer::Default);
+    }
+
+    #[test]
+    fn text_opens_in_default() {
+        let viewer = choose_viewer(Format::Text, None, |_| true);
+
+        assert_eq!(viewer, Viewer::Default);
+    }
+}
@@ -0,0 +1,166 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at https://mozilla.org/MPL/2.0/.
+
+//! Temporary git worktrees of past revisions (as requested via `--at <REV>`),
+//! allowing for their analysis without disturbing the user's working directory.
+
+use std::{
+    ffi::OsStr,
+    path::{Path, PathBuf},
+    process,
+};
+
+use log::{debug, warn};
+use tempfile::TempDir;
+
+/// A revision's tree, checked out into a temporary worktree of the project's repository,
+/// which gets removed again once dropped.
+pub(crate) struct Worktree {
+    repo_dir: PathBuf,
+    dir: PathBuf,
+    manifest_path: PathBuf,
+    // Removed (along with whatever the worktree left behind) once dropped:
+    _temp_dir: TempDir,
+}
+
+impl Worktree {
+    /// Checks out `rev` of the repository containing the project at `manifest_path`
+    /// into a temporary worktree.
+    pub(crate) fn add(manifest_path: &Path, rev: &str) -> anyhow::Result<Self> {
+        // Revisions get passed to git as-is, so they must not be mistaken for options:
+        if rev.starts_with('-') {
+            anyhow::bail!("Invalid revision `{rev}`");
+        }
+
+        let manifest_path = manifest_path.canonicalize()?;
+
+        let project_dir = if manifest_path.is_file() {
+            manifest_path.parent().expect("parent directory")
+        } else {
+            manifest_path.as_path()
+        };
+
+        let repo_dir = git(project_dir, ["rev-parse", "--show-toplevel"])
+            .map(PathBuf::from)
+            .and_then(|repo_dir| Ok(repo_dir.canonicalize()?))
+            .map_err(|_| {
+                anyhow::anyhow!("Expected project to be within a git repository for `--at {rev}`")
+            })?;
+
+        let commit = git(
+            &repo_dir,
+            [
+                "rev-parse",
+                "--verify",
+                "--quiet",
+                format!("{rev}^{{commit}}").as_str(),
+            ],
+        )
+        .map_err(|_| anyhow::anyhow!("Unknown revision `{rev}`"))?;
+
+        // Worktrees of runs that got killed before they could remove theirs
+        // (and whose directories have since been cleaned up) are pruned first:
+        if let Err(err) = git(&repo_dir, ["worktree", "prune"]) {
+            warn!("Could not prune worktrees: {err}");
+        }
+
+        // Each run gets a directory of its own, so that concurrent runs don't step onto each other's toes:
+        let temp_dir = tempfile::Builder::new()
+            .prefix("cargo-modules-at-")
+            .tempdir()?;
+
+        let dir = temp_dir.path().join(&commit);
+
+        git(
+            &repo_dir,
+            [
+                OsStr::new("worktree"),
+                OsStr::new("add"),
+                OsStr::new("--detach"),
+                OsStr::new("--quiet"),
+                dir.as_os_str(),
+                OsStr::new(&commit),
+            ],
+        )?;
+
+        // Interrupted runs (e.g. via Ctrl-C) remove their worktree before exiting:
+        let (handler_repo_dir, handler_dir) = (repo_dir.clone(), dir.clone());
+        if let Err(err) = ctrlc::set_handler(move || {
+            remove(&handler_repo_dir, &handler_dir);
+            process::exit(130);
+        }) {
+            warn!("Could not register interrupt handler: {err}");
+        }
+
+        debug!("Checked out `{rev}` ({commit}) into {}", dir.display());
+
+        // The project is to be found at the same place within the worktree as within the repository:
+        let relative_path = manifest_path
+            .strip_prefix(&repo_dir)
+            .expect("project within repository");
+
+        let worktree = Self {
+            manifest_path: dir.join(relative_path),
+            repo_dir,
+            dir,
+            _temp_dir: temp_dir,
+        };
+
+        if !worktree.manifest_path.exists() {
+            anyhow::bail!(
+                "Could not find project at `{rev}`, as {} didn't exist yet",
+                relative_path.display()
+            );
+        }
+
+        Ok(worktree)
+    }
+
+    /// Returns the path of the project's manifest (or directory) within the worktree.
+    pub(crate) fn manifest_path(&self) -> &Path {
+        &self.manifest_path
+    }
+}
+
+impl Drop for Worktree {
+    fn drop(&mut self) {
+        remove(&self.repo_dir, &self.dir);
+    }
+}
+
+/// Removes the worktree at `dir` from the repository at `repo_dir`.
+fn remove(repo_dir: &Path, dir: &Path) {
+    let args = [
+        OsStr::new("worktree"),
+        OsStr::new("remove"),
+        OsStr::new("--force"),
+        dir.as_os_str(),
+    ];
+
+    if let Err(err) = git(repo_dir, args) {
+        warn!("Could not remove worktree {}: {err}", dir.display());
+    }
+}
+
+/// Runs `git` with the given `args` in `dir`, returning its trimmed output on success.
+fn git<I, S>(dir: &Path, args: I) -> anyhow::Result<String>
+where
+    I: IntoIterator<Item = S>,
+    S: AsRef<OsStr>,
+{
+    let output = process::Command::new("git")
+        .arg("-C")
+        .arg(dir)
+        .args(args)
+        .output()?;
+
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        anyhow::bail!("{}", stderr.trim());
+    }
+
+    let stdout = String::from_utf8(output.stdout)?;
+
+    Ok(stdout.trim().to_owned())
+}
@@ -0,0 +1,33 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at https://mozilla.org/MPL/2.0/.
+
+//! Adapter for streaming the output of `fmt::Write`-based printers into `io::Write` sinks.
+
+use std::{fmt, io};
+
+pub(crate) struct FmtWriter<W> {
+    inner: W,
+}
+
+impl<W> FmtWriter<W>
+where
+    W: io::Write,
+{
+    pub(crate) fn new(inner: W) -> Self {
+        Self { inner }
+    }
+
+    pub(crate) fn flush(&mut self) -> io::Result<()> {
+        self.inner.flush()
+    }
+}
+
+impl<W> fmt::Write for FmtWriter<W>
+where
+    W: io::Write,
+{
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        self.inner.write_all(s.as_bytes()).map_err(|_| fmt::Error)
+    }
+}
@@ -0,0 +1,46 @@
+#[macro_use]
+mod util;
+
+mod help {
+    test_cmd!(
+        args: "between \
+                --help",
+        success: true,
+        color_mode: ColorMode::Plain,
+        project: smoke
+    );
+}
+
+mod pass {
+    test_cmd!(
+        args: "between \
+                layers::api \
+                layers::db",
+        success: true,
+        color_mode: ColorMode::Plain,
+        project: layers
+    );
+}
+
+mod max_length {
+    test_cmd!(
+        args: "between \
+                layers::api \
+                layers::db \
+                --max-length 1",
+        success: true,
+        color_mode: ColorMode::Plain,
+        project: layers
+    );
+}
+
+mod unknown_path {
+    test_cmd!(
+        args: "between \
+                layers::api \
+                layers::missing",
+        success: false,
+        color_mode: ColorMode::Plain,
+        project: layers
+    );
+}
@@ -0,0 +1,101 @@
+#[macro_use]
+mod util;
+
+mod help {
+    test_cmd!(
+        args: "check \
+                --help",
+        success: true,
+        color_mode: ColorMode::Plain,
+        project: smoke
+    );
+}
+
+mod budgets {
+    test_cmd!(
+        args: "check",
+        success: false,
+        color_mode: ColorMode::Plain,
+        project: budgets
+    );
+}
+
+mod no_budgets {
+    test_cmd!(
+        args: "check",
+        success: false,
+        color_mode: ColorMode::Plain,
+        project: smoke
+    );
+}
+
+mod lints {
+    test_cmd!(
+        args: "check \
+                --warn orphans \
+                --deny deep-nesting \
+                --max-nesting 0",
+        success: false,
+        color_mode: ColorMode::Plain,
+        project: orphans
+    );
+}
+
+mod output {
+    #[test]
+    fn orphans() {
+        let path = "target/cargo-modules-check-output.txt";
+        let _ = std::fs::remove_file(format!("tests/projects/orphans/{path}"));
+
+        let mut cmd = crate::util::cmd(
+            "orphans",
+            &format!("check --warn orphans --deny deep-nesting --max-nesting 0 -o {path}"),
+        );
+        cmd.env("NO_COLOR", "1");
+
+        let (stdout, stderr) = crate::util::output(cmd, false);
+        let written = std::fs::read_to_string(format!("tests/projects/orphans/{path}")).unwrap();
+        let output = format!("STDERR:\n{stderr}\nSTDOUT:\n{stdout}\nOUTPUT:\n{written}");
+
+        insta::assert_snapshot!(output);
+    }
+}
+
+mod unused_pub {
+    // Libraries and binaries are told apart by their target's kind,
+    // rather than by whether their root declares a `fn main`:
+
+    mod lib {
+        test_cmd!(
+            args: "check \
+                    --lib \
+                    --warn unused-pub",
+            success: true,
+            color_mode: ColorMode::Plain,
+            project: unused_pub
+        );
+    }
+
+    mod bin {
+        test_cmd!(
+            args: "check \
+                    --bin unused_pub \
+                    --warn unused-pub",
+            success: true,
+            color_mode: ColorMode::Plain,
+            project: unused_pub
+        );
+    }
+}
+
+mod suppressions {
+    test_cmd!(
+        args: "check \
+                --deny glob-imports \
+                --deny deep-nesting \
+                --max-nesting 1",
+        success: false,
+        color_mode: ColorMode::Plain,
+        project: suppressions
+    );
+}
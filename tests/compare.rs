@@ -0,0 +1,44 @@
+#[macro_use]
+mod util;
+
+mod help {
+    test_cmd!(
+        args: "compare \
+                --help",
+        success: true,
+        color_mode: ColorMode::Plain,
+        project: smoke
+    );
+}
+
+mod packages {
+    test_cmd!(
+        args: "compare \
+                -p crate_a \
+                -p crate_b",
+        success: true,
+        color_mode: ColorMode::Plain,
+        project: compare
+    );
+}
+
+mod single_package {
+    test_cmd!(
+        args: "compare \
+                -p crate_a",
+        success: false,
+        color_mode: ColorMode::Plain,
+        project: compare
+    );
+}
+
+mod moves {
+    test_cmd!(
+        args: "compare \
+                -p crate_a \
+                -p crate_b",
+        success: true,
+        color_mode: ColorMode::Plain,
+        project: compare_moves
+    );
+}
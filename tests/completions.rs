@@ -0,0 +1,12 @@
+#[macro_use]
+mod util;
+
+mod help {
+    test_cmd!(
+        args: "completions \
+                --help",
+        success: true,
+        color_mode: ColorMode::Plain,
+        project: smoke
+    );
+}
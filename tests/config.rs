@@ -0,0 +1,91 @@
+#[macro_use]
+mod util;
+
+mod config_file {
+    mod defaults {
+        test_cmd!(
+            args: "structure",
+            success: true,
+            color_mode: ColorMode::Plain,
+            project: config_file
+        );
+    }
+
+    mod overridden {
+        test_cmd!(
+            args: "structure \
+                    --max-depth 2",
+            success: true,
+            color_mode: ColorMode::Plain,
+            project: config_file
+        );
+    }
+
+    mod negated {
+        test_cmd!(
+            args: "structure \
+                    --fns \
+                    --max-depth 2",
+            success: true,
+            color_mode: ColorMode::Plain,
+            project: config_file
+        );
+    }
+
+    mod replaced_list {
+        test_cmd!(
+            args: "dependencies \
+                    --analyzer stats \
+                    --layout none",
+            success: true,
+            color_mode: ColorMode::Plain,
+            project: config_file
+        );
+    }
+}
+
+mod config_conflicts {
+    // Defaults conflicting with explicitly passed options make way for the latter:
+
+    mod conflicts_with {
+        test_cmd!(
+            args: "dependencies \
+                    --lib \
+                    --emit dot \
+                    --emit-path target/cargo-modules-emit/graph.{ext}",
+            success: true,
+            color_mode: ColorMode::Plain,
+            project: config_conflicts
+        );
+    }
+
+    mod exclusive_group {
+        test_cmd!(
+            args: "structure \
+                    --bin config_conflicts",
+            success: true,
+            color_mode: ColorMode::Plain,
+            project: config_conflicts
+        );
+    }
+}
+
+mod package_metadata {
+    test_cmd!(
+        args: "structure",
+        success: true,
+        color_mode: ColorMode::Plain,
+        project: config_metadata
+    );
+}
+
+mod node_styles {
+    test_cmd!(
+        args: "dependencies \
+                --no-externs \
+                --no-fns",
+        success: true,
+        color_mode: ColorMode::Plain,
+        project: node_styles
+    );
+}
@@ -0,0 +1,43 @@
+#[macro_use]
+mod util;
+
+mod help {
+    test_cmd!(
+        args: "crates \
+                --help",
+        success: true,
+        color_mode: ColorMode::Plain,
+        project: smoke
+    );
+}
+
+mod pass {
+    test_cmd!(
+        args: "crates",
+        success: true,
+        color_mode: ColorMode::Plain,
+        project: crate_dependencies
+    );
+}
+
+mod deny_unused {
+    test_cmd!(
+        args: "crates \
+                --deny-unused",
+        success: false,
+        color_mode: ColorMode::Plain,
+        project: crate_dependencies
+    );
+}
+
+mod with_impls {
+    test_cmd!(
+        args: "crates \
+                -p app_core \
+                -p app_impls \
+                --with-impls",
+        success: true,
+        color_mode: ColorMode::Plain,
+        project: crate_impls
+    );
+}
@@ -0,0 +1,128 @@
+#[macro_use]
+mod util;
+
+/// Runs the daemon on `project`, writing `requests` (one per line) to its stdin.
+fn respond(project: &str, args: &str, requests: &[&str]) -> String {
+    let mut cmd = crate::util::cmd(project, args);
+
+    cmd.env("NO_COLOR", "1");
+    cmd.write_stdin(requests.join("\n"));
+
+    let (stdout, stderr) = crate::util::output(cmd, true);
+
+    format!("STDERR:\n{}\nSTDOUT:\n{}", stderr, stdout)
+}
+
+mod help {
+    test_cmd!(
+        args: "daemon \
+                --help",
+        success: true,
+        color_mode: ColorMode::Plain,
+        project: smoke
+    );
+}
+
+mod tree {
+    #[test]
+    fn layers() {
+        let output = crate::respond(
+            "layers",
+            "daemon --lib",
+            &[r#"{"jsonrpc": "2.0", "id": 1, "method": "tree", "params": {"no_types": true}}"#],
+        );
+
+        insta::assert_snapshot!(output);
+    }
+}
+
+mod subgraph {
+    #[test]
+    fn layers() {
+        let output = crate::respond(
+            "layers",
+            "daemon --lib",
+            &[
+                r#"{"jsonrpc": "2.0", "id": 1, "method": "subgraph", "params": {"no_externs": true, "focus_on": "layers::db"}}"#,
+            ],
+        );
+
+        insta::assert_snapshot!(output);
+    }
+}
+
+mod why {
+    #[test]
+    fn layers() {
+        let output = crate::respond(
+            "layers",
+            "daemon --lib",
+            &[
+                r#"{"jsonrpc": "2.0", "id": 1, "method": "why", "params": {"from": "layers::api::Handler", "to": "layers::db::Connection"}}"#,
+                r#"{"jsonrpc": "2.0", "id": 2, "method": "why", "params": {"from": "layers::db", "to": "layers::util"}}"#,
+            ],
+        );
+
+        insta::assert_snapshot!(output);
+    }
+}
+
+mod locate {
+    #[test]
+    fn layers() {
+        let output = crate::respond(
+            "layers",
+            "daemon --lib",
+            &[
+                r#"{"jsonrpc": "2.0", "id": 1, "method": "locate", "params": {"path": "layers::service::Service"}}"#,
+            ],
+        );
+
+        // Locations are absolute paths, which differ between machines:
+        let output = output.replace(env!("CARGO_MANIFEST_DIR"), "$CARGO_MANIFEST_DIR");
+
+        insta::assert_snapshot!(output);
+    }
+}
+
+mod reload {
+    #[test]
+    fn layers() {
+        // Requests following a `shutdown` don't get answered anymore:
+
+        let output = crate::respond(
+            "layers",
+            "daemon --lib",
+            &[
+                r#"{"jsonrpc": "2.0", "id": 1, "method": "reload"}"#,
+                r#"{"jsonrpc": "2.0", "id": 2, "method": "why", "params": {"from": "layers::service::Service", "to": "layers::db::Connection"}}"#,
+                r#"{"jsonrpc": "2.0", "id": 3, "method": "shutdown"}"#,
+                r#"{"jsonrpc": "2.0", "id": 4, "method": "tree"}"#,
+            ],
+        );
+
+        insta::assert_snapshot!(output);
+    }
+}
+
+mod errors {
+    #[test]
+    fn layers() {
+        let output = crate::respond(
+            "layers",
+            "daemon --lib",
+            &[
+                r#"not json"#,
+                r#"{"jsonrpc": "2.0", "id": 1}"#,
+                r#"{"jsonrpc": "2.0", "id": 2, "method": "unknown"}"#,
+                r#"{"jsonrpc": "2.0", "id": 3, "method": "tree", "params": "no_fns"}"#,
+                r#"{"jsonrpc": "2.0", "id": 4, "method": "tree", "params": {"unknown": true}}"#,
+                r#"{"jsonrpc": "2.0", "id": 5, "method": "tree", "params": {"manifest_path": "."}}"#,
+                r#"{"jsonrpc": "2.0", "id": 6, "method": "why", "params": {"from": "layers::api"}}"#,
+                r#"{"jsonrpc": "2.0", "id": 7, "method": "locate", "params": {"path": "layers::unknown"}}"#,
+            ],
+        );
+
+        insta::assert_snapshot!(output);
+    }
+}
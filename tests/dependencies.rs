@@ -299,6 +299,42 @@ mod package_bin {
     }
 }
 
+mod merge_targets {
+    mod pass {
+        test_cmd!(
+            args: "dependencies \
+                    --merge-targets",
+            success: true,
+            color_mode: ColorMode::Plain,
+            project: package_multi_target
+        );
+    }
+}
+
+mod label {
+    mod short {
+        test_cmd!(
+            args: "dependencies \
+                    --bin package_multi_target \
+                    --label short",
+            success: true,
+            color_mode: ColorMode::Plain,
+            project: package_multi_target
+        );
+    }
+
+    mod max_len {
+        test_cmd!(
+            args: "dependencies \
+                    --bin package_multi_target \
+                    --label-max-len 12",
+            success: true,
+            color_mode: ColorMode::Plain,
+            project: package_multi_target
+        );
+    }
+}
+
 mod cfg_test {
     test_cmd!(
         args: "dependencies \
@@ -310,6 +346,36 @@ mod cfg_test {
 }
 
 mod selection {
+    mod collapse_externs {
+        test_cmd!(
+            args: "dependencies \
+                    --collapse-externs",
+            success: true,
+            color_mode: ColorMode::Plain,
+            project: collapse_externs
+        );
+    }
+
+    mod extern_filter {
+        test_cmd!(
+            args: "dependencies \
+                    --extern-filter tokio*",
+            success: true,
+            color_mode: ColorMode::Plain,
+            project: collapse_externs
+        );
+    }
+
+    mod collapse_sysroot {
+        test_cmd!(
+            args: "dependencies \
+                    --collapse-sysroot",
+            success: true,
+            color_mode: ColorMode::Plain,
+            project: smoke
+        );
+    }
+
     mod no_externs {
         test_cmd!(
             args: "dependencies \
@@ -411,6 +477,31 @@ mod focus_on {
             project: smoke
         );
     }
+
+    mod item_path {
+        test_cmd!(
+            args: "dependencies \
+                    --no-externs \
+                    --no-fns \
+                    --no-traits \
+                    --no-types \
+                    --focus-on \"github_issue_79::a::b::c::X\"",
+            success: true,
+            color_mode: ColorMode::Plain,
+            project: github_issue_79
+        );
+    }
+
+    mod highlight_path {
+        test_cmd!(
+            args: "dependencies \
+                    --focus-on \"smoke::visibility::dummy\" \
+                    --highlight-path",
+            success: true,
+            color_mode: ColorMode::Plain,
+            project: smoke
+        );
+    }
 }
 
 mod max_depth {
@@ -445,6 +536,40 @@ mod max_depth {
     }
 }
 
+mod select {
+    mod nodes {
+        test_cmd!(
+            args: "dependencies \
+                    --no-externs \
+                    --select \"kind == mod\"",
+            success: true,
+            color_mode: ColorMode::Plain,
+            project: github_issue_102
+        );
+    }
+
+    mod edges {
+        test_cmd!(
+            args: "dependencies \
+                    --no-externs \
+                    --select \"edges(uses) from a::Z to a::b\"",
+            success: true,
+            color_mode: ColorMode::Plain,
+            project: github_issue_102
+        );
+    }
+
+    mod invalid {
+        test_cmd!(
+            args: "dependencies \
+                    --select \"kind = mod\"",
+            success: false,
+            color_mode: ColorMode::Plain,
+            project: github_issue_102
+        );
+    }
+}
+
 mod fields {
     test_cmds!(
         args: "dependencies",
@@ -538,3 +663,620 @@ mod github {
         );
     }
 }
+
+mod template {
+    mod nodes {
+        test_cmd!(
+            args: "dependencies \
+                    --no-externs \
+                    --no-fns \
+                    --no-traits \
+                    --no-types \
+                    --template ../../templates/nodes.tera",
+            success: true,
+            color_mode: ColorMode::Plain,
+            project: github_issue_102
+        );
+    }
+
+    mod edges {
+        test_cmd!(
+            args: "dependencies \
+                    --no-externs \
+                    --template ../../templates/edges.tera",
+            success: true,
+            color_mode: ColorMode::Plain,
+            project: github_issue_102
+        );
+    }
+
+    mod spans {
+        test_cmd!(
+            args: "dependencies \
+                    --template ../../templates/spans.tera",
+            success: true,
+            color_mode: ColorMode::Plain,
+            project: layers
+        );
+    }
+}
+
+mod themes {
+    mod light {
+        test_cmd!(
+            args: "dependencies \
+                    --theme light",
+            success: true,
+            color_mode: ColorMode::Plain,
+            project: smoke
+        );
+    }
+}
+
+mod layout {
+    test_cmd!(
+        args: "dependencies \
+                --no-externs \
+                --no-fns \
+                --no-traits \
+                --no-types \
+                --layout-engine dot \
+                --layout-direction TB",
+        success: true,
+        color_mode: ColorMode::Plain,
+        project: github_issue_102
+    );
+}
+
+mod tuning {
+    test_cmd!(
+        args: "dependencies \
+                --no-externs \
+                --no-fns \
+                --no-traits \
+                --no-types \
+                --splines ortho \
+                --concentrate \
+                --nodesep 0.5 \
+                --ranksep 1.25",
+        success: true,
+        color_mode: ColorMode::Plain,
+        project: github_issue_102
+    );
+}
+
+mod cluster {
+    test_cmd!(
+        args: "dependencies \
+                --no-externs \
+                --no-fns \
+                --no-traits \
+                --no-types \
+                --cluster",
+        success: true,
+        color_mode: ColorMode::Plain,
+        project: github_issue_102
+    );
+}
+
+mod cluster_by {
+    test_cmd!(
+        args: "dependencies \
+                --no-externs \
+                --no-sysroot \
+                --cluster-by feature",
+        success: true,
+        color_mode: ColorMode::Plain,
+        project: cfg_modules
+    );
+}
+
+mod attrs {
+    test_cmd!(
+        args: "dependencies \
+                --no-externs \
+                --no-fns \
+                --no-traits \
+                --no-types \
+                --graph-attr bgcolor=transparent \
+                --node-attr \"fontname=Fira Code\" \
+                --node-attr penwidth=0 \
+                --edge-attr penwidth=2",
+        success: true,
+        color_mode: ColorMode::Plain,
+        project: github_issue_102
+    );
+}
+
+mod legend {
+    test_cmd!(
+        args: "dependencies \
+                --no-externs \
+                --no-fns \
+                --no-traits \
+                --no-types \
+                --with-legend",
+        success: true,
+        color_mode: ColorMode::Plain,
+        project: github_issue_102
+    );
+}
+
+mod color_by {
+    mod none {
+        test_cmd!(
+            args: "dependencies \
+                    --no-externs \
+                    --no-fns \
+                    --no-traits \
+                    --no-types \
+                    --color-by none",
+            success: true,
+            color_mode: ColorMode::Plain,
+            project: github_issue_102
+        );
+    }
+
+    mod loc {
+        test_cmd!(
+            args: "dependencies \
+                    --no-externs \
+                    --no-fns \
+                    --no-traits \
+                    --no-types \
+                    --color-by loc",
+            success: true,
+            color_mode: ColorMode::Plain,
+            project: github_issue_102
+        );
+    }
+
+    mod depth {
+        test_cmd!(
+            args: "dependencies \
+                    --no-externs \
+                    --no-fns \
+                    --no-traits \
+                    --no-types \
+                    --layout dot \
+                    --color-by depth \
+                    --rank-by-depth",
+            success: true,
+            color_mode: ColorMode::Plain,
+            project: github_issue_102
+        );
+    }
+}
+
+mod deprecated {
+    mod all {
+        test_cmd!(
+            args: "dependencies \
+                    --no-externs",
+            success: true,
+            color_mode: ColorMode::Plain,
+            project: deprecated
+        );
+    }
+
+    mod only_deprecated {
+        test_cmd!(
+            args: "dependencies \
+                    --no-externs \
+                    --only-deprecated",
+            success: true,
+            color_mode: ColorMode::Plain,
+            project: deprecated
+        );
+    }
+}
+
+mod dependency_kinds {
+    mod lib {
+        test_cmd!(
+            args: "dependencies \
+                    --cfg-test",
+            success: true,
+            color_mode: ColorMode::Plain,
+            project: dependency_kinds
+        );
+    }
+
+    mod build_script {
+        test_cmd!(
+            args: "dependencies \
+                    --build-script",
+            success: true,
+            color_mode: ColorMode::Plain,
+            project: dependency_kinds
+        );
+    }
+
+    mod with_build_script {
+        test_cmd!(
+            args: "dependencies \
+                    --with-build-script",
+            success: true,
+            color_mode: ColorMode::Plain,
+            project: dependency_kinds
+        );
+    }
+}
+
+mod with_docs {
+    test_cmd!(
+        args: "dependencies \
+                --no-externs \
+                --with-docs",
+        success: true,
+        color_mode: ColorMode::Plain,
+        project: doc_comments
+    );
+}
+
+mod with_orphans {
+    test_cmd!(
+        args: "dependencies \
+                --with-orphans",
+        success: true,
+        color_mode: ColorMode::Plain,
+        project: orphans
+    );
+}
+
+mod anonymize {
+    test_cmd!(
+        args: "dependencies \
+                --anonymize",
+        success: true,
+        color_mode: ColorMode::Plain,
+        project: smoke
+    );
+}
+
+mod url_template {
+    test_cmd!(
+        args: "dependencies \
+                --no-externs \
+                --no-fns \
+                --no-traits \
+                --no-types \
+                --url-template \"https://example.com/blob/main/{path}#L{line}\"",
+        success: true,
+        color_mode: ColorMode::Plain,
+        project: github_issue_102
+    );
+}
+
+mod edge_weights {
+    test_cmd!(
+        args: "dependencies \
+                --no-externs \
+                --no-fns \
+                --no-traits \
+                --no-types \
+                --edge-weights",
+        success: true,
+        color_mode: ColorMode::Plain,
+        project: github_issue_102
+    );
+}
+
+mod edge_labels {
+    mod default {
+        test_cmd!(
+            args: "dependencies \
+                    --no-externs \
+                    --no-fns \
+                    --no-types \
+                    --edge-labels",
+            success: true,
+            color_mode: ColorMode::Plain,
+            project: edge_labels
+        );
+    }
+
+    mod max_items {
+        test_cmd!(
+            args: "dependencies \
+                    --no-externs \
+                    --no-fns \
+                    --no-types \
+                    --edge-labels 1",
+            success: true,
+            color_mode: ColorMode::Plain,
+            project: edge_labels
+        );
+    }
+}
+
+mod output {
+    test_cmd!(
+        args: "dependencies \
+                -o target/cargo-modules-output.dot",
+        success: true,
+        color_mode: ColorMode::Plain,
+        project: smoke
+    );
+}
+
+#[cfg(unix)]
+mod open {
+    // The output gets written to a temporary file (rather than printed),
+    // which then gets passed to the viewer (here a no-op one):
+
+    #[test]
+    fn smoke() {
+        let temp_dir = std::env::current_dir()
+            .unwrap()
+            .join("tests/projects/smoke/target/cargo-modules-open");
+        let _ = std::fs::remove_dir_all(&temp_dir);
+        std::fs::create_dir_all(&temp_dir).unwrap();
+
+        let mut cmd = crate::util::cmd("smoke", "dependencies --open");
+        cmd.env("NO_COLOR", "1");
+        cmd.env("TMPDIR", &temp_dir);
+        cmd.env("CARGO_MODULES_VIEWER", "true");
+
+        let (stdout, stderr) = crate::util::output(cmd, true);
+
+        let file_paths: Vec<_> = std::fs::read_dir(temp_dir.join("cargo-modules/open"))
+            .unwrap()
+            .map(|entry| entry.unwrap().path())
+            .collect();
+
+        assert_eq!(file_paths.len(), 1);
+        assert_eq!(file_paths[0].extension().unwrap(), "dot");
+
+        let opened = std::fs::read_to_string(&file_paths[0]).unwrap();
+        let output = format!("STDERR:\n{stderr}\nSTDOUT:\n{stdout}\nOPENED:\n{opened}");
+
+        insta::assert_snapshot!(output);
+    }
+}
+
+mod streaming {
+    // Output printed to stdout gets streamed while being formatted,
+    // yet must match the output rendered in full (e.g. for writing it to a file):
+
+    #[test]
+    fn smoke() {
+        let path = "target/cargo-modules-streaming.dot";
+        let _ = std::fs::remove_file(format!("tests/projects/smoke/{path}"));
+
+        let mut cmd = crate::util::cmd("smoke", "dependencies");
+        cmd.env("NO_COLOR", "1");
+        let (streamed, _) = crate::util::output(cmd, true);
+
+        let mut cmd = crate::util::cmd("smoke", &format!("dependencies -o {path}"));
+        cmd.env("NO_COLOR", "1");
+        crate::util::output(cmd, true);
+        let rendered = std::fs::read_to_string(format!("tests/projects/smoke/{path}")).unwrap();
+
+        assert_eq!(streamed, rendered);
+
+        insta::assert_snapshot!(streamed);
+    }
+}
+
+mod cache {
+    // The contents of templates aren't part of the cache's key:
+
+    mod template {
+        test_cmd!(
+            args: "dependencies \
+                    --cache \
+                    --template ../../templates/nodes.tera",
+            success: false,
+            color_mode: ColorMode::Plain,
+            project: github_issue_102
+        );
+    }
+}
+
+mod split_output {
+    test_cmd!(
+        args: "dependencies \
+                --split-output target/cargo-modules-split",
+        success: true,
+        color_mode: ColorMode::Plain,
+        project: smoke
+    );
+}
+
+mod review_json {
+    test_cmd!(
+        args: "dependencies \
+                --no-sysroot \
+                --format review-json \
+                --changed src/lib.rs",
+        success: true,
+        color_mode: ColorMode::Plain,
+        project: budgets
+    );
+}
+
+mod emit {
+    test_cmd!(
+        args: "dependencies \
+                --emit dot,json,mermaid \
+                --emit-path target/cargo-modules-emit/graph.{ext}",
+        success: true,
+        color_mode: ColorMode::Plain,
+        project: smoke
+    );
+}
+
+mod fail_on {
+    mod orphans {
+        test_cmd!(
+            args: "dependencies \
+                    --layout none \
+                    --fail-on orphans",
+            success: false,
+            color_mode: ColorMode::Plain,
+            project: orphans
+        );
+    }
+
+    mod orphans_below_threshold {
+        test_cmd!(
+            args: "dependencies \
+                    --layout none \
+                    --fail-on orphans>2",
+            success: true,
+            color_mode: ColorMode::Plain,
+            project: orphans
+        );
+    }
+
+    mod depth {
+        test_cmd!(
+            args: "dependencies \
+                    --layout none \
+                    --fail-on depth>1",
+            success: false,
+            color_mode: ColorMode::Plain,
+            project: merge_leaves
+        );
+    }
+
+    mod depth_below_threshold {
+        test_cmd!(
+            args: "dependencies \
+                    --layout none \
+                    --fail-on depth>2",
+            success: true,
+            color_mode: ColorMode::Plain,
+            project: merge_leaves
+        );
+    }
+}
+
+mod simplify {
+    test_cmd!(
+        args: "dependencies \
+                --no-fns \
+                --simplify",
+        success: true,
+        color_mode: ColorMode::Plain,
+        project: simplify
+    );
+}
+
+mod merge_leaves {
+    test_cmd!(
+        args: "dependencies \
+                --merge-leaves 2",
+        success: true,
+        color_mode: ColorMode::Plain,
+        project: merge_leaves
+    );
+}
+
+mod analyzer {
+    mod stats_and_fan_out {
+        test_cmd!(
+            args: "dependencies \
+                    --no-sysroot \
+                    --analyzer stats \
+                    --analyzer fan-out \
+                    --layout none",
+            success: true,
+            color_mode: ColorMode::Plain,
+            project: function_body
+        );
+    }
+
+    mod no_std {
+        test_cmd!(
+            args: "dependencies \
+                    --analyzer no-std \
+                    --layout none",
+            success: true,
+            color_mode: ColorMode::Plain,
+            project: std_requirements
+        );
+    }
+}
+
+mod priming {
+    // Inference caches get primed in parallel (unless on a single thread),
+    // which must not change the resulting graph:
+
+    #[test]
+    fn function_body() {
+        let output = |threads: &str| {
+            let mut cmd = crate::util::cmd("function_body", "dependencies --no-sysroot");
+            cmd.env("NO_COLOR", "1");
+            cmd.env("RAYON_NUM_THREADS", threads);
+            crate::util::output(cmd, true).0
+        };
+
+        let primed = output("4");
+        let unprimed = output("1");
+
+        assert_eq!(primed, unprimed);
+
+        insta::assert_snapshot!(primed);
+    }
+}
+
+mod sysroot {
+    mod not_found {
+        test_cmd!(
+            args: "dependencies \
+                    --sysroot target/nonexistent-sysroot",
+            success: false,
+            color_mode: ColorMode::Plain,
+            project: smoke
+        );
+    }
+}
+
+mod test_uses {
+    mod marked {
+        test_cmd!(
+            args: "dependencies \
+                    --cfg-test \
+                    --template ../../templates/test_only.tera",
+            success: true,
+            color_mode: ColorMode::Plain,
+            project: test_uses
+        );
+    }
+
+    mod filtered {
+        test_cmd!(
+            args: "dependencies \
+                    --cfg-test \
+                    --no-test-uses \
+                    --template ../../templates/test_only.tera",
+            success: true,
+            color_mode: ColorMode::Plain,
+            project: test_uses
+        );
+    }
+}
+
+mod format {
+    mod ascii {
+        test_cmd!(
+            args: "dependencies \
+                    --format ascii",
+            success: true,
+            color_mode: ColorMode::Plain,
+            project: layers
+        );
+    }
+
+    mod unicode {
+        test_cmd!(
+            args: "dependencies \
+                    --format unicode",
+            success: true,
+            color_mode: ColorMode::Plain,
+            project: layers
+        );
+    }
+}
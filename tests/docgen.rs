@@ -0,0 +1,31 @@
+#[macro_use]
+mod util;
+
+mod help {
+    test_cmd!(
+        args: "docgen \
+                --help",
+        success: true,
+        color_mode: ColorMode::Plain,
+        project: smoke
+    );
+}
+
+mod document {
+    test_cmd!(
+        args: "docgen",
+        success: true,
+        color_mode: ColorMode::Plain,
+        project: docgen
+    );
+}
+
+mod wiki {
+    test_cmd!(
+        args: "docgen \
+                --wiki target/cargo-modules-wiki",
+        success: true,
+        color_mode: ColorMode::Plain,
+        project: docgen
+    );
+}
@@ -0,0 +1,31 @@
+#[macro_use]
+mod util;
+
+mod help {
+    test_cmd!(
+        args: "docs \
+                --help",
+        success: true,
+        color_mode: ColorMode::Plain,
+        project: smoke
+    );
+}
+
+mod coverage {
+    test_cmd!(
+        args: "docs",
+        success: true,
+        color_mode: ColorMode::Plain,
+        project: docs
+    );
+}
+
+mod deny_undocumented_public {
+    test_cmd!(
+        args: "docs \
+                --deny-undocumented-public",
+        success: false,
+        color_mode: ColorMode::Plain,
+        project: docs
+    );
+}
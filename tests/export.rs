@@ -0,0 +1,21 @@
+#[macro_use]
+mod util;
+
+mod help {
+    test_cmd!(
+        args: "export \
+                --help",
+        success: true,
+        color_mode: ColorMode::Plain,
+        project: smoke
+    );
+}
+
+mod pass {
+    test_cmd!(
+        args: "export",
+        success: true,
+        color_mode: ColorMode::Plain,
+        project: layers
+    );
+}
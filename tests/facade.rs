@@ -0,0 +1,21 @@
+#[macro_use]
+mod util;
+
+mod help {
+    test_cmd!(
+        args: "facade \
+                --help",
+        success: true,
+        color_mode: ColorMode::Plain,
+        project: smoke
+    );
+}
+
+mod report {
+    test_cmd!(
+        args: "facade",
+        success: true,
+        color_mode: ColorMode::Plain,
+        project: facade
+    );
+}
@@ -0,0 +1,32 @@
+#[macro_use]
+mod util;
+
+mod help {
+    test_cmd!(
+        args: "features \
+                --help",
+        success: true,
+        color_mode: ColorMode::Plain,
+        project: smoke
+    );
+}
+
+mod powerset {
+    test_cmd!(
+        args: "features",
+        success: true,
+        color_mode: ColorMode::Plain,
+        project: features
+    );
+}
+
+mod feature_sets {
+    test_cmd!(
+        args: "features \
+                --feature-set alpha \
+                --feature-set beta,gamma",
+        success: true,
+        color_mode: ColorMode::Plain,
+        project: features
+    );
+}
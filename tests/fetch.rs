@@ -0,0 +1,126 @@
+#[macro_use]
+mod util;
+
+mod help {
+    test_cmd!(
+        args: "fetch \
+                --help",
+        success: true,
+        color_mode: ColorMode::Plain,
+        project: smoke
+    );
+}
+
+mod spec {
+    // Specs that parse fine end up complaining about the (unknown) command instead:
+
+    mod name {
+        test_cmd!(
+            args: "fetch \
+                    serde \
+                    unknown",
+            success: false,
+            color_mode: ColorMode::Plain,
+            project: smoke
+        );
+    }
+
+    mod full_version {
+        test_cmd!(
+            args: "fetch \
+                    serde_json@1.0.117 \
+                    unknown",
+            success: false,
+            color_mode: ColorMode::Plain,
+            project: smoke
+        );
+    }
+
+    mod partial_version {
+        test_cmd!(
+            args: "fetch \
+                    serde@1.0 \
+                    unknown",
+            success: false,
+            color_mode: ColorMode::Plain,
+            project: smoke
+        );
+    }
+
+    mod pre_release_version {
+        test_cmd!(
+            args: "fetch \
+                    tokio@1.0.0-alpha.1+build.5 \
+                    unknown",
+            success: false,
+            color_mode: ColorMode::Plain,
+            project: smoke
+        );
+    }
+
+    mod path_traversal {
+        test_cmd!(
+            args: "fetch \
+                    ../evil \
+                    structure",
+            success: false,
+            color_mode: ColorMode::Plain,
+            project: smoke
+        );
+    }
+
+    mod manifest_injection {
+        test_cmd!(
+            args: "fetch \
+                    'serde = \"1\"\nevil' \
+                    structure",
+            success: false,
+            color_mode: ColorMode::Plain,
+            project: smoke
+        );
+    }
+
+    mod leading_digit {
+        test_cmd!(
+            args: "fetch \
+                    1serde \
+                    structure",
+            success: false,
+            color_mode: ColorMode::Plain,
+            project: smoke
+        );
+    }
+
+    mod missing_version {
+        test_cmd!(
+            args: "fetch \
+                    serde@ \
+                    structure",
+            success: false,
+            color_mode: ColorMode::Plain,
+            project: smoke
+        );
+    }
+
+    mod invalid_version {
+        test_cmd!(
+            args: "fetch \
+                    serde@1.x \
+                    structure",
+            success: false,
+            color_mode: ColorMode::Plain,
+            project: smoke
+        );
+    }
+
+    mod partial_pre_release_version {
+        test_cmd!(
+            args: "fetch \
+                    serde@1.0-beta \
+                    structure",
+            success: false,
+            color_mode: ColorMode::Plain,
+            project: smoke
+        );
+    }
+}
@@ -9,3 +9,43 @@ mod help {
         project: smoke
     );
 }
+
+mod default_command {
+    mod bare {
+        test_cmd!(
+            args: "",
+            success: true,
+            color_mode: ColorMode::Plain,
+            project: smoke
+        );
+    }
+
+    mod manifest_path {
+        test_cmd!(
+            args: "--manifest-path Cargo.toml",
+            success: true,
+            color_mode: ColorMode::Plain,
+            project: smoke
+        );
+    }
+}
+
+mod at {
+    mod unknown_revision {
+        test_cmd!(
+            args: "structure --at no-such-revision",
+            success: false,
+            color_mode: ColorMode::Plain,
+            project: smoke
+        );
+    }
+
+    mod option_like_revision {
+        test_cmd!(
+            args: "structure --at=--output=/tmp/evil",
+            success: false,
+            color_mode: ColorMode::Plain,
+            project: smoke
+        );
+    }
+}
@@ -0,0 +1,43 @@
+#[macro_use]
+mod util;
+
+mod help {
+    test_cmd!(
+        args: "impact \
+                --help",
+        success: true,
+        color_mode: ColorMode::Plain,
+        project: smoke
+    );
+}
+
+mod pass {
+    test_cmd!(
+        args: "impact \
+                layers::db",
+        success: true,
+        color_mode: ColorMode::Plain,
+        project: layers
+    );
+}
+
+mod list {
+    test_cmd!(
+        args: "impact \
+                layers::db \
+                --list",
+        success: true,
+        color_mode: ColorMode::Plain,
+        project: layers
+    );
+}
+
+mod unknown_path {
+    test_cmd!(
+        args: "impact \
+                layers::missing",
+        success: false,
+        color_mode: ColorMode::Plain,
+        project: layers
+    );
+}
@@ -0,0 +1,54 @@
+#[macro_use]
+mod util;
+
+mod help {
+    test_cmd!(
+        args: "imports \
+                --help",
+        success: true,
+        color_mode: ColorMode::Plain,
+        project: smoke
+    );
+}
+
+mod group_by {
+    mod krate {
+        test_cmd!(
+            args: "imports \
+                    --group-by crate",
+            success: true,
+            color_mode: ColorMode::Plain,
+            project: imports
+        );
+    }
+
+    mod module {
+        test_cmd!(
+            args: "imports \
+                    --group-by module",
+            success: true,
+            color_mode: ColorMode::Plain,
+            project: imports
+        );
+    }
+
+    mod item {
+        test_cmd!(
+            args: "imports \
+                    --group-by item",
+            success: true,
+            color_mode: ColorMode::Plain,
+            project: imports
+        );
+    }
+}
+
+mod no_internal {
+    test_cmd!(
+        args: "imports \
+                --no-internal",
+        success: true,
+        color_mode: ColorMode::Plain,
+        project: imports
+    );
+}
@@ -21,6 +21,15 @@ mod colors {
         );
     }
 
+    mod basic {
+        test_cmd!(
+            args: "orphans",
+            success: false,
+            color_mode: ColorMode::Basic,
+            project: orphans
+        );
+    }
+
     mod ansi {
         test_cmd!(
             args: "orphans",
@@ -57,3 +66,85 @@ mod orphans {
         project: orphans
     );
 }
+
+mod path_include {
+    test_cmd!(
+        args: "orphans",
+        success: false,
+        color_mode: ColorMode::Plain,
+        project: orphans_path_include
+    );
+}
+
+mod message_format {
+    mod quickfix {
+        test_cmd!(
+            args: "orphans \
+                    --message-format quickfix",
+            success: false,
+            color_mode: ColorMode::Plain,
+            project: orphans
+        );
+    }
+
+    mod github {
+        test_cmd!(
+            args: "orphans \
+                    --message-format github",
+            success: false,
+            color_mode: ColorMode::Plain,
+            project: orphans
+        );
+    }
+}
+
+mod themes {
+    mod colorblind_safe {
+        test_cmd!(
+            args: "orphans \
+                    --theme colorblind-safe",
+            success: false,
+            color_mode: ColorMode::TrueColor,
+            project: orphans
+        );
+    }
+}
+
+mod color {
+    mod never {
+        test_cmd!(
+            args: "orphans \
+                    --color never",
+            success: false,
+            color_mode: ColorMode::TrueColor,
+            project: orphans
+        );
+    }
+}
+
+mod workspace {
+    test_cmd!(
+        args: "orphans --workspace",
+        success: false,
+        color_mode: ColorMode::Plain,
+        project: orphans_workspace
+    );
+}
+
+mod ignore {
+    test_cmd!(
+        args: "orphans --ignore src/orphans/foo/**",
+        success: false,
+        color_mode: ColorMode::Plain,
+        project: orphans
+    );
+}
+
+mod vcs_ignore {
+    test_cmd!(
+        args: "orphans --vcs-ignore",
+        success: false,
+        color_mode: ColorMode::Plain,
+        project: orphans
+    );
+}
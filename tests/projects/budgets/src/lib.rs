@@ -0,0 +1,16 @@
+pub mod core {
+    use crate::{net::Client, util::helper};
+
+    pub fn run() -> Client {
+        helper();
+        Client
+    }
+}
+
+pub mod net {
+    pub struct Client;
+}
+
+pub mod util {
+    pub fn helper() {}
+}
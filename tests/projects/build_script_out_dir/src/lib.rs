@@ -0,0 +1,5 @@
+include!(concat!(env!("OUT_DIR"), "/generated.rs"));
+
+pub mod handwritten {
+    pub fn function() {}
+}
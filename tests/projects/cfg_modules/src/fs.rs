@@ -0,0 +1,3 @@
+#![cfg(feature = "fs")]
+
+pub struct File;
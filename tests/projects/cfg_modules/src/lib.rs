@@ -0,0 +1,14 @@
+#[cfg(feature = "net")]
+pub mod net;
+
+pub mod fs;
+
+#[cfg(all(feature = "net", not(feature = "tls")))]
+pub mod plain {
+    pub struct Connection;
+}
+
+#[cfg(feature = "tls")]
+pub mod tls {
+    pub struct Connection;
+}
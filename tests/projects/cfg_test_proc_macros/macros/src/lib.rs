@@ -0,0 +1,6 @@
+use proc_macro::TokenStream;
+
+#[proc_macro]
+pub fn generated_module(_input: TokenStream) -> TokenStream {
+    "pub mod generated { pub struct Generated; }".parse().unwrap()
+}
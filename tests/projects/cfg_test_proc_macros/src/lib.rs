@@ -0,0 +1,6 @@
+pub mod library {}
+
+#[cfg(test)]
+mod tests {
+    macros::generated_module!();
+}
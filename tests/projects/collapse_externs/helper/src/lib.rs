@@ -0,0 +1,9 @@
+pub mod alpha {
+    pub struct Alpha;
+}
+
+pub mod beta {
+    pub mod gamma {
+        pub fn gamma() {}
+    }
+}
@@ -0,0 +1,8 @@
+pub mod wrapper {
+    use helper::{alpha::Alpha, beta::gamma::gamma};
+
+    pub fn function() -> Alpha {
+        gamma();
+        Alpha
+    }
+}
@@ -0,0 +1,13 @@
+pub mod net {
+    pub struct Socket;
+
+    pub mod http {
+        use super::Socket;
+
+        pub fn get(_socket: &Socket) {}
+    }
+}
+
+pub mod util {
+    pub fn log() {}
+}
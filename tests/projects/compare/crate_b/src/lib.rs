@@ -0,0 +1,15 @@
+pub mod net {
+    pub mod http {
+        pub struct Request;
+
+        pub fn get(_request: &Request) {}
+
+        pub fn post(_request: &Request) {}
+    }
+}
+
+pub mod db {
+    pub mod pool {
+        pub struct Pool;
+    }
+}
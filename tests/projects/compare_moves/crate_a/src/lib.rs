@@ -0,0 +1,15 @@
+pub mod util {
+    pub struct Logger;
+
+    pub fn log(_logger: &Logger) {}
+}
+
+pub mod net {
+    pub mod http {
+        pub fn get() {}
+    }
+}
+
+pub mod config {
+    pub struct Config;
+}
@@ -0,0 +1,17 @@
+pub mod support {
+    pub mod logging {
+        pub struct Logger;
+
+        pub fn log(_logger: &Logger) {}
+    }
+}
+
+pub mod net {
+    pub mod client {
+        pub fn get() {}
+    }
+}
+
+pub mod settings {
+    pub struct Settings;
+}
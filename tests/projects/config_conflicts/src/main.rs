@@ -0,0 +1,3 @@
+mod bin_module {}
+
+fn main() {}
@@ -0,0 +1,7 @@
+mod lorem {
+    mod ipsum {
+        struct Dolor;
+    }
+
+    fn sit() {}
+}
@@ -0,0 +1,5 @@
+use used_helper::function as helper_function;
+
+pub fn function() {
+    helper_function();
+}
@@ -0,0 +1,7 @@
+pub trait Repository {
+    fn find(&self, id: u32) -> bool;
+}
+
+pub trait Service {
+    fn call(&self);
+}
@@ -0,0 +1,29 @@
+use app_core::{Repository, Service};
+
+pub struct Database;
+
+impl Repository for Database {
+    fn find(&self, _id: u32) -> bool {
+        true
+    }
+}
+
+pub struct Cache;
+
+impl Repository for Cache {
+    fn find(&self, _id: u32) -> bool {
+        false
+    }
+}
+
+pub struct Api;
+
+impl Service for Api {
+    fn call(&self) {}
+}
+
+impl Default for Api {
+    fn default() -> Self {
+        Api
+    }
+}
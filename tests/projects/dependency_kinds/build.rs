@@ -0,0 +1,5 @@
+use build_helper::function as build_function;
+
+fn main() {
+    build_function();
+}
@@ -0,0 +1,15 @@
+use runtime_helper::function as runtime_function;
+
+pub fn function() {
+    runtime_function();
+}
+
+#[cfg(test)]
+mod tests {
+    use dev_helper::function as dev_function;
+
+    #[test]
+    fn function() {
+        dev_function();
+    }
+}
@@ -0,0 +1,18 @@
+pub mod legacy {
+    #[deprecated(note = "use `modern::Widget` instead")]
+    pub struct Widget;
+
+    pub fn helper() {}
+}
+
+#[deprecated]
+pub mod old {
+    pub fn run() {}
+}
+
+pub mod modern {
+    pub struct Widget;
+
+    #[deprecated(since = "0.2.0")]
+    pub fn render() {}
+}
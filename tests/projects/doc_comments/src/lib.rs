@@ -0,0 +1,19 @@
+//! Crate for testing doc-comment summaries.
+
+/// Parsing of "quoted" input.
+///
+/// Any further lines get ignored.
+pub mod parser {
+    /// A parsed token.
+    pub struct Token;
+
+    pub fn parse() {}
+}
+
+#[doc = "Rendering of parsed input."]
+pub mod renderer {
+    use crate::parser::Token;
+
+    /// Renders a token.
+    pub fn render(_token: Token) {}
+}
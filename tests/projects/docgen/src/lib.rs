@@ -0,0 +1,24 @@
+//! An online shop.
+
+/// Handles HTTP requests.
+pub mod api {
+    use crate::db::Connection;
+
+    pub struct Handler {
+        connection: Connection,
+    }
+
+    /// Version 1 of the API.
+    pub mod v1 {
+        pub fn connect() -> crate::db::Connection {
+            crate::db::Connection
+        }
+    }
+}
+
+/// Stores the shop's data.
+pub mod db {
+    pub struct Connection;
+
+    pub(crate) fn migrate() {}
+}
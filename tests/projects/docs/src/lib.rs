@@ -0,0 +1,17 @@
+//! A crate for testing documentation coverage.
+
+/// Networking.
+pub mod net {
+    /// A socket.
+    pub struct Socket;
+
+    pub fn connect() -> Socket {
+        Socket
+    }
+
+    fn helper() {}
+}
+
+mod util {
+    pub(crate) fn log() {}
+}
@@ -0,0 +1,14 @@
+pub mod shapes {
+    pub struct Circle;
+    pub struct Hexagon;
+    pub struct Square;
+    pub struct Triangle;
+}
+
+pub mod render {
+    use crate::shapes::{Circle, Hexagon, Square, Triangle};
+
+    pub fn draw(_circle: Circle, _square: Square) {}
+
+    pub fn draw_more(_triangle: Triangle, _hexagon: Hexagon) {}
+}
@@ -0,0 +1,27 @@
+pub mod net {
+    pub struct Socket;
+
+    pub struct Error;
+
+    pub fn connect() {}
+}
+
+mod internal {
+    pub struct Hidden;
+
+    pub struct Exposed;
+}
+
+mod errors {
+    pub struct Error;
+
+    pub struct Timeout;
+}
+
+pub mod prelude {
+    pub use crate::net::Socket;
+}
+
+pub use errors::*;
+pub use internal::Exposed;
+pub use net::{connect, Error, Socket};
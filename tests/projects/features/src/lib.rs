@@ -0,0 +1,8 @@
+#[cfg(feature = "alpha")]
+pub mod alpha {}
+
+#[cfg(feature = "beta")]
+pub mod beta {}
+
+#[cfg(feature = "gamma")]
+pub mod gamma {}
@@ -0,0 +1,13 @@
+pub mod a {
+    pub struct A;
+
+    pub fn f() {}
+}
+
+pub mod b {
+    use crate::a::{f, A};
+}
+
+pub mod c {
+    use crate::{a::A, b};
+}
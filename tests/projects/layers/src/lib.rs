@@ -0,0 +1,19 @@
+pub mod api {
+    use crate::service::Service;
+
+    pub struct Handler(pub Service);
+}
+
+pub mod service {
+    use crate::db::Connection;
+
+    pub struct Service(pub Connection);
+}
+
+pub mod db {
+    pub struct Connection;
+}
+
+pub mod util {
+    pub struct Unrelated;
+}
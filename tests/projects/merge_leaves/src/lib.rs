@@ -0,0 +1,11 @@
+pub mod shapes {
+    pub mod circle {}
+
+    pub mod square {}
+
+    pub mod triangle {}
+}
+
+pub mod util {
+    pub mod io {}
+}
@@ -0,0 +1,9 @@
+#[path = "renamed.rs"]
+pub mod path_attribute;
+
+#[path = "nested/renamed.rs"]
+pub mod nested_path_attribute;
+
+pub mod include_macro {
+    include!("included.rs");
+}
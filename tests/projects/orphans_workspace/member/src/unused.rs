@@ -0,0 +1 @@
+// Not loaded from any module.
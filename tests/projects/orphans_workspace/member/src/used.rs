@@ -0,0 +1,3 @@
+// Loaded from the library, rather than from the binary.
+
+pub fn run() {}
@@ -0,0 +1 @@
+// Loaded from the crate's root module.
@@ -0,0 +1 @@
+// The root of a nested (and excluded) package, rather than an orphan.
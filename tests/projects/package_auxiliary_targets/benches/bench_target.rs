@@ -0,0 +1 @@
+fn bench_target() {}
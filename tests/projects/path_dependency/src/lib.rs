@@ -0,0 +1,5 @@
+pub mod wrapper {
+    pub fn function() {
+        helper::function()
+    }
+}
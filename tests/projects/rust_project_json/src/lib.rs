@@ -0,0 +1,3 @@
+pub mod project {
+    pub fn json() {}
+}
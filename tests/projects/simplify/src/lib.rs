@@ -0,0 +1,7 @@
+pub mod shapes {
+    pub struct Circle;
+
+    pub fn unit() -> Circle {
+        Circle
+    }
+}
@@ -0,0 +1,21 @@
+pub mod model {
+    pub struct User;
+
+    pub struct Order;
+}
+
+pub mod db {
+    use crate::model::{Order, User};
+
+    pub fn load_user(_user: &User) {}
+
+    pub fn load_order(_order: &Order) {}
+
+    pub fn save(_user: &User, _order: &Order) {}
+}
+
+pub mod api {
+    use crate::{db, model::User};
+
+    pub fn get_user(_user: &User) {}
+}
@@ -0,0 +1,27 @@
+pub mod buffer {
+    pub struct Buffer {
+        pub bytes: Vec<u8>,
+    }
+}
+
+pub mod index {
+    pub struct Index {
+        pub entries: std::collections::HashMap<u32, usize>,
+    }
+}
+
+pub mod math {
+    pub fn clamp(value: u32, max: u32) -> u32 {
+        if value > max {
+            max
+        } else {
+            value
+        }
+    }
+}
+
+pub mod report {
+    pub struct Report {
+        pub index: crate::index::Index,
+    }
+}
@@ -0,0 +1,14 @@
+// cargo-modules: allow(glob-imports)
+use std::collections::*;
+
+use std::io::*;
+
+pub mod legacy {
+    // Kept as is, until its callers have migrated.
+    // cargo-modules: allow(deep-nesting)
+    pub mod nested {}
+}
+
+pub mod current {
+    pub mod nested {}
+}
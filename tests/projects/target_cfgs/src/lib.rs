@@ -0,0 +1,11 @@
+#[cfg(target_os = "linux")]
+pub mod linux {}
+
+#[cfg(target_os = "macos")]
+pub mod macos {}
+
+#[cfg(target_os = "windows")]
+pub mod windows {}
+
+#[cfg(target_arch = "wasm32")]
+pub mod wasm {}
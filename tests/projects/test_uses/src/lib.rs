@@ -0,0 +1,21 @@
+pub mod model {
+    pub struct Model;
+}
+
+pub mod service {
+    use crate::model::Model;
+
+    pub struct Service(pub Model);
+
+    #[cfg(test)]
+    mod tests {
+        use crate::fixtures::Fixture;
+
+        struct Case(Fixture);
+    }
+}
+
+#[cfg(test)]
+mod fixtures {
+    pub(crate) struct Fixture;
+}
@@ -0,0 +1,6 @@
+// The file of this module is missing (e.g. got deleted, but not its declaration):
+pub mod missing;
+
+pub mod present {
+    pub fn function() {}
+}
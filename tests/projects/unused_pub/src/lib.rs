@@ -0,0 +1,10 @@
+// A library's root may declare a `fn main`, without becoming a binary:
+pub fn main() {}
+
+pub mod api {
+    pub fn reachable() {}
+}
+
+mod internal {
+    pub fn unreachable() {}
+}
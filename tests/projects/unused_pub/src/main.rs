@@ -0,0 +1,7 @@
+pub mod cli {
+    pub fn run() {}
+}
+
+fn main() {
+    cli::run();
+}
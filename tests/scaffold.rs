@@ -0,0 +1,35 @@
+#[macro_use]
+mod util;
+
+mod help {
+    test_cmd!(
+        args: "scaffold \
+                --help",
+        success: true,
+        color_mode: ColorMode::Plain,
+        project: smoke
+    );
+}
+
+mod dry_run {
+    test_cmd!(
+        args: "scaffold \
+                modules.txt \
+                --dry-run",
+        success: true,
+        color_mode: ColorMode::Plain,
+        project: scaffold
+    );
+}
+
+mod mod_rs {
+    test_cmd!(
+        args: "scaffold \
+                modules.txt \
+                --mod-rs \
+                --dry-run",
+        success: true,
+        color_mode: ColorMode::Plain,
+        project: scaffold
+    );
+}
@@ -0,0 +1,30 @@
+#[macro_use]
+mod util;
+
+mod help {
+    test_cmd!(
+        args: "schema \
+                --help",
+        success: true,
+        color_mode: ColorMode::Plain,
+        project: smoke
+    );
+}
+
+mod graph {
+    test_cmd!(
+        args: "schema graph",
+        success: true,
+        color_mode: ColorMode::Plain,
+        project: smoke
+    );
+}
+
+mod tree {
+    test_cmd!(
+        args: "schema tree",
+        success: true,
+        color_mode: ColorMode::Plain,
+        project: smoke
+    );
+}
@@ -0,0 +1,125 @@
+#[macro_use]
+mod util;
+
+use std::{
+    io::{Read as _, Write as _},
+    net::TcpStream,
+    thread,
+    time::{Duration, Instant},
+};
+
+use cargo_modules::api::{Builder, JsonPrinter, Printer as _};
+
+/// Returns the graph's JSON as exported for the viewer, pretty-printed.
+fn export(project: &str) -> String {
+    let graph = Builder::new(format!("tests/projects/{project}"))
+        .lib()
+        .build()
+        .unwrap();
+
+    let mut json = String::new();
+    JsonPrinter::new().print(&graph, &mut json).unwrap();
+
+    json::parse(&json).unwrap().pretty(2)
+}
+
+/// Sends a `GET` request for `path` to the server at `address`,
+/// returning the response's status code, content type and body.
+fn get(address: &str, path: &str) -> Option<(u16, String, String)> {
+    let mut stream = TcpStream::connect(address).ok()?;
+
+    write!(
+        stream,
+        "GET {path} HTTP/1.1\r\nHost: {address}\r\nConnection: close\r\n\r\n"
+    )
+    .ok()?;
+
+    let mut response = String::new();
+    stream.read_to_string(&mut response).ok()?;
+
+    let (head, body) = response.split_once("\r\n\r\n")?;
+    let mut lines = head.lines();
+
+    let status = lines.next()?.split(' ').nth(1)?.parse().ok()?;
+    let content_type = lines
+        .find_map(|line| line.strip_prefix("Content-Type: "))
+        .unwrap_or_default()
+        .to_owned();
+
+    Some((status, content_type, body.to_owned()))
+}
+
+mod help {
+    test_cmd!(
+        args: "serve \
+                --help",
+        success: true,
+        color_mode: ColorMode::Plain,
+        project: smoke
+    );
+}
+
+mod export {
+    #[test]
+    fn layers() {
+        let json = crate::export("layers");
+
+        // Spans of "uses" edges point to the project's absolute file paths:
+        let json = json.replace(env!("CARGO_MANIFEST_DIR"), "$CARGO_MANIFEST_DIR");
+
+        insta::assert_snapshot!(json);
+    }
+}
+
+mod server {
+    use super::*;
+
+    #[test]
+    fn layers() {
+        let address = "127.0.0.1:7391";
+
+        let mut child = crate::util::spawn("layers", &format!("serve --lib --address {address}"));
+
+        // The graph gets served once the project's initial analysis has completed:
+        let deadline = Instant::now() + Duration::from_secs(300);
+
+        loop {
+            if let Some((200, _, generation)) = get(address, "/generation") {
+                if generation != "0" {
+                    break;
+                }
+            }
+
+            if let Some(status) = child.try_wait().unwrap() {
+                panic!("Server exited ({status}) before serving the graph");
+            }
+
+            assert!(Instant::now() < deadline, "Timed out waiting for the graph");
+
+            thread::sleep(Duration::from_millis(100));
+        }
+
+        let graph = get(address, "/graph.json");
+        let not_found = get(address, "/unknown");
+
+        child.kill().unwrap();
+        child.wait().unwrap();
+
+        let (status, content_type, body) = graph.unwrap();
+
+        assert_eq!(status, 200);
+        assert_eq!(content_type, "application/json");
+
+        // The served graph is the very same as the one exported via the library:
+        assert_eq!(
+            json::parse(&body).unwrap().pretty(2),
+            crate::export("layers")
+        );
+
+        let (status, content_type, body) = not_found.unwrap();
+
+        assert_eq!(status, 404);
+        assert_eq!(content_type, "text/plain");
+        assert_eq!(body, "Not Found");
+    }
+}
@@ -0,0 +1,74 @@
+#[macro_use]
+mod util;
+
+mod help {
+    test_cmd!(
+        args: "stats \
+                --help",
+        success: true,
+        color_mode: ColorMode::Plain,
+        project: smoke
+    );
+}
+
+mod table {
+    test_cmd!(
+        args: "stats",
+        success: true,
+        color_mode: ColorMode::Plain,
+        project: orphans
+    );
+}
+
+mod json {
+    test_cmd!(
+        args: "stats \
+                --format json",
+        success: true,
+        color_mode: ColorMode::Plain,
+        project: orphans
+    );
+}
+
+mod dependencies {
+    test_cmd!(
+        args: "stats \
+                dependencies",
+        success: true,
+        color_mode: ColorMode::Plain,
+        project: stats_dependencies
+    );
+}
+
+mod dependencies_top {
+    test_cmd!(
+        args: "stats \
+                dependencies \
+                --top 1 \
+                --focus-on stats_dependencies::api",
+        success: true,
+        color_mode: ColorMode::Plain,
+        project: stats_dependencies
+    );
+}
+
+mod teams {
+    test_cmd!(
+        args: "stats \
+                teams",
+        success: true,
+        color_mode: ColorMode::Plain,
+        project: budgets
+    );
+}
+
+mod teams_csv {
+    test_cmd!(
+        args: "stats \
+                teams \
+                --format csv",
+        success: true,
+        color_mode: ColorMode::Plain,
+        project: budgets
+    );
+}
@@ -133,6 +133,290 @@ mod bin {
     }
 }
 
+mod cfg_modules {
+    mod default {
+        test_cmd!(
+            args: "structure",
+            success: true,
+            color_mode: ColorMode::Plain,
+            project: cfg_modules
+        );
+    }
+
+    mod all_features {
+        test_cmd!(
+            args: "structure \
+                    --all-features",
+            success: true,
+            color_mode: ColorMode::Plain,
+            project: cfg_modules
+        );
+    }
+}
+
+mod features {
+    mod default {
+        test_cmd!(
+            args: "structure",
+            success: true,
+            color_mode: ColorMode::Plain,
+            project: features
+        );
+    }
+
+    mod no_default_features {
+        test_cmd!(
+            args: "structure \
+                    --no-default-features",
+            success: true,
+            color_mode: ColorMode::Plain,
+            project: features
+        );
+    }
+
+    mod selected_features {
+        test_cmd!(
+            args: "structure \
+                    --no-default-features \
+                    --features beta,gamma",
+            success: true,
+            color_mode: ColorMode::Plain,
+            project: features
+        );
+    }
+
+    mod all_features {
+        test_cmd!(
+            args: "structure \
+                    --all-features",
+            success: true,
+            color_mode: ColorMode::Plain,
+            project: features
+        );
+    }
+}
+
+mod target {
+    mod linux {
+        test_cmd!(
+            args: "structure \
+                    --target x86_64-unknown-linux-gnu",
+            success: true,
+            color_mode: ColorMode::Plain,
+            project: target_cfgs
+        );
+    }
+
+    mod macos {
+        test_cmd!(
+            args: "structure \
+                    --target aarch64-apple-darwin",
+            success: true,
+            color_mode: ColorMode::Plain,
+            project: target_cfgs
+        );
+    }
+
+    mod windows {
+        test_cmd!(
+            args: "structure \
+                    --target x86_64-pc-windows-msvc",
+            success: true,
+            color_mode: ColorMode::Plain,
+            project: target_cfgs
+        );
+    }
+
+    mod wasm {
+        test_cmd!(
+            args: "structure \
+                    --target wasm32-unknown-unknown",
+            success: true,
+            color_mode: ColorMode::Plain,
+            project: target_cfgs
+        );
+    }
+}
+
+mod out_dir {
+    mod default {
+        test_cmd!(
+            args: "structure \
+                    --lib",
+            success: true,
+            color_mode: ColorMode::Plain,
+            project: build_script_out_dir
+        );
+    }
+
+    mod no_build_scripts {
+        test_cmd!(
+            args: "structure \
+                    --lib \
+                    --no-build-scripts",
+            success: true,
+            color_mode: ColorMode::Plain,
+            project: build_script_out_dir
+        );
+    }
+
+    mod with_out_dirs {
+        test_cmd!(
+            args: "structure \
+                    --lib \
+                    --with-out-dirs",
+            success: true,
+            color_mode: ColorMode::Plain,
+            project: build_script_out_dir
+        );
+    }
+}
+
+mod expand_path_deps {
+    mod default {
+        test_cmd!(
+            args: "structure",
+            success: true,
+            color_mode: ColorMode::Plain,
+            project: path_dependency
+        );
+    }
+
+    mod expanded {
+        test_cmd!(
+            args: "structure \
+                    --expand-path-deps",
+            success: true,
+            color_mode: ColorMode::Plain,
+            project: path_dependency
+        );
+    }
+
+    mod expanded_no_deps {
+        test_cmd!(
+            args: "structure \
+                    --expand-path-deps \
+                    --no-deps",
+            success: true,
+            color_mode: ColorMode::Plain,
+            project: path_dependency
+        );
+    }
+}
+
+mod cache {
+    test_cmds!(
+        args: "structure \
+                --cache",
+        success: true,
+        color_mode: ColorMode::Plain,
+        projects: [smoke, path_dependency]
+    );
+}
+
+mod output {
+    test_cmd!(
+        args: "structure \
+                --output target/cargo-modules-output.txt",
+        success: true,
+        color_mode: ColorMode::Plain,
+        project: smoke
+    );
+}
+
+mod rust_project_json {
+    test_cmd!(
+        args: "structure \
+                --manifest-path rust-project.json",
+        success: true,
+        color_mode: ColorMode::Plain,
+        project: rust_project_json
+    );
+}
+
+mod merge_targets {
+    mod pass {
+        test_cmds!(
+            args: "structure \
+                    --merge-targets",
+            success: true,
+            color_mode: ColorMode::Plain,
+            projects: [
+                package_multi_target,
+                workspace_single_package_multi_target,
+            ]
+        );
+    }
+}
+
+mod example {
+    mod pass {
+        test_cmd!(
+            args: "structure \
+                    --example example_target",
+            success: true,
+            color_mode: ColorMode::Plain,
+            project: package_auxiliary_targets
+        );
+    }
+
+    mod fail {
+        test_cmd!(
+            args: "structure \
+                    --example foobar", // does not exist
+            success: false,
+            color_mode: ColorMode::Plain,
+            project: package_auxiliary_targets
+        );
+    }
+}
+
+mod test {
+    mod pass {
+        test_cmd!(
+            args: "structure \
+                    --test test_target",
+            success: true,
+            color_mode: ColorMode::Plain,
+            project: package_auxiliary_targets
+        );
+    }
+}
+
+mod bench {
+    mod pass {
+        test_cmd!(
+            args: "structure \
+                    --bench bench_target",
+            success: true,
+            color_mode: ColorMode::Plain,
+            project: package_auxiliary_targets
+        );
+    }
+}
+
+mod build_script {
+    mod pass {
+        test_cmd!(
+            args: "structure \
+                    --build-script",
+            success: true,
+            color_mode: ColorMode::Plain,
+            project: package_auxiliary_targets
+        );
+    }
+
+    mod fail {
+        test_cmd!(
+            args: "structure \
+                    --build-script", // does not exist
+            success: false,
+            color_mode: ColorMode::Plain,
+            project: package_lib_target
+        );
+    }
+}
+
 mod package {
     mod pass {
         test_cmds!(
@@ -281,6 +565,40 @@ mod package_bin {
     }
 }
 
+mod packages {
+    mod pass {
+        test_cmd!(
+            args: "structure \
+                    --package package_bin_target \
+                    --package package_lib_target",
+            success: true,
+            color_mode: ColorMode::Plain,
+            project: virtual_workspace_multi_package
+        );
+    }
+
+    mod glob {
+        test_cmd!(
+            args: "structure \
+                    --package package_?i?_target",
+            success: true,
+            color_mode: ColorMode::Plain,
+            project: virtual_workspace_multi_package
+        );
+    }
+
+    mod fail {
+        test_cmd!(
+            args: "structure \
+                    --package package_bin_target \
+                    --package foobar",
+            success: false,
+            color_mode: ColorMode::Plain,
+            project: virtual_workspace_multi_package
+        );
+    }
+}
+
 mod cfg_test {
     test_cmd!(
         args: "dependencies \
@@ -289,6 +607,35 @@ mod cfg_test {
         color_mode: ColorMode::Plain,
         project: smoke
     );
+
+    // Analyzing with `#[cfg(test)]` enabled also builds the proc-macros of dev-dependencies
+    // (as used by `#[cfg(test)]` code), which analyzing without it skips:
+
+    #[test]
+    fn cfg_test_proc_macros() {
+        let deps_dir = "tests/projects/cfg_test_proc_macros/target/debug/deps";
+
+        let builds_proc_macro = |args: &str| {
+            let _ = std::fs::remove_dir_all("tests/projects/cfg_test_proc_macros/target");
+
+            let mut cmd = crate::util::cmd("cfg_test_proc_macros", args);
+            cmd.env("NO_COLOR", "1");
+            crate::util::output(cmd, true);
+
+            std::fs::read_dir(deps_dir).is_ok_and(|mut entries| {
+                entries.any(|entry| {
+                    let path = entry.unwrap().path();
+                    let file_name = path.file_name().unwrap().to_string_lossy().into_owned();
+
+                    file_name.contains("macros-")
+                        && path.extension() == Some(std::env::consts::DLL_EXTENSION.as_ref())
+                })
+            })
+        };
+
+        assert!(!builds_proc_macro("structure"));
+        assert!(builds_proc_macro("structure --cfg-test"));
+    }
 }
 
 mod selection {
@@ -363,6 +710,47 @@ mod focus_on {
             project: smoke
         );
     }
+
+    mod item_path {
+        test_cmd!(
+            args: "structure \
+                    --no-types \
+                    --focus-on \"github_issue_79::a::b::c::X\"",
+            success: true,
+            color_mode: ColorMode::Plain,
+            project: github_issue_79
+        );
+    }
+
+    mod reexport_path {
+        test_cmd!(
+            args: "structure \
+                    --focus-on \"github_issue_79::a::b::X\"",
+            success: true,
+            color_mode: ColorMode::Plain,
+            project: github_issue_79
+        );
+    }
+
+    mod file_path {
+        test_cmd!(
+            args: "structure \
+                    --focus-on src/orphans.rs",
+            success: true,
+            color_mode: ColorMode::Plain,
+            project: orphans
+        );
+    }
+
+    mod missing_file_path {
+        test_cmd!(
+            args: "structure \
+                    --focus-on src/missing.rs",
+            success: false,
+            color_mode: ColorMode::Plain,
+            project: orphans
+        );
+    }
 }
 
 mod max_depth {
@@ -406,6 +794,34 @@ mod max_depth {
     }
 }
 
+mod max_nodes {
+    mod budget_1 {
+        test_cmd!(
+            args: "structure \
+                    --no-types \
+                    --no-traits \
+                    --no-fns \
+                    --max-nodes 1",
+            success: true,
+            color_mode: ColorMode::Plain,
+            project: smoke
+        );
+    }
+
+    mod budget_12 {
+        test_cmd!(
+            args: "structure \
+                    --no-types \
+                    --no-traits \
+                    --no-fns \
+                    --max-nodes 12",
+            success: true,
+            color_mode: ColorMode::Plain,
+            project: smoke
+        );
+    }
+}
+
 mod sort_by {
     mod name {
         test_cmd!(
@@ -436,6 +852,26 @@ mod sort_by {
             project: smoke
         );
     }
+
+    mod source {
+        test_cmd!(
+            args: "structure \
+                    --sort-by \"source\"",
+            success: true,
+            color_mode: ColorMode::Plain,
+            project: smoke
+        );
+    }
+
+    mod size {
+        test_cmd!(
+            args: "structure \
+                    --sort-by \"size\"",
+            success: true,
+            color_mode: ColorMode::Plain,
+            project: smoke
+        );
+    }
 }
 
 mod sort_reversed {
@@ -448,6 +884,99 @@ mod sort_reversed {
     );
 }
 
+mod deprecated {
+    mod all {
+        test_cmd!(
+            args: "structure",
+            success: true,
+            color_mode: ColorMode::Plain,
+            project: deprecated
+        );
+    }
+
+    mod only_deprecated {
+        test_cmd!(
+            args: "structure \
+                    --only-deprecated",
+            success: true,
+            color_mode: ColorMode::Plain,
+            project: deprecated
+        );
+    }
+}
+
+mod with_docs {
+    test_cmd!(
+        args: "structure \
+                --with-docs",
+        success: true,
+        color_mode: ColorMode::Plain,
+        project: doc_comments
+    );
+}
+
+mod with_counts {
+    test_cmd!(
+        args: "structure \
+                --with-counts",
+        success: true,
+        color_mode: ColorMode::Plain,
+        project: smoke
+    );
+}
+
+mod icons {
+    mod nerd {
+        test_cmd!(
+            args: "structure \
+                    --icons nerd",
+            success: true,
+            color_mode: ColorMode::Plain,
+            project: smoke
+        );
+    }
+
+    mod unicode {
+        test_cmd!(
+            args: "structure \
+                    --icons unicode",
+            success: true,
+            color_mode: ColorMode::Plain,
+            project: smoke
+        );
+    }
+
+    mod ascii {
+        test_cmd!(
+            args: "structure \
+                    --icons ascii",
+            success: true,
+            color_mode: ColorMode::Plain,
+            project: smoke
+        );
+    }
+}
+
+mod with_paths {
+    test_cmd!(
+        args: "structure \
+                --with-paths",
+        success: true,
+        color_mode: ColorMode::Plain,
+        project: orphans_path_include
+    );
+}
+
+mod with_orphans {
+    test_cmd!(
+        args: "structure \
+                --with-orphans",
+        success: true,
+        color_mode: ColorMode::Plain,
+        project: orphans
+    );
+}
+
 mod github {
     mod issue_80 {
         mod tests {
@@ -486,3 +1015,22 @@ mod github {
         );
     }
 }
+
+mod acyclic {
+    test_cmd!(
+        args: "structure --acyclic",
+        success: true,
+        color_mode: ColorMode::Plain,
+        project: orphans
+    );
+}
+
+mod unresolved {
+    test_cmd!(
+        args: "structure \
+                --lib",
+        success: true,
+        color_mode: ColorMode::Plain,
+        project: unresolved
+    );
+}
@@ -1,13 +1,22 @@
 #![allow(unused_macros)]
 
-use std::{path::PathBuf, str::from_utf8};
-
-use assert_cmd::Command;
+use std::{
+    fs,
+    io::Read as _,
+    path::PathBuf,
+    process::{self, Child, Stdio},
+    str::from_utf8,
+    thread,
+    time::{Duration, Instant},
+};
+
+use assert_cmd::{cargo::cargo_bin, Command};
 use shellwords::split;
 
 #[allow(dead_code)]
 pub enum ColorMode {
     Plain,
+    Basic,
     Ansi,
     TrueColor,
 }
@@ -30,6 +39,59 @@ pub fn output(mut cmd: Command, expect_success: bool) -> (String, String) {
 
 #[allow(dead_code)]
 pub fn cmd(dir: &str, args: &str) -> Command {
+    let mut command = Command::cargo_bin("cargo-modules").unwrap();
+
+    command.current_dir(project_dir(dir));
+    command.args(split_args(args));
+
+    command
+}
+
+/// Spawns a command that keeps running until killed (e.g. `watch`),
+/// without any terminal colors.
+#[allow(dead_code)]
+pub fn spawn(dir: &str, args: &str) -> Child {
+    process::Command::new(cargo_bin("cargo-modules"))
+        .current_dir(project_dir(dir))
+        .args(split_args(args))
+        .env("NO_COLOR", "1")
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .unwrap()
+}
+
+/// Waits for a spawned command to write the file at `path` (relative to the project's directory),
+/// returning the file's contents.
+#[allow(dead_code)]
+pub fn wait_for_file(child: &mut Child, dir: &str, path: &str) -> String {
+    let file_path = project_dir(dir).join(path);
+    let deadline = Instant::now() + Duration::from_secs(300);
+
+    loop {
+        if let Ok(contents) = fs::read_to_string(&file_path) {
+            return contents;
+        }
+
+        if let Some(status) = child.try_wait().unwrap() {
+            let mut stderr = String::new();
+            child
+                .stderr
+                .take()
+                .unwrap()
+                .read_to_string(&mut stderr)
+                .unwrap();
+            panic!("Command exited ({status}) without writing {path}:\n{stderr}");
+        }
+
+        assert!(Instant::now() < deadline, "Timed out waiting for {path}");
+
+        thread::sleep(Duration::from_millis(100));
+    }
+}
+
+fn project_dir(dir: &str) -> PathBuf {
     let mut dir_path = PathBuf::new();
 
     dir_path.push(".");
@@ -37,18 +99,14 @@ pub fn cmd(dir: &str, args: &str) -> Command {
     dir_path.push("projects");
     dir_path.push(dir);
 
-    let args: Vec<String> = match split(args) {
+    dir_path
+}
+
+fn split_args(args: &str) -> Vec<String> {
+    match split(args) {
         Ok(args) => args,
         Err(err) => panic!("{}", err),
-    };
-    let args: Vec<&str> = args.iter().map(|s| s.as_str()).collect();
-
-    let mut command = Command::cargo_bin("cargo-modules").unwrap();
-
-    command.current_dir(dir_path);
-    command.args(args);
-
-    command
+    }
 }
 
 macro_rules! test_cmds {
@@ -131,10 +189,18 @@ macro_rules! test_cmd {
                 ColorMode::Plain => {
                     cmd.env("NO_COLOR", "1");
                 },
+                ColorMode::Basic => {
+                    cmd.env("CLICOLOR_FORCE", "1");
+                    cmd.env_remove("COLORTERM");
+                    cmd.env("TERM", "xterm");
+                },
                 ColorMode::Ansi => {
+                    cmd.env("CLICOLOR_FORCE", "1");
                     cmd.env_remove("COLORTERM");
+                    cmd.env("TERM", "xterm-256color");
                 },
                 ColorMode::TrueColor => {
+                    cmd.env("CLICOLOR_FORCE", "1");
                     cmd.env("COLORTERM", "truecolor");
                 },
             }
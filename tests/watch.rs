@@ -0,0 +1,61 @@
+#[macro_use]
+mod util;
+
+mod help {
+    test_cmd!(
+        args: "watch \
+                --help",
+        success: true,
+        color_mode: ColorMode::Plain,
+        project: smoke
+    );
+}
+
+mod unwatchable_command {
+    // Only commands whose output gets rendered in full can be watched:
+
+    test_cmd!(
+        args: "watch \
+                orphans",
+        success: false,
+        color_mode: ColorMode::Plain,
+        project: smoke
+    );
+}
+
+mod structure {
+    #[test]
+    fn smoke() {
+        let path = "target/cargo-modules-watch/structure.txt";
+        let _ = std::fs::remove_file(format!("tests/projects/smoke/{path}"));
+
+        let mut child = crate::util::spawn("smoke", &format!("watch structure -o {path}"));
+        let output = crate::util::wait_for_file(&mut child, "smoke", path);
+        child.kill().unwrap();
+        child.wait().unwrap();
+
+        insta::assert_snapshot!(output);
+    }
+}
+
+mod emit {
+    #[test]
+    fn smoke() {
+        // Formats get written in order, so the last one being present means all of them are:
+
+        let path = "target/cargo-modules-watch/emit/graph.mmd";
+        let _ = std::fs::remove_file(format!("tests/projects/smoke/{path}"));
+
+        let mut child = crate::util::spawn(
+            "smoke",
+            "watch dependencies \
+                --emit dot,mermaid \
+                --emit-path target/cargo-modules-watch/emit/graph.{ext}",
+        );
+        let output = crate::util::wait_for_file(&mut child, "smoke", path);
+        child.kill().unwrap();
+        child.wait().unwrap();
+
+        insta::assert_snapshot!(output);
+    }
+}